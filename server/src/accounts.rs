@@ -4,7 +4,10 @@ mod models;
 pub(crate) mod storage;
 
 pub use api::{
-    confirm_account_qrcode_login, confirm_account_qrcode_login_service, delete_account,
-    delete_account_service, list_accounts, list_accounts_service, rename_account,
-    rename_account_service, start_account_qrcode_login, start_account_qrcode_login_service,
+    add_s3_account, add_s3_account_service, add_webdav_account, add_webdav_account_service,
+    add_youtube_account, add_youtube_account_service, confirm_account_qrcode_login,
+    confirm_account_qrcode_login_service, delete_account, delete_account_service,
+    list_account_collections, list_account_collections_service, list_accounts,
+    list_accounts_service, rename_account, rename_account_service, start_account_qrcode_login,
+    start_account_qrcode_login_service,
 };
@@ -1,8 +1,15 @@
 use axum::{Json, extract::State, http::StatusCode};
-use shared::{AccountDeleteRequest, AccountRenameRequest, QrConfirmRequest, QrStartResponse};
+use shared::{
+    AccountDeleteRequest, AccountRenameRequest, CollectionListRequest, CollectionSeason,
+    QrConfirmRequest, QrStartResponse, S3AccountRequest, WebDavAccountRequest,
+    YouTubeAccountRequest,
+};
 use uuid::Uuid;
 
-use crate::state::SharedState;
+use crate::{
+    state::SharedState,
+    uploader::{s3::S3Account, webdav::WebDavAccount, youtube::YouTubeAccount},
+};
 
 use super::{bili, storage};
 
@@ -119,3 +126,147 @@ pub async fn delete_account_service(account_file: String) -> Result<(), (StatusC
         }
     }
 }
+
+pub async fn list_account_collections(
+    Json(payload): Json<CollectionListRequest>,
+) -> Result<Json<Vec<CollectionSeason>>, StatusCode> {
+    list_account_collections_service(payload.account_file)
+        .await
+        .map(Json)
+        .map_err(|(status, _)| status)
+}
+
+pub async fn list_account_collections_service(
+    account_file: String,
+) -> Result<Vec<CollectionSeason>, (StatusCode, String)> {
+    if !storage::account_file_exists(&account_file).await {
+        return Err((StatusCode::NOT_FOUND, format!("account not found: {account_file}")));
+    }
+    if storage::is_webdav_account(&account_file).await
+        || storage::is_youtube_account(&account_file).await
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "collections require a Bilibili account".to_string(),
+        ));
+    }
+    bili::list_collections(&account_file).await.map_err(|err| {
+        tracing::error!("{err}");
+        (StatusCode::BAD_GATEWAY, err)
+    })
+}
+
+pub async fn add_webdav_account(Json(payload): Json<WebDavAccountRequest>) -> StatusCode {
+    match add_webdav_account_service(payload).await {
+        Ok(()) => StatusCode::OK,
+        Err((status, _)) => status,
+    }
+}
+
+/// Checks the server and credentials before saving, so only working accounts are stored.
+pub async fn add_webdav_account_service(
+    payload: WebDavAccountRequest,
+) -> Result<(), (StatusCode, String)> {
+    let name = payload.name.trim();
+    let account = WebDavAccount {
+        base_url: payload.base_url.trim().to_string(),
+        username: payload.username.trim().to_string(),
+        password: payload.password,
+    };
+    if name.is_empty() || account.username.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "name and username are required".to_string()));
+    }
+    if !url::Url::parse(&account.base_url)
+        .is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.host_str().is_some())
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("base_url must be an http(s) URL: {}", account.base_url),
+        ));
+    }
+    if let Err(e) = account.check().await {
+        return Err((StatusCode::BAD_REQUEST, format!("WebDAV login failed: {e}")));
+    }
+
+    storage::save_webdav_account(name, &account).await.map_err(|e| {
+        tracing::error!("Failed to save WebDAV account: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "failed to save WebDAV account".to_string())
+    })
+}
+
+pub async fn add_s3_account(Json(payload): Json<S3AccountRequest>) -> StatusCode {
+    match add_s3_account_service(payload).await {
+        Ok(()) => StatusCode::OK,
+        Err((status, _)) => status,
+    }
+}
+
+pub async fn add_s3_account_service(payload: S3AccountRequest) -> Result<(), (StatusCode, String)> {
+    let name = payload.name.trim();
+    let account = S3Account {
+        access_key_id: payload.access_key_id.trim().to_string(),
+        secret_access_key: payload.secret_access_key.trim().to_string(),
+    };
+    if name.is_empty() || account.access_key_id.is_empty() || account.secret_access_key.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "name, access key id and secret access key are required".to_string(),
+        ));
+    }
+
+    storage::save_s3_account(name, &account).await.map_err(|e| {
+        tracing::error!("Failed to save S3 account: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "failed to save S3 account".to_string())
+    })
+}
+
+pub async fn add_youtube_account(Json(payload): Json<YouTubeAccountRequest>) -> StatusCode {
+    match add_youtube_account_service(payload).await {
+        Ok(()) => StatusCode::OK,
+        Err((status, _)) => status,
+    }
+}
+
+/// Exchanges the refresh token once before saving, so only working credentials are stored.
+pub async fn add_youtube_account_service(
+    payload: YouTubeAccountRequest,
+) -> Result<(), (StatusCode, String)> {
+    let name = payload.name.trim();
+    let api_base = payload.api_base.trim();
+    let account = YouTubeAccount {
+        client_id: payload.client_id.trim().to_string(),
+        client_secret: payload.client_secret.trim().to_string(),
+        refresh_token: payload.refresh_token.trim().to_string(),
+        api_base: if api_base.is_empty() {
+            YouTubeAccount::DEFAULT_API_BASE.to_string()
+        } else {
+            api_base.to_string()
+        },
+    };
+    if name.is_empty()
+        || account.client_id.is_empty()
+        || account.client_secret.is_empty()
+        || account.refresh_token.is_empty()
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "name, client_id, client_secret and refresh_token are required".to_string(),
+        ));
+    }
+    if !url::Url::parse(&account.api_base)
+        .is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.host_str().is_some())
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("api_base must be an http(s) URL: {}", account.api_base),
+        ));
+    }
+    if let Err(e) = account.access_token().await {
+        return Err((StatusCode::BAD_REQUEST, format!("YouTube authorization failed: {e}")));
+    }
+
+    storage::save_youtube_account(name, &account).await.map_err(|e| {
+        tracing::error!("Failed to save YouTube account: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "failed to save YouTube account".to_string())
+    })
+}
@@ -1,4 +1,5 @@
-use biliup::uploader::credential::{Credential, LoginInfo};
+use biliup::uploader::credential::{Credential, LoginInfo, login_by_cookies};
+use shared::{CollectionSeason, CollectionSection};
 
 use super::models::NavResponse;
 
@@ -52,3 +53,75 @@ pub async fn nav_from_cookie_info(cookie_info: &serde_json::Value) -> Option<(St
 
     nav_from_cookie_header(&cookie_header).await.ok()
 }
+
+/// Collections (seasons) of the account in `account_file`, with their sections.
+pub async fn list_collections(account_file: &str) -> Result<Vec<CollectionSeason>, String> {
+    let bili = login_by_cookies(account_file, None)
+        .await
+        .map_err(|e| format!("Failed to login by {account_file}: {e}"))?;
+    let ret: serde_json::Value = bili
+        .client
+        .get("https://member.bilibili.com/x2/creative/web/seasons?pn=1&ps=50&order=&sort=")
+        .send()
+        .await
+        .map_err(|e| format!("seasons request failed: {e}"))?
+        .json()
+        .await
+        .map_err(|e| format!("seasons decode failed: {e}"))?;
+    if ret["code"].as_i64() != Some(0) {
+        return Err(format!("seasons code: {}, {}", ret["code"], ret["message"]));
+    }
+    Ok(parse_collections(&ret["data"]))
+}
+
+fn parse_collections(data: &serde_json::Value) -> Vec<CollectionSeason> {
+    let title = |value: &serde_json::Value| value["title"].as_str().unwrap_or_default().to_string();
+    data["seasons"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let season = &entry["season"];
+            let sections = entry["sections"]["sections"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|section| {
+                    Some(CollectionSection { id: section["id"].as_u64()?, title: title(section) })
+                })
+                .collect();
+            Some(CollectionSeason { id: season["id"].as_u64()?, title: title(season), sections })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_collections;
+
+    #[test]
+    fn collections_are_read_with_their_sections() {
+        let data = serde_json::json!({
+            "seasons": [
+                {
+                    "season": { "id": 11, "title": "直播回放" },
+                    "sections": { "sections": [
+                        { "id": 101, "title": "正片" },
+                        { "id": 102, "title": "2026" }
+                    ] }
+                },
+                { "season": { "title": "缺少 id" } }
+            ],
+            "total": 2
+        });
+        let seasons = parse_collections(&data);
+
+        assert_eq!(seasons.len(), 1);
+        assert_eq!(seasons[0].id, 11);
+        assert_eq!(seasons[0].title, "直播回放");
+        let sections: Vec<_> =
+            seasons[0].sections.iter().map(|s| (s.id, s.title.as_str())).collect();
+        assert_eq!(sections, vec![(101, "正片"), (102, "2026")]);
+        assert!(parse_collections(&serde_json::json!({ "seasons": null })).is_empty());
+    }
+}
@@ -1,9 +1,10 @@
 use biliup::uploader::credential::LoginInfo;
-use shared::UploadAccount;
+use shared::{AccountKind, UploadAccount};
 use std::collections::BTreeMap;
 use std::path::{Path as FsPath, PathBuf};
 
 use super::{bili, models::CookieFile};
+use crate::uploader::{s3::S3Account, webdav::WebDavAccount, youtube::YouTubeAccount};
 
 pub async fn scan_saved_accounts() -> Vec<UploadAccount> {
     let dir = cookies_dir();
@@ -23,7 +24,15 @@ pub async fn scan_saved_accounts() -> Vec<UploadAccount> {
     let meta = load_account_meta().await;
     let mut accounts = Vec::new();
     for file in files {
-        let mut account = inspect_cookie_account(file.clone()).await;
+        let mut account = if let Some(webdav) = read_account_json(&file).await {
+            inspect_webdav_account(file.clone(), webdav).await
+        } else if let Some(youtube) = read_account_json(&file).await {
+            inspect_youtube_account(file.clone(), youtube).await
+        } else if let Some(s3) = read_account_json(&file).await {
+            inspect_s3_account(file.clone(), s3)
+        } else {
+            inspect_cookie_account(file.clone()).await
+        };
         let key = file.file_name().and_then(|s| s.to_str()).map(str::to_string).unwrap_or_default();
         if let Some(display_name) = meta.get(&key) {
             account.name = display_name.clone();
@@ -74,24 +83,91 @@ pub async fn account_file_exists(account_file: &str) -> bool {
         || tokio::fs::metadata(cookies_dir().join(key)).await.map(|m| m.is_file()).unwrap_or(false)
 }
 
+/// Whether `account_file` is a WebDAV account rather than Bilibili cookies.
+pub async fn is_webdav_account(account_file: &str) -> bool {
+    find_account_json::<WebDavAccount>(account_file).await.is_some()
+}
+
+/// Whether `account_file` holds YouTube OAuth credentials rather than Bilibili cookies.
+pub async fn is_youtube_account(account_file: &str) -> bool {
+    find_account_json::<YouTubeAccount>(account_file).await.is_some()
+}
+
+/// Whether `account_file` holds an S3 access key rather than Bilibili cookies.
+pub async fn is_s3_account(account_file: &str) -> bool {
+    find_account_json::<S3Account>(account_file).await.is_some()
+}
+
+/// Saves a WebDAV account; an account for the same server and user is replaced, so adding it
+/// again updates the password.
+pub async fn save_webdav_account(
+    display_name: &str,
+    account: &WebDavAccount,
+) -> Result<(), Box<dyn std::error::Error>> {
+    tokio::fs::create_dir_all(cookies_dir()).await?;
+    let host = url::Url::parse(&account.base_url)?.host_str().unwrap_or_default().to_string();
+    let file_name = format!(
+        "webdav_{}_{}.json",
+        sanitize_file_name(&host),
+        sanitize_file_name(&account.username)
+    );
+    tokio::fs::write(cookies_dir().join(&file_name), serde_json::to_string_pretty(account)?)
+        .await?;
+
+    let mut meta = load_account_meta().await;
+    meta.insert(file_name, display_name.to_string());
+    save_account_meta(&meta).await
+}
+
+/// Saves a YouTube account under a new file; the display name is the only name it has.
+pub async fn save_youtube_account(
+    display_name: &str,
+    account: &YouTubeAccount,
+) -> Result<(), Box<dyn std::error::Error>> {
+    tokio::fs::create_dir_all(cookies_dir()).await?;
+    let file_name = format!("youtube_{}.json", uuid::Uuid::new_v4().simple());
+    tokio::fs::write(cookies_dir().join(&file_name), serde_json::to_string_pretty(account)?)
+        .await?;
+
+    let mut meta = load_account_meta().await;
+    meta.insert(file_name, display_name.to_string());
+    save_account_meta(&meta).await
+}
+
+/// Saves an S3 access key; adding the same key id again replaces its secret.
+pub async fn save_s3_account(
+    display_name: &str,
+    account: &S3Account,
+) -> Result<(), Box<dyn std::error::Error>> {
+    tokio::fs::create_dir_all(cookies_dir()).await?;
+    let file_name = format!("s3_{}.json", sanitize_file_name(&account.access_key_id));
+    tokio::fs::write(cookies_dir().join(&file_name), serde_json::to_string_pretty(account)?)
+        .await?;
+
+    let mut meta = load_account_meta().await;
+    meta.insert(file_name, display_name.to_string());
+    save_account_meta(&meta).await
+}
+
 pub async fn save_login_info(login_info: LoginInfo) -> Result<(), Box<dyn std::error::Error>> {
     tokio::fs::create_dir_all(cookies_dir()).await?;
     let (name, mid) = bili::nav_from_cookie_info(&login_info.cookie_info)
         .await
         .unwrap_or_else(|| ("bilibili-user".to_string(), 0));
 
-    let sanitized_name = name
-        .chars()
-        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
-        .collect::<String>();
-
-    let file_name = format!("cookies_{}_{}.json", mid, sanitized_name);
+    let file_name = format!("cookies_{}_{}.json", mid, sanitize_file_name(&name));
     let full_path = cookies_dir().join(file_name);
     let file = std::fs::File::create(full_path)?;
     serde_json::to_writer_pretty(file, &login_info)?;
     Ok(())
 }
 
+fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+
 fn cookies_dir() -> PathBuf {
     std::env::var("BILIUP_COOKIES_DIR")
         .map(PathBuf::from)
@@ -122,6 +198,69 @@ async fn save_account_meta(
     Ok(())
 }
 
+async fn read_account_json<T: serde::de::DeserializeOwned>(path: &FsPath) -> Option<T> {
+    let raw = tokio::fs::read_to_string(path).await.ok()?;
+    serde_json::from_str(&raw).ok()
+}
+
+/// `account_file` as given or inside the cookies dir, parsed as `T`.
+async fn find_account_json<T: serde::de::DeserializeOwned>(account_file: &str) -> Option<T> {
+    let key = account_file_key(account_file);
+    if key.is_empty() {
+        return None;
+    }
+    match read_account_json(FsPath::new(account_file)).await {
+        Some(account) => Some(account),
+        None => read_account_json(&cookies_dir().join(key)).await,
+    }
+}
+
+async fn inspect_webdav_account(path: PathBuf, webdav: WebDavAccount) -> UploadAccount {
+    let account_file = path.to_string_lossy().to_string();
+    let host = url::Url::parse(&webdav.base_url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_default();
+    let check = webdav.check().await;
+    UploadAccount {
+        id: account_file.clone(),
+        name: format!("{}@{}", webdav.username, host),
+        mid: None,
+        account_file,
+        valid: check.is_ok(),
+        error: check.err().map(|e| e.to_string()),
+        kind: AccountKind::WebDav,
+    }
+}
+
+async fn inspect_youtube_account(path: PathBuf, youtube: YouTubeAccount) -> UploadAccount {
+    let account_file = path.to_string_lossy().to_string();
+    let token = youtube.access_token().await;
+    UploadAccount {
+        id: account_file.clone(),
+        name: path.file_stem().and_then(|s| s.to_str()).unwrap_or("youtube").to_string(),
+        mid: None,
+        account_file,
+        valid: token.is_ok(),
+        error: token.err().map(|e| e.to_string()),
+        kind: AccountKind::YouTube,
+    }
+}
+
+/// The key can only be tried against a bucket, which the upload template names.
+fn inspect_s3_account(path: PathBuf, s3: S3Account) -> UploadAccount {
+    let account_file = path.to_string_lossy().to_string();
+    UploadAccount {
+        id: account_file.clone(),
+        name: s3.access_key_id,
+        mid: None,
+        account_file,
+        valid: true,
+        error: None,
+        kind: AccountKind::S3,
+    }
+}
+
 async fn inspect_cookie_account(path: PathBuf) -> UploadAccount {
     let account_file = path.to_string_lossy().to_string();
     let fallback_name =
@@ -137,6 +276,7 @@ async fn inspect_cookie_account(path: PathBuf) -> UploadAccount {
                 account_file,
                 valid: false,
                 error: Some(e.to_string()),
+                kind: AccountKind::Bilibili,
             };
         }
     };
@@ -151,6 +291,7 @@ async fn inspect_cookie_account(path: PathBuf) -> UploadAccount {
                 account_file,
                 valid: false,
                 error: Some(format!("Invalid cookie json: {e}")),
+                kind: AccountKind::Bilibili,
             };
         }
     };
@@ -171,6 +312,7 @@ async fn inspect_cookie_account(path: PathBuf) -> UploadAccount {
             account_file,
             valid: false,
             error: Some("cookie_info.cookies is empty".to_string()),
+            kind: AccountKind::Bilibili,
         };
     }
 
@@ -182,6 +324,7 @@ async fn inspect_cookie_account(path: PathBuf) -> UploadAccount {
             account_file,
             valid: true,
             error: None,
+            kind: AccountKind::Bilibili,
         },
         Err(err) => UploadAccount {
             id: account_file.clone(),
@@ -190,6 +333,7 @@ async fn inspect_cookie_account(path: PathBuf) -> UploadAccount {
             account_file,
             valid: false,
             error: Some(err),
+            kind: AccountKind::Bilibili,
         },
     }
 }
@@ -2,12 +2,13 @@ use anyhow::{Result, anyhow};
 use dashmap::DashMap;
 use shared::RecordingSettings;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{Notify, RwLock};
 
 use crate::{
     checker::StreamlinkChecker,
     db::Db,
     state::{AppState, SharedState},
+    upload_limits::UploadLimits,
 };
 
 pub async fn build_state() -> Result<SharedState> {
@@ -37,6 +38,9 @@ pub async fn build_state() -> Result<SharedState> {
     Ok(Arc::new(AppState {
         tasks: DashMap::new(),
         handles: DashMap::new(),
+        upload_handles: DashMap::new(),
+        upload_notify: Notify::new(),
+        upload_limits: UploadLimits::default(),
         checking_urls: DashMap::new(),
         db,
         checker,
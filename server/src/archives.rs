@@ -0,0 +1,70 @@
+use chrono::Local;
+use shared::UploadConfig;
+
+use crate::{db::SubmittedArchive, state::SharedState, uploader::Submission};
+
+/// Archive the config's parts should be appended to: the configured BV id, or the archive the
+/// same template submitted for this source within the merge window.
+pub(crate) async fn append_target(
+    state: &SharedState,
+    task_id: &str,
+    config: &UploadConfig,
+) -> Option<String> {
+    if let Some(bvid) = &config.append_bvid {
+        return Some(bvid.clone());
+    }
+    if config.merge_window_hours == 0 {
+        return None;
+    }
+    let source_url = task_source_url(state, task_id)?;
+    let since = Local::now().timestamp() - i64::from(config.merge_window_hours) * 60 * 60;
+    match state
+        .db
+        .find_recent_archive_bvid(
+            &source_url,
+            config.template_id.as_deref(),
+            &config.account_file,
+            since,
+        )
+        .await
+    {
+        Ok(bvid) => bvid,
+        Err(e) => {
+            tracing::warn!(
+                "Failed to look up archive to merge into, task_id={}, source={}: {}",
+                task_id,
+                source_url,
+                e
+            );
+            None
+        }
+    }
+}
+
+/// Remembers the archive so later sessions within the merge window can append to it.
+pub(crate) async fn record_submission(
+    state: &SharedState,
+    task_id: &str,
+    config: &UploadConfig,
+    submission: &Submission,
+) {
+    let Some(source_url) = task_source_url(state, task_id) else {
+        return;
+    };
+    let archive = SubmittedArchive {
+        task_id: task_id.to_string(),
+        source_url,
+        template_id: config.template_id.clone(),
+        account_file: config.account_file.clone(),
+        aid: submission.aid,
+        bvid: submission.bvid.clone(),
+        submitted_at: Local::now().timestamp(),
+    };
+    if let Err(e) = state.db.save_submitted_archive(&archive).await {
+        tracing::error!("Failed to record submitted archive, task_id={}: {}", task_id, e);
+    }
+}
+
+fn task_source_url(state: &SharedState, task_id: &str) -> Option<String> {
+    state.tasks.get(task_id).map(|task| task.url.clone()).filter(|url| !url.is_empty())
+}
@@ -22,10 +22,30 @@ async fn main() -> Result<()> {
         .filter(|v| !v.is_empty())
         .unwrap_or_else(|| vec!["omnistream".to_string()]);
 
-    let uploader = uploader::UploadTarget::Bilibili.create_uploader();
     let config = UploadConfig { title, tags, account_file, ..Default::default() };
+    let uploader = uploader::UploadTarget::for_config(&config).create_uploader();
 
-    uploader.upload(vec![filename], &config, None, "upload_once").await?;
+    let submission = uploader
+        .upload(
+            vec![filename.into()],
+            &config,
+            None,
+            "upload_once",
+            &uploader::UploadContext::default(),
+        )
+        .await?;
+    if let Some(submission) = submission {
+        println!(
+            "{} archive {:?}: bvid={:?} aid={:?}",
+            if submission.appended { "appended to" } else { "submitted" },
+            submission.title,
+            submission.bvid,
+            submission.aid
+        );
+        if let Some(error) = &submission.collection_error {
+            eprintln!("warning: {error}");
+        }
+    }
     println!("upload test finished successfully");
     Ok(())
 }
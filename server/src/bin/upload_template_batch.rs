@@ -1,5 +1,5 @@
 use anyhow::{Context, Result, anyhow};
-use shared::UploadConfig;
+use shared::{CoverSource, UploadConfig};
 use sqlx::sqlite::SqliteConnectOptions;
 use sqlx::{Row, SqlitePool};
 use std::path::Path;
@@ -50,9 +50,33 @@ async fn main() -> Result<()> {
     );
     println!("uploading {} files...", files.len());
 
-    let uploader = uploader::UploadTarget::Bilibili.create_uploader();
-    uploader.upload(files, &config, None, &task_name).await?;
+    let uploader = uploader::UploadTarget::for_config(&config).create_uploader();
+    // Generated covers need the recording service; a fixed cover file works here too.
+    let cover = match &config.cover {
+        CoverSource::File { path } => Some(path.into()),
+        _ => None,
+    };
+    let context = uploader::UploadContext {
+        append_bvid: config.append_bvid.clone(),
+        cover,
+        ..Default::default()
+    };
+    let submission = uploader
+        .upload(files.into_iter().map(Into::into).collect(), &config, None, &task_name, &context)
+        .await?;
 
+    if let Some(submission) = submission {
+        println!(
+            "{} archive {:?}: bvid={:?} aid={:?}",
+            if submission.appended { "appended to" } else { "submitted" },
+            submission.title,
+            submission.bvid,
+            submission.aid
+        );
+        if let Some(error) = &submission.collection_error {
+            eprintln!("warning: {error}");
+        }
+    }
     println!("batch upload finished successfully");
     Ok(())
 }
@@ -0,0 +1,198 @@
+use shared::{CoverSource, UploadConfig};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::process::Command;
+
+use crate::{platform, uploader::UploadPart};
+
+const FFMPEG_PATH: &str = "ffmpeg";
+const FFPROBE_PATH: &str = "ffprobe";
+const SHARPEST_FRAME_SAMPLES: u32 = 8;
+const COVER_COMMAND_TIMEOUT: Duration = Duration::from_secs(60);
+const COVER_DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Cover image for one submission. Generated images live in a temp dir and are removed by
+/// [`PreparedCover::discard`]; a configured file is left alone.
+pub(crate) struct PreparedCover {
+    pub path: PathBuf,
+    temporary: bool,
+}
+
+impl PreparedCover {
+    pub(crate) async fn discard(self) {
+        if self.temporary
+            && let Err(e) = tokio::fs::remove_file(&self.path).await
+            && e.kind() != std::io::ErrorKind::NotFound
+        {
+            tracing::warn!("Failed to remove cover {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+/// Produces the cover `config` asks for. Failures only cost the cover, so they are logged and
+/// the archive is submitted without one.
+pub(crate) async fn prepare_cover(
+    config: &UploadConfig,
+    source_url: Option<&str>,
+    parts: &[UploadPart],
+    key: &str,
+) -> Option<PreparedCover> {
+    let result = match &config.cover {
+        CoverSource::Auto => return None,
+        CoverSource::File { path } => {
+            return Some(PreparedCover { path: PathBuf::from(path), temporary: false });
+        }
+        CoverSource::Frame { offset_secs } => {
+            let input = parts.first()?;
+            extract_frame_at(&input.filename, f64::from(*offset_secs), key).await
+        }
+        CoverSource::SharpestFrame => {
+            let input = parts.first()?;
+            extract_sharpest_frame(&input.filename, key).await
+        }
+        CoverSource::LiveRoom => download_live_cover(source_url?, key).await,
+    };
+    match result {
+        Ok(path) => {
+            tracing::info!("Cover prepared: source={:?}, path={}", config.cover, path.display());
+            Some(PreparedCover { path, temporary: true })
+        }
+        Err(e) => {
+            tracing::warn!("Cover unavailable, source={:?}: {}", config.cover, e);
+            None
+        }
+    }
+}
+
+fn cover_dir() -> PathBuf {
+    std::env::temp_dir().join("omnistream-covers")
+}
+
+async fn extract_frame_at(input: &str, offset_secs: f64, key: &str) -> Result<PathBuf, String> {
+    tokio::fs::create_dir_all(cover_dir()).await.map_err(|e| e.to_string())?;
+    let output = cover_dir().join(format!("{key}.jpg"));
+    if extract_frame(input, offset_secs, &output).await.is_ok() {
+        return Ok(output);
+    }
+    // The offset may lie past the end of a short first part.
+    extract_frame(input, 0.0, &output).await?;
+    Ok(output)
+}
+
+/// Samples frames across the part and keeps the one that compresses largest: at the same
+/// quality, blurry or flat frames encode to fewer bytes than detailed ones.
+async fn extract_sharpest_frame(input: &str, key: &str) -> Result<PathBuf, String> {
+    tokio::fs::create_dir_all(cover_dir()).await.map_err(|e| e.to_string())?;
+    let duration = probe_duration(input).await.unwrap_or_default();
+    let mut best: Option<(PathBuf, u64)> = None;
+    for (index, offset) in sample_offsets(duration, SHARPEST_FRAME_SAMPLES).into_iter().enumerate()
+    {
+        let candidate = cover_dir().join(format!("{key}-{index}.jpg"));
+        if extract_frame(input, offset, &candidate).await.is_err() {
+            continue;
+        }
+        let size = tokio::fs::metadata(&candidate).await.map(|m| m.len()).unwrap_or_default();
+        match &best {
+            Some((_, best_size)) if *best_size >= size => {
+                let _ = tokio::fs::remove_file(&candidate).await;
+            }
+            _ => {
+                if let Some((previous, _)) = best.replace((candidate, size)) {
+                    let _ = tokio::fs::remove_file(previous).await;
+                }
+            }
+        }
+    }
+    let (path, _) = best.ok_or_else(|| format!("no frame could be extracted from {input}"))?;
+    let output = cover_dir().join(format!("{key}.jpg"));
+    tokio::fs::rename(&path, &output).await.map_err(|e| e.to_string())?;
+    Ok(output)
+}
+
+/// Evenly spaced sample points, each in the middle of its slice of the part.
+fn sample_offsets(duration_secs: f64, samples: u32) -> Vec<f64> {
+    if duration_secs <= 0.0 || samples == 0 {
+        return vec![0.0];
+    }
+    let step = duration_secs / f64::from(samples);
+    (0..samples).map(|index| step * (f64::from(index) + 0.5)).collect()
+}
+
+async fn extract_frame(input: &str, offset_secs: f64, output: &Path) -> Result<(), String> {
+    let mut command = Command::new(FFMPEG_PATH);
+    command
+        .args(["-v", "error", "-y", "-ss"])
+        .arg(format!("{offset_secs:.3}"))
+        .arg("-i")
+        .arg(input)
+        .args(["-frames:v", "1", "-q:v", "2"])
+        .arg(output)
+        .kill_on_drop(true);
+    let result = tokio::time::timeout(COVER_COMMAND_TIMEOUT, command.output())
+        .await
+        .map_err(|_| format!("ffmpeg timed out extracting a frame from {input}"))?
+        .map_err(|e| format!("failed to execute ffmpeg for {input}: {e}"))?;
+    if !result.status.success() {
+        return Err(format!(
+            "ffmpeg exited with {} for {input}: {}",
+            result.status,
+            String::from_utf8_lossy(&result.stderr).trim()
+        ));
+    }
+    match tokio::fs::metadata(output).await {
+        Ok(metadata) if metadata.len() > 0 => Ok(()),
+        _ => Err(format!("ffmpeg wrote no frame at {offset_secs:.0}s of {input}")),
+    }
+}
+
+async fn probe_duration(input: &str) -> Option<f64> {
+    let mut command = Command::new(FFPROBE_PATH);
+    command
+        .args(["-v", "error", "-show_entries", "format=duration", "-of", "csv=p=0"])
+        .arg(input)
+        .kill_on_drop(true);
+    let output = match tokio::time::timeout(COVER_COMMAND_TIMEOUT, command.output()).await {
+        Ok(output) => output.ok()?,
+        Err(_) => {
+            tracing::warn!("Timed out reading the duration of {} with ffprobe", input);
+            return None;
+        }
+    };
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+async fn download_live_cover(source_url: &str, key: &str) -> Result<PathBuf, String> {
+    let cover_url = platform::fetch_live_cover_url(source_url)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("no live room cover for {source_url}"))?;
+    let bytes = reqwest::Client::builder()
+        .timeout(COVER_DOWNLOAD_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?
+        .get(&cover_url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("failed to download cover {cover_url}: {e}"))?
+        .bytes()
+        .await
+        .map_err(|e| format!("failed to download cover {cover_url}: {e}"))?;
+
+    let extension = if cover_url.to_ascii_lowercase().contains(".png") { "png" } else { "jpg" };
+    tokio::fs::create_dir_all(cover_dir()).await.map_err(|e| e.to_string())?;
+    let output = cover_dir().join(format!("{key}.{extension}"));
+    tokio::fs::write(&output, &bytes).await.map_err(|e| e.to_string())?;
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sample_offsets;
+
+    #[test]
+    fn sample_offsets_spread_over_the_part() {
+        assert_eq!(sample_offsets(80.0, 4), vec![10.0, 30.0, 50.0, 70.0]);
+        assert_eq!(sample_offsets(0.0, 4), vec![0.0]);
+    }
+}
@@ -5,10 +5,16 @@ use sqlx::{
 use std::error::Error;
 use std::path::Path;
 
+pub use archives::SubmittedArchive;
+
+mod archives;
 mod downloads;
+mod history;
 mod migrations;
+mod segments;
 mod settings;
 mod tasks;
+mod upload_jobs;
 mod uploads;
 
 #[derive(Clone)]
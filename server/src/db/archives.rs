@@ -0,0 +1,116 @@
+use sqlx::Row;
+use std::error::Error;
+
+use super::Db;
+
+/// An archive created (or extended) by an upload config, kept so later sessions of the same
+/// source can append to it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SubmittedArchive {
+    pub task_id: String,
+    pub source_url: String,
+    pub template_id: Option<String>,
+    pub account_file: String,
+    pub aid: Option<u64>,
+    pub bvid: Option<String>,
+    pub submitted_at: i64,
+}
+
+impl Db {
+    pub async fn save_submitted_archive(
+        &self,
+        archive: &SubmittedArchive,
+    ) -> Result<(), Box<dyn Error>> {
+        sqlx::query(
+            r#"
+            INSERT INTO submitted_archives (
+                task_id, source_url, template_id, account_file, aid, bvid, submitted_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&archive.task_id)
+        .bind(&archive.source_url)
+        .bind(&archive.template_id)
+        .bind(&archive.account_file)
+        .bind(archive.aid.map(|aid| i64::try_from(aid).unwrap_or(i64::MAX)))
+        .bind(&archive.bvid)
+        .bind(archive.submitted_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// BV id of the newest archive the same template and account submitted for `source_url`
+    /// at or after `since`.
+    pub async fn find_recent_archive_bvid(
+        &self,
+        source_url: &str,
+        template_id: Option<&str>,
+        account_file: &str,
+        since: i64,
+    ) -> Result<Option<String>, Box<dyn Error>> {
+        let row = sqlx::query(
+            r#"
+            SELECT bvid FROM submitted_archives
+            WHERE source_url = ? AND template_id IS ? AND account_file = ?
+                AND submitted_at >= ? AND bvid IS NOT NULL
+            ORDER BY submitted_at DESC, id DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(source_url)
+        .bind(template_id)
+        .bind(account_file)
+        .bind(since)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(|row| row.get("bvid")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Db, SubmittedArchive};
+    use std::path::PathBuf;
+    use uuid::Uuid;
+
+    fn temp_db_path() -> PathBuf {
+        std::env::temp_dir().join(format!("omnistream-db-archives-{}.db", Uuid::new_v4()))
+    }
+
+    #[tokio::test]
+    async fn recent_archive_matches_source_template_account_and_window() {
+        let path = temp_db_path();
+        let db = Db::new(path.to_str().expect("db path")).await.expect("open db");
+        let archive = |bvid: &str, template_id: &str, submitted_at| SubmittedArchive {
+            task_id: "task".to_string(),
+            source_url: "https://live.bilibili.com/6".to_string(),
+            template_id: Some(template_id.to_string()),
+            account_file: "cookies.json".to_string(),
+            aid: Some(1),
+            bvid: Some(bvid.to_string()),
+            submitted_at,
+        };
+        db.save_submitted_archive(&archive("BV1old0000000", "t1", 100)).await.expect("save");
+        db.save_submitted_archive(&archive("BV1new0000000", "t1", 200)).await.expect("save");
+        db.save_submitted_archive(&archive("BV1other00000", "t2", 300)).await.expect("save");
+
+        let find = |template_id: &'static str, since| {
+            let db = db.clone();
+            async move {
+                db.find_recent_archive_bvid(
+                    "https://live.bilibili.com/6",
+                    Some(template_id),
+                    "cookies.json",
+                    since,
+                )
+                .await
+                .expect("query")
+            }
+        };
+        assert_eq!(find("t1", 50).await.as_deref(), Some("BV1new0000000"));
+        assert_eq!(find("t1", 250).await, None);
+        assert_eq!(find("t2", 250).await.as_deref(), Some("BV1other00000"));
+    }
+}
@@ -7,7 +7,7 @@ use super::Db;
 impl Db {
     pub async fn get_downloads(&self) -> Result<Vec<DownloadConfig>, Box<dyn Error>> {
         let rows = sqlx::query(
-            "SELECT id, name, url, linked_upload_ids, enabled, use_custom_recording_settings, recording_settings FROM downloads",
+            "SELECT id, name, url, linked_upload_ids, enabled, use_custom_recording_settings, recording_settings, recorder_options FROM downloads",
         )
             .fetch_all(&self.pool)
             .await?;
@@ -78,6 +78,32 @@ impl Db {
                         }
                     });
 
+                let recorder_options_json: Option<String> = match row.try_get("recorder_options") {
+                    Ok(value) => value,
+                    Err(e) => {
+                        tracing::warn!(
+                            "Failed to read recorder_options column for download_id={}: {}",
+                            id,
+                            e
+                        );
+                        None
+                    }
+                };
+                let recorder_options = recorder_options_json
+                    .as_deref()
+                    .and_then(|json| match serde_json::from_str(json) {
+                        Ok(options) => Some(options),
+                        Err(e) => {
+                            tracing::warn!(
+                                "Failed to parse recorder_options for download_id={}: {}",
+                                id,
+                                e
+                            );
+                            None
+                        }
+                    })
+                    .unwrap_or_default();
+
                 DownloadConfig {
                     id,
                     name: row.get("name"),
@@ -88,6 +114,9 @@ impl Db {
                     use_custom_recording_settings: use_custom_recording_settings != 0,
                     recording_settings,
                     recording_file_size_bytes: 0,
+                    latest_segment: None,
+                    upload_progress: None,
+                    recorder_options,
                 }
             })
             .collect();
@@ -101,18 +130,20 @@ impl Db {
             config.recording_settings.as_ref().map(serde_json::to_string).transpose()?;
         let use_custom_recording_settings =
             if config.use_custom_recording_settings { 1 } else { 0 };
+        let recorder_options_json = serde_json::to_string(&config.recorder_options)?;
 
         sqlx::query(
             r#"
-            INSERT INTO downloads (id, name, url, linked_upload_ids, enabled, use_custom_recording_settings, recording_settings)
-            VALUES (?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO downloads (id, name, url, linked_upload_ids, enabled, use_custom_recording_settings, recording_settings, recorder_options)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
             ON CONFLICT(id) DO UPDATE SET
                 name = excluded.name,
                 url = excluded.url,
                 linked_upload_ids = excluded.linked_upload_ids,
                 enabled = excluded.enabled,
                 use_custom_recording_settings = excluded.use_custom_recording_settings,
-                recording_settings = excluded.recording_settings
+                recording_settings = excluded.recording_settings,
+                recorder_options = excluded.recorder_options
             "#,
         )
         .bind(&config.id)
@@ -122,6 +153,7 @@ impl Db {
         .bind(if config.enabled { 1 } else { 0 })
         .bind(use_custom_recording_settings)
         .bind(recording_settings_json)
+        .bind(recorder_options_json)
         .execute(&self.pool)
         .await?;
 
@@ -167,9 +199,10 @@ mod tests {
             .execute(
                 r#"
                 INSERT INTO downloads (
-                    id, name, url, linked_upload_ids, use_custom_recording_settings, recording_settings
+                    id, name, url, linked_upload_ids, use_custom_recording_settings, recording_settings,
+                    recorder_options
                 )
-                VALUES ('d1', 'demo', 'https://example.com', 'not-json', 1, '{bad-json')
+                VALUES ('d1', 'demo', 'https://example.com', 'not-json', 1, '{bad-json', '[1')
                 "#,
             )
             .await
@@ -180,5 +213,6 @@ mod tests {
         assert!(downloads[0].linked_upload_ids.is_empty());
         assert!(downloads[0].recording_settings.is_none());
         assert!(downloads[0].use_custom_recording_settings);
+        assert!(downloads[0].recorder_options.is_default());
     }
 }
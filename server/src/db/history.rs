@@ -0,0 +1,235 @@
+use shared::UploadHistoryEntry;
+use sqlx::{Row, sqlite::SqliteRow};
+use std::collections::HashMap;
+use std::error::Error;
+
+use super::Db;
+
+const UPLOAD_HISTORY_COLUMNS: &str = r#"
+    id, job_id, task_id, task_name, download_id, template_id, account_file, aid, bvid, title,
+    appended, files, total_size, attempts, error, collection_error, queued_at, started_at,
+    finished_at
+"#;
+
+impl Db {
+    /// Inserts the entry, replacing an earlier outcome of the same job config (e.g. a failure
+    /// that a manual retry later turned into a success).
+    pub async fn save_upload_history(
+        &self,
+        entry: &UploadHistoryEntry,
+    ) -> Result<(), Box<dyn Error>> {
+        let files_json = serde_json::to_string(&entry.files)?;
+
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO upload_history (
+                id, job_id, task_id, task_name, download_id, template_id, account_file, aid,
+                bvid, title, appended, files, total_size, attempts, error, collection_error,
+                queued_at, started_at, finished_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&entry.id)
+        .bind(&entry.job_id)
+        .bind(&entry.task_id)
+        .bind(&entry.task_name)
+        .bind(&entry.download_id)
+        .bind(&entry.template_id)
+        .bind(&entry.account_file)
+        .bind(entry.aid.map(|aid| i64::try_from(aid).unwrap_or(i64::MAX)))
+        .bind(&entry.bvid)
+        .bind(&entry.title)
+        .bind(entry.appended)
+        .bind(files_json)
+        .bind(i64::try_from(entry.total_size).unwrap_or(i64::MAX))
+        .bind(i64::from(entry.attempts))
+        .bind(&entry.error)
+        .bind(&entry.collection_error)
+        .bind(entry.queued_at)
+        .bind(entry.started_at)
+        .bind(entry.finished_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Newest entries first; returns the requested page and the total number of entries.
+    pub async fn get_upload_history(
+        &self,
+        limit: u32,
+        offset: u64,
+    ) -> Result<(Vec<UploadHistoryEntry>, u64), Box<dyn Error>> {
+        let total: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM upload_history").fetch_one(&self.pool).await?;
+        let query = format!(
+            "SELECT {UPLOAD_HISTORY_COLUMNS} FROM upload_history
+            ORDER BY finished_at DESC, rowid DESC LIMIT ? OFFSET ?"
+        );
+        let rows = sqlx::query(sqlx::AssertSqlSafe(query))
+            .bind(i64::from(limit))
+            .bind(i64::try_from(offset).unwrap_or(i64::MAX))
+            .fetch_all(&self.pool)
+            .await?;
+        Ok((rows.iter().map(history_entry_from_row).collect(), u64::try_from(total)?))
+    }
+
+    /// Account of the newest successful upload of the template.
+    pub async fn last_upload_account(
+        &self,
+        template_id: Option<&str>,
+    ) -> Result<Option<String>, Box<dyn Error>> {
+        let account = sqlx::query_scalar(
+            r#"
+            SELECT account_file FROM upload_history
+            WHERE template_id IS ? AND error IS NULL
+            ORDER BY finished_at DESC, rowid DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(template_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(account)
+    }
+
+    /// Successful uploads per account finished at or after `since`.
+    pub async fn count_uploads_by_account(
+        &self,
+        since: i64,
+    ) -> Result<HashMap<String, u64>, Box<dyn Error>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT account_file, COUNT(*) AS uploads FROM upload_history
+            WHERE error IS NULL AND finished_at >= ?
+            GROUP BY account_file
+            "#,
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let uploads: i64 = row.get("uploads");
+                (row.get("account_file"), u64::try_from(uploads).unwrap_or_default())
+            })
+            .collect())
+    }
+}
+
+fn history_entry_from_row(row: &SqliteRow) -> UploadHistoryEntry {
+    let id: String = row.get("id");
+    let files_json: String = row.get("files");
+    let files = serde_json::from_str(&files_json).unwrap_or_else(|e| {
+        tracing::warn!("Failed to parse files for upload_history_id={}: {}", id, e);
+        vec![]
+    });
+    let aid: Option<i64> = row.get("aid");
+    let total_size: i64 = row.get("total_size");
+    let attempts: i64 = row.get("attempts");
+
+    UploadHistoryEntry {
+        id,
+        job_id: row.get("job_id"),
+        task_id: row.get("task_id"),
+        task_name: row.get("task_name"),
+        download_id: row.get("download_id"),
+        template_id: row.get("template_id"),
+        account_file: row.get("account_file"),
+        aid: aid.and_then(|aid| u64::try_from(aid).ok()),
+        bvid: row.get("bvid"),
+        title: row.get("title"),
+        appended: row.get("appended"),
+        files,
+        total_size: u64::try_from(total_size).unwrap_or_default(),
+        attempts: u32::try_from(attempts).unwrap_or_default(),
+        error: row.get("error"),
+        collection_error: row.get("collection_error"),
+        queued_at: row.get("queued_at"),
+        started_at: row.get("started_at"),
+        finished_at: row.get("finished_at"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Db;
+    use shared::UploadHistoryEntry;
+    use std::path::PathBuf;
+    use uuid::Uuid;
+
+    fn temp_db_path() -> PathBuf {
+        std::env::temp_dir().join(format!("omnistream-db-history-{}.db", Uuid::new_v4()))
+    }
+
+    fn entry(id: &str, finished_at: i64) -> UploadHistoryEntry {
+        UploadHistoryEntry {
+            id: id.to_string(),
+            job_id: "job".to_string(),
+            task_id: "task".to_string(),
+            task_name: "主播".to_string(),
+            account_file: "cookies.json".to_string(),
+            files: vec!["a.mp4".to_string(), "b.mp4".to_string()],
+            total_size: 1024,
+            attempts: 1,
+            finished_at,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn history_pages_newest_first_and_replaces_same_config() {
+        let path = temp_db_path();
+        let db = Db::new(path.to_str().expect("db path")).await.expect("open db");
+        for (index, finished_at) in [100, 300, 200].into_iter().enumerate() {
+            db.save_upload_history(&entry(&format!("job:{index}"), finished_at))
+                .await
+                .expect("save entry");
+        }
+        let mut retried = entry("job:0", 400);
+        retried.aid = Some(42);
+        retried.bvid = Some("BV1xx411c7mD".to_string());
+        retried.attempts = 2;
+        retried.collection_error = Some("合集小节不存在".to_string());
+        db.save_upload_history(&retried).await.expect("replace entry");
+
+        let (first, total) = db.get_upload_history(2, 0).await.expect("first page");
+        assert_eq!(total, 3);
+        assert_eq!(first, vec![retried, entry("job:1", 300)]);
+        let (second, _) = db.get_upload_history(2, 2).await.expect("second page");
+        assert_eq!(second, vec![entry("job:2", 200)]);
+    }
+
+    #[tokio::test]
+    async fn account_usage_counts_successful_uploads() {
+        let path = temp_db_path();
+        let db = Db::new(path.to_str().expect("db path")).await.expect("open db");
+        let upload =
+            |id: &str, account: &str, finished_at, error: Option<&str>| UploadHistoryEntry {
+                template_id: Some("t1".to_string()),
+                account_file: account.to_string(),
+                error: error.map(str::to_string),
+                ..entry(id, finished_at)
+            };
+        for entry in [
+            upload("job:0", "a.json", 100, None),
+            upload("job:1", "b.json", 200, None),
+            upload("job:2", "b.json", 300, None),
+            upload("job:3", "a.json", 400, Some("账号登录失效")),
+            upload("job:4", "c.json", 50, None),
+        ] {
+            db.save_upload_history(&entry).await.expect("save entry");
+        }
+
+        let counts = db.count_uploads_by_account(100).await.expect("count");
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts["a.json"], 1);
+        assert_eq!(counts["b.json"], 2);
+        assert_eq!(
+            db.last_upload_account(Some("t1")).await.expect("last").as_deref(),
+            Some("b.json")
+        );
+        assert_eq!(db.last_upload_account(Some("t2")).await.expect("last"), None);
+    }
+}
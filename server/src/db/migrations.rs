@@ -1,6 +1,8 @@
 use sqlx::{Pool, Row, Sqlite};
 use std::error::Error;
 
+use super::upload_jobs::config_statuses_from_completed;
+
 #[derive(Clone, Copy)]
 struct Migration {
     version: i64,
@@ -12,6 +14,15 @@ const MIGRATIONS: &[Migration] = &[
     Migration { version: 2, name: "add_tasks_upload_configs" },
     Migration { version: 3, name: "add_download_recording_settings" },
     Migration { version: 4, name: "add_download_enabled" },
+    Migration { version: 5, name: "create_recording_segments" },
+    Migration { version: 6, name: "add_recording_segment_title" },
+    Migration { version: 7, name: "add_download_recorder_options" },
+    Migration { version: 8, name: "create_upload_jobs" },
+    Migration { version: 9, name: "add_upload_job_kind" },
+    Migration { version: 10, name: "create_submitted_archives" },
+    Migration { version: 11, name: "create_upload_history" },
+    Migration { version: 12, name: "add_upload_job_config_statuses" },
+    Migration { version: 13, name: "add_upload_history_collection_error" },
 ];
 
 pub async fn run_migrations(pool: &Pool<Sqlite>) -> Result<(), Box<dyn Error>> {
@@ -129,6 +140,183 @@ async fn apply_migration(pool: &Pool<Sqlite>, migration: Migration) -> Result<()
                     .await?;
             }
         }
+        5 => {
+            sqlx::query(
+                r#"
+                CREATE TABLE IF NOT EXISTS recording_segments (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    task_id TEXT NOT NULL,
+                    filename TEXT NOT NULL,
+                    started_at INTEGER NOT NULL,
+                    ended_at INTEGER NOT NULL,
+                    size_bytes INTEGER NOT NULL DEFAULT 0,
+                    duration_sec REAL,
+                    bit_rate_kbps INTEGER,
+                    frame_rate REAL,
+                    width INTEGER,
+                    height INTEGER,
+                    video_codec TEXT,
+                    audio_codec TEXT
+                );
+                "#,
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                "CREATE INDEX IF NOT EXISTS idx_recording_segments_task_id ON recording_segments (task_id)",
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        6 => {
+            if !column_exists(&mut tx, "recording_segments", "title").await? {
+                sqlx::query("ALTER TABLE recording_segments ADD COLUMN title TEXT")
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+        7 => {
+            if !column_exists(&mut tx, "downloads", "recorder_options").await? {
+                sqlx::query("ALTER TABLE downloads ADD COLUMN recorder_options TEXT")
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+        8 => {
+            sqlx::query(
+                r#"
+                CREATE TABLE IF NOT EXISTS upload_jobs (
+                    id TEXT PRIMARY KEY,
+                    task_id TEXT NOT NULL,
+                    task_name TEXT NOT NULL,
+                    live_title TEXT,
+                    parts TEXT NOT NULL,
+                    configs TEXT NOT NULL,
+                    auto_cleanup_after_upload INTEGER NOT NULL DEFAULT 0,
+                    state TEXT NOT NULL,
+                    priority INTEGER NOT NULL DEFAULT 0,
+                    attempts INTEGER NOT NULL DEFAULT 0,
+                    max_attempts INTEGER NOT NULL,
+                    completed_configs INTEGER NOT NULL DEFAULT 0,
+                    last_error TEXT,
+                    next_attempt_at INTEGER NOT NULL,
+                    created_at INTEGER NOT NULL,
+                    updated_at INTEGER NOT NULL
+                );
+                "#,
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                "CREATE INDEX IF NOT EXISTS idx_upload_jobs_state ON upload_jobs (state, next_attempt_at)",
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        9 => {
+            if !column_exists(&mut tx, "upload_jobs", "kind").await? {
+                sqlx::query(
+                    "ALTER TABLE upload_jobs ADD COLUMN kind TEXT NOT NULL DEFAULT 'archive'",
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+        10 => {
+            sqlx::query(
+                r#"
+                CREATE TABLE IF NOT EXISTS submitted_archives (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    task_id TEXT NOT NULL,
+                    source_url TEXT NOT NULL,
+                    template_id TEXT,
+                    account_file TEXT NOT NULL,
+                    aid INTEGER,
+                    bvid TEXT,
+                    submitted_at INTEGER NOT NULL
+                );
+                "#,
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                "CREATE INDEX IF NOT EXISTS idx_submitted_archives_source ON submitted_archives (source_url, submitted_at)",
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        11 => {
+            sqlx::query(
+                r#"
+                CREATE TABLE IF NOT EXISTS upload_history (
+                    id TEXT PRIMARY KEY,
+                    job_id TEXT NOT NULL,
+                    task_id TEXT NOT NULL,
+                    task_name TEXT NOT NULL,
+                    download_id TEXT,
+                    template_id TEXT,
+                    account_file TEXT NOT NULL,
+                    aid INTEGER,
+                    bvid TEXT,
+                    title TEXT,
+                    appended INTEGER NOT NULL DEFAULT 0,
+                    files TEXT NOT NULL,
+                    total_size INTEGER NOT NULL DEFAULT 0,
+                    attempts INTEGER NOT NULL DEFAULT 0,
+                    error TEXT,
+                    queued_at INTEGER NOT NULL,
+                    started_at INTEGER NOT NULL,
+                    finished_at INTEGER NOT NULL
+                );
+                "#,
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                "CREATE INDEX IF NOT EXISTS idx_upload_history_finished ON upload_history (finished_at)",
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        12 => {
+            if !column_exists(&mut tx, "upload_jobs", "config_statuses").await? {
+                sqlx::query(
+                    "ALTER TABLE upload_jobs ADD COLUMN config_statuses TEXT NOT NULL DEFAULT '[]'",
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            // Older jobs only know how many leading configs succeeded.
+            let rows = sqlx::query(
+                "SELECT id, configs, completed_configs FROM upload_jobs WHERE config_statuses = '[]'",
+            )
+            .fetch_all(&mut *tx)
+            .await?;
+            for row in rows {
+                let configs: Vec<serde_json::Value> =
+                    serde_json::from_str(&row.get::<String, _>("configs")).unwrap_or_default();
+                let completed =
+                    usize::try_from(row.get::<i64, _>("completed_configs")).unwrap_or_default();
+                let statuses = config_statuses_from_completed(configs.len(), completed);
+                sqlx::query("UPDATE upload_jobs SET config_statuses = ? WHERE id = ?")
+                    .bind(serde_json::to_string(&statuses)?)
+                    .bind(row.get::<String, _>("id"))
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+        13 => {
+            if !column_exists(&mut tx, "upload_history", "collection_error").await? {
+                sqlx::query("ALTER TABLE upload_history ADD COLUMN collection_error TEXT")
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
         _ => return Err(format!("unknown migration version: {}", migration.version).into()),
     }
 
@@ -155,6 +343,7 @@ async fn column_exists(
 #[cfg(test)]
 mod tests {
     use super::run_migrations;
+    use shared::{UploadConfigState, UploadConfigStatus};
     use sqlx::{
         Executor, Pool, Row, Sqlite,
         sqlite::{SqliteConnectOptions, SqlitePoolOptions},
@@ -196,7 +385,7 @@ mod tests {
                 .fetch_all(&pool)
                 .await
                 .expect("fetch versions");
-        assert_eq!(versions, vec![1, 2, 3, 4]);
+        assert_eq!(versions, vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13]);
 
         let task_columns = column_names(&pool, "tasks").await;
         assert!(task_columns.contains(&"upload_configs".to_string()));
@@ -205,6 +394,60 @@ mod tests {
         assert!(download_columns.contains(&"use_custom_recording_settings".to_string()));
         assert!(download_columns.contains(&"recording_settings".to_string()));
         assert!(download_columns.contains(&"enabled".to_string()));
+        assert!(download_columns.contains(&"recorder_options".to_string()));
+
+        let segment_columns = column_names(&pool, "recording_segments").await;
+        assert!(segment_columns.contains(&"task_id".to_string()));
+        assert!(segment_columns.contains(&"bit_rate_kbps".to_string()));
+        assert!(segment_columns.contains(&"title".to_string()));
+
+        let upload_job_columns = column_names(&pool, "upload_jobs").await;
+        assert!(upload_job_columns.contains(&"priority".to_string()));
+        assert!(upload_job_columns.contains(&"next_attempt_at".to_string()));
+        assert!(upload_job_columns.contains(&"kind".to_string()));
+        assert!(upload_job_columns.contains(&"config_statuses".to_string()));
+
+        let history_columns = column_names(&pool, "upload_history").await;
+        assert!(history_columns.contains(&"bvid".to_string()));
+        assert!(history_columns.contains(&"total_size".to_string()));
+        assert!(history_columns.contains(&"collection_error".to_string()));
+    }
+
+    #[tokio::test]
+    async fn config_statuses_are_backfilled_from_completed_configs() {
+        let path = temp_db_path("config-statuses");
+        let pool = open_pool(&path).await;
+        run_migrations(&pool).await.expect("run migrations");
+
+        // Replay the last migration against a job stored before statuses were tracked.
+        pool.execute(
+            r#"
+            INSERT INTO upload_jobs (
+                id, task_id, task_name, parts, configs, state, max_attempts, completed_configs,
+                next_attempt_at, created_at, updated_at
+            )
+            VALUES ('job', 'task', 'name', '[]', '[{}, {}, {}]', 'retrying', 3, 1, 0, 0, 0);
+            DELETE FROM schema_migrations WHERE version = 12;
+            "#,
+        )
+        .await
+        .expect("seed legacy job");
+        run_migrations(&pool).await.expect("rerun migrations");
+
+        let raw: String = sqlx::query_scalar("SELECT config_statuses FROM upload_jobs")
+            .fetch_one(&pool)
+            .await
+            .expect("fetch statuses");
+        let statuses: Vec<UploadConfigStatus> = serde_json::from_str(&raw).expect("parse statuses");
+        let states: Vec<_> = statuses.iter().map(|status| status.state).collect();
+        assert_eq!(
+            states,
+            vec![
+                UploadConfigState::Succeeded,
+                UploadConfigState::Pending,
+                UploadConfigState::Pending
+            ]
+        );
     }
 
     #[tokio::test]
@@ -283,7 +526,7 @@ mod tests {
                 .fetch_all(&pool)
                 .await
                 .expect("fetch versions");
-        assert_eq!(versions, vec![1, 2, 3, 4]);
+        assert_eq!(versions, vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13]);
 
         let task_columns = column_names(&pool, "tasks").await;
         assert!(task_columns.contains(&"upload_configs".to_string()));
@@ -292,5 +535,6 @@ mod tests {
         assert!(download_columns.contains(&"use_custom_recording_settings".to_string()));
         assert!(download_columns.contains(&"recording_settings".to_string()));
         assert!(download_columns.contains(&"enabled".to_string()));
+        assert!(download_columns.contains(&"recorder_options".to_string()));
     }
 }
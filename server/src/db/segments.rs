@@ -0,0 +1,129 @@
+use shared::RecordingSegment;
+use sqlx::{Row, sqlite::SqliteRow};
+use std::collections::HashMap;
+use std::error::Error;
+
+use super::Db;
+
+impl Db {
+    pub async fn save_recording_segment(
+        &self,
+        task_id: &str,
+        segment: &RecordingSegment,
+    ) -> Result<(), Box<dyn Error>> {
+        sqlx::query(
+            r#"
+            INSERT INTO recording_segments (
+                task_id, filename, title, started_at, ended_at, size_bytes, duration_sec,
+                bit_rate_kbps, frame_rate, width, height, video_codec, audio_codec
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(task_id)
+        .bind(&segment.filename)
+        .bind(&segment.title)
+        .bind(segment.started_at)
+        .bind(segment.ended_at)
+        .bind(i64::try_from(segment.size_bytes).unwrap_or(i64::MAX))
+        .bind(segment.duration_sec)
+        .bind(segment.bit_rate_kbps.map(|v| i64::try_from(v).unwrap_or(i64::MAX)))
+        .bind(segment.frame_rate)
+        .bind(segment.width.map(i64::from))
+        .bind(segment.height.map(i64::from))
+        .bind(&segment.video_codec)
+        .bind(&segment.audio_codec)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub(super) async fn recording_segments_by_task(
+        &self,
+    ) -> Result<HashMap<String, Vec<RecordingSegment>>, Box<dyn Error>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT task_id, filename, title, started_at, ended_at, size_bytes, duration_sec,
+                   bit_rate_kbps, frame_rate, width, height, video_codec, audio_codec
+            FROM recording_segments
+            ORDER BY id
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut segments: HashMap<String, Vec<RecordingSegment>> = HashMap::new();
+        for row in rows {
+            let task_id: String = row.get("task_id");
+            segments.entry(task_id).or_default().push(segment_from_row(&row));
+        }
+        Ok(segments)
+    }
+}
+
+fn segment_from_row(row: &SqliteRow) -> RecordingSegment {
+    let size_bytes: i64 = row.get("size_bytes");
+    let bit_rate_kbps: Option<i64> = row.get("bit_rate_kbps");
+    let width: Option<i64> = row.get("width");
+    let height: Option<i64> = row.get("height");
+
+    RecordingSegment {
+        filename: row.get("filename"),
+        title: row.get("title"),
+        started_at: row.get("started_at"),
+        ended_at: row.get("ended_at"),
+        size_bytes: u64::try_from(size_bytes).unwrap_or_default(),
+        duration_sec: row.get("duration_sec"),
+        bit_rate_kbps: bit_rate_kbps.and_then(|v| u64::try_from(v).ok()),
+        frame_rate: row.get("frame_rate"),
+        width: width.and_then(|v| u32::try_from(v).ok()),
+        height: height.and_then(|v| u32::try_from(v).ok()),
+        video_codec: row.get("video_codec"),
+        audio_codec: row.get("audio_codec"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Db;
+    use shared::RecordingSegment;
+    use std::path::PathBuf;
+    use uuid::Uuid;
+
+    fn temp_db_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("omnistream-db-segments-{name}-{}.db", Uuid::new_v4()))
+    }
+
+    #[tokio::test]
+    async fn recording_segments_round_trip_grouped_by_task() {
+        let path = temp_db_path("round-trip");
+        let db = Db::new(path.to_str().expect("db path")).await.expect("open db");
+
+        let probed = RecordingSegment {
+            filename: "a.mp4".to_string(),
+            title: Some("排位冲分".to_string()),
+            started_at: 100,
+            ended_at: 200,
+            size_bytes: 4096,
+            duration_sec: Some(100.0),
+            bit_rate_kbps: Some(6000),
+            frame_rate: Some(60.0),
+            width: Some(1920),
+            height: Some(1080),
+            video_codec: Some("h264".to_string()),
+            audio_codec: Some("aac".to_string()),
+        };
+        let unprobed = RecordingSegment {
+            filename: "b.mp4".to_string(),
+            started_at: 200,
+            ended_at: 300,
+            ..Default::default()
+        };
+        db.save_recording_segment("t1", &probed).await.expect("save probed segment");
+        db.save_recording_segment("t1", &unprobed).await.expect("save unprobed segment");
+
+        let segments = db.recording_segments_by_task().await.expect("read segments");
+
+        assert_eq!(segments.get("t1"), Some(&vec![probed, unprobed]));
+    }
+}
@@ -37,6 +37,7 @@ impl Db {
         let rows = sqlx::query("SELECT id, name, url, status, filename, upload_configs FROM tasks")
             .fetch_all(&self.pool)
             .await?;
+        let mut segments = self.recording_segments_by_task().await?;
 
         let tasks = rows
             .into_iter()
@@ -61,13 +62,18 @@ impl Db {
                     None => vec![],
                 };
 
+                let id: String = row.get("id");
+                let segments = segments.remove(&id).unwrap_or_default();
+
                 StreamTask {
-                    id: row.get("id"),
+                    id,
                     name: row.get("name"),
                     url: row.get("url"),
                     status,
                     filename: row.get("filename"),
                     upload_configs,
+                    segments,
+                    upload_progress: None,
                 }
             })
             .collect();
@@ -117,7 +123,11 @@ fn parse_status(raw: &str) -> TaskStatus {
         "Uploading" => TaskStatus::Uploading,
         "Stopped" => TaskStatus::Stopped,
         "Completed" => TaskStatus::Completed,
+        "PendingUpload" => TaskStatus::PendingUpload,
         s if s.starts_with("Error:") => TaskStatus::Error(s[6..].to_string()),
+        s if s.starts_with("PartiallyCompleted:") => {
+            TaskStatus::PartiallyCompleted(s[19..].to_string())
+        }
         _ => TaskStatus::Idle,
     }
 }
@@ -129,7 +139,9 @@ fn stringify_status(status: &TaskStatus) -> String {
         TaskStatus::Uploading => "Uploading".to_string(),
         TaskStatus::Stopped => "Stopped".to_string(),
         TaskStatus::Completed => "Completed".to_string(),
+        TaskStatus::PendingUpload => "PendingUpload".to_string(),
         TaskStatus::Error(e) => format!("Error:{e}"),
+        TaskStatus::PartiallyCompleted(e) => format!("PartiallyCompleted:{e}"),
     }
 }
 
@@ -144,12 +156,24 @@ mod tests {
         assert_eq!(stringify_status(&TaskStatus::Stopped), "Stopped");
     }
 
+    #[test]
+    fn pending_upload_status_round_trips() {
+        assert_eq!(parse_status("PendingUpload"), TaskStatus::PendingUpload);
+        assert_eq!(stringify_status(&TaskStatus::PendingUpload), "PendingUpload");
+    }
+
     #[test]
     fn error_status_round_trips() {
         assert_eq!(parse_status("Error:boom"), TaskStatus::Error("boom".to_string()));
         assert_eq!(stringify_status(&TaskStatus::Error("boom".to_string())), "Error:boom");
     }
 
+    #[test]
+    fn partially_completed_status_round_trips() {
+        let status = TaskStatus::PartiallyCompleted("config 2/2 failed".to_string());
+        assert_eq!(parse_status(&stringify_status(&status)), status);
+    }
+
     #[test]
     fn unknown_status_falls_back_to_idle() {
         assert_eq!(parse_status("mystery"), TaskStatus::Idle);
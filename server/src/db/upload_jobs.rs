@@ -0,0 +1,426 @@
+use shared::{
+    UploadConfig, UploadConfigState, UploadConfigStatus, UploadJob, UploadJobKind, UploadJobState,
+};
+use sqlx::{Row, sqlite::SqliteRow};
+use std::error::Error;
+
+use super::Db;
+
+const UPLOAD_JOB_COLUMNS: &str = r#"
+    id, task_id, task_name, kind, live_title, parts, configs, auto_cleanup_after_upload,
+    state, priority, attempts, max_attempts, completed_configs, config_statuses, last_error,
+    next_attempt_at, created_at, updated_at
+"#;
+
+impl Db {
+    /// Saves the job's progress. Priority is only written on insert, so a worker saving its copy
+    /// never undoes a priority change made while the job was uploading.
+    pub async fn save_upload_job(&self, job: &UploadJob) -> Result<(), Box<dyn Error>> {
+        let parts_json = serde_json::to_string(&job.parts)?;
+        let configs_json = serde_json::to_string(&job.configs)?;
+        let config_statuses_json = serde_json::to_string(&job.config_statuses)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO upload_jobs (
+                id, task_id, task_name, kind, live_title, parts, configs,
+                auto_cleanup_after_upload, state, priority, attempts, max_attempts,
+                completed_configs, config_statuses, last_error, next_attempt_at, created_at,
+                updated_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(id) DO UPDATE SET
+                state = excluded.state,
+                attempts = excluded.attempts,
+                max_attempts = excluded.max_attempts,
+                completed_configs = excluded.completed_configs,
+                config_statuses = excluded.config_statuses,
+                last_error = excluded.last_error,
+                next_attempt_at = excluded.next_attempt_at,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&job.id)
+        .bind(&job.task_id)
+        .bind(&job.task_name)
+        .bind(stringify_job_kind(job.kind))
+        .bind(&job.live_title)
+        .bind(parts_json)
+        .bind(configs_json)
+        .bind(job.auto_cleanup_after_upload)
+        .bind(stringify_job_state(job.state))
+        .bind(job.priority)
+        .bind(i64::from(job.attempts))
+        .bind(i64::from(job.max_attempts))
+        .bind(i64::try_from(job.completed_configs).unwrap_or(i64::MAX))
+        .bind(config_statuses_json)
+        .bind(&job.last_error)
+        .bind(job.next_attempt_at)
+        .bind(job.created_at)
+        .bind(job.updated_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Returns `false` when no unfinished job has this id.
+    pub async fn set_upload_job_priority(
+        &self,
+        id: &str,
+        priority: i64,
+        now: i64,
+    ) -> Result<bool, Box<dyn Error>> {
+        let result = sqlx::query(
+            r#"
+            UPDATE upload_jobs SET priority = ?, updated_at = ?
+            WHERE id = ? AND state IN ('queued', 'retrying', 'uploading')
+            "#,
+        )
+        .bind(priority)
+        .bind(now)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn get_upload_jobs(&self) -> Result<Vec<UploadJob>, Box<dyn Error>> {
+        let query = format!(
+            "SELECT {UPLOAD_JOB_COLUMNS} FROM upload_jobs ORDER BY created_at DESC, rowid DESC"
+        );
+        let rows = sqlx::query(sqlx::AssertSqlSafe(query)).fetch_all(&self.pool).await?;
+        Ok(rows.iter().map(upload_job_from_row).collect())
+    }
+
+    pub async fn get_upload_job(&self, id: &str) -> Result<Option<UploadJob>, Box<dyn Error>> {
+        let query = format!("SELECT {UPLOAD_JOB_COLUMNS} FROM upload_jobs WHERE id = ?");
+        let row =
+            sqlx::query(sqlx::AssertSqlSafe(query)).bind(id).fetch_optional(&self.pool).await?;
+        Ok(row.as_ref().map(upload_job_from_row))
+    }
+
+    /// Marks the most urgent due job as uploading and returns it with the attempt counted.
+    /// An archive job waits while segments of its task are still being uploaded.
+    pub async fn claim_next_upload_job(
+        &self,
+        now: i64,
+    ) -> Result<Option<UploadJob>, Box<dyn Error>> {
+        let id: Option<String> = sqlx::query_scalar(
+            r#"
+            SELECT id FROM upload_jobs AS job
+            WHERE state IN ('queued', 'retrying') AND next_attempt_at <= ?
+                AND NOT (
+                    job.kind = 'archive' AND EXISTS (
+                        SELECT 1 FROM upload_jobs AS segment
+                        WHERE segment.task_id = job.task_id
+                            AND segment.kind = 'segment'
+                            AND segment.state = 'uploading'
+                    )
+                )
+            ORDER BY priority DESC, created_at, rowid
+            LIMIT 1
+            "#,
+        )
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some(id) = id else {
+            return Ok(None);
+        };
+
+        let result = sqlx::query(
+            r#"
+            UPDATE upload_jobs
+            SET state = 'uploading', attempts = attempts + 1, updated_at = ?
+            WHERE id = ? AND state IN ('queued', 'retrying')
+            "#,
+        )
+        .bind(now)
+        .bind(&id)
+        .execute(&self.pool)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Ok(None);
+        }
+        self.get_upload_job(&id).await
+    }
+
+    /// Cancels a task's segment jobs that have not started; the archive job uploads those parts.
+    pub async fn cancel_pending_segment_jobs(
+        &self,
+        task_id: &str,
+        now: i64,
+    ) -> Result<u64, Box<dyn Error>> {
+        let result = sqlx::query(
+            r#"
+            UPDATE upload_jobs
+            SET state = 'cancelled', updated_at = ?
+            WHERE task_id = ? AND kind = 'segment' AND state IN ('queued', 'retrying')
+            "#,
+        )
+        .bind(now)
+        .bind(task_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Requeues jobs whose worker was lost with the previous process.
+    pub async fn requeue_interrupted_upload_jobs(&self) -> Result<u64, Box<dyn Error>> {
+        let result =
+            sqlx::query("UPDATE upload_jobs SET state = 'queued' WHERE state = 'uploading'")
+                .execute(&self.pool)
+                .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+fn upload_job_from_row(row: &SqliteRow) -> UploadJob {
+    let id: String = row.get("id");
+    let parts_json: String = row.get("parts");
+    let parts = serde_json::from_str(&parts_json).unwrap_or_else(|e| {
+        tracing::warn!("Failed to parse parts for upload_job_id={}: {}", id, e);
+        vec![]
+    });
+    let configs_json: String = row.get("configs");
+    let configs: Vec<UploadConfig> = serde_json::from_str(&configs_json).unwrap_or_else(|e| {
+        tracing::warn!("Failed to parse configs for upload_job_id={}: {}", id, e);
+        vec![]
+    });
+    let completed_configs: i64 = row.get("completed_configs");
+    let completed_configs = usize::try_from(completed_configs).unwrap_or_default();
+    let config_statuses_json: String = row.get("config_statuses");
+    let mut config_statuses: Vec<UploadConfigStatus> = serde_json::from_str(&config_statuses_json)
+        .unwrap_or_else(|e| {
+            tracing::warn!("Failed to parse config statuses for upload_job_id={}: {}", id, e);
+            vec![]
+        });
+    // Workers index the statuses by config, so they must line up.
+    if config_statuses.len() != configs.len() {
+        tracing::warn!(
+            "Config statuses of upload_job_id={} do not match its {} configs, rebuilding them from completed_configs={}",
+            id,
+            configs.len(),
+            completed_configs
+        );
+        config_statuses = config_statuses_from_completed(configs.len(), completed_configs);
+    }
+    let kind: String = row.get("kind");
+    let state: String = row.get("state");
+    let attempts: i64 = row.get("attempts");
+    let max_attempts: i64 = row.get("max_attempts");
+
+    UploadJob {
+        id,
+        task_id: row.get("task_id"),
+        task_name: row.get("task_name"),
+        kind: parse_job_kind(&kind),
+        live_title: row.get("live_title"),
+        parts,
+        configs,
+        auto_cleanup_after_upload: row.get("auto_cleanup_after_upload"),
+        state: parse_job_state(&state),
+        priority: row.get("priority"),
+        attempts: u32::try_from(attempts).unwrap_or_default(),
+        max_attempts: u32::try_from(max_attempts).unwrap_or_default(),
+        completed_configs,
+        config_statuses,
+        last_error: row.get("last_error"),
+        next_attempt_at: row.get("next_attempt_at"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+/// Statuses for a job that only knows how many leading configs succeeded.
+pub(super) fn config_statuses_from_completed(
+    config_count: usize,
+    completed: usize,
+) -> Vec<UploadConfigStatus> {
+    (0..config_count)
+        .map(|index| UploadConfigStatus {
+            state: if index < completed {
+                UploadConfigState::Succeeded
+            } else {
+                UploadConfigState::Pending
+            },
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn parse_job_kind(raw: &str) -> UploadJobKind {
+    match raw {
+        "segment" => UploadJobKind::Segment,
+        _ => UploadJobKind::Archive,
+    }
+}
+
+fn stringify_job_kind(kind: UploadJobKind) -> &'static str {
+    match kind {
+        UploadJobKind::Archive => "archive",
+        UploadJobKind::Segment => "segment",
+    }
+}
+
+fn parse_job_state(raw: &str) -> UploadJobState {
+    match raw {
+        "queued" => UploadJobState::Queued,
+        "uploading" => UploadJobState::Uploading,
+        "retrying" => UploadJobState::Retrying,
+        "succeeded" => UploadJobState::Succeeded,
+        "partially_succeeded" => UploadJobState::PartiallySucceeded,
+        "cancelled" => UploadJobState::Cancelled,
+        _ => UploadJobState::Failed,
+    }
+}
+
+fn stringify_job_state(state: UploadJobState) -> &'static str {
+    match state {
+        UploadJobState::Queued => "queued",
+        UploadJobState::Uploading => "uploading",
+        UploadJobState::Retrying => "retrying",
+        UploadJobState::Succeeded => "succeeded",
+        UploadJobState::PartiallySucceeded => "partially_succeeded",
+        UploadJobState::Failed => "failed",
+        UploadJobState::Cancelled => "cancelled",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Db;
+    use shared::{
+        UploadConfigState, UploadConfigStatus, UploadJob, UploadJobKind, UploadJobPart,
+        UploadJobState,
+    };
+    use std::path::PathBuf;
+    use uuid::Uuid;
+
+    fn temp_db_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("omnistream-db-upload-jobs-{name}-{}.db", Uuid::new_v4()))
+    }
+
+    fn job(id: &str, priority: i64, created_at: i64) -> UploadJob {
+        UploadJob {
+            id: id.to_string(),
+            task_id: format!("task-{id}"),
+            task_name: "主播".to_string(),
+            parts: vec![UploadJobPart { filename: "a.mp4".to_string(), title: None, size: 2048 }],
+            configs: vec![Default::default(), Default::default()],
+            config_statuses: vec![
+                UploadConfigStatus {
+                    state: UploadConfigState::Succeeded,
+                    attempts: 1,
+                    last_error: None,
+                    account_file: Some("cookies.json".to_string()),
+                },
+                UploadConfigStatus {
+                    state: UploadConfigState::Retrying,
+                    attempts: 1,
+                    last_error: Some("timeout".to_string()),
+                    account_file: None,
+                },
+            ],
+            priority,
+            max_attempts: 3,
+            created_at,
+            updated_at: created_at,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn claim_picks_highest_priority_due_job_and_counts_attempt() {
+        let path = temp_db_path("claim");
+        let db = Db::new(path.to_str().expect("db path")).await.expect("open db");
+
+        db.save_upload_job(&job("old", 0, 100)).await.expect("save old job");
+        db.save_upload_job(&job("urgent", 5, 200)).await.expect("save urgent job");
+        let mut later = job("later", 9, 300);
+        later.state = UploadJobState::Retrying;
+        later.next_attempt_at = 1_000;
+        db.save_upload_job(&later).await.expect("save retrying job");
+
+        let claimed = db.claim_next_upload_job(500).await.expect("claim").expect("due job");
+        assert_eq!(claimed.id, "urgent");
+        assert_eq!(claimed.state, UploadJobState::Uploading);
+        assert_eq!(claimed.attempts, 1);
+        assert_eq!(claimed.parts, job("urgent", 5, 200).parts);
+        assert_eq!(claimed.config_statuses, job("urgent", 5, 200).config_statuses);
+
+        let next = db.claim_next_upload_job(500).await.expect("claim").expect("due job");
+        assert_eq!(next.id, "old");
+        assert!(db.claim_next_upload_job(500).await.expect("claim").is_none());
+
+        assert_eq!(db.requeue_interrupted_upload_jobs().await.expect("requeue"), 2);
+        let requeued = db.get_upload_job("urgent").await.expect("load").expect("job exists");
+        assert_eq!(requeued.state, UploadJobState::Queued);
+    }
+
+    #[tokio::test]
+    async fn priority_change_survives_a_worker_saving_its_copy() {
+        let path = temp_db_path("priority");
+        let db = Db::new(path.to_str().expect("db path")).await.expect("open db");
+
+        db.save_upload_job(&job("running", 0, 100)).await.expect("save job");
+        let mut running = db.claim_next_upload_job(500).await.expect("claim").expect("due job");
+        assert!(db.set_upload_job_priority("running", 7, 600).await.expect("set priority"));
+        running.state = UploadJobState::Retrying;
+        db.save_upload_job(&running).await.expect("save worker copy");
+
+        let stored = db.get_upload_job("running").await.expect("load").expect("job exists");
+        assert_eq!(stored.priority, 7);
+        assert_eq!(stored.state, UploadJobState::Retrying);
+
+        running.state = UploadJobState::Succeeded;
+        db.save_upload_job(&running).await.expect("finish job");
+        assert!(!db.set_upload_job_priority("running", 1, 700).await.expect("set priority"));
+    }
+
+    #[tokio::test]
+    async fn mismatched_config_statuses_are_rebuilt_on_load() {
+        let path = temp_db_path("statuses");
+        let db = Db::new(path.to_str().expect("db path")).await.expect("open db");
+
+        let mut broken = job("broken", 0, 100);
+        broken.config_statuses.truncate(1);
+        broken.completed_configs = 1;
+        db.save_upload_job(&broken).await.expect("save job");
+
+        let loaded = db.get_upload_job("broken").await.expect("load").expect("job exists");
+        let states: Vec<_> = loaded.config_statuses.iter().map(|status| status.state).collect();
+        assert_eq!(states, vec![UploadConfigState::Succeeded, UploadConfigState::Pending]);
+    }
+
+    #[tokio::test]
+    async fn archive_job_waits_for_running_segment_uploads_of_its_task() {
+        let path = temp_db_path("segments");
+        let db = Db::new(path.to_str().expect("db path")).await.expect("open db");
+
+        let mut segment = job("segment", 0, 100);
+        segment.kind = UploadJobKind::Segment;
+        segment.state = UploadJobState::Uploading;
+        segment.task_id = "task-live".to_string();
+        db.save_upload_job(&segment).await.expect("save segment job");
+        let mut pending = job("pending", 0, 150);
+        pending.kind = UploadJobKind::Segment;
+        pending.task_id = "task-live".to_string();
+        db.save_upload_job(&pending).await.expect("save pending segment job");
+        let mut archive = job("archive", 9, 200);
+        archive.task_id = "task-live".to_string();
+        db.save_upload_job(&archive).await.expect("save archive job");
+
+        assert_eq!(db.cancel_pending_segment_jobs("task-live", 300).await.expect("cancel"), 1);
+        assert!(db.claim_next_upload_job(500).await.expect("claim").is_none());
+
+        segment.state = UploadJobState::Succeeded;
+        db.save_upload_job(&segment).await.expect("finish segment job");
+        let claimed = db.claim_next_upload_job(500).await.expect("claim").expect("archive job");
+        assert_eq!(claimed.id, "archive");
+        assert_eq!(claimed.kind, UploadJobKind::Archive);
+        let cancelled = db.get_upload_job("pending").await.expect("load").expect("job exists");
+        assert_eq!(cancelled.state, UploadJobState::Cancelled);
+        assert_eq!(cancelled.kind, UploadJobKind::Segment);
+    }
+}
@@ -3,7 +3,7 @@ use axum::{
     extract::{Path, State},
     http::StatusCode,
 };
-use shared::{DownloadConfig, StreamTask, TaskStatus};
+use shared::{DownloadConfig, RecordingSegment, StreamTask, TaskStatus};
 use uuid::Uuid;
 
 use crate::{
@@ -12,8 +12,9 @@ use crate::{
         resolve_auto_cleanup_after_upload, resolve_manual_upload_configs,
         resolve_min_upload_file_size_bytes, scan_recording_files,
     },
-    recording, settings,
-    state::{RecorderHandle, SharedState},
+    recording, recovery, settings,
+    state::SharedState,
+    upload_queue,
 };
 
 pub async fn list_downloads(
@@ -40,10 +41,25 @@ pub async fn list_downloads_service(
         d.current_status = Some(resolve_download_status(state, d));
         d.recording_file_size_bytes =
             recording_files_size_bytes(&recording::recording_task_dir(&d.name)).await;
+        d.latest_segment = latest_recording_segment(state, &d.url);
+        d.upload_progress = state
+            .tasks
+            .iter()
+            .filter(|task| task.url == d.url)
+            .find_map(|task| task.upload_progress.clone());
     }
     Ok(downloads)
 }
 
+fn latest_recording_segment(state: &SharedState, url: &str) -> Option<RecordingSegment> {
+    state
+        .tasks
+        .iter()
+        .filter(|task| task.url == url)
+        .flat_map(|task| task.segments.clone())
+        .max_by_key(|segment| segment.ended_at)
+}
+
 pub async fn add_download(
     State(state): State<SharedState>,
     Json(payload): Json<DownloadConfig>,
@@ -87,6 +103,9 @@ fn normalize_download_config(config: &mut DownloadConfig) {
         .collect();
     config.current_status = None;
     config.recording_file_size_bytes = 0;
+    config.latest_segment = None;
+    config.upload_progress = None;
+    recording::normalize_recorder_options(&mut config.recorder_options);
 }
 
 async fn validate_download_config(
@@ -120,6 +139,9 @@ async fn validate_download_config(
             .map_err(|message| (StatusCode::BAD_REQUEST, message))?;
     }
 
+    recording::validate_recorder_options(&config.recorder_options)
+        .map_err(|message| (StatusCode::BAD_REQUEST, message))?;
+
     if !config.linked_upload_ids.is_empty() {
         let uploads = state.db.get_uploads().await.map_err(|e| {
             tracing::error!("Failed to validate linked upload templates: {}", e);
@@ -295,6 +317,7 @@ async fn stop_active_tasks_for_url(state: &SharedState, url: &str) -> usize {
         if let Some((_, handle)) = state.handles.remove(&task_id) {
             handle.abort_handle.abort();
         }
+        upload_queue::cancel_upload_jobs_for_task(state, &task_id).await;
 
         if let Some(mut task) = state.tasks.get_mut(&task_id) {
             task.status = TaskStatus::Stopped;
@@ -320,6 +343,48 @@ fn is_supported_download_scheme(scheme: &str) -> bool {
     scheme == "http" || scheme == "https"
 }
 
+pub async fn resume_pending_uploads(
+    Path(id): Path<String>,
+    State(state): State<SharedState>,
+) -> (StatusCode, String) {
+    match resume_pending_uploads_service(&state, &id).await {
+        Ok((status, message)) => (status, message),
+        Err(response) => response,
+    }
+}
+
+pub async fn resume_pending_uploads_service(
+    state: &SharedState,
+    id: &str,
+) -> Result<(StatusCode, String), (StatusCode, String)> {
+    let download = load_download_for_manual_upload(state, id).await?;
+    let pending_task_ids: Vec<String> = state
+        .tasks
+        .iter()
+        .filter(|r| r.value().url == download.url && r.value().status == TaskStatus::PendingUpload)
+        .map(|r| r.key().clone())
+        .collect();
+    if pending_task_ids.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "no pending uploads".to_string()));
+    }
+
+    let mut resumed = 0;
+    for task_id in &pending_task_ids {
+        match recovery::resume_pending_upload(state, task_id, Some(&download)).await {
+            Ok(()) => resumed += 1,
+            Err(e) => {
+                tracing::warn!("Failed to resume pending upload, task_id={}: {}", task_id, e)
+            }
+        }
+    }
+    if resumed == 0 {
+        return Err((StatusCode::CONFLICT, "pending uploads could not be resumed".to_string()));
+    }
+    Ok((StatusCode::ACCEPTED, format!("resumed {resumed} pending uploads")))
+}
+
+pub(crate) const MANUAL_UPLOAD_TASK_PREFIX: &str = "manual-upload-";
+
 pub async fn trigger_manual_upload(
     Path(id): Path<String>,
     State(state): State<SharedState>,
@@ -410,7 +475,7 @@ pub async fn trigger_manual_upload_service(
     let auto_cleanup_after_upload = resolve_auto_cleanup_after_upload(state, &download).await;
     let min_upload_file_size_bytes = resolve_min_upload_file_size_bytes(state, &download).await;
 
-    let manual_task_id = format!("manual-upload-{}", Uuid::new_v4());
+    let manual_task_id = format!("{MANUAL_UPLOAD_TASK_PREFIX}{}", Uuid::new_v4());
     tracing::info!(
         "Manual upload accepted: manual_task_id={}, task_name={}, files={}, upload_configs={}, min_upload_file_size_bytes={}",
         manual_task_id,
@@ -420,7 +485,6 @@ pub async fn trigger_manual_upload_service(
         min_upload_file_size_bytes
     );
 
-    let state_for_upload = state.clone();
     let manual_task = StreamTask {
         id: manual_task_id.clone(),
         name: task_name.clone(),
@@ -428,6 +492,8 @@ pub async fn trigger_manual_upload_service(
         status: TaskStatus::Uploading,
         filename: files.first().cloned().unwrap_or_else(|| "manual-upload".to_string()),
         upload_configs: upload_configs.clone(),
+        segments: vec![],
+        upload_progress: None,
     };
     state.tasks.insert(manual_task_id.clone(), manual_task.clone());
     if let Err(e) = state.db.save_task(&manual_task).await {
@@ -445,23 +511,19 @@ pub async fn trigger_manual_upload_service(
         ));
     }
 
-    let task_id_for_upload = manual_task_id.clone();
-    let handle = tokio::spawn(async move {
-        recording::run_upload(
-            task_id_for_upload,
-            files,
-            state_for_upload,
-            true,
-            upload_configs,
-            live_title,
-            task_name,
-            recording::UploadRunOptions { auto_cleanup_after_upload, min_upload_file_size_bytes },
-        )
-        .await;
-    });
-    state.handles.insert(manual_task_id, RecorderHandle { abort_handle: handle.abort_handle() });
-
-    Ok((StatusCode::ACCEPTED, "manual upload started".to_string()))
+    recording::enqueue_upload(
+        manual_task_id,
+        files,
+        state.clone(),
+        true,
+        upload_configs,
+        live_title,
+        task_name,
+        recording::UploadRunOptions { auto_cleanup_after_upload, min_upload_file_size_bytes },
+    )
+    .await;
+
+    Ok((StatusCode::ACCEPTED, "manual upload queued".to_string()))
 }
 
 fn resolve_download_status(state: &SharedState, download: &DownloadConfig) -> String {
@@ -485,16 +547,20 @@ fn resolve_download_status(state: &SharedState, download: &DownloadConfig) -> St
 
 fn status_label_for_tasks(statuses: &[TaskStatus], is_checking: bool) -> String {
     let mut has_error = false;
+    let mut has_partial = false;
     let mut has_completed = false;
     let mut has_stopped = false;
+    let mut has_pending_upload = false;
 
     for status in statuses {
         match status {
             TaskStatus::Recording => return "下载中".to_string(),
             TaskStatus::Uploading => return "上传中".to_string(),
             TaskStatus::Error(_) => has_error = true,
+            TaskStatus::PartiallyCompleted(_) => has_partial = true,
             TaskStatus::Completed => has_completed = true,
             TaskStatus::Stopped => has_stopped = true,
+            TaskStatus::PendingUpload => has_pending_upload = true,
             TaskStatus::Idle => {}
         }
     }
@@ -502,9 +568,15 @@ fn status_label_for_tasks(statuses: &[TaskStatus], is_checking: bool) -> String
     if is_checking {
         return "检测中".to_string();
     }
+    if has_pending_upload {
+        return "待上传".to_string();
+    }
     if has_error {
         return "失败".to_string();
     }
+    if has_partial {
+        return "部分完成".to_string();
+    }
     if has_completed {
         return "已完成".to_string();
     }
@@ -550,6 +622,7 @@ mod tests {
             current_status: Some("下载中".to_string()),
             enabled: false,
             recording_file_size_bytes: 123,
+            latest_segment: Some(shared::RecordingSegment::default()),
             ..Default::default()
         };
 
@@ -560,6 +633,7 @@ mod tests {
         assert_eq!(config.linked_upload_ids, vec!["u1"]);
         assert_eq!(config.current_status, None);
         assert_eq!(config.recording_file_size_bytes, 0);
+        assert_eq!(config.latest_segment, None);
         assert!(!config.enabled);
     }
 
@@ -588,7 +662,18 @@ mod tests {
         assert_eq!(status_label_for_tasks(&[], true), "检测中");
         assert_eq!(status_label_for_tasks(&[TaskStatus::Error("x".to_string())], false), "失败");
         assert_eq!(status_label_for_tasks(&[TaskStatus::Completed], false), "已完成");
+        assert_eq!(
+            status_label_for_tasks(
+                &[TaskStatus::Completed, TaskStatus::PartiallyCompleted("x".to_string())],
+                false
+            ),
+            "部分完成"
+        );
         assert_eq!(status_label_for_tasks(&[TaskStatus::Stopped], false), "已停止");
         assert_eq!(status_label_for_tasks(&[TaskStatus::Idle], false), "空闲");
+        assert_eq!(
+            status_label_for_tasks(&[TaskStatus::Completed, TaskStatus::PendingUpload], false),
+            "待上传"
+        );
     }
 }
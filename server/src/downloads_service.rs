@@ -43,7 +43,7 @@ pub(crate) fn select_upload_configs(
     linked_upload_ids
         .iter()
         .filter_map(|uid| uploads.iter().find(|u| &u.id == uid))
-        .map(|u| u.config.clone())
+        .map(|u| UploadConfig { template_id: Some(u.id.clone()), ..u.config.clone() })
         .collect()
 }
 
@@ -58,6 +58,21 @@ pub(crate) async fn resolve_auto_cleanup_after_upload(
     }
 }
 
+pub(crate) async fn resolve_auto_resume_uploads(
+    state: &SharedState,
+    download: &DownloadConfig,
+) -> bool {
+    if download.use_custom_recording_settings {
+        download
+            .recording_settings
+            .as_ref()
+            .map(|s| s.auto_resume_uploads)
+            .unwrap_or_else(shared::default_auto_resume_uploads)
+    } else {
+        state.recording_settings.read().await.auto_resume_uploads
+    }
+}
+
 pub(crate) async fn resolve_min_upload_file_size_bytes(
     state: &SharedState,
     download: &DownloadConfig,
@@ -81,7 +96,7 @@ pub(crate) fn is_recording_file(path: &Path) -> bool {
         .and_then(|e| e.to_str())
         .map(|e| e.to_ascii_lowercase())
         .unwrap_or_default();
-    matches!(ext.as_str(), "mp4" | "flv" | "mkv" | "ts")
+    matches!(ext.as_str(), "mp4" | "flv" | "mkv" | "ts" | "m4a" | "aac")
 }
 
 pub(crate) async fn scan_recording_files(
@@ -160,6 +175,7 @@ mod tests {
         assert!(is_recording_file(Path::new("a.MKV")));
         assert!(is_recording_file(Path::new("a.ts")));
         assert!(is_recording_file(Path::new("a.flv")));
+        assert!(is_recording_file(Path::new("a.m4a")));
         assert!(!is_recording_file(Path::new("a.txt")));
         assert!(!is_recording_file(Path::new("a")));
     }
@@ -221,5 +237,6 @@ mod tests {
         assert_eq!(selected.len(), 2);
         assert_eq!(selected[0].title.as_deref(), Some("t2"));
         assert_eq!(selected[1].title.as_deref(), Some("t1"));
+        assert_eq!(selected[0].template_id.as_deref(), Some("u2"));
     }
 }
@@ -10,13 +10,16 @@ use tracing_subscriber::{
 
 mod accounts;
 mod app;
+mod archives;
 mod checker;
+mod covers;
 mod db;
 mod downloads;
 mod downloads_service;
 mod monitor;
 mod platform;
 mod recording;
+mod recovery;
 mod router;
 mod settings;
 mod state;
@@ -24,6 +27,12 @@ mod storage;
 mod storage_guard;
 mod task_launcher;
 mod tasks;
+mod upload_accounts;
+mod upload_history;
+mod upload_limits;
+mod upload_lines;
+mod upload_progress;
+mod upload_queue;
 mod uploader;
 mod uploads;
 
@@ -32,7 +41,14 @@ async fn main() -> Result<()> {
     let _log_guards = init_logging().context("failed to initialize logging")?;
     let state = app::build_state().await.context("failed to build application state")?;
 
-    tokio::spawn(monitor::run_monitor(state.clone()));
+    tokio::spawn(upload_limits::run_upload_limit_scheduler(state.clone()));
+    let state_for_monitor = state.clone();
+    tokio::spawn(async move {
+        // Upload workers start only after recovery has settled which tasks they own.
+        recovery::recover_interrupted_tasks(&state_for_monitor).await;
+        tokio::spawn(upload_queue::run_upload_workers(state_for_monitor.clone()));
+        monitor::run_monitor(state_for_monitor).await;
+    });
 
     let app = router::build_router(state);
     let bind_addr = resolve_bind_addr();
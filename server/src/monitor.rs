@@ -1,6 +1,7 @@
-use shared::{TaskStatus, UploadConfig};
+use shared::{DownloadConfig, StreamTask, TaskStatus, UploadTemplate};
 
 use crate::{
+    downloads_service::select_upload_configs,
     state::SharedState,
     storage_guard::recording_storage_below_min_free_percent,
     task_launcher::{LaunchTaskParams, launch_recording_task},
@@ -33,9 +34,9 @@ pub async fn run_monitor(state: SharedState) {
                 continue;
             }
 
+            // Uploads run in the upload queue, so a finished session no longer blocks the next one.
             let is_busy = state.tasks.iter().any(|r| {
-                r.value().url == download.url
-                    && matches!(r.value().status, TaskStatus::Recording | TaskStatus::Uploading)
+                r.value().url == download.url && r.value().status == TaskStatus::Recording
             });
             if is_busy {
                 continue;
@@ -69,31 +70,8 @@ pub async fn run_monitor(state: SharedState) {
                     if is_live {
                         tracing::info!("Streamer {} is live, starting recording", download.name);
 
-                        let upload_configs: Vec<UploadConfig> = download
-                            .linked_upload_ids
-                            .iter()
-                            .filter_map(|uid| all_uploads.iter().find(|u| &u.id == uid))
-                            .map(|u| u.config.clone())
-                            .collect();
-
-                        let custom_recording_settings = if download.use_custom_recording_settings {
-                            download.recording_settings.clone()
-                        } else {
-                            None
-                        };
-                        let initial_filename = format!("{}-pending.mp4", download.name);
-                        let task = launch_recording_task(
-                            state.clone(),
-                            LaunchTaskParams {
-                                initial_filename,
-                                name: download.name.clone(),
-                                url: download.url.clone(),
-                                upload_configs,
-                                custom_recording_settings,
-                            },
-                        )
-                        .await;
-
+                        let task =
+                            start_recording_for_download(&state, &download, &all_uploads).await;
                         tracing::info!("task info: {:?}", task);
                     }
                 }
@@ -105,3 +83,27 @@ pub async fn run_monitor(state: SharedState) {
         }
     }
 }
+
+pub(crate) async fn start_recording_for_download(
+    state: &SharedState,
+    download: &DownloadConfig,
+    all_uploads: &[UploadTemplate],
+) -> StreamTask {
+    let custom_recording_settings = if download.use_custom_recording_settings {
+        download.recording_settings.clone()
+    } else {
+        None
+    };
+    launch_recording_task(
+        state.clone(),
+        LaunchTaskParams {
+            initial_filename: format!("{}-pending.mp4", download.name),
+            name: download.name.clone(),
+            url: download.url.clone(),
+            upload_configs: select_upload_configs(&download.linked_upload_ids, all_uploads),
+            custom_recording_settings,
+            recorder_options: download.recorder_options.clone(),
+        },
+    )
+    .await
+}
@@ -19,6 +19,23 @@ pub enum LivePlatform {
     Unknown,
 }
 
+impl LivePlatform {
+    /// Display name used in upload templates.
+    pub fn label(&self) -> &'static str {
+        match self {
+            LivePlatform::Bilibili => "B站",
+            LivePlatform::Douyu => "斗鱼",
+            LivePlatform::Huya => "虎牙",
+            LivePlatform::Tiktok => "TikTok",
+            LivePlatform::Douyin => "抖音",
+            LivePlatform::Twitch => "Twitch",
+            LivePlatform::Youtube => "YouTube",
+            LivePlatform::Kick => "Kick",
+            LivePlatform::Unknown => "",
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ResolvedStream {
     pub input_url: String,
@@ -35,6 +52,30 @@ pub async fn resolve_stream(url: &str, quality: &str) -> Result<Option<ResolvedS
     }
 }
 
+/// Cover image URL of the live room, for platforms whose room info exposes one.
+pub async fn fetch_live_cover_url(url: &str) -> Result<Option<String>> {
+    match detect_platform(url) {
+        LivePlatform::Bilibili => {
+            let room_id = extract_bilibili_room_id(url).await?;
+            let client = bilibili_http_client()?;
+            let room_info = fetch_bilibili_room_info(&client, &room_id).await;
+            Ok(room_info.and_then(|info| non_empty_json_string(&info, &["data", "user_cover"])))
+        }
+        LivePlatform::Douyu => {
+            let room_id = extract_douyu_room_id(url).await?;
+            let room_info: Value = Client::new()
+                .get(format!("https://www.douyu.com/betard/{room_id}"))
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            Ok(non_empty_json_string(&room_info, &["room", "room_pic"]))
+        }
+        _ => Ok(None),
+    }
+}
+
 pub fn detect_platform(url: &str) -> LivePlatform {
     let Some(host) =
         Url::parse(url).ok().and_then(|url| url.host_str().map(|host| host.to_ascii_lowercase()))
@@ -155,6 +196,11 @@ fn parse_bilibili_room_id_from_url(raw_url: &str) -> Option<String> {
 }
 
 async fn fetch_bilibili_title(client: &Client, room_id: &str) -> Option<String> {
+    let response = fetch_bilibili_room_info(client, room_id).await?;
+    non_empty_json_string(&response, &["data", "title"])
+}
+
+async fn fetch_bilibili_room_info(client: &Client, room_id: &str) -> Option<Value> {
     let response: Value = client
         .get(format!("https://api.live.bilibili.com/room/v1/Room/get_info?room_id={room_id}"))
         .send()
@@ -168,7 +214,7 @@ async fn fetch_bilibili_title(client: &Client, room_id: &str) -> Option<String>
     if ensure_bilibili_api_ok(&response, "get_info").is_err() {
         return None;
     }
-    non_empty_json_string(&response, &["data", "title"])
+    Some(response)
 }
 
 async fn fetch_bilibili_play_info(client: &Client, room_id: &str, quality: &str) -> Result<Value> {
@@ -498,6 +544,20 @@ mod tests {
         assert_eq!(non_empty_json_string(&value, &["data", "title"]), Some("开播测试".to_string()));
     }
 
+    #[test]
+    fn extracts_room_covers_from_room_info() {
+        let bilibili = json!({
+            "code": 0,
+            "data": { "title": "开播测试", "user_cover": "https://i0.hdslb.com/bfs/live/cover.jpg" }
+        });
+        assert_eq!(
+            non_empty_json_string(&bilibili, &["data", "user_cover"]).as_deref(),
+            Some("https://i0.hdslb.com/bfs/live/cover.jpg")
+        );
+        let douyu = json!({ "room": { "room_pic": " " } });
+        assert_eq!(non_empty_json_string(&douyu, &["room", "room_pic"]), None);
+    }
+
     #[test]
     fn extracts_and_selects_douyin_hls_streams() {
         let html = r#"\"hls_pull_url_map\":{\"FULL_HD1\":\"http://pull-hls-l11.douyincdn.com/stage/origin.m3u8?expire=1\u0026sign=a\",\"SD1\":\"http://pull-hls-l11.douyincdn.com/stage/sd.m3u8?expire=1\u0026sign=b\",\"LD\":\"http://pull-hls-l11.douyincdn.com/stage/ld.m3u8?expire=1\u0026sign=c\"}"#;
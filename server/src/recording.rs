@@ -1,31 +1,33 @@
+mod options;
+mod probe;
 mod runtime;
 mod segment;
 mod task_state;
 
 use chrono::Local;
-use shared::{TaskStatus, UploadConfig};
+use shared::{TaskStatus, UploadConfig, UploadJobKind};
 use std::collections::HashSet;
 use std::path::PathBuf;
-use std::time::Duration;
-use tokio::time::sleep;
 
+pub(crate) use self::options::{normalize_recorder_options, validate_recorder_options};
+use self::probe::collect_segment_metrics;
 use self::runtime::{RecorderRuntimeConfig, build_runtime_config};
 use self::segment::{
-    SegmentLoopAction, decide_next_segment_action, record_segment, update_recorded_files,
+    SegmentLoopAction, StreamChange, decide_next_segment_action, record_segment,
+    update_recorded_files,
 };
+pub(crate) use self::task_state::set_task_status;
 use self::task_state::{
-    clear_task_handle, finish_recording_without_files, resolve_task_name, set_task_filename,
-    set_task_status,
+    clear_task_handle, finish_recording_without_files, record_task_segment, resolve_task_name,
+    set_task_filename,
 };
 use crate::{
     state::{RecorderHandle, SharedState},
     storage_guard::recording_storage_below_min_free_percent,
-    uploader::UploadTarget,
+    upload_queue::{NewUploadJob, enqueue_upload_job},
+    uploader::UploadPart,
 };
 
-const MAX_BATCH_UPLOAD_ATTEMPTS: usize = 3;
-const RETRY_BACKOFF_SECS: &[u64] = &[30, 120];
-
 #[derive(Debug, Clone, Copy)]
 pub struct UploadRunOptions {
     pub auto_cleanup_after_upload: bool,
@@ -35,15 +37,17 @@ pub struct UploadRunOptions {
 async fn prepare_segment_file(
     state: &SharedState,
     task_id: &str,
+    extension: &str,
 ) -> Result<String, std::io::Error> {
     let task_name = resolve_task_name(state, task_id);
     let task_dir = recording_task_dir(&task_name);
     tokio::fs::create_dir_all(&task_dir).await?;
 
     let basename = format!(
-        "{}-{}.mp4",
+        "{}-{}.{}",
         sanitize_for_filename(&task_name),
-        Local::now().format("%Y%m%d_%H%M%S")
+        Local::now().format("%Y%m%d_%H%M%S"),
+        extension
     );
     let current_filename = task_dir.join(basename).to_string_lossy().to_string();
     set_task_filename(state, task_id, &current_filename).await;
@@ -56,7 +60,7 @@ async fn stop_segment_process(child: &mut tokio::process::Child, task_id: &str,
     }
 }
 
-pub async fn run_upload(
+pub async fn enqueue_upload(
     task_id: String,
     filenames: Vec<String>,
     state: SharedState,
@@ -99,186 +103,108 @@ pub async fn run_upload(
         return;
     }
 
+    if update_status {
+        // Persisted so a restart mid-upload can offer the files again.
+        set_task_status(&state, &task_id, TaskStatus::Uploading).await;
+    }
+
+    let parts = upload_parts_with_titles(&state, &task_id, &filenames);
+    // The archive is named after the title the session started under, not the one it ended on.
+    let live_title = parts.iter().find_map(|part| part.title.clone()).or(live_title);
+
     tracing::info!(
-        "Task {} start uploading files: {:?} with {} configs",
+        "Task {} queueing files as one multi-part archive: {:?} with {} configs",
         task_id,
         filenames,
         configs.len()
     );
-
-    if update_status && let Some(mut task) = state.tasks.get_mut(&task_id) {
-        task.status = TaskStatus::Uploading;
-    }
-
-    let target = UploadTarget::Bilibili;
-    let uploader = target.create_uploader();
-
-    let mut all_success = true;
-    let mut error_msg = String::new();
-    for (config_index, config) in configs.iter().enumerate() {
-        tracing::info!(
-            "Task {} uploading {} files as one multi-part archive with config {}/{}: title_template={:?}",
-            task_id,
-            filenames.len(),
-            config_index + 1,
-            configs.len(),
-            config.title
-        );
-
-        if let Err(e) = upload_batch_with_retry(
-            uploader.as_ref(),
-            &task_id,
-            &filenames,
-            config,
-            live_title.as_deref(),
-            &task_name,
-            config_index,
-            configs.len(),
-        )
-        .await
-        {
-            tracing::error!(
-                "Task {} multi-part upload failed for config {}/{}: task_name={}, files={:?}, title_template={:?}: {:?}",
-                task_id,
-                config_index + 1,
-                configs.len(),
-                task_name,
-                filenames,
-                config.title,
-                e
-            );
-            all_success = false;
-            error_msg = format!(
-                "Multi-part upload config {}/{} failed: {:?}",
-                config_index + 1,
-                configs.len(),
-                e
-            );
-            break;
+    let queued = enqueue_upload_job(
+        &state,
+        NewUploadJob {
+            task_id: task_id.clone(),
+            task_name,
+            kind: UploadJobKind::Archive,
+            live_title,
+            parts,
+            configs,
+            auto_cleanup_after_upload: options.auto_cleanup_after_upload,
+        },
+    )
+    .await;
+    if let Err(e) = queued {
+        tracing::error!("Task {} failed to queue upload job: {}", task_id, e);
+        if update_status {
+            set_task_status(&state, &task_id, TaskStatus::Error(e)).await;
         }
     }
-
-    let final_status = if all_success {
-        if options.auto_cleanup_after_upload {
-            cleanup_uploaded_files(&task_id, &filenames).await;
-        }
-        TaskStatus::Completed
-    } else {
-        TaskStatus::Error(error_msg)
-    };
-
     if update_status {
-        set_task_status(&state, &task_id, final_status).await;
         clear_task_handle(&state, &task_id);
     }
 }
 
-async fn upload_batch_with_retry(
-    uploader: &dyn crate::uploader::Uploader,
+/// Queues a finished segment for upload while the session keeps recording; the archive
+/// queued when the session ends submits it without sending the file again.
+async fn enqueue_segment_upload(
+    state: &SharedState,
     task_id: &str,
-    filenames: &[String],
-    config: &UploadConfig,
-    live_title: Option<&str>,
-    task_name: &str,
-    config_index: usize,
-    config_count: usize,
-) -> anyhow::Result<()> {
-    let mut last_error = None;
-
-    for attempt in 1..=MAX_BATCH_UPLOAD_ATTEMPTS {
-        match uploader.upload(filenames.to_vec(), config, live_title, task_name).await {
-            Ok(()) => {
-                tracing::info!(
-                    "Task {} submitted multi-part upload successfully: file_count={}, config={}/{}, attempt={}/{}",
-                    task_id,
-                    filenames.len(),
-                    config_index + 1,
-                    config_count,
-                    attempt,
-                    MAX_BATCH_UPLOAD_ATTEMPTS
-                );
-                return Ok(());
-            }
-            Err(e) => {
-                let message = format!("{e:?}");
-                let transient = is_transient_upload_error(&message);
-                tracing::warn!(
-                    "Task {} multi-part upload attempt failed: file_count={}, config={}/{}, attempt={}/{}, transient={}, error={}",
-                    task_id,
-                    filenames.len(),
-                    config_index + 1,
-                    config_count,
-                    attempt,
-                    MAX_BATCH_UPLOAD_ATTEMPTS,
-                    transient,
-                    message
-                );
-                last_error = Some(e);
-
-                if !transient || attempt == MAX_BATCH_UPLOAD_ATTEMPTS {
-                    break;
-                }
-
-                let backoff = RETRY_BACKOFF_SECS
-                    .get(attempt.saturating_sub(1))
-                    .copied()
-                    .unwrap_or_else(|| *RETRY_BACKOFF_SECS.last().unwrap_or(&120));
-                tracing::warn!(
-                    "Task {} will retry multi-part upload after {}s: file_count={}, config={}/{}, next_attempt={}",
-                    task_id,
-                    backoff,
-                    filenames.len(),
-                    config_index + 1,
-                    config_count,
-                    attempt + 1
-                );
-                sleep(Duration::from_secs(backoff)).await;
-            }
-        }
+    filename: &str,
+    configs: &[UploadConfig],
+    options: UploadRunOptions,
+) {
+    if configs.is_empty() {
+        return;
+    }
+    // Small files are left for the end-of-session cleanup, which deletes them.
+    if options.min_upload_file_size_bytes > 0
+        && let Ok(meta) = tokio::fs::metadata(filename).await
+        && meta.len() < options.min_upload_file_size_bytes
+    {
+        tracing::info!(
+            "Task {} skipped early upload of small segment: file={}, size={} bytes",
+            task_id,
+            filename,
+            meta.len()
+        );
+        return;
     }
 
-    Err(last_error.unwrap_or_else(|| anyhow::anyhow!("upload failed without error detail")))
-}
-
-async fn cleanup_uploaded_files(task_id: &str, filenames: &[String]) {
-    let mut unique_files = HashSet::new();
-    for file in filenames {
-        if !unique_files.insert(file) {
-            continue;
-        }
-        if let Err(e) = tokio::fs::remove_file(file).await {
-            tracing::warn!("Task {} cleanup failed for {}: {}", task_id, file, e);
-        } else {
-            tracing::info!(
-                "Task {} cleaned up local file after multi-part submission: {}",
-                task_id,
-                file
-            );
-        }
+    let parts = upload_parts_with_titles(state, task_id, &[filename.to_string()]);
+    let queued = enqueue_upload_job(
+        state,
+        NewUploadJob {
+            task_id: task_id.to_string(),
+            task_name: resolve_task_name(state, task_id),
+            kind: UploadJobKind::Segment,
+            live_title: parts[0].title.clone(),
+            parts,
+            configs: configs.to_vec(),
+            auto_cleanup_after_upload: options.auto_cleanup_after_upload,
+        },
+    )
+    .await;
+    if let Err(e) = queued {
+        // Not fatal: the archive uploads the segment when the session ends.
+        tracing::warn!("Task {} failed to queue segment upload for {}: {}", task_id, filename, e);
     }
 }
 
-fn is_transient_upload_error(message: &str) -> bool {
-    let message = message.to_ascii_lowercase();
-    [
-        "500",
-        "502",
-        "503",
-        "504",
-        "gateway time-out",
-        "gateway timeout",
-        "timeout",
-        "timed out",
-        "error sending request",
-        "connection reset",
-        "connection closed",
-        "connection refused",
-        "connection aborted",
-        "temporarily unavailable",
-        "network",
-    ]
-    .iter()
-    .any(|needle| message.contains(needle))
+fn upload_parts_with_titles(
+    state: &SharedState,
+    task_id: &str,
+    filenames: &[String],
+) -> Vec<UploadPart> {
+    let segments = state.tasks.get(task_id).map(|task| task.segments.clone()).unwrap_or_default();
+    filenames
+        .iter()
+        .map(|filename| UploadPart {
+            filename: filename.clone(),
+            title: segments
+                .iter()
+                .rev()
+                .find(|segment| &segment.filename == filename)
+                .and_then(|segment| segment.title.clone()),
+        })
+        .collect()
 }
 
 async fn prepare_upload_files(
@@ -340,6 +266,7 @@ pub async fn spawn_recorder(
     _initial_filename: String,
     state: SharedState,
     custom_recording_settings: Option<shared::RecordingSettings>,
+    recorder_options: shared::RecorderOptions,
 ) {
     let task_id_clone = task_id.clone();
     let state_for_task = state.clone();
@@ -349,7 +276,7 @@ pub async fn spawn_recorder(
     } else {
         state.recording_settings.read().await.clone()
     };
-    let runtime = build_runtime_config(&url, &effective_settings);
+    let runtime = build_runtime_config(&url, &effective_settings, recorder_options);
 
     let upload_configs = if let Some(task) = state.tasks.get(&task_id) {
         task.upload_configs.clone()
@@ -358,6 +285,11 @@ pub async fn spawn_recorder(
         return;
     };
 
+    let upload_options = UploadRunOptions {
+        auto_cleanup_after_upload: runtime.auto_cleanup_after_upload,
+        min_upload_file_size_bytes: runtime.min_upload_file_size_bytes,
+    };
+
     let handle = tokio::spawn(async move {
         tracing::info!("Task {} preparing to record: {}", task_id, url);
 
@@ -367,6 +299,7 @@ pub async fn spawn_recorder(
         let mut live_title = state_for_task.checker.fetch_live_title(&url).await;
         let mut consecutive_empty_segments = 0u8;
         let mut terminal_error: Option<String> = None;
+        let mut pending_metrics: Option<tokio::task::JoinHandle<()>> = None;
 
         loop {
             match recording_storage_below_min_free_percent().await {
@@ -392,15 +325,49 @@ pub async fn spawn_recorder(
                 }
             }
 
-            let result = record_segment(&task_id, &url, &state_for_task, &runtime).await;
+            let segment_started_at = Local::now().timestamp();
+            let result =
+                record_segment(&task_id, &url, &state_for_task, &runtime, live_title.as_deref())
+                    .await;
 
             if !result.filename.is_empty() {
+                // Probe in the background so the next segment starts right away; each probe
+                // waits for the previous one before recording, keeping segments in order.
+                let previous_metrics = pending_metrics.take();
+                let metrics_state = state_for_task.clone();
+                let metrics_task_id = task_id.clone();
+                let filename = result.filename.clone();
+                let title = live_title.clone();
+                let ended_at = Local::now().timestamp();
+                pending_metrics = Some(tokio::spawn(async move {
+                    let segment =
+                        collect_segment_metrics(&filename, title, segment_started_at, ended_at)
+                            .await;
+                    if let Some(previous) = previous_metrics {
+                        let _ = previous.await;
+                    }
+                    if let Some(segment) = segment {
+                        record_task_segment(&metrics_state, &metrics_task_id, segment).await;
+                    }
+                }));
+
                 update_recorded_files(
                     &task_id,
-                    result.filename,
+                    result.filename.clone(),
                     &mut recorded_files,
                     &mut consecutive_empty_segments,
                 );
+                if runtime.upload_while_recording && recorded_files.last() == Some(&result.filename)
+                {
+                    enqueue_segment_upload(
+                        &state_for_task,
+                        &task_id,
+                        &result.filename,
+                        &upload_configs,
+                        upload_options,
+                    )
+                    .await;
+                }
             }
 
             if result.disk_full {
@@ -433,6 +400,20 @@ pub async fn spawn_recorder(
                 continue;
             }
 
+            if let Some(change) = result.stream_change {
+                if let StreamChange::Title(title) = change {
+                    live_title = Some(title);
+                }
+                continue;
+            }
+
+            if result.stalled {
+                tracing::warn!(
+                    "Task {} recorder stalled; checking live status before restarting the segment",
+                    task_id
+                );
+            }
+
             match decide_next_segment_action(
                 &state_for_task,
                 &task_id,
@@ -446,6 +427,10 @@ pub async fn spawn_recorder(
             }
         }
 
+        if let Some(metrics) = pending_metrics {
+            let _ = metrics.await;
+        }
+
         if !recorded_files.is_empty() {
             let refreshed_title = state_for_task.checker.fetch_live_title(&url).await;
             if refreshed_title.is_some() {
@@ -456,7 +441,7 @@ pub async fn spawn_recorder(
             } else {
                 task_id.clone()
             };
-            run_upload(
+            enqueue_upload(
                 task_id.clone(),
                 recorded_files,
                 state_for_task.clone(),
@@ -464,10 +449,7 @@ pub async fn spawn_recorder(
                 upload_configs,
                 live_title,
                 final_task_name,
-                UploadRunOptions {
-                    auto_cleanup_after_upload: runtime.auto_cleanup_after_upload,
-                    min_upload_file_size_bytes: runtime.min_upload_file_size_bytes,
-                },
+                upload_options,
             )
             .await;
         } else {
@@ -536,7 +518,7 @@ fn sanitize_for_filename(raw: &str) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::{is_transient_upload_error, prepare_upload_files, quality_for_url};
+    use super::{prepare_upload_files, quality_for_url};
     use shared::PlatformQualityConfig;
     use uuid::Uuid;
 
@@ -604,19 +586,4 @@ mod tests {
 
         tokio::fs::remove_dir_all(&dir).await.expect("cleanup temp dir");
     }
-
-    #[test]
-    fn transient_upload_error_detects_temporary_bilibili_failures() {
-        assert!(is_transient_upload_error("HTTP status server error (504 Gateway Time-out)"));
-        assert!(is_transient_upload_error(
-            "error sending request for url (https://upos-sz-upcdnbda2.bilivideo.com)"
-        ));
-        assert!(is_transient_upload_error("connection reset by peer"));
-    }
-
-    #[test]
-    fn transient_upload_error_ignores_permanent_config_failures() {
-        assert!(!is_transient_upload_error("account_file is required"));
-        assert!(!is_transient_upload_error("copyright must be 1 (Original) or 2 (Reprint)"));
-    }
 }
@@ -0,0 +1,295 @@
+use shared::{RecorderBackend, RecorderOptions};
+
+const MAX_EXTRA_ARGS: usize = 64;
+
+// Output options that only change how the segment is encoded or muxed, and whether they take
+// a value. Anything else could add inputs, read local files or write a second output; filters
+// are left out too, since many of them read or write files named in their options.
+const ALLOWED_FFMPEG_OPTIONS: &[(&str, bool)] = &[
+    ("-rw_timeout", true),
+    ("-timeout", true),
+    ("-loglevel", true),
+    ("-threads", true),
+    ("-map", true),
+    ("-metadata", true),
+    ("-c:v", true),
+    ("-c:a", true),
+    ("-vcodec", true),
+    ("-acodec", true),
+    ("-b:v", true),
+    ("-b:a", true),
+    ("-maxrate", true),
+    ("-bufsize", true),
+    ("-crf", true),
+    ("-preset", true),
+    ("-tune", true),
+    ("-profile:v", true),
+    ("-pix_fmt", true),
+    ("-g", true),
+    ("-r", true),
+    ("-s", true),
+    ("-aspect", true),
+    ("-ar", true),
+    ("-ac", true),
+    ("-fps_mode", true),
+    ("-max_muxing_queue_size", true),
+    ("-copyts", false),
+    ("-start_at_zero", false),
+    ("-an", false),
+    ("-vn", false),
+    ("-sn", false),
+    ("-dn", false),
+    ("-nostats", false),
+];
+
+const ALLOWED_STREAMLINK_OPTIONS: &[(&str, bool)] = &[
+    ("-l", true),
+    ("--loglevel", true),
+    ("--retry-streams", true),
+    ("--retry-max", true),
+    ("--retry-open", true),
+    ("--stream-timeout", true),
+    ("--stream-segment-threads", true),
+    ("--stream-segment-attempts", true),
+    ("--stream-segment-timeout", true),
+    ("--stream-sorting-excludes", true),
+    ("--ringbuffer-size", true),
+    ("--hls-live-edge", true),
+    ("--hls-playlist-reload-time", true),
+    ("--hls-audio-select", true),
+    ("--hls-live-restart", false),
+    ("--hls-segment-stream-data", false),
+    ("--http-header", true),
+    ("--http-cookie", true),
+    ("--http-timeout", true),
+    ("--http-proxy", true),
+    ("--twitch-disable-ads", false),
+    ("--twitch-low-latency", false),
+];
+
+pub(crate) fn normalize_recorder_options(options: &mut RecorderOptions) {
+    options.extra_args = options
+        .extra_args
+        .iter()
+        .map(|arg| arg.trim().to_string())
+        .filter(|arg| !arg.is_empty())
+        .collect();
+    options.headers = options
+        .headers
+        .iter()
+        .map(|header| header.trim().to_string())
+        .filter(|header| !header.is_empty())
+        .collect();
+    options.cookies =
+        options.cookies.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(str::to_string);
+    options.user_agent =
+        options.user_agent.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(str::to_string);
+}
+
+pub(crate) fn validate_recorder_options(options: &RecorderOptions) -> Result<(), String> {
+    if !options.extra_args.is_empty() && options.backend == RecorderBackend::Auto {
+        return Err("extra recorder arguments require choosing streamlink or ffmpeg".to_string());
+    }
+    if options.extra_args.len() > MAX_EXTRA_ARGS {
+        return Err(format!("at most {MAX_EXTRA_ARGS} extra recorder arguments are allowed"));
+    }
+
+    let allowed = match options.backend {
+        RecorderBackend::Ffmpeg => ALLOWED_FFMPEG_OPTIONS,
+        RecorderBackend::Streamlink | RecorderBackend::Auto => ALLOWED_STREAMLINK_OPTIONS,
+    };
+    let mut args = options.extra_args.iter();
+    while let Some(arg) = args.next() {
+        check_recorder_arg(arg)?;
+        // Streamlink also takes `--name=value`; ffmpeg only `-name value`.
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, value)) if arg.starts_with("--") => (name, Some(value)),
+            _ => (arg.as_str(), None),
+        };
+        if !name.starts_with('-') {
+            return Err(format!("recorder argument {arg} is not an option or its value"));
+        }
+        let Some(&(_, takes_value)) = allowed.iter().find(|(option, _)| *option == name) else {
+            return Err(format!("recorder argument {name} is not allowed"));
+        };
+        match (takes_value, inline_value) {
+            (true, None) => {
+                let value = args.next().ok_or_else(|| format!("{name} requires a value"))?;
+                check_recorder_arg(value)?;
+            }
+            (false, Some(_)) => return Err(format!("{name} does not take a value")),
+            (true, Some(_)) | (false, None) => {}
+        }
+    }
+
+    for header in &options.headers {
+        let Some((name, _)) = header.split_once(':') else {
+            return Err(format!("header must use `Name: value` format: {header}"));
+        };
+        let name = name.trim();
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(format!("invalid header name: {header}"));
+        }
+        if header.chars().any(char::is_control) {
+            return Err(format!("header contains control characters: {name}"));
+        }
+    }
+    for (label, value) in [("cookies", &options.cookies), ("user agent", &options.user_agent)] {
+        if value.as_deref().is_some_and(|v| v.chars().any(char::is_control)) {
+            return Err(format!("{label} must not contain control characters"));
+        }
+    }
+    Ok(())
+}
+
+fn check_recorder_arg(arg: &str) -> Result<(), String> {
+    if arg.chars().any(char::is_control) {
+        return Err(format!("recorder argument contains control characters: {arg:?}"));
+    }
+    if arg.to_ascii_lowercase().contains("file:") {
+        return Err(format!("recorder argument must not reference local files: {arg}"));
+    }
+    Ok(())
+}
+
+/// Layers the download's user agent, headers and cookies over the recorder defaults.
+pub(super) fn merged_headers(defaults: &str, options: &RecorderOptions) -> Vec<(String, String)> {
+    let mut headers: Vec<(String, String)> = defaults
+        .split("\r\n")
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect();
+    let mut set = |name: &str, value: &str| {
+        headers.retain(|(existing, _)| !existing.eq_ignore_ascii_case(name));
+        headers.push((name.to_string(), value.to_string()));
+    };
+
+    if let Some(user_agent) = &options.user_agent {
+        set("User-Agent", user_agent);
+    }
+    for header in &options.headers {
+        if let Some((name, value)) = header.split_once(':') {
+            set(name.trim(), value.trim());
+        }
+    }
+    if let Some(cookies) = &options.cookies {
+        set("Cookie", cookies);
+    }
+    headers
+}
+
+pub(super) fn streamlink_http_args(options: &RecorderOptions) -> Vec<String> {
+    let mut args = Vec::new();
+    for (name, value) in merged_headers("", options) {
+        if name.eq_ignore_ascii_case("Cookie") {
+            for cookie in value.split(';').map(str::trim).filter(|c| c.contains('=')) {
+                args.push("--http-cookie".to_string());
+                args.push(cookie.to_string());
+            }
+        } else {
+            args.push("--http-header".to_string());
+            args.push(format!("{name}={value}"));
+        }
+    }
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{merged_headers, streamlink_http_args, validate_recorder_options};
+    use shared::{RecorderBackend, RecorderOptions};
+
+    #[test]
+    fn validation_rejects_output_redirects_and_auto_backend_args() {
+        let options = |backend, args: &[&str]| RecorderOptions {
+            backend,
+            extra_args: args.iter().map(|v| v.to_string()).collect(),
+            ..Default::default()
+        };
+
+        assert!(
+            validate_recorder_options(&options(RecorderBackend::Ffmpeg, &["-rw_timeout", "1"]))
+                .is_ok()
+        );
+        assert!(validate_recorder_options(&options(RecorderBackend::Auto, &["-v"])).is_err());
+        assert!(validate_recorder_options(&options(RecorderBackend::Ffmpeg, &["-i"])).is_err());
+        assert!(
+            validate_recorder_options(&options(RecorderBackend::Streamlink, &["--output=/x"]))
+                .is_err()
+        );
+        assert!(
+            validate_recorder_options(&options(RecorderBackend::Ffmpeg, &["file:/etc/passwd"]))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn validation_only_allows_known_options_and_their_values() {
+        let check = |backend, args: &[&str]| {
+            validate_recorder_options(&RecorderOptions {
+                backend,
+                extra_args: args.iter().map(|v| v.to_string()).collect(),
+                ..Default::default()
+            })
+        };
+        let ffmpeg = |args: &[&str]| check(RecorderBackend::Ffmpeg, args);
+        let streamlink = |args: &[&str]| check(RecorderBackend::Streamlink, args);
+
+        assert!(ffmpeg(&["-c:a", "aac", "-metadata", "title=/home/user", "-an"]).is_ok());
+        // A bare path would be a second output that -y overwrites.
+        assert!(ffmpeg(&["/home/user/.bashrc"]).is_err());
+        assert!(ffmpeg(&["-an", "/home/user/.bashrc"]).is_err());
+        assert!(ffmpeg(&["-c:a"]).is_err());
+        assert!(ffmpeg(&["-filter_complex", "movie=/etc/passwd"]).is_err());
+        assert!(ffmpeg(&["-vf", "movie=/etc/passwd[m];[in][m]overlay"]).is_err());
+        assert!(ffmpeg(&["-af", "amovie=/tmp/a.wav"]).is_err());
+        assert!(ffmpeg(&["-vf", "drawtext=textfile=/etc/passwd"]).is_err());
+        assert!(ffmpeg(&["-vf", "vidstabdetect=result=/tmp/out"]).is_err());
+        assert!(ffmpeg(&["-filter:a", "arnndn=model=/tmp/model"]).is_err());
+
+        assert!(
+            streamlink(&["--retry-streams=5", "--hls-live-edge", "3", "--twitch-disable-ads"])
+                .is_ok()
+        );
+        assert!(streamlink(&["-o/tmp/x"]).is_err());
+        assert!(streamlink(&["--twitch-disable-ads=1"]).is_err());
+        assert!(streamlink(&["--retry-max", "3", "extra"]).is_err());
+    }
+
+    #[test]
+    fn custom_headers_override_defaults_case_insensitively() {
+        let options = RecorderOptions {
+            headers: vec!["referer: https://example.com/".to_string()],
+            cookies: Some("a=1; b=2".to_string()),
+            user_agent: Some("OmniStream".to_string()),
+            ..Default::default()
+        };
+
+        let headers = merged_headers(
+            "Referer: https://live.bilibili.com/\r\nUser-Agent: Mozilla\r\n",
+            &options,
+        );
+
+        assert_eq!(
+            headers,
+            vec![
+                ("User-Agent".to_string(), "OmniStream".to_string()),
+                ("referer".to_string(), "https://example.com/".to_string()),
+                ("Cookie".to_string(), "a=1; b=2".to_string()),
+            ]
+        );
+        assert_eq!(
+            streamlink_http_args(&options),
+            vec![
+                "--http-header",
+                "User-Agent=OmniStream",
+                "--http-header",
+                "referer=https://example.com/",
+                "--http-cookie",
+                "a=1",
+                "--http-cookie",
+                "b=2",
+            ]
+        );
+    }
+}
@@ -0,0 +1,209 @@
+use std::fmt;
+use std::time::Duration;
+
+use serde_json::Value;
+use shared::RecordingSegment;
+use tokio::process::Command;
+
+const FFPROBE_PATH: &str = "ffprobe";
+const STREAM_PROBE_TIMEOUT: Duration = Duration::from_secs(20);
+/// A local file reads fast; a probe taking longer is stuck on a damaged segment.
+const SEGMENT_PROBE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Video parameters whose change mid-session warrants a new segment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct StreamSignature {
+    width: Option<u32>,
+    height: Option<u32>,
+    video_codec: Option<String>,
+}
+
+impl StreamSignature {
+    fn from_probe(probe: &Value) -> Option<Self> {
+        let mut segment = RecordingSegment::default();
+        apply_probe_metrics(&mut segment, probe);
+        let signature =
+            Self { width: segment.width, height: segment.height, video_codec: segment.video_codec };
+        (signature.height.is_some() || signature.video_codec.is_some()).then_some(signature)
+    }
+}
+
+impl fmt::Display for StreamSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let dimension = |v: Option<u32>| v.map(|v| v.to_string()).unwrap_or_else(|| "?".into());
+        write!(
+            f,
+            "{}x{} {}",
+            dimension(self.width),
+            dimension(self.height),
+            self.video_codec.as_deref().unwrap_or("?")
+        )
+    }
+}
+
+/// Builds the metrics row for a finished segment; returns `None` when no file was written.
+pub(super) async fn collect_segment_metrics(
+    filename: &str,
+    title: Option<String>,
+    started_at: i64,
+    ended_at: i64,
+) -> Option<RecordingSegment> {
+    let size_bytes = tokio::fs::metadata(filename).await.ok()?.len();
+    let mut segment = RecordingSegment {
+        filename: filename.to_string(),
+        title,
+        started_at,
+        ended_at,
+        size_bytes,
+        ..Default::default()
+    };
+    match tokio::time::timeout(SEGMENT_PROBE_TIMEOUT, probe_segment(filename, None)).await {
+        Ok(Ok(probe)) => apply_probe_metrics(&mut segment, &probe),
+        Ok(Err(e)) => tracing::warn!("Segment metrics unavailable, filename={}: {}", filename, e),
+        Err(_) => tracing::warn!(
+            "Segment metrics unavailable, filename={}: ffprobe timed out after {}s",
+            filename,
+            SEGMENT_PROBE_TIMEOUT.as_secs()
+        ),
+    }
+    Some(segment)
+}
+
+/// Probes the live input itself, since a copied output keeps the parameters it started with.
+pub(super) async fn probe_stream_signature(
+    input_url: &str,
+    headers: &str,
+) -> Result<Option<StreamSignature>, String> {
+    let probe = tokio::time::timeout(STREAM_PROBE_TIMEOUT, probe_segment(input_url, Some(headers)))
+        .await
+        .map_err(|_| format!("ffprobe timed out after {}s", STREAM_PROBE_TIMEOUT.as_secs()))??;
+    Ok(StreamSignature::from_probe(&probe))
+}
+
+async fn probe_segment(input: &str, headers: Option<&str>) -> Result<Value, String> {
+    let mut command = Command::new(FFPROBE_PATH);
+    command.args(["-v", "error", "-print_format", "json", "-show_format", "-show_streams"]);
+    if let Some(headers) = headers {
+        command.arg("-headers").arg(headers);
+    }
+    let output = command
+        .arg(input)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| format!("failed to execute ffprobe for {input}: {e}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "ffprobe exited with {} for {input}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("failed to parse ffprobe output for {input}: {e}"))
+}
+
+/// Fills stream metrics from ffprobe's JSON into `segment`, leaving unknown fields empty.
+fn apply_probe_metrics(segment: &mut RecordingSegment, probe: &Value) {
+    let streams = probe["streams"].as_array().map(Vec::as_slice).unwrap_or_default();
+    let video = streams.iter().find(|s| s["codec_type"] == "video");
+    let audio = streams.iter().find(|s| s["codec_type"] == "audio");
+
+    segment.duration_sec = number_field(&probe["format"]["duration"]).filter(|v| *v > 0.0);
+    segment.video_codec = video.and_then(|s| s["codec_name"].as_str()).map(str::to_string);
+    segment.audio_codec = audio.and_then(|s| s["codec_name"].as_str()).map(str::to_string);
+    segment.width = video.and_then(|s| s["width"].as_u64()).and_then(|v| u32::try_from(v).ok());
+    segment.height = video.and_then(|s| s["height"].as_u64()).and_then(|v| u32::try_from(v).ok());
+    segment.frame_rate = video.and_then(|s| {
+        parse_frame_rate(s["avg_frame_rate"].as_str().unwrap_or_default())
+            .or_else(|| parse_frame_rate(s["r_frame_rate"].as_str().unwrap_or_default()))
+    });
+
+    let bit_rate_bps = number_field(&probe["format"]["bit_rate"])
+        .or_else(|| {
+            let total: f64 = streams.iter().filter_map(|s| number_field(&s["bit_rate"])).sum();
+            (total > 0.0).then_some(total)
+        })
+        .or_else(|| {
+            segment.duration_sec.map(|duration| segment.size_bytes as f64 * 8.0 / duration)
+        });
+    segment.bit_rate_kbps =
+        bit_rate_bps.filter(|v| *v > 0.0).map(|bps| (bps / 1000.0).round() as u64);
+}
+
+fn number_field(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn parse_frame_rate(raw: &str) -> Option<f64> {
+    let rate = match raw.split_once('/') {
+        Some((num, den)) => {
+            let num: f64 = num.trim().parse().ok()?;
+            let den: f64 = den.trim().parse().ok()?;
+            if den == 0.0 {
+                return None;
+            }
+            num / den
+        }
+        None => raw.trim().parse().ok()?,
+    };
+    (rate.is_finite() && rate > 0.0).then(|| (rate * 100.0).round() / 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{StreamSignature, apply_probe_metrics, parse_frame_rate};
+    use serde_json::json;
+    use shared::RecordingSegment;
+
+    #[test]
+    fn frame_rate_parses_ffprobe_fractions() {
+        assert_eq!(parse_frame_rate("30000/1001"), Some(29.97));
+        assert_eq!(parse_frame_rate("60/1"), Some(60.0));
+        assert_eq!(parse_frame_rate("0/0"), None);
+        assert_eq!(parse_frame_rate(""), None);
+    }
+
+    #[test]
+    fn probe_metrics_fall_back_to_file_size_when_bit_rate_missing() {
+        let probe = json!({
+            "streams": [
+                {"codec_type": "video", "codec_name": "h264", "width": 1920, "height": 1080,
+                 "avg_frame_rate": "60/1"},
+                {"codec_type": "audio", "codec_name": "aac"}
+            ],
+            "format": {"duration": "10.000000"}
+        });
+        let mut segment = RecordingSegment { size_bytes: 7_500_000, ..Default::default() };
+
+        apply_probe_metrics(&mut segment, &probe);
+
+        assert_eq!(segment.width, Some(1920));
+        assert_eq!(segment.height, Some(1080));
+        assert_eq!(segment.frame_rate, Some(60.0));
+        assert_eq!(segment.video_codec.as_deref(), Some("h264"));
+        assert_eq!(segment.audio_codec.as_deref(), Some("aac"));
+        assert_eq!(segment.duration_sec, Some(10.0));
+        assert_eq!(segment.bit_rate_kbps, Some(6000));
+    }
+
+    #[test]
+    fn stream_signature_tracks_resolution_and_codec() {
+        let probe = |height: u32| {
+            json!({"streams": [
+                {"codec_type": "video", "codec_name": "hevc", "width": 1280, "height": height}
+            ]})
+        };
+
+        let sd = StreamSignature::from_probe(&probe(720)).expect("video signature");
+        let hd = StreamSignature::from_probe(&probe(1080)).expect("video signature");
+
+        assert_ne!(sd, hd);
+        assert_eq!(sd.to_string(), "1280x720 hevc");
+        assert_eq!(StreamSignature::from_probe(&json!({"streams": []})), None);
+    }
+}
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use super::quality_for_url;
 
 pub(super) struct RecorderRuntimeConfig {
@@ -6,11 +8,16 @@ pub(super) struct RecorderRuntimeConfig {
     pub(super) quality: String,
     pub(super) auto_cleanup_after_upload: bool,
     pub(super) min_upload_file_size_bytes: u64,
+    pub(super) stall_timeout: Option<Duration>,
+    pub(super) audio_only: bool,
+    pub(super) upload_while_recording: bool,
+    pub(super) recorder_options: shared::RecorderOptions,
 }
 
 pub(super) fn build_runtime_config(
     url: &str,
     settings: &shared::RecordingSettings,
+    recorder_options: shared::RecorderOptions,
 ) -> RecorderRuntimeConfig {
     RecorderRuntimeConfig {
         segment_size_bytes: settings
@@ -21,5 +28,9 @@ pub(super) fn build_runtime_config(
         quality: quality_for_url(url, &settings.quality),
         auto_cleanup_after_upload: settings.auto_cleanup_after_upload,
         min_upload_file_size_bytes: settings.min_upload_file_size_mb.saturating_mul(1024 * 1024),
+        stall_timeout: Some(settings.stall_timeout_sec).filter(|v| *v > 0).map(Duration::from_secs),
+        audio_only: settings.audio_only,
+        upload_while_recording: settings.upload_while_recording,
+        recorder_options,
     }
 }
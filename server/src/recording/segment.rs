@@ -1,10 +1,13 @@
 use std::{collections::VecDeque, process::Stdio, sync::Arc, time::Duration};
 
+use shared::{RecorderBackend, RecorderOptions};
 use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
 use tokio::process::Command;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, oneshot};
 use tokio::time::{Instant, MissedTickBehavior, interval, sleep};
 
+use super::options::{merged_headers, streamlink_http_args};
+use super::probe::{StreamSignature, probe_stream_signature};
 use super::{RecorderRuntimeConfig, prepare_segment_file, resolve_task_name, stop_segment_process};
 use crate::{
     checker::STREAMLINK_PATH, platform::resolve_stream, state::SharedState,
@@ -14,6 +17,8 @@ use crate::{
 const FFMPEG_PATH: &str = "ffmpeg";
 const RECORDER_OUTPUT_LINES: usize = 20;
 const STORAGE_GUARD_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+const STREAM_CHANGE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const LIVE_TITLE_LOOKUP_TIMEOUT: Duration = Duration::from_secs(20);
 
 type RecorderOutputBuffer = Arc<Mutex<VecDeque<String>>>;
 
@@ -28,6 +33,88 @@ pub(super) struct SegmentRecordResult {
     pub(super) terminal_error: Option<String>,
     pub(super) disk_full: bool,
     pub(super) storage_guard_triggered: bool,
+    pub(super) stalled: bool,
+    pub(super) stream_change: Option<StreamChange>,
+}
+
+/// Why a segment was closed early so the next one starts under new stream parameters.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum StreamChange {
+    Title(String),
+    Video { from: StreamSignature, to: StreamSignature },
+}
+
+impl std::fmt::Display for StreamChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StreamChange::Title(title) => write!(f, "live title changed to {title:?}"),
+            StreamChange::Video { from, to } => write!(f, "video changed from {from} to {to}"),
+        }
+    }
+}
+
+/// Tracks the live title and, for direct inputs, the video parameters of the running segment.
+struct StreamChangeWatcher {
+    title: Option<String>,
+    signature: Option<StreamSignature>,
+}
+
+impl StreamChangeWatcher {
+    fn new(title: Option<&str>) -> Self {
+        Self { title: normalized_title(title), signature: None }
+    }
+
+    /// Lookup failures and blank titles never split a segment.
+    fn observe_title(&mut self, fetched: Option<&str>) -> Option<StreamChange> {
+        let fetched = normalized_title(fetched)?;
+        match &self.title {
+            Some(current) if *current == fetched => None,
+            Some(_) => Some(StreamChange::Title(fetched)),
+            None => {
+                self.title = Some(fetched);
+                None
+            }
+        }
+    }
+
+    /// The first probe becomes the baseline for this segment.
+    fn observe_signature(&mut self, probed: StreamSignature) -> Option<StreamChange> {
+        match &self.signature {
+            Some(current) if *current == probed => None,
+            Some(current) => Some(StreamChange::Video { from: current.clone(), to: probed }),
+            None => {
+                self.signature = Some(probed);
+                None
+            }
+        }
+    }
+}
+
+fn normalized_title(title: Option<&str>) -> Option<String> {
+    title.map(str::trim).filter(|v| !v.is_empty()).map(str::to_string)
+}
+
+struct StallWatchdog {
+    timeout: Duration,
+    last_len: u64,
+    last_growth_at: Instant,
+}
+
+impl StallWatchdog {
+    fn new(timeout: Duration, now: Instant) -> Self {
+        Self { timeout, last_len: 0, last_growth_at: now }
+    }
+
+    /// Returns the idle duration once the output has not grown for longer than the timeout.
+    fn observe(&mut self, len: u64, now: Instant) -> Option<Duration> {
+        if len > self.last_len {
+            self.last_len = len;
+            self.last_growth_at = now;
+            return None;
+        }
+        let idle = now.saturating_duration_since(self.last_growth_at);
+        (idle >= self.timeout).then_some(idle)
+    }
 }
 
 pub(super) async fn record_segment(
@@ -35,8 +122,10 @@ pub(super) async fn record_segment(
     url: &str,
     state: &SharedState,
     runtime: &RecorderRuntimeConfig,
+    live_title: Option<&str>,
 ) -> SegmentRecordResult {
-    let current_filename = match prepare_segment_file(state, task_id).await {
+    let extension = if runtime.audio_only { "m4a" } else { "mp4" };
+    let current_filename = match prepare_segment_file(state, task_id, extension).await {
         Ok(filename) => filename,
         Err(e) => {
             let task_name = resolve_task_name(state, task_id);
@@ -53,47 +142,35 @@ pub(super) async fn record_segment(
                 terminal_error: Some(format!("Failed to prepare recording file: {}", e)),
                 disk_full: is_disk_full_error(&e),
                 storage_guard_triggered: false,
+                stalled: false,
+                stream_change: None,
             };
         }
     };
 
     tracing::info!("Task {} starting segment: {}", task_id, current_filename);
 
-    let recorder = match resolve_stream(url, &runtime.quality).await {
-        Ok(Some(stream)) => {
-            tracing::info!(
-                "Task {} resolved platform stream: original={}, resolved={}",
-                task_id,
-                url,
-                stream.input_url
-            );
-            if stream.direct_input {
-                RecorderCommand::Ffmpeg { input_url: stream.input_url }
-            } else {
-                RecorderCommand::Streamlink {
-                    input_url: stream.input_url,
-                    quality: "best".to_string(),
-                }
-            }
-        }
-        Ok(None) => RecorderCommand::Streamlink {
-            input_url: url.to_string(),
-            quality: runtime.quality.clone(),
-        },
+    let recorder = match select_recorder(task_id, url, runtime).await {
+        Ok(recorder) => recorder,
         Err(e) => {
-            tracing::warn!(
-                "Task {} failed to resolve platform stream, falling back to original URL: {}",
-                task_id,
-                e
-            );
-            RecorderCommand::Streamlink {
-                input_url: url.to_string(),
-                quality: runtime.quality.clone(),
-            }
+            // Nothing was written, so the segment counts as empty and the live check decides
+            // whether to try again.
+            tracing::error!("Task {} could not start segment {}: {}", task_id, current_filename, e);
+            return SegmentRecordResult {
+                filename: current_filename,
+                limit_reached: false,
+                terminal_error: None,
+                disk_full: false,
+                storage_guard_triggered: false,
+                stalled: false,
+                stream_change: None,
+            };
         }
     };
+    let options = &runtime.recorder_options;
 
-    let (mut command, recorder_name) = build_recorder_command(&recorder, &current_filename);
+    let (mut command, recorder_name) =
+        build_recorder_command(&recorder, &current_filename, options);
 
     let mut child = match command.spawn() {
         Ok(child) => child,
@@ -106,6 +183,8 @@ pub(super) async fn record_segment(
                 terminal_error: Some(message),
                 disk_full: is_disk_full_error(&e),
                 storage_guard_triggered: false,
+                stalled: false,
+                stream_change: None,
             };
         }
     };
@@ -129,9 +208,15 @@ pub(super) async fn record_segment(
 
     let mut limit_reached = false;
     let mut storage_guard_triggered = false;
+    let mut stalled = false;
+    let mut stream_change = None;
     let mut recorder_error = None;
     let segment_started_at = Instant::now();
+    let mut stall_watchdog =
+        runtime.stall_timeout.map(|timeout| StallWatchdog::new(timeout, segment_started_at));
     let mut last_storage_guard_check: Option<Instant> = None;
+    let (stream_change_watch, mut stream_change_rx) =
+        spawn_stream_change_watch(task_id, url, state, &recorder, options, live_title);
     let mut check_interval = interval(Duration::from_secs(1));
     check_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
 
@@ -173,6 +258,17 @@ pub(super) async fn record_segment(
                 }
                 break;
             }
+            Ok(change) = &mut stream_change_rx => {
+                tracing::info!(
+                    "Task {} {}, closing segment {} to start a new one",
+                    task_id,
+                    change,
+                    current_filename
+                );
+                stream_change = Some(change);
+                stop_segment_process(&mut child, task_id, "stream change").await;
+                break;
+            }
             _ = check_interval.tick() => {
                 if let Some(limit_sec) = runtime.segment_time_sec
                     && segment_started_at.elapsed() >= Duration::from_secs(limit_sec)
@@ -198,6 +294,27 @@ pub(super) async fn record_segment(
                     break;
                 }
 
+                if let Some(watchdog) = stall_watchdog.as_mut() {
+                    let len = tokio::fs::metadata(&current_filename)
+                        .await
+                        .map(|meta| meta.len())
+                        .unwrap_or(0);
+                    if let Some(idle) = watchdog.observe(len, Instant::now()) {
+                        tracing::warn!(
+                            "Task {} stall event: {} output has not grown for {}s (size={} bytes, input={}, output={}), restarting segment with a freshly resolved stream",
+                            task_id,
+                            recorder_name,
+                            idle.as_secs(),
+                            len,
+                            recorder.input_url(),
+                            current_filename
+                        );
+                        stalled = true;
+                        stop_segment_process(&mut child, task_id, "stall").await;
+                        break;
+                    }
+                }
+
                 if last_storage_guard_check
                     .map(|last| last.elapsed() >= STORAGE_GUARD_CHECK_INTERVAL)
                     .unwrap_or(true)
@@ -230,6 +347,7 @@ pub(super) async fn record_segment(
             }
         }
     }
+    stream_change_watch.abort();
 
     let disk_full = recorder_error.as_deref().is_some_and(is_disk_full_message);
     if disk_full {
@@ -240,8 +358,23 @@ pub(super) async fn record_segment(
         );
     }
 
+    if stalled
+        && !recorded_file_has_content(&current_filename).await
+        && let Err(e) = tokio::fs::remove_file(&current_filename).await
+        && e.kind() != std::io::ErrorKind::NotFound
+    {
+        tracing::warn!(
+            "Task {} failed to remove empty stalled segment {}: {}",
+            task_id,
+            current_filename,
+            e
+        );
+    }
+
     let terminal_error = if !limit_reached
         && !storage_guard_triggered
+        && !stalled
+        && stream_change.is_none()
         && recorder_error.is_some()
         && !recorded_file_has_content(&current_filename).await
     {
@@ -256,6 +389,81 @@ pub(super) async fn record_segment(
         terminal_error,
         disk_full,
         storage_guard_triggered,
+        stalled,
+        stream_change,
+    }
+}
+
+/// Runs the live title lookup and stream probe in a task of their own so slow network calls never
+/// hold up the recorder loop. The first check waits a full interval after the segment starts.
+fn spawn_stream_change_watch(
+    task_id: &str,
+    url: &str,
+    state: &SharedState,
+    recorder: &RecorderCommand,
+    options: &RecorderOptions,
+    live_title: Option<&str>,
+) -> (tokio::task::JoinHandle<()>, oneshot::Receiver<StreamChange>) {
+    let (sender, receiver) = oneshot::channel();
+    let task_id = task_id.to_string();
+    let url = url.to_string();
+    let state = state.clone();
+    let probe_input = stream_probe_input(recorder, options);
+    let mut watcher = StreamChangeWatcher::new(live_title);
+    let handle = tokio::spawn(async move {
+        loop {
+            sleep(STREAM_CHANGE_CHECK_INTERVAL).await;
+            if let Some(change) =
+                check_stream_change(&task_id, &url, &state, probe_input.as_ref(), &mut watcher)
+                    .await
+            {
+                let _ = sender.send(change);
+                return;
+            }
+        }
+    });
+    (handle, receiver)
+}
+
+/// Streamlink resolves the page itself, so only direct inputs can be probed for parameters, and an
+/// audio-only recording has no video signature worth comparing.
+fn stream_probe_input(
+    recorder: &RecorderCommand,
+    options: &RecorderOptions,
+) -> Option<(String, String)> {
+    match recorder {
+        RecorderCommand::Ffmpeg { input_url, audio_only: false } => {
+            Some((input_url.clone(), ffmpeg_headers(input_url, options)))
+        }
+        _ => None,
+    }
+}
+
+async fn check_stream_change(
+    task_id: &str,
+    url: &str,
+    state: &SharedState,
+    probe_input: Option<&(String, String)>,
+    watcher: &mut StreamChangeWatcher,
+) -> Option<StreamChange> {
+    match tokio::time::timeout(LIVE_TITLE_LOOKUP_TIMEOUT, state.checker.fetch_live_title(url)).await
+    {
+        Ok(title) => {
+            if let Some(change) = watcher.observe_title(title.as_deref()) {
+                return Some(change);
+            }
+        }
+        Err(_) => tracing::warn!("Task {} live title lookup timed out during segment", task_id),
+    }
+
+    let (input_url, headers) = probe_input?;
+    match probe_stream_signature(input_url, headers).await {
+        Ok(Some(signature)) => watcher.observe_signature(signature),
+        Ok(None) => None,
+        Err(e) => {
+            tracing::warn!("Task {} failed to probe stream parameters: {}", task_id, e);
+            None
+        }
     }
 }
 
@@ -273,35 +481,125 @@ fn is_disk_full_message(message: &str) -> bool {
 
 enum RecorderCommand {
     Streamlink { input_url: String, quality: String },
-    Ffmpeg { input_url: String },
+    Ffmpeg { input_url: String, audio_only: bool },
 }
 
 impl RecorderCommand {
     fn input_url(&self) -> &str {
         match self {
             RecorderCommand::Streamlink { input_url, .. }
-            | RecorderCommand::Ffmpeg { input_url } => input_url,
+            | RecorderCommand::Ffmpeg { input_url, .. } => input_url,
         }
     }
 
     fn quality_label(&self) -> &str {
         match self {
             RecorderCommand::Streamlink { quality, .. } => quality,
-            RecorderCommand::Ffmpeg { .. } => "copy",
+            RecorderCommand::Ffmpeg { audio_only: true, .. } => "audio copy",
+            RecorderCommand::Ffmpeg { audio_only: false, .. } => "copy",
         }
     }
 }
 
-fn build_recorder_command(recorder: &RecorderCommand, output: &str) -> (Command, &'static str) {
+/// Streamlink would write the container it received into the `.m4a` file, so audio-only always
+/// records with ffmpeg, whichever backend is configured.
+async fn select_recorder(
+    task_id: &str,
+    url: &str,
+    runtime: &RecorderRuntimeConfig,
+) -> Result<RecorderCommand, String> {
+    let backend = runtime.recorder_options.backend;
+    if backend == RecorderBackend::Streamlink && !runtime.audio_only {
+        return Ok(RecorderCommand::Streamlink {
+            input_url: url.to_string(),
+            quality: runtime.quality.clone(),
+        });
+    }
+
+    // Audio-only needs ffmpeg to drop the video stream, same as an explicit ffmpeg backend.
+    let force_ffmpeg = backend == RecorderBackend::Ffmpeg || runtime.audio_only;
+    match resolve_stream(url, &runtime.quality).await {
+        Ok(Some(stream)) => {
+            tracing::info!(
+                "Task {} resolved platform stream: original={}, resolved={}",
+                task_id,
+                url,
+                stream.input_url
+            );
+            if stream.direct_input || force_ffmpeg {
+                warn_streamlink_args_ignored(task_id, &runtime.recorder_options);
+                return Ok(RecorderCommand::Ffmpeg {
+                    input_url: stream.input_url,
+                    audio_only: runtime.audio_only,
+                });
+            }
+            return Ok(RecorderCommand::Streamlink {
+                input_url: stream.input_url,
+                quality: "best".to_string(),
+            });
+        }
+        Ok(None) => {}
+        Err(e) => {
+            tracing::warn!(
+                "Task {} failed to resolve platform stream, falling back to original URL: {}",
+                task_id,
+                e
+            );
+        }
+    }
+
+    if force_ffmpeg {
+        streamlink_resolved_ffmpeg_recorder(task_id, url, runtime).await
+    } else {
+        Ok(RecorderCommand::Streamlink {
+            input_url: url.to_string(),
+            quality: runtime.quality.clone(),
+        })
+    }
+}
+
+/// Logs that a configured streamlink backend records with ffmpeg, which drops its extra arguments.
+fn warn_streamlink_args_ignored(task_id: &str, options: &RecorderOptions) {
+    if options.backend == RecorderBackend::Streamlink && !options.extra_args.is_empty() {
+        tracing::warn!(
+            "Task {} records with ffmpeg, ignoring the extra streamlink arguments",
+            task_id
+        );
+    }
+}
+
+/// Extra arguments are written for one recorder, so the other one runs without them;
+/// `select_recorder` refuses or logs such a switch.
+fn extra_args_for<'a>(recorder: &RecorderCommand, options: &'a RecorderOptions) -> &'a [String] {
+    let matches_backend = matches!(
+        (recorder, options.backend),
+        (RecorderCommand::Streamlink { .. }, RecorderBackend::Streamlink)
+            | (RecorderCommand::Ffmpeg { .. }, RecorderBackend::Ffmpeg)
+    );
+    if matches_backend { &options.extra_args } else { &[] }
+}
+
+fn build_recorder_command(
+    recorder: &RecorderCommand,
+    output: &str,
+    options: &RecorderOptions,
+) -> (Command, &'static str) {
+    let extra_args = extra_args_for(recorder, options);
     match recorder {
         RecorderCommand::Streamlink { input_url, quality } => {
             let mut command = Command::new(STREAMLINK_PATH);
-            command.arg("-o").arg(output).arg(input_url).arg(quality);
+            command
+                .args(streamlink_http_args(options))
+                .args(extra_args)
+                .arg("-o")
+                .arg(output)
+                .arg(input_url)
+                .arg(quality);
             command.stdout(Stdio::piped()).stderr(Stdio::piped());
             command.kill_on_drop(true);
             (command, STREAMLINK_PATH)
         }
-        RecorderCommand::Ffmpeg { input_url } => {
+        RecorderCommand::Ffmpeg { input_url, audio_only } => {
             let mut command = Command::new(FFMPEG_PATH);
             command
                 .arg("-hide_banner")
@@ -315,15 +613,20 @@ fn build_recorder_command(recorder: &RecorderCommand, output: &str) -> (Command,
                 .arg("-reconnect_delay_max")
                 .arg("5")
                 .arg("-headers")
-                .arg(ffmpeg_headers_for_input(input_url))
+                .arg(ffmpeg_headers(input_url, options))
                 .arg("-i")
-                .arg(input_url)
-                .arg("-c")
-                .arg("copy")
+                .arg(input_url);
+            if *audio_only {
+                command.arg("-vn").arg("-c:a").arg("copy");
+            } else {
+                command.arg("-c").arg("copy");
+            }
+            command
                 .arg("-bsf:a")
                 .arg("aac_adtstoasc")
                 .arg("-movflags")
                 .arg("frag_keyframe+empty_moov")
+                .args(extra_args)
                 .arg("-f")
                 .arg("mp4")
                 .arg(output);
@@ -334,6 +637,90 @@ fn build_recorder_command(recorder: &RecorderCommand, output: &str) -> (Command,
     }
 }
 
+/// Asks streamlink for the stream URL (the audio rendition when audio-only) so ffmpeg records it.
+async fn streamlink_resolved_ffmpeg_recorder(
+    task_id: &str,
+    url: &str,
+    runtime: &RecorderRuntimeConfig,
+) -> Result<RecorderCommand, String> {
+    let selector = if runtime.audio_only {
+        audio_only_quality_selector(&runtime.quality)
+    } else {
+        runtime.quality.clone()
+    };
+    match streamlink_stream_url(url, &selector, &runtime.recorder_options).await {
+        Ok(input_url) => {
+            tracing::info!(
+                "Task {} resolved stream URL via streamlink for ffmpeg: selector={}, resolved={}",
+                task_id,
+                selector,
+                input_url
+            );
+            warn_streamlink_args_ignored(task_id, &runtime.recorder_options);
+            Ok(RecorderCommand::Ffmpeg { input_url, audio_only: runtime.audio_only })
+        }
+        Err(e) if runtime.audio_only => {
+            Err(format!("could not resolve an audio stream URL for ffmpeg: {e}"))
+        }
+        // Streamlink would run without the ffmpeg arguments the download was configured with.
+        Err(e) if !runtime.recorder_options.extra_args.is_empty() => Err(format!(
+            "could not resolve a stream URL for ffmpeg, not falling back to streamlink without the extra ffmpeg arguments: {e}"
+        )),
+        Err(e) => {
+            tracing::warn!(
+                "Task {} could not resolve a stream URL for ffmpeg, recording streamlink output as-is: {}",
+                task_id,
+                e
+            );
+            Ok(RecorderCommand::Streamlink { input_url: url.to_string(), quality: selector })
+        }
+    }
+}
+
+/// Streamlink picks the first available name, so audio renditions win over the video quality.
+fn audio_only_quality_selector(quality: &str) -> String {
+    let quality = quality.trim();
+    let fallback = if quality.is_empty() { "best" } else { quality };
+    format!("audio_only,audio_mp4,audio_webm,audio,{fallback}")
+}
+
+async fn streamlink_stream_url(
+    url: &str,
+    selector: &str,
+    options: &RecorderOptions,
+) -> Result<String, String> {
+    let output = Command::new(STREAMLINK_PATH)
+        .args(streamlink_http_args(options))
+        .arg("--stream-url")
+        .arg(url)
+        .arg(selector)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| format!("failed to execute streamlink --stream-url: {e}"))?;
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if output.status.success() && stdout.starts_with("http") {
+        Ok(stdout)
+    } else {
+        Err(format!(
+            "streamlink --stream-url exited with {}: {}",
+            output.status,
+            if stdout.is_empty() {
+                String::from_utf8_lossy(&output.stderr).trim().to_string()
+            } else {
+                stdout
+            }
+        ))
+    }
+}
+
+fn ffmpeg_headers(input_url: &str, options: &RecorderOptions) -> String {
+    merged_headers(ffmpeg_headers_for_input(input_url), options)
+        .into_iter()
+        .map(|(name, value)| format!("{name}: {value}\r\n"))
+        .collect()
+}
+
 fn ffmpeg_headers_for_input(input_url: &str) -> &'static str {
     if is_bilibili_cdn_url(input_url) {
         "Referer: https://live.bilibili.com/\r\nUser-Agent: Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/135.0.0.0 Safari/537.36\r\n"
@@ -479,7 +866,15 @@ pub(super) async fn decide_next_segment_action(
 
 #[cfg(test)]
 mod tests {
-    use super::{ffmpeg_headers_for_input, is_bilibili_cdn_url, is_disk_full_message};
+    use super::{
+        RecorderCommand, StallWatchdog, StreamChange, StreamChangeWatcher,
+        audio_only_quality_selector, build_recorder_command, ffmpeg_headers_for_input,
+        is_bilibili_cdn_url, is_disk_full_message, select_recorder, stream_probe_input,
+    };
+    use crate::recording::runtime::build_runtime_config;
+    use shared::{RecorderBackend, RecorderOptions, RecordingSettings};
+    use std::time::Duration;
+    use tokio::time::Instant;
 
     #[test]
     fn ffmpeg_headers_include_bilibili_referer_for_bilibili_cdn() {
@@ -505,4 +900,147 @@ mod tests {
         assert!(is_disk_full_message("fatal error: disk full"));
         assert!(!is_disk_full_message("network timeout"));
     }
+
+    #[test]
+    fn stall_watchdog_fires_only_after_timeout_without_growth() {
+        let start = Instant::now();
+        let mut watchdog = StallWatchdog::new(Duration::from_secs(30), start);
+
+        assert_eq!(watchdog.observe(0, start + Duration::from_secs(29)), None);
+        assert_eq!(
+            watchdog.observe(0, start + Duration::from_secs(30)),
+            Some(Duration::from_secs(30))
+        );
+    }
+
+    #[test]
+    fn stall_watchdog_resets_when_output_grows() {
+        let start = Instant::now();
+        let mut watchdog = StallWatchdog::new(Duration::from_secs(30), start);
+
+        assert_eq!(watchdog.observe(1024, start + Duration::from_secs(25)), None);
+        assert_eq!(watchdog.observe(1024, start + Duration::from_secs(50)), None);
+        assert!(watchdog.observe(1024, start + Duration::from_secs(55)).is_some());
+    }
+
+    #[test]
+    fn stream_watcher_splits_only_on_a_real_title_change() {
+        let mut watcher = StreamChangeWatcher::new(Some("原神 开荒"));
+
+        assert_eq!(watcher.observe_title(Some(" 原神 开荒 ")), None);
+        assert_eq!(watcher.observe_title(None), None);
+        assert_eq!(watcher.observe_title(Some("  ")), None);
+        assert_eq!(
+            watcher.observe_title(Some("星铁 抽卡")),
+            Some(StreamChange::Title("星铁 抽卡".to_string()))
+        );
+    }
+
+    #[test]
+    fn stream_watcher_adopts_first_title_when_session_started_without_one() {
+        let mut watcher = StreamChangeWatcher::new(None);
+
+        assert_eq!(watcher.observe_title(Some("晚间杂谈")), None);
+        assert_eq!(
+            watcher.observe_title(Some("深夜电台")),
+            Some(StreamChange::Title("深夜电台".to_string()))
+        );
+    }
+
+    #[test]
+    fn stream_probe_skips_audio_only_and_streamlink_inputs() {
+        let options = RecorderOptions::default();
+        let video = RecorderCommand::Ffmpeg {
+            input_url: "https://cdn/live.flv".to_string(),
+            audio_only: false,
+        };
+        let audio = RecorderCommand::Ffmpeg {
+            input_url: "https://cdn/live.flv".to_string(),
+            audio_only: true,
+        };
+
+        assert_eq!(
+            stream_probe_input(&video, &options).map(|(input_url, _)| input_url),
+            Some("https://cdn/live.flv".to_string())
+        );
+        assert!(stream_probe_input(&audio, &options).is_none());
+    }
+
+    #[test]
+    fn audio_only_selector_prefers_audio_renditions_then_configured_quality() {
+        assert_eq!(
+            audio_only_quality_selector("720p"),
+            "audio_only,audio_mp4,audio_webm,audio,720p"
+        );
+        assert_eq!(audio_only_quality_selector("  "), "audio_only,audio_mp4,audio_webm,audio,best");
+    }
+
+    #[test]
+    fn audio_only_ffmpeg_command_drops_video_stream() {
+        let recorder = RecorderCommand::Ffmpeg {
+            input_url: "https://example.com/live.m3u8".to_string(),
+            audio_only: true,
+        };
+
+        let (command, _) =
+            build_recorder_command(&recorder, "out.m4a", &RecorderOptions::default());
+        let args: Vec<String> =
+            command.as_std().get_args().map(|arg| arg.to_string_lossy().to_string()).collect();
+
+        assert!(args.iter().any(|arg| arg == "-vn"));
+        assert!(args.windows(2).any(|pair| pair == ["-c:a", "copy"]));
+        assert_eq!(args.last().map(String::as_str), Some("out.m4a"));
+    }
+
+    #[tokio::test]
+    async fn forced_ffmpeg_only_falls_back_to_streamlink_without_extra_args() {
+        // No platform resolver and no plugin for this host, so streamlink finds no stream URL.
+        let url = "https://recorder.invalid/live";
+        let runtime = |extra_args: Vec<String>| {
+            build_runtime_config(
+                url,
+                &RecordingSettings::default(),
+                RecorderOptions {
+                    backend: RecorderBackend::Ffmpeg,
+                    extra_args,
+                    ..Default::default()
+                },
+            )
+        };
+
+        let fallback = select_recorder("task", url, &runtime(vec![])).await;
+        assert!(matches!(fallback, Ok(RecorderCommand::Streamlink { .. })));
+        let with_args = runtime(vec!["-rw_timeout".to_string(), "10000000".to_string()]);
+        assert!(select_recorder("task", url, &with_args).await.is_err());
+    }
+
+    #[test]
+    fn extra_args_only_apply_to_the_chosen_backend() {
+        let options = RecorderOptions {
+            backend: RecorderBackend::Ffmpeg,
+            extra_args: vec!["-rw_timeout".to_string(), "10000000".to_string()],
+            user_agent: Some("OmniStream".to_string()),
+            ..Default::default()
+        };
+        let args = |recorder: &RecorderCommand| -> Vec<String> {
+            let (command, _) = build_recorder_command(recorder, "out.mp4", &options);
+            command.as_std().get_args().map(|arg| arg.to_string_lossy().to_string()).collect()
+        };
+
+        let ffmpeg = args(&RecorderCommand::Ffmpeg {
+            input_url: "https://example.com/live.flv".to_string(),
+            audio_only: false,
+        });
+        let streamlink = args(&RecorderCommand::Streamlink {
+            input_url: "https://example.com/live".to_string(),
+            quality: "best".to_string(),
+        });
+
+        assert!(ffmpeg.windows(2).any(|pair| pair == ["-rw_timeout", "10000000"]));
+        assert!(ffmpeg.iter().any(|arg| arg == "User-Agent: OmniStream\r\n"));
+        assert!(!streamlink.iter().any(|arg| arg == "-rw_timeout"));
+        assert!(
+            streamlink.windows(2).any(|pair| pair == ["--http-header", "User-Agent=OmniStream"])
+        );
+    }
 }
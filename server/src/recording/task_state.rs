@@ -1,4 +1,4 @@
-use shared::TaskStatus;
+use shared::{RecordingSegment, TaskStatus};
 
 use crate::state::SharedState;
 
@@ -24,8 +24,11 @@ async fn persist_task_filename(state: &SharedState, task_id: &str, filename: &st
     }
 }
 
-pub(super) async fn set_task_status(state: &SharedState, task_id: &str, status: TaskStatus) {
+pub(crate) async fn set_task_status(state: &SharedState, task_id: &str, status: TaskStatus) {
     if let Some(mut task) = state.tasks.get_mut(task_id) {
+        if status != TaskStatus::Uploading {
+            task.upload_progress = None;
+        }
         task.status = status.clone();
     }
     persist_task_status(state, task_id, &status).await;
@@ -38,6 +41,24 @@ pub(super) async fn set_task_filename(state: &SharedState, task_id: &str, filena
     persist_task_filename(state, task_id, filename).await;
 }
 
+pub(super) async fn record_task_segment(
+    state: &SharedState,
+    task_id: &str,
+    segment: RecordingSegment,
+) {
+    if let Err(e) = state.db.save_recording_segment(task_id, &segment).await {
+        tracing::error!(
+            "Failed to persist recording segment, task_id={}, filename={}: {}",
+            task_id,
+            segment.filename,
+            e
+        );
+    }
+    if let Some(mut task) = state.tasks.get_mut(task_id) {
+        task.segments.push(segment);
+    }
+}
+
 pub(super) fn clear_task_handle(state: &SharedState, task_id: &str) {
     state.handles.remove(task_id);
 }
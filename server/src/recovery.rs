@@ -0,0 +1,318 @@
+use shared::{DownloadConfig, StreamTask, TaskStatus, UploadJob, UploadJobKind, UploadJobState};
+use std::collections::HashSet;
+
+use crate::{
+    downloads::MANUAL_UPLOAD_TASK_PREFIX,
+    downloads_service::{
+        resolve_auto_cleanup_after_upload, resolve_auto_resume_uploads,
+        resolve_min_upload_file_size_bytes, scan_recording_files,
+    },
+    monitor::start_recording_for_download,
+    recording,
+    state::SharedState,
+};
+
+/// Restores tasks left active by a previous run: sessions with recorded files become
+/// pending uploads, and streams that are still live start recording again right away.
+pub async fn recover_interrupted_tasks(state: &SharedState) {
+    let tasks = match state.db.get_all_tasks().await {
+        Ok(tasks) => tasks,
+        Err(e) => {
+            tracing::error!("Startup recovery failed to load tasks from DB: {}", e);
+            return;
+        }
+    };
+
+    let jobs = match state.db.get_upload_jobs().await {
+        Ok(jobs) => jobs,
+        Err(e) => {
+            tracing::error!("Startup recovery failed to load upload jobs from DB: {}", e);
+            vec![]
+        }
+    };
+    // Tasks whose archive job is still queued are resumed by the upload workers instead;
+    // segment jobs of an interrupted recording do not submit anything on their own.
+    let queued_task_ids: HashSet<&str> = jobs
+        .iter()
+        .filter(|job| job.kind == UploadJobKind::Archive && !job.state.is_finished())
+        .map(|job| job.task_id.as_str())
+        .collect();
+
+    let mut interrupted_urls: Vec<String> = Vec::new();
+    for mut task in tasks {
+        if matches!(task.status, TaskStatus::Recording | TaskStatus::Uploading)
+            && !state.handles.contains_key(&task.id)
+            && !queued_task_ids.contains(task.id.as_str())
+        {
+            let files = existing_recorded_files(&task, &pre_uploaded_files(&jobs, &task.id)).await;
+            let status =
+                if files.is_empty() { TaskStatus::Stopped } else { TaskStatus::PendingUpload };
+            tracing::warn!(
+                "Recovering interrupted task: task_id={}, name={}, previous_status={:?}, status={:?}, files={}",
+                task.id,
+                task.name,
+                task.status,
+                status,
+                files.len()
+            );
+            if let Err(e) = state.db.update_status(&task.id, &status).await {
+                tracing::error!(
+                    "Failed to persist recovered task status, task_id={}: {}",
+                    task.id,
+                    e
+                );
+            }
+            task.status = status;
+            if !interrupted_urls.contains(&task.url) {
+                interrupted_urls.push(task.url.clone());
+            }
+        }
+        state.tasks.entry(task.id.clone()).or_insert(task);
+    }
+
+    let downloads = match state.db.get_downloads().await {
+        Ok(downloads) => downloads,
+        Err(e) => {
+            tracing::error!("Startup recovery failed to load downloads from DB: {}", e);
+            vec![]
+        }
+    };
+
+    restart_live_recordings(state, &downloads, &interrupted_urls).await;
+
+    let pending: Vec<(String, String)> = state
+        .tasks
+        .iter()
+        .filter(|r| r.value().status == TaskStatus::PendingUpload)
+        .map(|r| (r.key().clone(), r.value().url.clone()))
+        .collect();
+    for (task_id, url) in pending {
+        let download = downloads.iter().find(|d| d.url == url);
+        let auto_resume = match download {
+            Some(download) => resolve_auto_resume_uploads(state, download).await,
+            None => state.recording_settings.read().await.auto_resume_uploads,
+        };
+        if !auto_resume {
+            tracing::info!("Pending upload left for manual resume: task_id={}", task_id);
+            continue;
+        }
+        if let Err(e) = resume_pending_upload(state, &task_id, download).await {
+            tracing::warn!("Failed to resume pending upload, task_id={}: {}", task_id, e);
+        }
+    }
+}
+
+async fn restart_live_recordings(
+    state: &SharedState,
+    downloads: &[DownloadConfig],
+    interrupted_urls: &[String],
+) {
+    let to_restart: Vec<&DownloadConfig> =
+        downloads.iter().filter(|d| d.enabled && interrupted_urls.contains(&d.url)).collect();
+    if to_restart.is_empty() {
+        return;
+    }
+
+    let all_uploads = match state.db.get_uploads().await {
+        Ok(uploads) => uploads,
+        Err(e) => {
+            tracing::error!("Startup recovery failed to load uploads from DB: {}", e);
+            vec![]
+        }
+    };
+
+    for download in to_restart {
+        state.checking_urls.insert(download.url.clone(), ());
+        match state.checker.check_live(&download.url).await {
+            Ok(true) => {
+                tracing::info!(
+                    "Streamer {} is still live after restart, resuming recording",
+                    download.name
+                );
+                let task = start_recording_for_download(state, download, &all_uploads).await;
+                tracing::info!("task info: {:?}", task);
+            }
+            Ok(false) => {}
+            Err(e) => {
+                tracing::warn!(
+                    "Startup recovery could not check live status for {}: {:?}",
+                    download.name,
+                    e
+                );
+            }
+        }
+        state.checking_urls.remove(&download.url);
+    }
+}
+
+/// Queues the recorded files of a `PendingUpload` task with its original templates.
+pub(crate) async fn resume_pending_upload(
+    state: &SharedState,
+    task_id: &str,
+    download: Option<&DownloadConfig>,
+) -> Result<(), String> {
+    let task = {
+        let Some(mut task) = state.tasks.get_mut(task_id) else {
+            return Err(format!("task {task_id} not found"));
+        };
+        if task.status != TaskStatus::PendingUpload {
+            return Err(format!("task {task_id} is not pending upload"));
+        }
+        // Claim the task before awaiting so a concurrent resume cannot start it twice.
+        task.status = TaskStatus::Uploading;
+        task.clone()
+    };
+
+    let jobs = state.db.get_upload_jobs().await.unwrap_or_else(|e| {
+        tracing::warn!("Failed to load upload jobs for task_id={}: {}", task_id, e);
+        vec![]
+    });
+    let files = existing_recorded_files(&task, &pre_uploaded_files(&jobs, task_id)).await;
+    let status = if files.is_empty() { TaskStatus::Stopped } else { TaskStatus::Uploading };
+    if let Some(mut entry) = state.tasks.get_mut(task_id) {
+        entry.status = status.clone();
+    }
+    if let Err(e) = state.db.update_status(task_id, &status).await {
+        tracing::error!("Failed to persist resumed task status, task_id={}: {}", task_id, e);
+    }
+    if files.is_empty() {
+        return Err(format!("recorded files of task {task_id} no longer exist"));
+    }
+
+    let options = match download {
+        Some(download) => recording::UploadRunOptions {
+            auto_cleanup_after_upload: resolve_auto_cleanup_after_upload(state, download).await,
+            min_upload_file_size_bytes: resolve_min_upload_file_size_bytes(state, download).await,
+        },
+        None => {
+            let settings = state.recording_settings.read().await;
+            recording::UploadRunOptions {
+                auto_cleanup_after_upload: settings.auto_cleanup_after_upload,
+                min_upload_file_size_bytes: settings
+                    .min_upload_file_size_mb
+                    .saturating_mul(1024 * 1024),
+            }
+        }
+    };
+
+    tracing::info!(
+        "Resuming pending upload: task_id={}, name={}, files={}, upload_configs={}",
+        task.id,
+        task.name,
+        files.len(),
+        task.upload_configs.len()
+    );
+    recording::enqueue_upload(
+        task.id,
+        files,
+        state.clone(),
+        true,
+        task.upload_configs,
+        None,
+        task.name,
+        options,
+    )
+    .await;
+    Ok(())
+}
+
+/// Segments uploaded while recording may already be cleaned up; their upload records still let
+/// the archive reuse them.
+fn pre_uploaded_files(jobs: &[UploadJob], task_id: &str) -> HashSet<String> {
+    jobs.iter()
+        .filter(|job| {
+            job.task_id == task_id
+                && job.kind == UploadJobKind::Segment
+                && job.state == UploadJobState::Succeeded
+        })
+        .flat_map(|job| job.parts.iter().map(|part| part.filename.clone()))
+        .collect()
+}
+
+/// Recorded files still on disk, plus those already uploaded by a segment job.
+async fn existing_recorded_files(task: &StreamTask, pre_uploaded: &HashSet<String>) -> Vec<String> {
+    if task.id.starts_with(MANUAL_UPLOAD_TASK_PREFIX) {
+        // Manual uploads cover every recording file in the directory at the time they started.
+        return scan_recording_files(&recording::recording_task_dir(&task.name))
+            .await
+            .unwrap_or_default();
+    }
+
+    let mut files = Vec::new();
+    for filename in recorded_filenames(task) {
+        if pre_uploaded.contains(&filename)
+            || tokio::fs::metadata(&filename).await.is_ok_and(|meta| meta.is_file())
+        {
+            files.push(filename);
+        }
+    }
+    files
+}
+
+/// Finished segments in recording order, followed by the segment that was being written.
+fn recorded_filenames(task: &StreamTask) -> Vec<String> {
+    let mut filenames: Vec<String> = Vec::new();
+    let in_progress = std::iter::once(&task.filename);
+    for filename in task.segments.iter().map(|segment| &segment.filename).chain(in_progress) {
+        if !filenames.contains(filename) {
+            filenames.push(filename.clone());
+        }
+    }
+    filenames
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{existing_recorded_files, recorded_filenames};
+    use shared::{RecordingSegment, StreamTask, TaskStatus};
+    use std::collections::HashSet;
+
+    #[test]
+    fn recorded_filenames_append_in_progress_segment_without_duplicates() {
+        let segment = |filename: &str| RecordingSegment {
+            filename: filename.to_string(),
+            ..Default::default()
+        };
+        let task = StreamTask {
+            id: "t1".to_string(),
+            name: "主播".to_string(),
+            url: "https://live.bilibili.com/1".to_string(),
+            status: TaskStatus::PendingUpload,
+            filename: "c.mp4".to_string(),
+            upload_configs: vec![],
+            segments: vec![segment("a.mp4"), segment("b.mp4"), segment("a.mp4")],
+            upload_progress: None,
+        };
+
+        assert_eq!(recorded_filenames(&task), vec!["a.mp4", "b.mp4", "c.mp4"]);
+    }
+
+    #[tokio::test]
+    async fn recovery_keeps_pre_uploaded_segments_that_were_cleaned_up() {
+        let dir =
+            std::env::temp_dir().join(format!("omnistream-recovery-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await.expect("create dir");
+        let path = |name: &str| dir.join(name).to_string_lossy().to_string();
+        tokio::fs::write(path("a.mp4"), b"data").await.expect("write segment");
+        let segment = |filename: String| RecordingSegment { filename, ..Default::default() };
+        let task = StreamTask {
+            id: "t1".to_string(),
+            name: "主播".to_string(),
+            status: TaskStatus::Recording,
+            filename: path("c.mp4"),
+            url: "https://live.bilibili.com/1".to_string(),
+            upload_configs: vec![],
+            segments: vec![segment(path("a.mp4")), segment(path("b.mp4"))],
+            upload_progress: None,
+        };
+
+        let uploaded = HashSet::from([path("b.mp4")]);
+        assert_eq!(
+            existing_recorded_files(&task, &uploaded).await,
+            vec![path("a.mp4"), path("b.mp4")]
+        );
+        assert_eq!(existing_recorded_files(&task, &HashSet::new()).await, vec![path("a.mp4")]);
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+}
@@ -5,12 +5,18 @@ use axum::{
 };
 use dioxus_server::{DioxusRouterExt, ServeConfig};
 use shared::{
-    DownloadConfig, QrStartResponse, RecordingSettings, StorageStats, UploadAccount, UploadTemplate,
+    CollectionSeason, DownloadConfig, QrStartResponse, RecordingSettings, S3AccountRequest,
+    StorageStats, StreamTask, UploadAccount, UploadConfig, UploadHistoryPage, UploadJob,
+    UploadLineBenchmark, UploadTemplate, UploadTemplatePreview, WebDavAccountRequest,
+    YouTubeAccountRequest,
 };
 use std::sync::Arc;
 use tower_http::cors::CorsLayer;
 
-use crate::{accounts, downloads, settings, state::SharedState, storage, tasks, uploads};
+use crate::{
+    accounts, downloads, settings, state::SharedState, storage, tasks, upload_history,
+    upload_lines, upload_queue, uploads,
+};
 
 #[derive(Clone)]
 struct FrontendBackend {
@@ -85,6 +91,25 @@ impl app::api::BackendApi for FrontendBackend {
         accounts::delete_account_service(account_file).await.map_err(message)
     }
 
+    async fn add_webdav_account(&self, payload: WebDavAccountRequest) -> Result<(), String> {
+        accounts::add_webdav_account_service(payload).await.map_err(message)
+    }
+
+    async fn add_s3_account(&self, payload: S3AccountRequest) -> Result<(), String> {
+        accounts::add_s3_account_service(payload).await.map_err(message)
+    }
+
+    async fn add_youtube_account(&self, payload: YouTubeAccountRequest) -> Result<(), String> {
+        accounts::add_youtube_account_service(payload).await.map_err(message)
+    }
+
+    async fn list_account_collections(
+        &self,
+        account_file: String,
+    ) -> Result<Vec<CollectionSeason>, String> {
+        accounts::list_account_collections_service(account_file).await.map_err(message)
+    }
+
     async fn fetch_recording_settings(&self) -> Result<RecordingSettings, String> {
         Ok(settings::get_recording_settings_service(&self.state).await)
     }
@@ -103,6 +128,60 @@ impl app::api::BackendApi for FrontendBackend {
             .map(|(_, message)| message)
             .map_err(message)
     }
+
+    async fn resume_pending_uploads(&self, id: String) -> Result<String, String> {
+        downloads::resume_pending_uploads_service(&self.state, &id)
+            .await
+            .map(|(_, message)| message)
+            .map_err(message)
+    }
+
+    async fn fetch_tasks(&self) -> Result<Vec<StreamTask>, String> {
+        Ok(tasks::list_tasks_service(&self.state).await)
+    }
+
+    async fn fetch_upload_jobs(&self) -> Result<Vec<UploadJob>, String> {
+        upload_queue::list_upload_jobs_service(&self.state).await.map_err(message)
+    }
+
+    async fn retry_upload_job(&self, id: String) -> Result<String, String> {
+        upload_queue::retry_upload_job_service(&self.state, &id).await.map_err(message)
+    }
+
+    async fn retry_upload_config(&self, id: String, index: usize) -> Result<String, String> {
+        upload_queue::retry_upload_config_service(&self.state, &id, index).await.map_err(message)
+    }
+
+    async fn cancel_upload_job(&self, id: String) -> Result<String, String> {
+        upload_queue::cancel_upload_job_service(&self.state, &id).await.map_err(message)
+    }
+
+    async fn set_upload_job_priority(&self, id: String, priority: i64) -> Result<String, String> {
+        upload_queue::set_upload_job_priority_service(&self.state, &id, priority)
+            .await
+            .map_err(message)
+    }
+
+    async fn benchmark_upload_lines(&self) -> Result<Vec<UploadLineBenchmark>, String> {
+        upload_lines::benchmark_upload_lines_service().await.map_err(message)
+    }
+
+    async fn fetch_upload_history(
+        &self,
+        page: u32,
+        page_size: u32,
+    ) -> Result<UploadHistoryPage, String> {
+        upload_history::list_upload_history_service(&self.state, Some(page), Some(page_size))
+            .await
+            .map_err(message)
+    }
+
+    async fn preview_upload_template(
+        &self,
+        config: UploadConfig,
+    ) -> Result<UploadTemplatePreview, String> {
+        uploads::preview_upload_template_service(&config).map_err(message)
+    }
 }
 
 pub fn build_router(state: SharedState) -> Router {
@@ -116,14 +195,30 @@ pub fn build_router(state: SharedState) -> Router {
         .route("/api/downloads", get(downloads::list_downloads).post(downloads::add_download))
         .route("/api/downloads/{id}", delete(downloads::delete_download))
         .route("/api/downloads/{id}/upload", post(downloads::trigger_manual_upload))
+        .route("/api/downloads/{id}/upload/resume", post(downloads::resume_pending_uploads))
         .route("/api/downloads/{id}/stop", post(downloads::stop_download))
         .route("/api/downloads/{id}/resume", post(downloads::resume_download))
         .route("/api/downloads/{id}/files", delete(downloads::clear_download_files))
         .route("/api/uploads", get(uploads::list_uploads).post(uploads::add_upload))
+        .route("/api/uploads/preview", post(uploads::preview_upload_template))
         .route("/api/uploads/{id}", delete(uploads::delete_upload))
+        .route("/api/upload-jobs", get(upload_queue::list_upload_jobs))
+        .route("/api/upload-jobs/{id}/retry", post(upload_queue::retry_upload_job))
+        .route(
+            "/api/upload-jobs/{id}/configs/{index}/retry",
+            post(upload_queue::retry_upload_config),
+        )
+        .route("/api/upload-jobs/{id}/cancel", post(upload_queue::cancel_upload_job))
+        .route("/api/upload-jobs/{id}/priority", post(upload_queue::set_upload_job_priority))
+        .route("/api/upload-history", get(upload_history::list_upload_history))
+        .route("/api/upload-lines/benchmark", post(upload_lines::benchmark_upload_lines))
         .route("/api/accounts", get(accounts::list_accounts))
         .route("/api/accounts/rename", post(accounts::rename_account))
         .route("/api/accounts/delete", post(accounts::delete_account))
+        .route("/api/accounts/webdav", post(accounts::add_webdav_account))
+        .route("/api/accounts/youtube", post(accounts::add_youtube_account))
+        .route("/api/accounts/s3", post(accounts::add_s3_account))
+        .route("/api/accounts/collections", post(accounts::list_account_collections))
         .route("/api/accounts/qrcode/start", post(accounts::start_account_qrcode_login))
         .route("/api/accounts/qrcode/confirm", post(accounts::confirm_account_qrcode_login))
         .route(
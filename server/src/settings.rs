@@ -1,11 +1,25 @@
 use axum::{Json, extract::State, http::StatusCode};
-use shared::RecordingSettings;
+use shared::{
+    AccountUploadLimit, RecordingSettings, UPLOAD_LINE_AUTO, UPLOAD_LINES, UploadBandwidthSettings,
+    UploadRetrySettings,
+};
 
-use crate::state::SharedState;
+use crate::{
+    state::SharedState,
+    upload_limits::{self, MAX_UPLOAD_LIMIT_RULES},
+};
 
 const MAX_SEGMENT_SIZE_MB: u64 = 102_400;
 const MAX_SEGMENT_TIME_SEC: u64 = 86_400;
 const MAX_MIN_UPLOAD_FILE_SIZE_MB: u64 = 102_400;
+/// Below this, slow starts and CDN hiccups would restart healthy segments.
+const MIN_STALL_TIMEOUT_SEC: u64 = 10;
+const MAX_STALL_TIMEOUT_SEC: u64 = 3_600;
+const MAX_UPLOAD_CONCURRENCY: u32 = 8;
+const MAX_UPLOAD_THREADS: u32 = 16;
+const MAX_UPLOAD_ATTEMPTS: u32 = 10;
+const MAX_UPLOAD_RETRY_STEPS: usize = 10;
+const MAX_UPLOAD_RETRY_BACKOFF_SECS: u64 = 86_400;
 
 pub async fn get_recording_settings(State(state): State<SharedState>) -> Json<RecordingSettings> {
     Json(get_recording_settings_service(&state).await)
@@ -48,6 +62,9 @@ pub async fn set_recording_settings_service(
         let mut lock = state.recording_settings.write().await;
         *lock = settings;
     }
+    // Wake the upload queue so a new concurrency limit applies right away.
+    state.upload_notify.notify_one();
+    upload_limits::apply_upload_limits(state).await;
     Ok(())
 }
 
@@ -93,10 +110,71 @@ pub(crate) fn sanitize_recording_settings(
             MAX_MIN_UPLOAD_FILE_SIZE_MB
         ));
     }
+    if settings.stall_timeout_sec > MAX_STALL_TIMEOUT_SEC {
+        return Err(format!(
+            "stall_timeout_sec exceeds maximum allowed value: {}",
+            MAX_STALL_TIMEOUT_SEC
+        ));
+    }
+    // 0 turns the stall check off.
+    if (1..MIN_STALL_TIMEOUT_SEC).contains(&settings.stall_timeout_sec) {
+        return Err(format!("stall_timeout_sec must be 0 or at least {}", MIN_STALL_TIMEOUT_SEC));
+    }
+    if !(1..=MAX_UPLOAD_CONCURRENCY).contains(&settings.upload_concurrency) {
+        return Err(format!("upload_concurrency must be between 1 and {}", MAX_UPLOAD_CONCURRENCY));
+    }
+    normalize_upload_bandwidth(&mut settings.upload_bandwidth)?;
+    if !(1..=MAX_UPLOAD_THREADS).contains(&settings.upload_threads) {
+        return Err(format!("upload_threads must be between 1 and {}", MAX_UPLOAD_THREADS));
+    }
+    normalize_upload_retry(&mut settings.upload_retry)?;
+    settings.upload_line = settings.upload_line.trim().to_ascii_lowercase();
+    if settings.upload_line.is_empty() {
+        settings.upload_line = UPLOAD_LINE_AUTO.to_string();
+    }
+    if settings.upload_line != UPLOAD_LINE_AUTO
+        && !UPLOAD_LINES.contains(&settings.upload_line.as_str())
+    {
+        return Err(format!("unsupported upload_line: {}", settings.upload_line));
+    }
 
     Ok(settings)
 }
 
+fn normalize_upload_bandwidth(bandwidth: &mut UploadBandwidthSettings) -> Result<(), String> {
+    if bandwidth.schedule.len() > MAX_UPLOAD_LIMIT_RULES {
+        return Err(format!(
+            "upload limit schedule allows at most {} rules",
+            MAX_UPLOAD_LIMIT_RULES
+        ));
+    }
+    for rule in &mut bandwidth.schedule {
+        for clock in [&mut rule.start, &mut rule.end] {
+            let Some(time) = upload_limits::parse_clock(clock) else {
+                return Err(format!("invalid upload limit schedule time: {:?}", clock));
+            };
+            *clock = time.format("%H:%M").to_string();
+        }
+        if rule.start == rule.end {
+            return Err(format!("upload limit schedule rule {} has an empty window", rule.start));
+        }
+    }
+
+    let mut account_limits: Vec<AccountUploadLimit> = Vec::new();
+    for mut limit in std::mem::take(&mut bandwidth.account_limits) {
+        limit.account_file = limit.account_file.trim().to_string();
+        if limit.account_file.is_empty() || limit.limit_kbps == 0 {
+            continue;
+        }
+        match account_limits.iter_mut().find(|l| l.account_file == limit.account_file) {
+            Some(existing) => existing.limit_kbps = limit.limit_kbps,
+            None => account_limits.push(limit),
+        }
+    }
+    bandwidth.account_limits = account_limits;
+    Ok(())
+}
+
 fn normalize_quality(v: &mut String) {
     let trimmed = v.trim();
     if trimmed.is_empty() {
@@ -106,10 +184,35 @@ fn normalize_quality(v: &mut String) {
     }
 }
 
+fn normalize_upload_retry(retry: &mut UploadRetrySettings) -> Result<(), String> {
+    if !(1..=MAX_UPLOAD_ATTEMPTS).contains(&retry.max_attempts) {
+        return Err(format!(
+            "upload_retry.max_attempts must be between 1 and {}",
+            MAX_UPLOAD_ATTEMPTS
+        ));
+    }
+    if retry.backoff_secs.is_empty() {
+        retry.backoff_secs = shared::default_upload_retry_backoff_secs();
+    }
+    if retry.backoff_secs.len() > MAX_UPLOAD_RETRY_STEPS {
+        return Err(format!(
+            "upload_retry.backoff_secs allows at most {} steps",
+            MAX_UPLOAD_RETRY_STEPS
+        ));
+    }
+    if retry.backoff_secs.iter().any(|secs| *secs > MAX_UPLOAD_RETRY_BACKOFF_SECS) {
+        return Err(format!(
+            "upload_retry.backoff_secs exceeds maximum allowed value: {}",
+            MAX_UPLOAD_RETRY_BACKOFF_SECS
+        ));
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::sanitize_recording_settings;
-    use shared::RecordingSettings;
+    use shared::{AccountUploadLimit, RecordingSettings, UploadLimitRule};
 
     #[test]
     fn sanitize_recording_settings_normalizes_zero_and_blank_quality() {
@@ -144,4 +247,91 @@ mod tests {
 
         assert!(sanitize_recording_settings(settings).is_err());
     }
+
+    #[test]
+    fn sanitize_recording_settings_rejects_extreme_stall_timeout() {
+        let settings = RecordingSettings { stall_timeout_sec: 3_601, ..Default::default() };
+
+        assert!(sanitize_recording_settings(settings).is_err());
+    }
+
+    #[test]
+    fn sanitize_recording_settings_rejects_too_short_stall_timeout() {
+        for stall_timeout_sec in [1, 9] {
+            let settings = RecordingSettings { stall_timeout_sec, ..Default::default() };
+            assert!(sanitize_recording_settings(settings).is_err(), "{stall_timeout_sec}");
+        }
+        for stall_timeout_sec in [0, 10] {
+            let settings = RecordingSettings { stall_timeout_sec, ..Default::default() };
+            assert!(sanitize_recording_settings(settings).is_ok(), "{stall_timeout_sec}");
+        }
+    }
+
+    #[test]
+    fn sanitize_recording_settings_rejects_out_of_range_upload_concurrency() {
+        for upload_concurrency in [0, 9] {
+            let settings = RecordingSettings { upload_concurrency, ..Default::default() };
+
+            assert!(sanitize_recording_settings(settings).is_err());
+        }
+    }
+
+    #[test]
+    fn sanitize_recording_settings_validates_upload_line_and_threads() {
+        let settings = RecordingSettings { upload_line: " WS ".to_string(), ..Default::default() };
+        assert_eq!(sanitize_recording_settings(settings).expect("valid line").upload_line, "ws");
+
+        let settings = RecordingSettings { upload_line: "fast".to_string(), ..Default::default() };
+        assert!(sanitize_recording_settings(settings).is_err());
+
+        for upload_threads in [0, 17] {
+            let settings = RecordingSettings { upload_threads, ..Default::default() };
+            assert!(sanitize_recording_settings(settings).is_err());
+        }
+    }
+
+    #[test]
+    fn sanitize_recording_settings_validates_upload_retry() {
+        let mut settings = RecordingSettings::default();
+        settings.upload_retry.backoff_secs.clear();
+        let sanitized = sanitize_recording_settings(settings).expect("default backoff");
+        assert_eq!(sanitized.upload_retry.backoff_secs, vec![30, 120]);
+
+        for max_attempts in [0, 11] {
+            let mut settings = RecordingSettings::default();
+            settings.upload_retry.max_attempts = max_attempts;
+            assert!(sanitize_recording_settings(settings).is_err());
+        }
+        let mut settings = RecordingSettings::default();
+        settings.upload_retry.backoff_secs = vec![86_401];
+        assert!(sanitize_recording_settings(settings).is_err());
+    }
+
+    #[test]
+    fn sanitize_recording_settings_normalizes_upload_bandwidth() {
+        let mut settings = RecordingSettings::default();
+        settings.upload_bandwidth.schedule = vec![UploadLimitRule {
+            start: " 9:00 ".to_string(),
+            end: "18:00".to_string(),
+            limit_kbps: 2048,
+        }];
+        settings.upload_bandwidth.account_limits = vec![
+            AccountUploadLimit { account_file: " a.json ".to_string(), limit_kbps: 100 },
+            AccountUploadLimit { account_file: "a.json".to_string(), limit_kbps: 200 },
+            AccountUploadLimit { account_file: "b.json".to_string(), limit_kbps: 0 },
+        ];
+
+        let sanitized = sanitize_recording_settings(settings).expect("settings are valid");
+
+        assert_eq!(sanitized.upload_bandwidth.schedule[0].start, "09:00");
+        assert_eq!(
+            sanitized.upload_bandwidth.account_limits,
+            vec![AccountUploadLimit { account_file: "a.json".to_string(), limit_kbps: 200 }]
+        );
+
+        let mut invalid = RecordingSettings::default();
+        invalid.upload_bandwidth.schedule =
+            vec![UploadLimitRule { start: "25:00".to_string(), ..Default::default() }];
+        assert!(sanitize_recording_settings(invalid).is_err());
+    }
 }
@@ -1,9 +1,9 @@
 use dashmap::DashMap;
 use shared::{RecordingSettings, StreamTask};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{Notify, RwLock};
 
-use crate::{checker::StreamlinkChecker, db::Db};
+use crate::{checker::StreamlinkChecker, db::Db, upload_limits::UploadLimits};
 
 pub struct RecorderHandle {
     pub abort_handle: tokio::task::AbortHandle,
@@ -12,6 +12,9 @@ pub struct RecorderHandle {
 pub struct AppState {
     pub tasks: DashMap<String, StreamTask>,
     pub handles: DashMap<String, RecorderHandle>,
+    pub upload_handles: DashMap<String, RecorderHandle>,
+    pub upload_notify: Notify,
+    pub upload_limits: UploadLimits,
     pub checking_urls: DashMap<String, ()>,
     pub db: Db,
     pub checker: StreamlinkChecker,
@@ -9,6 +9,7 @@ pub struct LaunchTaskParams {
     pub initial_filename: String,
     pub upload_configs: Vec<UploadConfig>,
     pub custom_recording_settings: Option<shared::RecordingSettings>,
+    pub recorder_options: shared::RecorderOptions,
 }
 
 fn build_stream_task(task_id: String, params: &LaunchTaskParams) -> StreamTask {
@@ -19,6 +20,8 @@ fn build_stream_task(task_id: String, params: &LaunchTaskParams) -> StreamTask {
         status: TaskStatus::Idle,
         filename: params.initial_filename.clone(),
         upload_configs: params.upload_configs.clone(),
+        segments: vec![],
+        upload_progress: None,
     }
 }
 
@@ -37,6 +40,7 @@ pub async fn launch_recording_task(state: SharedState, params: LaunchTaskParams)
         params.initial_filename,
         state,
         params.custom_recording_settings,
+        params.recorder_options,
     )
     .await;
 
@@ -56,6 +60,7 @@ mod tests {
             initial_filename: "pending.mp4".to_string(),
             upload_configs: vec![],
             custom_recording_settings: None,
+            recorder_options: Default::default(),
         };
 
         let task = build_stream_task("task-1".to_string(), &params);
@@ -79,6 +84,7 @@ mod tests {
                 UploadConfig { title: Some("B".to_string()), ..Default::default() },
             ],
             custom_recording_settings: None,
+            recorder_options: Default::default(),
         };
 
         let task = build_stream_task("task-2".to_string(), &params);
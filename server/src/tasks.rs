@@ -8,25 +8,29 @@ use shared::{CreateTaskRequest, StreamTask, TaskStatus};
 use crate::{
     state::SharedState,
     task_launcher::{LaunchTaskParams, launch_recording_task},
+    upload_queue,
 };
 
 pub async fn list_tasks(State(state): State<SharedState>) -> Json<Vec<StreamTask>> {
+    Json(list_tasks_service(&state).await)
+}
+
+pub async fn list_tasks_service(state: &SharedState) -> Vec<StreamTask> {
     if state.tasks.is_empty() {
         match state.db.get_all_tasks().await {
             Ok(mut tasks) => {
                 for task in &mut tasks {
-                    archive_orphan_active_task(&state, task);
+                    archive_orphan_active_task(state, task);
                     state.tasks.insert(task.id.clone(), task.clone());
                 }
-                return Json(tasks);
+                return tasks;
             }
             Err(e) => {
                 tracing::error!("Failed to load tasks from DB during initial list: {}", e);
             }
         }
     }
-    let tasks: Vec<StreamTask> = state.tasks.iter().map(|r| r.value().clone()).collect();
-    Json(tasks)
+    state.tasks.iter().map(|r| r.value().clone()).collect()
 }
 
 fn archive_orphan_active_task(state: &SharedState, task: &mut StreamTask) {
@@ -59,6 +63,7 @@ pub async fn add_task(
             url: payload.url,
             upload_configs: vec![],
             custom_recording_settings: None,
+            recorder_options: Default::default(),
         },
     )
     .await;
@@ -66,18 +71,22 @@ pub async fn add_task(
 }
 
 pub async fn stop_task(Path(id): Path<String>, State(state): State<SharedState>) -> StatusCode {
-    if let Some((_, handle)) = state.handles.remove(&id) {
+    let recorder = state.handles.remove(&id);
+    if let Some((_, handle)) = &recorder {
         handle.abort_handle.abort();
-        if let Some(mut task) = state.tasks.get_mut(&id) {
-            task.status = TaskStatus::Stopped;
-        }
-        if let Err(e) = state.db.update_status(&id, &TaskStatus::Stopped).await {
-            tracing::error!("Failed to persist stopped task status, task_id={}: {}", id, e);
-        }
-        StatusCode::OK
-    } else {
-        StatusCode::NOT_FOUND
     }
+    let cancelled_uploads = upload_queue::cancel_upload_jobs_for_task(&state, &id).await;
+    if recorder.is_none() && cancelled_uploads == 0 {
+        return StatusCode::NOT_FOUND;
+    }
+
+    if let Some(mut task) = state.tasks.get_mut(&id) {
+        task.status = TaskStatus::Stopped;
+    }
+    if let Err(e) = state.db.update_status(&id, &TaskStatus::Stopped).await {
+        tracing::error!("Failed to persist stopped task status, task_id={}: {}", id, e);
+    }
+    StatusCode::OK
 }
 
 #[cfg(test)]
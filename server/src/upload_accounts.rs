@@ -0,0 +1,123 @@
+use chrono::Local;
+use shared::{AccountStrategy, UploadConfig, UploadTargetConfig};
+use std::collections::HashMap;
+
+use crate::{state::SharedState, uploader::UploadError};
+
+/// Accounts of `config` in the order its strategy tries them; each later account takes over
+/// when the one before it fails with a login or quota error. Round-robin starts after the
+/// account that published the template's last upload, least-used starts with the account
+/// that published the fewest uploads today. Both go by the upload history, so the order is
+/// the same for every segment of a session until its archive is submitted.
+pub(crate) async fn ordered_accounts(state: &SharedState, config: &UploadConfig) -> Vec<String> {
+    let accounts = config_accounts(config);
+    if accounts.len() < 2 {
+        return accounts;
+    }
+    match config.account_strategy {
+        AccountStrategy::PrimaryWithFallback => accounts,
+        AccountStrategy::RoundRobin => {
+            match state.db.last_upload_account(config.template_id.as_deref()).await {
+                Ok(last) => rotate_after(accounts, last.as_deref()),
+                Err(e) => {
+                    tracing::warn!("Failed to look up last upload account: {}", e);
+                    accounts
+                }
+            }
+        }
+        AccountStrategy::LeastUsedToday => {
+            let midnight = Local::now()
+                .date_naive()
+                .and_hms_opt(0, 0, 0)
+                .and_then(|time| time.and_local_timezone(Local).earliest())
+                .map_or(0, |time| time.timestamp());
+            match state.db.count_uploads_by_account(midnight).await {
+                Ok(counts) => least_used_first(accounts, &counts),
+                Err(e) => {
+                    tracing::warn!("Failed to count today's uploads per account: {}", e);
+                    accounts
+                }
+            }
+        }
+    }
+}
+
+/// Whether another account may succeed where this one failed: its login expired or it hit
+/// its submission limit, neither of which says anything about the other accounts.
+pub(crate) fn should_switch_account(error: &UploadError) -> bool {
+    matches!(error, UploadError::AuthExpired(_) | UploadError::QuotaExceeded(_))
+}
+
+/// The primary account and then the backups, without blanks and duplicates. Local archives
+/// and S3 buckets never switch accounts, so only their primary is used.
+fn config_accounts(config: &UploadConfig) -> Vec<String> {
+    let backups = match config.target {
+        UploadTargetConfig::LocalArchive { .. } | UploadTargetConfig::S3 { .. } => &[][..],
+        _ => &config.backup_accounts[..],
+    };
+    let mut accounts: Vec<String> = Vec::with_capacity(1 + backups.len());
+    for account in std::iter::once(&config.account_file).chain(backups) {
+        let account = account.trim();
+        if !account.is_empty() && !accounts.iter().any(|known| known == account) {
+            accounts.push(account.to_string());
+        }
+    }
+    if accounts.is_empty() {
+        accounts.push(config.account_file.clone());
+    }
+    accounts
+}
+
+fn rotate_after(mut accounts: Vec<String>, last: Option<&str>) -> Vec<String> {
+    if let Some(position) = accounts.iter().position(|account| Some(account.as_str()) == last) {
+        accounts.rotate_left(position + 1);
+    }
+    accounts
+}
+
+/// Ties keep the configured order, so the primary account goes first among equals.
+fn least_used_first(mut accounts: Vec<String>, counts: &HashMap<String, u64>) -> Vec<String> {
+    accounts.sort_by_key(|account| counts.get(account).copied().unwrap_or_default());
+    accounts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{config_accounts, least_used_first, rotate_after};
+    use shared::{UploadConfig, UploadTargetConfig};
+    use std::collections::HashMap;
+
+    fn accounts(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn config_accounts_put_primary_first_without_duplicates() {
+        let mut config = UploadConfig {
+            account_file: "a.json".to_string(),
+            backup_accounts: accounts(&["b.json", " ", "a.json", "c.json", "b.json"]),
+            ..Default::default()
+        };
+        assert_eq!(config_accounts(&config), accounts(&["a.json", "b.json", "c.json"]));
+        config.target = UploadTargetConfig::LocalArchive {
+            root_dir: "/nas".to_string(),
+            path_template: String::new(),
+            move_files: false,
+        };
+        assert_eq!(config_accounts(&config), accounts(&["a.json"]));
+    }
+
+    #[test]
+    fn strategies_order_accounts() {
+        let all = accounts(&["a", "b", "c"]);
+        assert_eq!(rotate_after(all.clone(), Some("a")), accounts(&["b", "c", "a"]));
+        assert_eq!(rotate_after(all.clone(), Some("c")), accounts(&["a", "b", "c"]));
+        assert_eq!(rotate_after(all.clone(), Some("removed")), all);
+        assert_eq!(rotate_after(all.clone(), None), all);
+
+        let counts = HashMap::from([("a".to_string(), 3), ("b".to_string(), 1)]);
+        assert_eq!(least_used_first(all.clone(), &counts), accounts(&["c", "b", "a"]));
+        let tied = HashMap::from([("a".to_string(), 1), ("c".to_string(), 1)]);
+        assert_eq!(least_used_first(all, &tied), accounts(&["b", "a", "c"]));
+    }
+}
@@ -0,0 +1,136 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+};
+use chrono::Local;
+use serde::Deserialize;
+use shared::{UploadHistoryEntry, UploadHistoryPage, UploadJob};
+
+use crate::{state::SharedState, uploader::Submission};
+
+const DEFAULT_PAGE_SIZE: u32 = 20;
+const MAX_PAGE_SIZE: u32 = 100;
+
+#[derive(Debug, Deserialize)]
+pub struct UploadHistoryQuery {
+    #[serde(default)]
+    pub page: Option<u32>,
+    #[serde(default)]
+    pub page_size: Option<u32>,
+}
+
+/// Records the outcome of one config of an archive job: the submission on success, or the
+/// error once the job gives up on it.
+pub(crate) async fn record_upload_outcome(
+    state: &SharedState,
+    job: &UploadJob,
+    index: usize,
+    started_at: i64,
+    outcome: Result<&Submission, &str>,
+) {
+    let Some(config) = job.configs.get(index) else {
+        return;
+    };
+    let files: Vec<String> = job.parts.iter().map(|part| part.filename.clone()).collect();
+    let mut total_size = 0;
+    for part in &job.parts {
+        // Jobs queued before part sizes were recorded only have the files still on disk.
+        total_size += if part.size > 0 {
+            part.size
+        } else {
+            tokio::fs::metadata(&part.filename).await.map(|metadata| metadata.len()).unwrap_or(0)
+        };
+    }
+    let (submission, error) = match outcome {
+        Ok(submission) => (Some(submission), None),
+        Err(error) => (None, Some(error.to_string())),
+    };
+    let entry = UploadHistoryEntry {
+        id: format!("{}:{}", job.id, index),
+        job_id: job.id.clone(),
+        task_id: job.task_id.clone(),
+        task_name: job.task_name.clone(),
+        download_id: download_id_for_task(state, &job.task_id).await,
+        template_id: config.template_id.clone(),
+        // The account that actually uploaded, which may be a backup of the config's own.
+        account_file: job
+            .config_statuses
+            .get(index)
+            .and_then(|status| status.account_file.clone())
+            .unwrap_or_else(|| config.account_file.clone()),
+        aid: submission.and_then(|s| s.aid),
+        bvid: submission.and_then(|s| s.bvid.clone()),
+        title: submission.map(|s| s.title.clone()),
+        appended: submission.is_some_and(|s| s.appended),
+        files,
+        total_size,
+        attempts: job.config_statuses.get(index).map_or(job.attempts, |status| status.attempts),
+        error,
+        collection_error: submission.and_then(|s| s.collection_error.clone()),
+        queued_at: job.created_at,
+        started_at,
+        finished_at: Local::now().timestamp(),
+    };
+    if let Err(e) = state.db.save_upload_history(&entry).await {
+        tracing::error!("Failed to record upload history, job_id={}: {}", job.id, e);
+    }
+}
+
+/// Download config recording the task's source, matched by URL.
+async fn download_id_for_task(state: &SharedState, task_id: &str) -> Option<String> {
+    let url = state.tasks.get(task_id).map(|task| task.url.clone())?;
+    match state.db.get_downloads().await {
+        Ok(downloads) => downloads.into_iter().find(|d| d.url == url).map(|d| d.id),
+        Err(e) => {
+            tracing::warn!("Failed to load downloads for upload history: {}", e);
+            None
+        }
+    }
+}
+
+pub async fn list_upload_history(
+    State(state): State<SharedState>,
+    Query(query): Query<UploadHistoryQuery>,
+) -> (StatusCode, Json<UploadHistoryPage>) {
+    match list_upload_history_service(&state, query.page, query.page_size).await {
+        Ok(page) => (StatusCode::OK, Json(page)),
+        Err((status, message)) => {
+            tracing::error!("Failed to list upload history: {}", message);
+            (status, Json(UploadHistoryPage::default()))
+        }
+    }
+}
+
+pub async fn list_upload_history_service(
+    state: &SharedState,
+    page: Option<u32>,
+    page_size: Option<u32>,
+) -> Result<UploadHistoryPage, (StatusCode, String)> {
+    let (page, page_size) = normalize_page(page, page_size);
+    let offset = u64::from(page - 1) * u64::from(page_size);
+    let (entries, total) = state
+        .db
+        .get_upload_history(page_size, offset)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(UploadHistoryPage { entries, total, page, page_size })
+}
+
+fn normalize_page(page: Option<u32>, page_size: Option<u32>) -> (u32, u32) {
+    let page = page.unwrap_or(1).max(1);
+    let page_size = page_size.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    (page, page_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE, normalize_page};
+
+    #[test]
+    fn page_parameters_are_clamped() {
+        assert_eq!(normalize_page(None, None), (1, DEFAULT_PAGE_SIZE));
+        assert_eq!(normalize_page(Some(0), Some(0)), (1, 1));
+        assert_eq!(normalize_page(Some(3), Some(10_000)), (3, MAX_PAGE_SIZE));
+    }
+}
@@ -0,0 +1,213 @@
+use chrono::{Local, NaiveTime, Timelike};
+use dashmap::DashMap;
+use shared::{UploadBandwidthSettings, UploadLimitRule};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::{state::SharedState, uploader::UploadThrottle};
+
+/// Schedules are re-evaluated this often so time windows switch without any user action.
+const SCHEDULE_TICK: Duration = Duration::from_secs(30);
+pub(crate) const MAX_UPLOAD_LIMIT_RULES: usize = 24;
+
+/// Token bucket shared by every upload it applies to. The rate can change at any time and
+/// takes effect on the next slice of bytes; a limit of 0 disables throttling.
+pub(crate) struct RateLimiter {
+    bytes_per_sec: AtomicU64,
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    available: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    fn new(bytes_per_sec: u64) -> Self {
+        Self {
+            bytes_per_sec: AtomicU64::new(bytes_per_sec),
+            bucket: Mutex::new(Bucket { available: 0.0, refilled_at: Instant::now() }),
+        }
+    }
+
+    fn set_limit(&self, bytes_per_sec: u64) {
+        self.bytes_per_sec.store(bytes_per_sec, Ordering::Relaxed);
+    }
+
+    fn reserve_at(&self, bytes: u64, now: Instant) -> Duration {
+        let limit = self.bytes_per_sec.load(Ordering::Relaxed);
+        let mut bucket = self.bucket.lock().unwrap_or_else(PoisonError::into_inner);
+        let elapsed = now.saturating_duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.refilled_at = now;
+        if limit == 0 {
+            bucket.available = 0.0;
+            return Duration::ZERO;
+        }
+        // At most one second of unused bandwidth can be saved up as a burst.
+        let rate = limit as f64;
+        bucket.available = (bucket.available + elapsed * rate).min(rate) - bytes as f64;
+        if bucket.available >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.available / rate)
+        }
+    }
+}
+
+impl UploadThrottle for RateLimiter {
+    fn reserve(&self, bytes: u64) -> Duration {
+        self.reserve_at(bytes, Instant::now())
+    }
+}
+
+/// Global and per-account limiters, reconfigured from recording settings.
+pub struct UploadLimits {
+    global: Arc<RateLimiter>,
+    accounts: DashMap<String, Arc<RateLimiter>>,
+}
+
+impl Default for UploadLimits {
+    fn default() -> Self {
+        Self { global: Arc::new(RateLimiter::new(0)), accounts: DashMap::new() }
+    }
+}
+
+impl UploadLimits {
+    /// Limiters an upload with `account_file` has to pass through.
+    pub(crate) fn throttles_for(&self, account_file: &str) -> Vec<Arc<dyn UploadThrottle>> {
+        let mut throttles: Vec<Arc<dyn UploadThrottle>> = vec![self.global.clone()];
+        if let Some(account) = self.accounts.get(account_file) {
+            throttles.push(account.clone());
+        }
+        throttles
+    }
+
+    fn apply(&self, settings: &UploadBandwidthSettings, now: NaiveTime) {
+        self.global.set_limit(kbps_to_bytes(effective_global_limit_kbps(settings, now)));
+        for limit in &settings.account_limits {
+            let bytes_per_sec = kbps_to_bytes(limit.limit_kbps);
+            match self.accounts.get(&limit.account_file) {
+                Some(limiter) => limiter.set_limit(bytes_per_sec),
+                None => {
+                    self.accounts.insert(
+                        limit.account_file.clone(),
+                        Arc::new(RateLimiter::new(bytes_per_sec)),
+                    );
+                }
+            }
+        }
+        // Accounts removed from the settings keep their limiter object but stop throttling.
+        for entry in &self.accounts {
+            if !settings.account_limits.iter().any(|l| &l.account_file == entry.key()) {
+                entry.value().set_limit(0);
+            }
+        }
+    }
+}
+
+/// Applies the current settings to the running limiters.
+pub(crate) async fn apply_upload_limits(state: &SharedState) {
+    let settings = state.recording_settings.read().await.upload_bandwidth.clone();
+    state.upload_limits.apply(&settings, Local::now().time());
+}
+
+/// Keeps the scheduled global limit in step with the clock.
+pub async fn run_upload_limit_scheduler(state: SharedState) {
+    loop {
+        apply_upload_limits(&state).await;
+        tokio::time::sleep(SCHEDULE_TICK).await;
+    }
+}
+
+/// The first schedule rule covering `now` overrides the global limit.
+fn effective_global_limit_kbps(settings: &UploadBandwidthSettings, now: NaiveTime) -> u64 {
+    settings
+        .schedule
+        .iter()
+        .find(|rule| rule_covers(rule, now))
+        .map_or(settings.limit_kbps, |rule| rule.limit_kbps)
+}
+
+fn rule_covers(rule: &UploadLimitRule, now: NaiveTime) -> bool {
+    let (Some(start), Some(end)) = (parse_clock(&rule.start), parse_clock(&rule.end)) else {
+        return false;
+    };
+    let minute = minute_of_day(now);
+    let (start, end) = (minute_of_day(start), minute_of_day(end));
+    if start <= end { (start..end).contains(&minute) } else { minute >= start || minute < end }
+}
+
+pub(crate) fn parse_clock(raw: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(raw.trim(), "%H:%M").ok()
+}
+
+fn minute_of_day(time: NaiveTime) -> u32 {
+    time.hour() * 60 + time.minute()
+}
+
+fn kbps_to_bytes(kbps: u64) -> u64 {
+    kbps.saturating_mul(1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RateLimiter, UploadLimits, effective_global_limit_kbps};
+    use chrono::NaiveTime;
+    use shared::{AccountUploadLimit, UploadBandwidthSettings, UploadLimitRule};
+    use std::time::{Duration, Instant};
+
+    fn at(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).expect("valid time")
+    }
+
+    #[test]
+    fn schedule_rules_override_global_limit_including_overnight_windows() {
+        let rule = |start: &str, end: &str, limit_kbps| UploadLimitRule {
+            start: start.to_string(),
+            end: end.to_string(),
+            limit_kbps,
+        };
+        let settings = UploadBandwidthSettings {
+            limit_kbps: 500,
+            schedule: vec![rule("09:00", "18:00", 2048), rule("23:00", "06:00", 0)],
+            account_limits: vec![],
+        };
+
+        assert_eq!(effective_global_limit_kbps(&settings, at(9, 0)), 2048);
+        assert_eq!(effective_global_limit_kbps(&settings, at(17, 59)), 2048);
+        assert_eq!(effective_global_limit_kbps(&settings, at(18, 0)), 500);
+        assert_eq!(effective_global_limit_kbps(&settings, at(23, 30)), 0);
+        assert_eq!(effective_global_limit_kbps(&settings, at(5, 59)), 0);
+    }
+
+    #[test]
+    fn rate_limiter_delays_bytes_beyond_the_limit_and_follows_changes() {
+        let limiter = RateLimiter::new(1_000);
+        let start = Instant::now();
+        let approx = |wait: Duration, expected: f64| (wait.as_secs_f64() - expected).abs() < 0.01;
+
+        assert!(approx(limiter.reserve_at(500, start), 0.5));
+        // Half a second later the debt is paid off and the next 1000 bytes need one second.
+        assert!(approx(limiter.reserve_at(1_000, start + Duration::from_millis(500)), 1.0));
+
+        limiter.set_limit(0);
+        assert_eq!(limiter.reserve_at(1_000_000, start + Duration::from_secs(1)), Duration::ZERO);
+    }
+
+    #[test]
+    fn account_limits_add_a_second_throttle_only_for_configured_accounts() {
+        let limits = UploadLimits::default();
+        let settings = UploadBandwidthSettings {
+            account_limits: vec![AccountUploadLimit {
+                account_file: "a.json".to_string(),
+                limit_kbps: 100,
+            }],
+            ..Default::default()
+        };
+        limits.apply(&settings, at(12, 0));
+
+        assert_eq!(limits.throttles_for("a.json").len(), 2);
+        assert_eq!(limits.throttles_for("b.json").len(), 1);
+    }
+}
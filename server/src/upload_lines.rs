@@ -0,0 +1,116 @@
+use axum::{Json, http::StatusCode};
+use shared::{UPLOAD_LINES, UploadLineBenchmark};
+use std::time::{Duration, Instant};
+
+const BENCHMARK_PAYLOAD_BYTES: usize = 2 * 1024 * 1024;
+const BENCHMARK_TIMEOUT: Duration = Duration::from_secs(10);
+/// The whole run answers within this; lines not measured by then are reported as skipped.
+const BENCHMARK_TOTAL_TIMEOUT: Duration = Duration::from_secs(60);
+
+pub async fn benchmark_upload_lines() -> (StatusCode, Json<Vec<UploadLineBenchmark>>) {
+    match benchmark_upload_lines_service().await {
+        Ok(results) => (StatusCode::OK, Json(results)),
+        Err((status, message)) => {
+            tracing::error!("Failed to benchmark upload lines: {}", message);
+            (status, Json(vec![]))
+        }
+    }
+}
+
+/// Measures every UPOS line in turn (latency of the probe endpoint, then the time to post a
+/// fixed payload), fastest first. Lines are measured one at a time so they do not compete
+/// for bandwidth, and the run stops at `BENCHMARK_TOTAL_TIMEOUT` with the results so far.
+pub async fn benchmark_upload_lines_service()
+-> Result<Vec<UploadLineBenchmark>, (StatusCode, String)> {
+    let client = reqwest::Client::builder()
+        .timeout(BENCHMARK_TIMEOUT)
+        .build()
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let deadline = tokio::time::Instant::now() + BENCHMARK_TOTAL_TIMEOUT;
+    let mut results = Vec::with_capacity(UPLOAD_LINES.len());
+    for line in UPLOAD_LINES {
+        let result = tokio::time::timeout_at(deadline, benchmark_line(&client, line))
+            .await
+            .unwrap_or_else(|_| UploadLineBenchmark {
+                line: line.to_string(),
+                error: Some("skipped: benchmark time limit reached".to_string()),
+                ..Default::default()
+            });
+        tracing::info!(
+            "Upload line benchmark: line={}, latency_ms={:?}, throughput_kbps={:?}, error={:?}",
+            result.line,
+            result.latency_ms,
+            result.throughput_kbps,
+            result.error
+        );
+        results.push(result);
+    }
+    sort_benchmarks(&mut results);
+    Ok(results)
+}
+
+async fn benchmark_line(client: &reqwest::Client, line: &str) -> UploadLineBenchmark {
+    let url = format!("https://upos-cs-upcdn{line}.bilivideo.com/OK");
+    let failed = |error: String| UploadLineBenchmark {
+        line: line.to_string(),
+        error: Some(error),
+        ..Default::default()
+    };
+
+    let started = Instant::now();
+    match client.get(&url).send().await {
+        Ok(response) if response.status().is_success() => {}
+        Ok(response) => return failed(format!("HTTP {}", response.status())),
+        Err(e) => return failed(e.to_string()),
+    }
+    let latency = started.elapsed();
+
+    let started = Instant::now();
+    match client.post(&url).body(vec![0u8; BENCHMARK_PAYLOAD_BYTES]).send().await {
+        Ok(response) if response.status().is_success() => {}
+        Ok(response) => return failed(format!("HTTP {}", response.status())),
+        Err(e) => return failed(e.to_string()),
+    }
+
+    UploadLineBenchmark {
+        line: line.to_string(),
+        latency_ms: Some(u64::try_from(latency.as_millis()).unwrap_or(u64::MAX)),
+        throughput_kbps: Some(throughput_kbps(BENCHMARK_PAYLOAD_BYTES, started.elapsed())),
+        error: None,
+    }
+}
+
+fn throughput_kbps(bytes: usize, elapsed: Duration) -> u64 {
+    let secs = elapsed.as_secs_f64().max(0.001);
+    (bytes as f64 / 1024.0 / secs) as u64
+}
+
+/// Fastest lines first; lines that failed go last.
+fn sort_benchmarks(results: &mut [UploadLineBenchmark]) {
+    results.sort_by_key(|r| std::cmp::Reverse(r.throughput_kbps.unwrap_or(0)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sort_benchmarks, throughput_kbps};
+    use shared::UploadLineBenchmark;
+    use std::time::Duration;
+
+    #[test]
+    fn benchmarks_are_sorted_fastest_first_with_failures_last() {
+        let result = |line: &str, throughput_kbps| UploadLineBenchmark {
+            line: line.to_string(),
+            throughput_kbps,
+            ..Default::default()
+        };
+        let mut results =
+            vec![result("ws", None), result("qn", Some(800)), result("bda2", Some(2048))];
+
+        sort_benchmarks(&mut results);
+
+        let order: Vec<&str> = results.iter().map(|r| r.line.as_str()).collect();
+        assert_eq!(order, vec!["bda2", "qn", "ws"]);
+        assert_eq!(throughput_kbps(2 * 1024 * 1024, Duration::from_secs(2)), 1024);
+    }
+}
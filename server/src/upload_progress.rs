@@ -0,0 +1,163 @@
+use shared::{FileUploadProgress, UploadProgress};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::{
+    state::SharedState,
+    uploader::{ProgressCallback, UploadPart},
+};
+
+/// Below this much elapsed time the byte counts are too noisy to report a rate.
+const MIN_RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Turns the uploader's per-file byte reports into throughput and ETA figures.
+struct ProgressTracker {
+    progress: UploadProgress,
+    started_at: Instant,
+    /// First report of each file; bytes resumed from an earlier attempt do not count as throughput.
+    baselines: Vec<Option<(Instant, u64)>>,
+}
+
+impl ProgressTracker {
+    fn new(
+        parts: &[UploadPart],
+        config_index: usize,
+        config_count: usize,
+        started_at: Instant,
+    ) -> Self {
+        let files: Vec<FileUploadProgress> = parts
+            .iter()
+            .map(|part| FileUploadProgress {
+                filename: part.filename.clone(),
+                total_bytes: std::fs::metadata(&part.filename).map(|m| m.len()).unwrap_or(0),
+                ..Default::default()
+            })
+            .collect();
+        let total_bytes = files.iter().map(|f| f.total_bytes).sum();
+        Self {
+            baselines: vec![None; files.len()],
+            progress: UploadProgress {
+                config_index,
+                config_count,
+                files,
+                total_bytes,
+                ..Default::default()
+            },
+            started_at,
+        }
+    }
+
+    fn record(&mut self, filename: &str, bytes_sent: u64, total_bytes: u64, now: Instant) {
+        let Some(index) = self.progress.files.iter().position(|f| f.filename == filename) else {
+            return;
+        };
+        let (base_at, base_bytes) = *self.baselines[index].get_or_insert((now, bytes_sent));
+        let file = &mut self.progress.files[index];
+        file.bytes_sent = bytes_sent;
+        file.total_bytes = total_bytes;
+        (file.bytes_per_sec, file.eta_secs) = estimate(
+            bytes_sent.saturating_sub(base_bytes),
+            now.saturating_duration_since(base_at),
+            total_bytes.saturating_sub(bytes_sent),
+        );
+
+        let gained: u64 = self
+            .progress
+            .files
+            .iter()
+            .zip(&self.baselines)
+            .filter_map(|(f, base)| base.map(|(_, bytes)| f.bytes_sent.saturating_sub(bytes)))
+            .sum();
+        self.progress.bytes_sent = self.progress.files.iter().map(|f| f.bytes_sent).sum();
+        self.progress.total_bytes = self.progress.files.iter().map(|f| f.total_bytes).sum();
+        (self.progress.bytes_per_sec, self.progress.eta_secs) = estimate(
+            gained,
+            now.saturating_duration_since(self.started_at),
+            self.progress.total_bytes.saturating_sub(self.progress.bytes_sent),
+        );
+    }
+}
+
+/// Returns bytes per second and the remaining seconds at that rate.
+fn estimate(gained: u64, elapsed: Duration, remaining: u64) -> (u64, Option<u64>) {
+    if elapsed < MIN_RATE_WINDOW || gained == 0 {
+        return (0, None);
+    }
+    let rate = (gained as f64 / elapsed.as_secs_f64()) as u64;
+    if rate == 0 {
+        return (0, None);
+    }
+    (rate, Some(remaining.div_ceil(rate)))
+}
+
+/// Publishes an empty progress for the task and returns the callback that keeps it current
+/// while the uploader sends `parts` for one upload config.
+pub(crate) fn begin(
+    state: &SharedState,
+    task_id: &str,
+    parts: &[UploadPart],
+    config_index: usize,
+    config_count: usize,
+) -> ProgressCallback {
+    let tracker = ProgressTracker::new(parts, config_index, config_count, Instant::now());
+    publish(state, task_id, Some(tracker.progress.clone()));
+
+    let tracker = Mutex::new(tracker);
+    let state = state.clone();
+    let task_id = task_id.to_string();
+    Arc::new(move |path: &Path, bytes_sent: u64, total_bytes: u64| {
+        let progress = {
+            let Ok(mut tracker) = tracker.lock() else {
+                return;
+            };
+            tracker.record(&path.to_string_lossy(), bytes_sent, total_bytes, Instant::now());
+            tracker.progress.clone()
+        };
+        publish(&state, &task_id, Some(progress));
+    })
+}
+
+pub(crate) fn clear(state: &SharedState, task_id: &str) {
+    publish(state, task_id, None);
+}
+
+fn publish(state: &SharedState, task_id: &str, progress: Option<UploadProgress>) {
+    if let Some(mut task) = state.tasks.get_mut(task_id) {
+        task.upload_progress = progress;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ProgressTracker, estimate};
+    use crate::uploader::UploadPart;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn estimate_waits_for_a_meaningful_window() {
+        assert_eq!(estimate(1_000, Duration::from_millis(200), 5_000), (0, None));
+        assert_eq!(estimate(1_000, Duration::from_secs(2), 5_000), (500, Some(10)));
+    }
+
+    #[test]
+    fn tracker_excludes_resumed_bytes_from_throughput() {
+        let start = Instant::now();
+        let parts: Vec<UploadPart> =
+            vec!["missing-a.mp4".to_string().into(), "missing-b.mp4".to_string().into()];
+        let mut tracker = ProgressTracker::new(&parts, 1, 2, start);
+
+        // The first file resumes with 600 bytes already accepted by the server.
+        tracker.record("missing-a.mp4", 600, 1_000, start);
+        tracker.record("missing-a.mp4", 1_000, 1_000, start + Duration::from_secs(2));
+        tracker.record("missing-b.mp4", 0, 1_000, start + Duration::from_secs(2));
+
+        let progress = &tracker.progress;
+        assert_eq!(progress.bytes_sent, 1_000);
+        assert_eq!(progress.total_bytes, 2_000);
+        assert_eq!(progress.files[0].bytes_per_sec, 200);
+        assert_eq!(progress.bytes_per_sec, 200);
+        assert_eq!(progress.eta_secs, Some(5));
+        assert_eq!(progress.config_index, 1);
+    }
+}
@@ -0,0 +1,921 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use chrono::Local;
+use shared::{
+    SetUploadJobPriorityRequest, TaskStatus, UPLOAD_LINE_AUTO, UploadConfig, UploadConfigState,
+    UploadConfigStatus, UploadJob, UploadJobKind, UploadJobPart, UploadJobState,
+    UploadRetrySettings, UploadTargetConfig,
+};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use tokio::task::JoinSet;
+use uuid::Uuid;
+
+use crate::{
+    archives, covers, platform, recording,
+    state::{RecorderHandle, SharedState},
+    upload_accounts, upload_history, upload_progress,
+    uploader::{
+        Submission, UploadContext, UploadError, UploadPart, UploadTarget, template::TemplateVars,
+    },
+};
+
+const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Upper bound for a server-provided `Retry-After`, so a bogus header cannot park a job forever.
+const MAX_RETRY_DELAY_SECS: u64 = 24 * 60 * 60;
+
+pub(crate) struct NewUploadJob {
+    pub task_id: String,
+    pub task_name: String,
+    pub kind: UploadJobKind,
+    pub live_title: Option<String>,
+    pub parts: Vec<UploadPart>,
+    pub configs: Vec<UploadConfig>,
+    pub auto_cleanup_after_upload: bool,
+}
+
+/// Persists an upload job and wakes the workers; the upload itself runs in the pool.
+pub(crate) async fn enqueue_upload_job(
+    state: &SharedState,
+    new_job: NewUploadJob,
+) -> Result<UploadJob, String> {
+    let now = Local::now().timestamp();
+    if new_job.kind == UploadJobKind::Archive {
+        // Segments still waiting for a worker are uploaded as part of the archive instead.
+        match state.db.cancel_pending_segment_jobs(&new_job.task_id, now).await {
+            Ok(0) => {}
+            Ok(count) => tracing::info!(
+                "Task {} archive takes over {} pending segment uploads",
+                new_job.task_id,
+                count
+            ),
+            Err(e) => tracing::warn!(
+                "Task {} failed to cancel pending segment uploads: {}",
+                new_job.task_id,
+                e
+            ),
+        }
+    }
+    let mut parts = Vec::with_capacity(new_job.parts.len());
+    for part in new_job.parts {
+        let size = part_size(state, &new_job.task_id, &part.filename).await;
+        parts.push(UploadJobPart { filename: part.filename, title: part.title, size });
+    }
+    let config_statuses = vec![UploadConfigStatus::default(); new_job.configs.len()];
+    let max_attempts = state.recording_settings.read().await.upload_retry.max_attempts;
+    let job = UploadJob {
+        id: Uuid::new_v4().to_string(),
+        task_id: new_job.task_id,
+        task_name: new_job.task_name,
+        kind: new_job.kind,
+        live_title: new_job.live_title,
+        parts,
+        configs: new_job.configs,
+        auto_cleanup_after_upload: new_job.auto_cleanup_after_upload,
+        state: UploadJobState::Queued,
+        priority: 0,
+        attempts: 0,
+        max_attempts,
+        completed_configs: 0,
+        config_statuses,
+        last_error: None,
+        next_attempt_at: now,
+        created_at: now,
+        updated_at: now,
+    };
+    state.db.save_upload_job(&job).await.map_err(|e| e.to_string())?;
+    tracing::info!(
+        "Upload job queued: job_id={}, task_id={}, kind={:?}, parts={}, configs={}",
+        job.id,
+        job.task_id,
+        job.kind,
+        job.parts.len(),
+        job.configs.len()
+    );
+    state.upload_notify.notify_one();
+    Ok(job)
+}
+
+pub async fn run_upload_workers(state: SharedState) {
+    match state.db.requeue_interrupted_upload_jobs().await {
+        Ok(0) => {}
+        Ok(count) => tracing::warn!("Requeued {} upload jobs interrupted by restart", count),
+        Err(e) => tracing::error!("Failed to requeue interrupted upload jobs: {}", e),
+    }
+
+    let mut workers = JoinSet::new();
+    let mut running: HashMap<tokio::task::Id, String> = HashMap::new();
+    loop {
+        let concurrency = state.recording_settings.read().await.upload_concurrency.max(1) as usize;
+        while workers.len() < concurrency {
+            let job = match state.db.claim_next_upload_job(Local::now().timestamp()).await {
+                Ok(Some(job)) => job,
+                Ok(None) => break,
+                Err(e) => {
+                    tracing::error!("Upload queue failed to claim next job: {}", e);
+                    break;
+                }
+            };
+            let job_id = job.id.clone();
+            let abort_handle = workers.spawn(process_upload_job(state.clone(), job));
+            running.insert(abort_handle.id(), job_id.clone());
+            state.upload_handles.insert(job_id, RecorderHandle { abort_handle });
+        }
+
+        tokio::select! {
+            Some(result) = workers.join_next_with_id() => {
+                let id = match result {
+                    Ok((id, ())) => id,
+                    Err(e) => e.id(),
+                };
+                if let Some(job_id) = running.remove(&id) {
+                    state.upload_handles.remove(&job_id);
+                }
+            }
+            _ = state.upload_notify.notified() => {}
+            _ = tokio::time::sleep(IDLE_POLL_INTERVAL) => {}
+        }
+    }
+}
+
+/// Size of a part when it is queued. A segment already uploaded while recording may have been
+/// cleaned up by then, so its size comes from the recorded segment metrics.
+async fn part_size(state: &SharedState, task_id: &str, filename: &str) -> u64 {
+    if let Ok(metadata) = tokio::fs::metadata(filename).await {
+        return metadata.len();
+    }
+    state
+        .tasks
+        .get(task_id)
+        .and_then(|task| {
+            task.segments.iter().find(|segment| segment.filename == filename).map(|s| s.size_bytes)
+        })
+        .unwrap_or_default()
+}
+
+async fn process_upload_job(state: SharedState, mut job: UploadJob) {
+    let (line, threads, retry) = {
+        let settings = state.recording_settings.read().await;
+        let line = (settings.upload_line != UPLOAD_LINE_AUTO).then(|| settings.upload_line.clone());
+        (line, settings.upload_threads as usize, settings.upload_retry.clone())
+    };
+    // Retry settings changed since the job was queued apply to its remaining attempts.
+    job.max_attempts = retry.max_attempts;
+    tracing::info!(
+        "Upload job started: job_id={}, task_id={}, kind={:?}, attempt={}/{}, configs_done={}/{}",
+        job.id,
+        job.task_id,
+        job.kind,
+        job.attempts,
+        job.max_attempts,
+        job.completed_configs,
+        job.configs.len()
+    );
+    let parts: Vec<UploadPart> = job
+        .parts
+        .iter()
+        .map(|part| UploadPart { filename: part.filename.clone(), title: part.title.clone() })
+        .collect();
+
+    let template_vars = stream_template_vars(&state, &job);
+    let mut retry_delay = None;
+    // Each config uploads on its own: one failing does not stop the others, and configs that
+    // already succeeded are skipped so their archives are not duplicated.
+    for index in upload_order(&job.configs) {
+        if !matches!(
+            job.config_statuses[index].state,
+            UploadConfigState::Pending | UploadConfigState::Retrying
+        ) {
+            continue;
+        }
+        let mut config = job.configs[index].clone();
+        // Moving the files away would leave nothing for configs that still have to retry.
+        if let UploadTargetConfig::LocalArchive { move_files, .. } = &mut config.target
+            && *move_files
+            && job.config_statuses.iter().enumerate().any(|(other, status)| {
+                other != index && status.state != UploadConfigState::Succeeded
+            })
+        {
+            *move_files = false;
+        }
+        let submits_to_bilibili = config.target == UploadTargetConfig::Bilibili;
+        let accounts = upload_accounts::ordered_accounts(&state, &config).await;
+        let base_context = UploadContext {
+            progress: Some(upload_progress::begin(
+                &state,
+                &job.task_id,
+                &parts,
+                index + 1,
+                job.configs.len(),
+            )),
+            line: line.clone(),
+            threads,
+            upload_only: job.kind == UploadJobKind::Segment,
+            template_vars: template_vars.clone(),
+            ..Default::default()
+        };
+        job.config_statuses[index].attempts += 1;
+        let started_at = Local::now().timestamp();
+        let mut result = Ok(None);
+        // An account whose login expired or that hit its limit hands over to the next one.
+        for (position, account) in accounts.iter().enumerate() {
+            config.account_file = account.clone();
+            // The collection and the configured archive to append to belong to the primary
+            // account; a backup can't add to either.
+            let is_primary = *account == job.configs[index].account_file;
+            config.collection = job.configs[index].collection.clone().filter(|_| is_primary);
+            config.append_bvid = job.configs[index].append_bvid.clone().filter(|_| is_primary);
+            job.config_statuses[index].account_file = Some(account.clone());
+            result = upload_with_account(&state, &job, index, &config, &parts, &base_context).await;
+            let (Err(e), Some(next)) = (&result, accounts.get(position + 1)) else {
+                break;
+            };
+            if !upload_accounts::should_switch_account(e) {
+                break;
+            }
+            tracing::warn!(
+                "Upload account failed, switching account: job_id={}, task_id={}, config={}/{}, account={}, next={}, error={}",
+                job.id,
+                job.task_id,
+                index + 1,
+                job.configs.len(),
+                account,
+                next,
+                e
+            );
+        }
+        match result {
+            Ok(submission) => {
+                tracing::info!(
+                    "Upload job finished config: job_id={}, task_id={}, kind={:?}, config={}/{}, account={}, submission={:?}",
+                    job.id,
+                    job.task_id,
+                    job.kind,
+                    index + 1,
+                    job.configs.len(),
+                    config.account_file,
+                    submission
+                );
+                let status = &mut job.config_statuses[index];
+                status.state = UploadConfigState::Succeeded;
+                status.last_error = None;
+                if let Some(submission) = &submission {
+                    if submits_to_bilibili {
+                        archives::record_submission(&state, &job.task_id, &config, submission)
+                            .await;
+                    }
+                    upload_history::record_upload_outcome(
+                        &state,
+                        &job,
+                        index,
+                        started_at,
+                        Ok(submission),
+                    )
+                    .await;
+                }
+            }
+            Err(e) => {
+                let message = format!(
+                    "{} config {}/{} failed: {}",
+                    job_kind_name(job.kind),
+                    index + 1,
+                    job.configs.len(),
+                    e
+                );
+                let status = &mut job.config_statuses[index];
+                let delay = retry_delay_secs(&e, status.attempts, &retry);
+                status.state = if delay.is_some() {
+                    UploadConfigState::Retrying
+                } else {
+                    UploadConfigState::Failed
+                };
+                status.last_error = Some(message.clone());
+                if let Some(delay) = delay {
+                    // The job waits for the config that needs the longest pause.
+                    retry_delay = retry_delay.max(Some(delay));
+                    tracing::warn!(
+                        "Upload config will retry after {}s: job_id={}, task_id={}, config={}/{}, attempt={}/{}, error={}",
+                        delay,
+                        job.id,
+                        job.task_id,
+                        index + 1,
+                        job.configs.len(),
+                        status.attempts,
+                        job.max_attempts,
+                        message
+                    );
+                } else {
+                    tracing::error!(
+                        "Upload config failed: job_id={}, task_id={}, config={}/{}, attempt={}/{}, error={}",
+                        job.id,
+                        job.task_id,
+                        index + 1,
+                        job.configs.len(),
+                        status.attempts,
+                        job.max_attempts,
+                        message
+                    );
+                    // A failed segment is uploaded again by the archive job when the session ends.
+                    if job.kind == UploadJobKind::Archive {
+                        upload_history::record_upload_outcome(
+                            &state,
+                            &job,
+                            index,
+                            started_at,
+                            Err(message.as_str()),
+                        )
+                        .await;
+                    }
+                }
+            }
+        }
+        job.completed_configs = succeeded_configs(&job.config_statuses);
+        job.updated_at = Local::now().timestamp();
+        save_job(&state, &job).await;
+    }
+
+    upload_progress::clear(&state, &job.task_id);
+    let retry_delay =
+        retry_delay.unwrap_or_else(|| retry_backoff_secs(&retry.backoff_secs, job.attempts));
+    finish_job(&state, job, retry_delay).await;
+}
+
+/// Uploads the config with the account in `config.account_file`. The archive to append to
+/// and the cover are looked up for that account, as it can only extend its own archives.
+async fn upload_with_account(
+    state: &SharedState,
+    job: &UploadJob,
+    index: usize,
+    config: &UploadConfig,
+    parts: &[UploadPart],
+    base_context: &UploadContext,
+) -> Result<Option<Submission>, UploadError> {
+    let submits_to_bilibili = config.target == UploadTargetConfig::Bilibili;
+    let append_bvid = match job.kind {
+        UploadJobKind::Archive if submits_to_bilibili => {
+            archives::append_target(state, &job.task_id, config).await
+        }
+        _ => None,
+    };
+    // Appended parts keep the archive's existing cover.
+    let cover = match job.kind {
+        UploadJobKind::Archive if submits_to_bilibili && append_bvid.is_none() => {
+            let source_url = state.tasks.get(&job.task_id).map(|task| task.url.clone());
+            let key = format!("{}-{}", job.id, index);
+            covers::prepare_cover(config, source_url.as_deref(), parts, &key).await
+        }
+        _ => None,
+    };
+    let context = UploadContext {
+        throttles: state.upload_limits.throttles_for(&config.account_file),
+        append_bvid,
+        cover: cover.as_ref().map(|cover| cover.path.clone()),
+        ..base_context.clone()
+    };
+    let result = UploadTarget::for_config(config)
+        .create_uploader()
+        .upload(parts.to_vec(), config, job.live_title.as_deref(), &job.task_name, &context)
+        .await;
+    if let Some(cover) = cover {
+        cover.discard().await;
+    }
+    result
+}
+
+/// Settles the job once every config has had its turn in this attempt; configs left to retry
+/// are tried again after `retry_delay` seconds.
+async fn finish_job(state: &SharedState, mut job: UploadJob, retry_delay: u64) {
+    let now = Local::now().timestamp();
+    job.updated_at = now;
+    job.state = job_state_for(&job.config_statuses);
+    job.last_error = config_errors(&job.config_statuses);
+    match job.state {
+        UploadJobState::Retrying => {
+            job.next_attempt_at =
+                now.saturating_add(i64::try_from(retry_delay).unwrap_or(i64::MAX));
+            tracing::warn!(
+                "Upload job will retry after {}s: job_id={}, task_id={}, configs_done={}/{}",
+                retry_delay,
+                job.id,
+                job.task_id,
+                job.completed_configs,
+                job.configs.len()
+            );
+            save_job(state, &job).await;
+        }
+        UploadJobState::Succeeded => {
+            save_job(state, &job).await;
+            match job.kind {
+                // Frees disk space while recording goes on, when the archive job can only
+                // submit the segments with the accounts that pre-uploaded them.
+                UploadJobKind::Segment => {
+                    if job.auto_cleanup_after_upload && segments_can_be_removed(&job.configs) {
+                        cleanup_uploaded_files(&job).await;
+                    }
+                }
+                UploadJobKind::Archive => {
+                    if job.auto_cleanup_after_upload {
+                        cleanup_uploaded_files(&job).await;
+                    }
+                    recording::set_task_status(state, &job.task_id, TaskStatus::Completed).await;
+                }
+            }
+        }
+        _ => {
+            let message = job.last_error.clone().unwrap_or_default();
+            tracing::error!(
+                "Upload job finished with failures: job_id={}, task_id={}, state={:?}, configs_done={}/{}, error={}",
+                job.id,
+                job.task_id,
+                job.state,
+                job.completed_configs,
+                job.configs.len(),
+                message
+            );
+            // Files stay on disk so the failed configs can be retried.
+            save_job(state, &job).await;
+            if job.kind == UploadJobKind::Archive {
+                let status = if job.state == UploadJobState::PartiallySucceeded {
+                    TaskStatus::PartiallyCompleted(message)
+                } else {
+                    TaskStatus::Error(message)
+                };
+                recording::set_task_status(state, &job.task_id, status).await;
+            }
+        }
+    }
+}
+
+fn succeeded_configs(statuses: &[UploadConfigStatus]) -> usize {
+    statuses.iter().filter(|status| status.state == UploadConfigState::Succeeded).count()
+}
+
+fn job_state_for(statuses: &[UploadConfigStatus]) -> UploadJobState {
+    let succeeded = succeeded_configs(statuses);
+    if statuses.iter().any(|status| {
+        matches!(status.state, UploadConfigState::Pending | UploadConfigState::Retrying)
+    }) {
+        UploadJobState::Retrying
+    } else if succeeded == statuses.len() {
+        UploadJobState::Succeeded
+    } else if succeeded == 0 {
+        UploadJobState::Failed
+    } else {
+        UploadJobState::PartiallySucceeded
+    }
+}
+
+fn config_errors(statuses: &[UploadConfigStatus]) -> Option<String> {
+    let errors: Vec<&str> = statuses
+        .iter()
+        .filter(|status| status.state != UploadConfigState::Succeeded)
+        .filter_map(|status| status.last_error.as_deref())
+        .collect();
+    (!errors.is_empty()).then(|| errors.join("; "))
+}
+
+fn job_kind_name(kind: UploadJobKind) -> &'static str {
+    match kind {
+        UploadJobKind::Archive => "Multi-part upload",
+        UploadJobKind::Segment => "Segment upload",
+    }
+}
+
+/// Stream details for the upload templates. The session spans the job's segments; without
+/// segment records it is taken to end when the job was queued.
+fn stream_template_vars(state: &SharedState, job: &UploadJob) -> TemplateVars {
+    let mut vars = TemplateVars { streamer: job.task_name.clone(), ..Default::default() };
+    if let Some(task) = state.tasks.get(&job.task_id) {
+        vars.platform = platform::detect_platform(&task.url).label().to_string();
+        vars.url = task.url.clone();
+        let segments: Vec<_> = task
+            .segments
+            .iter()
+            .filter(|segment| job.parts.iter().any(|part| part.filename == segment.filename))
+            .collect();
+        vars.start_time = segments.iter().map(|segment| segment.started_at).min();
+        vars.end_time = segments.iter().map(|segment| segment.ended_at).filter(|t| *t > 0).max();
+    }
+    vars.end_time = vars.end_time.or(Some(job.created_at));
+    vars
+}
+
+/// Archives that move the files go last, once the other configs had their turn.
+fn upload_order(configs: &[UploadConfig]) -> Vec<usize> {
+    let (moving, others): (Vec<usize>, Vec<usize>) = (0..configs.len()).partition(|&index| {
+        matches!(configs[index].target, UploadTargetConfig::LocalArchive { move_files: true, .. })
+    });
+    others.into_iter().chain(moving).collect()
+}
+
+/// Whether pre-uploaded segments can go before the session is submitted. With two configs on
+/// one account the second archive uploads the file again, local archives copy it when the
+/// session ends, and with backup accounts the archive may be submitted by an account that
+/// never pre-uploaded it; in each case the file has to stay until submission.
+fn segments_can_be_removed(configs: &[UploadConfig]) -> bool {
+    let mut accounts = HashSet::new();
+    configs.iter().all(|config| {
+        config.target == UploadTargetConfig::Bilibili
+            && config.backup_accounts.is_empty()
+            && accounts.insert(config.account_file.as_str())
+    })
+}
+
+async fn save_job(state: &SharedState, job: &UploadJob) {
+    if let Err(e) = state.db.save_upload_job(job).await {
+        tracing::error!(
+            "Failed to persist upload job, job_id={}, state={:?}: {}",
+            job.id,
+            job.state,
+            e
+        );
+    }
+}
+
+/// Seconds to wait before another attempt, or `None` when the error is not worth retrying:
+/// only network hiccups and rate limiting go away by themselves.
+fn retry_delay_secs(
+    error: &UploadError,
+    attempts: u32,
+    retry: &UploadRetrySettings,
+) -> Option<u64> {
+    if attempts >= retry.max_attempts {
+        return None;
+    }
+    let backoff = retry_backoff_secs(&retry.backoff_secs, attempts);
+    match error {
+        UploadError::NetworkTransient(_) => Some(backoff),
+        UploadError::RateLimited { retry_after, .. } => {
+            let delay = retry_after.map_or(backoff, |after| after.as_secs().max(backoff));
+            Some(delay.min(MAX_RETRY_DELAY_SECS))
+        }
+        UploadError::AuthExpired(_)
+        | UploadError::ContentRejected(_)
+        | UploadError::QuotaExceeded(_)
+        | UploadError::FileInvalid(_)
+        | UploadError::Other(_) => None,
+    }
+}
+
+fn retry_backoff_secs(backoff_secs: &[u64], attempt: u32) -> u64 {
+    let index = usize::try_from(attempt.saturating_sub(1)).unwrap_or(usize::MAX);
+    backoff_secs.get(index).or(backoff_secs.last()).copied().unwrap_or_default()
+}
+
+async fn cleanup_uploaded_files(job: &UploadJob) {
+    let mut unique_files = HashSet::new();
+    for part in &job.parts {
+        let file = &part.filename;
+        if !unique_files.insert(file) {
+            continue;
+        }
+        match tokio::fs::remove_file(file).await {
+            Ok(()) => tracing::info!(
+                "Task {} cleaned up local file after {}: {}",
+                job.task_id,
+                job_kind_name(job.kind).to_ascii_lowercase(),
+                file
+            ),
+            // Already removed after its segment upload.
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => tracing::warn!("Task {} cleanup failed for {}: {}", job.task_id, file, e),
+        }
+    }
+}
+
+/// Cancels the unfinished upload jobs of a task; returns how many were cancelled.
+pub(crate) async fn cancel_upload_jobs_for_task(state: &SharedState, task_id: &str) -> usize {
+    let jobs = match state.db.get_upload_jobs().await {
+        Ok(jobs) => jobs,
+        Err(e) => {
+            tracing::error!("Failed to load upload jobs for task_id={}: {}", task_id, e);
+            return 0;
+        }
+    };
+    let mut cancelled = 0;
+    for job in jobs.into_iter().filter(|j| j.task_id == task_id && !j.state.is_finished()) {
+        cancel_job(state, job).await;
+        cancelled += 1;
+    }
+    cancelled
+}
+
+async fn cancel_job(state: &SharedState, mut job: UploadJob) {
+    if let Some((_, handle)) = state.upload_handles.remove(&job.id) {
+        handle.abort_handle.abort();
+        // The aborted worker never reaches its own cleanup.
+        upload_progress::clear(state, &job.task_id);
+    }
+    job.state = UploadJobState::Cancelled;
+    job.updated_at = Local::now().timestamp();
+    save_job(state, &job).await;
+    tracing::info!("Upload job cancelled: job_id={}, task_id={}", job.id, job.task_id);
+    state.upload_notify.notify_one();
+}
+
+async fn load_job(state: &SharedState, id: &str) -> Result<UploadJob, (StatusCode, String)> {
+    match state.db.get_upload_job(id).await {
+        Ok(Some(job)) => Ok(job),
+        Ok(None) => Err((StatusCode::NOT_FOUND, "upload job not found".to_string())),
+        Err(e) => {
+            tracing::error!("Failed to load upload job, id={}: {}", id, e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, "failed to load upload job".to_string()))
+        }
+    }
+}
+
+async fn persist_job(state: &SharedState, job: &UploadJob) -> Result<(), (StatusCode, String)> {
+    state.db.save_upload_job(job).await.map_err(|e| {
+        tracing::error!("Failed to save upload job, id={}: {}", job.id, e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "failed to save upload job".to_string())
+    })
+}
+
+pub async fn list_upload_jobs(
+    State(state): State<SharedState>,
+) -> (StatusCode, Json<Vec<UploadJob>>) {
+    match list_upload_jobs_service(&state).await {
+        Ok(jobs) => (StatusCode::OK, Json(jobs)),
+        Err((status, message)) => {
+            tracing::error!("Failed to list upload jobs: {}", message);
+            (status, Json(vec![]))
+        }
+    }
+}
+
+pub async fn list_upload_jobs_service(
+    state: &SharedState,
+) -> Result<Vec<UploadJob>, (StatusCode, String)> {
+    state.db.get_upload_jobs().await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+pub async fn retry_upload_job(
+    Path(id): Path<String>,
+    State(state): State<SharedState>,
+) -> (StatusCode, String) {
+    match retry_upload_job_service(&state, &id).await {
+        Ok(message) => (StatusCode::OK, message),
+        Err(response) => response,
+    }
+}
+
+pub async fn retry_upload_job_service(
+    state: &SharedState,
+    id: &str,
+) -> Result<String, (StatusCode, String)> {
+    let mut job = load_retryable_job(state, id).await?;
+    // Succeeded configs keep their state so their archives are not submitted again.
+    for status in &mut job.config_statuses {
+        if status.state != UploadConfigState::Succeeded {
+            *status = UploadConfigStatus::default();
+        }
+    }
+    requeue_job(state, job).await?;
+    Ok("upload job requeued".to_string())
+}
+
+pub async fn retry_upload_config(
+    Path((id, index)): Path<(String, usize)>,
+    State(state): State<SharedState>,
+) -> (StatusCode, String) {
+    match retry_upload_config_service(&state, &id, index).await {
+        Ok(message) => (StatusCode::OK, message),
+        Err(response) => response,
+    }
+}
+
+/// Requeues one failed config of a job; the job's other failed configs stay failed.
+pub async fn retry_upload_config_service(
+    state: &SharedState,
+    id: &str,
+    index: usize,
+) -> Result<String, (StatusCode, String)> {
+    let mut job = load_retryable_job(state, id).await?;
+    let Some(status) = job.config_statuses.get_mut(index) else {
+        return Err((StatusCode::NOT_FOUND, "upload config not found".to_string()));
+    };
+    if status.state != UploadConfigState::Failed {
+        return Err((StatusCode::CONFLICT, "only failed configs can be retried".to_string()));
+    }
+    *status = UploadConfigStatus::default();
+    requeue_job(state, job).await?;
+    Ok(format!("upload config {} requeued", index + 1))
+}
+
+async fn load_retryable_job(
+    state: &SharedState,
+    id: &str,
+) -> Result<UploadJob, (StatusCode, String)> {
+    let job = load_job(state, id).await?;
+    if !matches!(
+        job.state,
+        UploadJobState::Failed | UploadJobState::PartiallySucceeded | UploadJobState::Cancelled
+    ) {
+        return Err((
+            StatusCode::CONFLICT,
+            "only failed, partially succeeded or cancelled jobs can be retried".to_string(),
+        ));
+    }
+    Ok(job)
+}
+
+async fn requeue_job(state: &SharedState, mut job: UploadJob) -> Result<(), (StatusCode, String)> {
+    let now = Local::now().timestamp();
+    job.state = UploadJobState::Queued;
+    job.attempts = 0;
+    job.last_error = config_errors(&job.config_statuses);
+    job.next_attempt_at = now;
+    job.updated_at = now;
+    persist_job(state, &job).await?;
+    if job.kind == UploadJobKind::Archive {
+        recording::set_task_status(state, &job.task_id, TaskStatus::Uploading).await;
+    }
+    state.upload_notify.notify_one();
+    Ok(())
+}
+
+pub async fn cancel_upload_job(
+    Path(id): Path<String>,
+    State(state): State<SharedState>,
+) -> (StatusCode, String) {
+    match cancel_upload_job_service(&state, &id).await {
+        Ok(message) => (StatusCode::OK, message),
+        Err(response) => response,
+    }
+}
+
+pub async fn cancel_upload_job_service(
+    state: &SharedState,
+    id: &str,
+) -> Result<String, (StatusCode, String)> {
+    let job = load_job(state, id).await?;
+    if job.state.is_finished() {
+        return Err((StatusCode::CONFLICT, "upload job already finished".to_string()));
+    }
+
+    let (task_id, kind) = (job.task_id.clone(), job.kind);
+    cancel_job(state, job).await;
+    // Cancelling a segment leaves the recording alone; the archive still includes the part.
+    if kind == UploadJobKind::Archive {
+        recording::set_task_status(state, &task_id, TaskStatus::Stopped).await;
+    }
+    Ok("upload job cancelled".to_string())
+}
+
+pub async fn set_upload_job_priority(
+    Path(id): Path<String>,
+    State(state): State<SharedState>,
+    Json(payload): Json<SetUploadJobPriorityRequest>,
+) -> (StatusCode, String) {
+    match set_upload_job_priority_service(&state, &id, payload.priority).await {
+        Ok(message) => (StatusCode::OK, message),
+        Err(response) => response,
+    }
+}
+
+pub async fn set_upload_job_priority_service(
+    state: &SharedState,
+    id: &str,
+    priority: i64,
+) -> Result<String, (StatusCode, String)> {
+    load_job(state, id).await?;
+    let updated =
+        state.db.set_upload_job_priority(id, priority, Local::now().timestamp()).await.map_err(
+            |e| {
+                tracing::error!("Failed to update upload job priority, id={}: {}", id, e);
+                (StatusCode::INTERNAL_SERVER_ERROR, "failed to save upload job".to_string())
+            },
+        )?;
+    if !updated {
+        return Err((StatusCode::CONFLICT, "upload job already finished".to_string()));
+    }
+    state.upload_notify.notify_one();
+    Ok(format!("upload job priority set to {priority}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        MAX_RETRY_DELAY_SECS, config_errors, job_state_for, retry_backoff_secs, retry_delay_secs,
+        segments_can_be_removed, upload_order,
+    };
+    use crate::uploader::UploadError;
+    use shared::{
+        UploadConfig, UploadConfigState, UploadConfigStatus, UploadJobState, UploadRetrySettings,
+        UploadTargetConfig,
+    };
+    use std::time::Duration;
+
+    fn status(state: UploadConfigState, error: Option<&str>) -> UploadConfigStatus {
+        UploadConfigStatus {
+            state,
+            attempts: 1,
+            last_error: error.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn retry_policy_follows_error_type() {
+        let retry = UploadRetrySettings::default();
+        let network = UploadError::NetworkTransient("connection reset by peer".to_string());
+        assert_eq!(retry_delay_secs(&network, 1, &retry), Some(30));
+        assert_eq!(retry_delay_secs(&network, 2, &retry), Some(120));
+        assert_eq!(retry_delay_secs(&network, 3, &retry), None);
+
+        let throttled = UploadError::RateLimited {
+            message: "429".to_string(),
+            retry_after: Some(Duration::from_secs(600)),
+        };
+        assert_eq!(retry_delay_secs(&throttled, 1, &retry), Some(600));
+        let bogus = UploadError::RateLimited {
+            message: "429".to_string(),
+            retry_after: Some(Duration::from_secs(u64::MAX)),
+        };
+        assert_eq!(retry_delay_secs(&bogus, 1, &retry), Some(MAX_RETRY_DELAY_SECS));
+
+        for error in [
+            UploadError::AuthExpired("-101".to_string()),
+            UploadError::ContentRejected("21012".to_string()),
+            UploadError::QuotaExceeded("上限".to_string()),
+            UploadError::FileInvalid("missing".to_string()),
+            UploadError::Other("HTTP 500 in the title".to_string()),
+        ] {
+            assert_eq!(retry_delay_secs(&error, 1, &retry), None, "{error}");
+        }
+    }
+
+    #[test]
+    fn segment_cleanup_requires_one_config_per_account() {
+        let config = |account: &str| UploadConfig {
+            account_file: account.to_string(),
+            ..Default::default()
+        };
+        assert!(segments_can_be_removed(&[config("a.json"), config("b.json")]));
+        assert!(!segments_can_be_removed(&[config("a.json"), config("a.json")]));
+        // The archive may be submitted by a backup that never saw the segments.
+        let with_backup =
+            UploadConfig { backup_accounts: vec!["b.json".to_string()], ..config("a.json") };
+        assert!(!segments_can_be_removed(&[with_backup, config("c.json")]));
+        let webdav = UploadConfig {
+            target: UploadTargetConfig::WebDav { remote_dir_template: String::new() },
+            ..config("c.json")
+        };
+        assert!(!segments_can_be_removed(&[config("a.json"), webdav]));
+    }
+
+    #[test]
+    fn archives_that_move_files_upload_last() {
+        let archive = |move_files: bool| UploadConfig {
+            target: UploadTargetConfig::LocalArchive {
+                root_dir: "/nas".to_string(),
+                path_template: String::new(),
+                move_files,
+            },
+            ..Default::default()
+        };
+        let configs = [archive(true), UploadConfig::default(), archive(false)];
+        assert_eq!(upload_order(&configs), vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn retry_backoff_grows_then_holds_at_last_step() {
+        assert_eq!(retry_backoff_secs(&[30, 120], 1), 30);
+        assert_eq!(retry_backoff_secs(&[30, 120], 2), 120);
+        assert_eq!(retry_backoff_secs(&[30, 120], 5), 120);
+        assert_eq!(retry_backoff_secs(&[], 1), 0);
+    }
+
+    #[test]
+    fn job_state_reflects_each_config() {
+        use UploadConfigState::{Failed, Pending, Retrying, Succeeded};
+        let states = |states: &[UploadConfigState]| {
+            states.iter().map(|state| status(*state, None)).collect::<Vec<_>>()
+        };
+        assert_eq!(job_state_for(&states(&[Succeeded, Succeeded])), UploadJobState::Succeeded);
+        assert_eq!(
+            job_state_for(&states(&[Succeeded, Failed])),
+            UploadJobState::PartiallySucceeded
+        );
+        assert_eq!(job_state_for(&states(&[Failed, Failed])), UploadJobState::Failed);
+        assert_eq!(job_state_for(&states(&[Failed, Retrying])), UploadJobState::Retrying);
+        assert_eq!(job_state_for(&states(&[Succeeded, Pending])), UploadJobState::Retrying);
+    }
+
+    #[test]
+    fn config_errors_skip_succeeded_configs() {
+        let statuses = [
+            status(UploadConfigState::Succeeded, Some("stale")),
+            status(UploadConfigState::Failed, Some("config 2/3 failed")),
+            status(UploadConfigState::Failed, Some("config 3/3 failed")),
+        ];
+        assert_eq!(
+            config_errors(&statuses).as_deref(),
+            Some("config 2/3 failed; config 3/3 failed")
+        );
+        assert_eq!(config_errors(&statuses[..1]), None);
+    }
+}
@@ -1,31 +1,49 @@
-use super::Uploader;
+use super::template::{self, TemplateVars};
+use super::{Submission, UploadContext, UploadError, UploadPart, Uploader, upos};
 use anyhow::{Result, anyhow};
 use async_trait::async_trait;
-use biliup::client::StatelessClient;
-use biliup::uploader::VideoFile;
-use biliup::uploader::bilibili::Studio;
+use biliup::uploader::bilibili::{BiliBili, Studio, Vid, Video};
 use biliup::uploader::credential::login_by_cookies;
-use biliup::uploader::line::Probe;
-use chrono::Local;
-use futures::TryStreamExt;
-use shared::UploadConfig;
+use chrono::{DateTime, Days, Local, NaiveTime, TimeZone};
+use shared::{
+    MAX_PUBLISH_DELAY_HOURS, MIN_PUBLISH_DELAY_HOURS, PublishSchedule, UploadCollection,
+    UploadConfig,
+};
 use std::path::Path;
 
 pub struct BilibiliUploader;
 
 impl BilibiliUploader {
     const MAX_TITLE_CHARS: usize = 80;
+    /// Slack on top of the minimum delay so the time is still valid when the submit arrives.
+    const PUBLISH_MARGIN_SECS: i64 = 5 * 60;
+    const ARCHIVE_VIEW_URL: &str = "https://member.bilibili.com/x/vupre/web/archive/view";
+    const SECTION_EPISODES_ADD_URL: &str =
+        "https://member.bilibili.com/x2/creative/web/season/section/episodes/add";
+    /// A fresh archive gets its cid only once transcoding has picked it up.
+    const CID_LOOKUP_ATTEMPTS: u32 = 6;
+    const CID_LOOKUP_DELAY: std::time::Duration = std::time::Duration::from_secs(10);
 
     pub fn new() -> Self {
         Self
     }
 
-    fn render_title(template: &str, live_title: Option<&str>, task_name: &str) -> String {
-        let placeholder_title =
-            live_title.map(str::trim).filter(|v| !v.is_empty()).unwrap_or(task_name);
-        let merged = template.replace("{title}", placeholder_title);
-        // Supports chrono strftime placeholders, e.g. %Y-%m-%d / %H:%M.
-        Local::now().format(&merged).to_string()
+    /// Stream values for the templates; the streamer falls back to the task name.
+    fn template_vars(
+        context: &UploadContext,
+        live_title: Option<&str>,
+        task_name: &str,
+    ) -> TemplateVars {
+        let mut vars = context.template_vars.clone();
+        vars.title = live_title.map(str::to_string);
+        if vars.streamer.is_empty() {
+            vars.streamer = task_name.to_string();
+        }
+        vars
+    }
+
+    fn render(field: &str, text: &str, vars: &TemplateVars) -> Result<String> {
+        template::render(text, vars).map_err(|e| anyhow!("invalid {} template: {}", field, e))
     }
 
     fn normalize_title(raw: String) -> String {
@@ -47,18 +65,43 @@ impl BilibiliUploader {
 
     fn resolve_title(
         config: &UploadConfig,
-        live_title: Option<&str>,
-        task_name: &str,
+        vars: &TemplateVars,
         fallback_title: Option<&str>,
-    ) -> String {
-        let raw_title = config
-            .title
-            .as_ref()
-            .map(|t| Self::render_title(t, live_title, task_name))
+    ) -> Result<String> {
+        let rendered = match &config.title {
+            Some(title) => Self::render("title", title, vars)?,
+            None => String::new(),
+        };
+        let raw_title = Some(rendered)
             .filter(|t| !t.trim().is_empty())
             .unwrap_or_else(|| fallback_title.unwrap_or("Uploaded by OmniStream").to_string());
 
-        Self::normalize_title(raw_title)
+        Ok(Self::normalize_title(raw_title))
+    }
+
+    /// Name of part `index` (0-based): the part title template, else the segment's live title.
+    fn resolve_part_title(
+        config: &UploadConfig,
+        vars: &TemplateVars,
+        index: usize,
+        segment_title: Option<String>,
+    ) -> Result<Option<String>> {
+        let segment_title = segment_title.filter(|t| !t.trim().is_empty());
+        let raw = match &config.part_title {
+            Some(part_title) => {
+                let part_vars =
+                    TemplateVars { part_index: Some(index + 1), segment_title, ..vars.clone() };
+                Some(Self::render("part title", part_title, &part_vars)?)
+            }
+            None => segment_title,
+        };
+        Ok(raw.filter(|t| !t.trim().is_empty()).map(Self::normalize_title))
+    }
+
+    fn is_audio_only_file(path: &Path) -> bool {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("m4a") || e.eq_ignore_ascii_case("aac"))
     }
 
     fn validate_config(config: &UploadConfig) -> Result<()> {
@@ -71,89 +114,236 @@ impl BilibiliUploader {
         if config.copyright != 1 && config.copyright != 2 {
             return Err(anyhow!("copyright must be 1 (Original) or 2 (Reprint)"));
         }
+        // Fail before uploading anything rather than at submission.
+        let vars = TemplateVars::default();
+        for (field, text) in [
+            ("title", config.title.as_deref()),
+            ("description", Some(config.description.as_str())),
+            ("dynamic", Some(config.dynamic.as_str())),
+            ("part title", config.part_title.as_deref()),
+        ] {
+            if let Some(text) = text {
+                Self::render(field, text, &vars)?;
+            }
+        }
         Ok(())
     }
-}
 
-#[async_trait]
-impl Uploader for BilibiliUploader {
-    async fn upload(
-        &self,
-        filenames: Vec<String>,
-        config: &UploadConfig,
-        live_title: Option<&str>,
-        task_name: &str,
-    ) -> Result<()> {
-        if filenames.is_empty() {
-            return Ok(());
-        }
-        Self::validate_config(config)?;
+    /// Adds `videos` as new parts after the existing ones of archive `bvid`.
+    async fn append_to_archive(
+        bili: &BiliBili,
+        bvid: &str,
+        videos: Vec<Video>,
+    ) -> Result<Submission, UploadError> {
+        let mut studio = bili
+            .studio_data(&Vid::Bvid(bvid.to_string()), None)
+            .await
+            .map_err(|e| Self::api_error(e).context(format!("Failed to load archive {}", bvid)))?;
+        let existing = studio.videos.len();
+        studio.videos.extend(videos);
+        tracing::info!(
+            "Appending parts to archive {}: existing={}, total={}",
+            bvid,
+            existing,
+            studio.videos.len()
+        );
 
-        // 1. 登录
-        // login_by_cookies returns Result<BiliBili>
-        let bili = login_by_cookies(&config.account_file, None)
+        let ret = bili
+            .edit_by_web(&studio)
             .await
-            .map_err(|e| anyhow!("Failed to login by {}: {}", config.account_file, e))?;
+            .map_err(|e| Self::api_error(e).context(format!("Failed to edit archive {}", bvid)))?;
+        tracing::info!("Edit result: {:?}", ret);
+        Ok(Submission {
+            aid: studio.aid,
+            bvid: Some(bvid.to_string()),
+            title: studio.title,
+            appended: true,
+            ..Default::default()
+        })
+    }
 
-        let client = StatelessClient::default();
-        let mut videos = Vec::new();
+    /// Adds a newly submitted archive to the configured collection section. The archive is
+    /// already submitted at this point, so a failure does not fail the upload, which would
+    /// submit it again on retry; it is returned to be recorded with the submission instead.
+    async fn add_to_collection(
+        bili: &BiliBili,
+        collection: &UploadCollection,
+        submission: &Submission,
+    ) -> Option<String> {
+        let Some(aid) = submission.aid else {
+            tracing::warn!("Submission has no aid, not adding it to collection {:?}", collection);
+            return Some("submission has no aid".to_string());
+        };
+        match Self::add_episode(bili, collection.section_id, aid, &submission.title).await {
+            Ok(()) => {
+                tracing::info!(
+                    "Added archive {} to collection {:?} (section {})",
+                    aid,
+                    collection.title,
+                    collection.section_id
+                );
+                None
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to add archive {} to collection section {}: {}",
+                    aid,
+                    collection.section_id,
+                    e
+                );
+                Some(format!(
+                    "failed to add archive to collection section {}: {e}",
+                    collection.section_id
+                ))
+            }
+        }
+    }
 
-        // 2. 上传每个文件
-        for filename in filenames {
-            let path = Path::new(&filename);
-            if !path.exists() {
-                return Err(anyhow!("File not found: {}", filename));
+    async fn add_episode(bili: &BiliBili, section_id: u64, aid: u64, title: &str) -> Result<()> {
+        let csrf = Self::csrf_token(&bili.login_info.cookie_info)
+            .ok_or_else(|| anyhow!("bili_jct cookie is missing"))?;
+        let cid = Self::archive_cid(bili, aid).await?;
+
+        let ret: serde_json::Value = bili
+            .client
+            .post(url::Url::parse_with_params(Self::SECTION_EPISODES_ADD_URL, [("csrf", &csrf)])?)
+            .json(&Self::episodes_request(section_id, aid, cid, title, &csrf))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Self::check_code(&ret)
+    }
+
+    /// Polls the archive until its first part has a cid.
+    async fn archive_cid(bili: &BiliBili, aid: u64) -> Result<u64> {
+        let mut attempt = 1;
+        loop {
+            let view: serde_json::Value = bili
+                .client
+                .get(url::Url::parse_with_params(
+                    Self::ARCHIVE_VIEW_URL,
+                    [("aid", aid.to_string())],
+                )?)
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            Self::check_code(&view)?;
+            if let Some(cid) = view["data"]["videos"][0]["cid"].as_u64() {
+                return Ok(cid);
+            }
+            if attempt >= Self::CID_LOOKUP_ATTEMPTS {
+                return Err(anyhow!("archive {} has no cid after {} lookups", aid, attempt));
             }
+            tracing::info!("Archive {} has no cid yet, looking it up again", aid);
+            tokio::time::sleep(Self::CID_LOOKUP_DELAY).await;
+            attempt += 1;
+        }
+    }
 
-            tracing::info!("Starting upload for: {}", filename);
+    /// The `bili_jct` cookie, which the creative center API expects as the CSRF token.
+    fn csrf_token(cookie_info: &serde_json::Value) -> Option<String> {
+        cookie_info["cookies"]
+            .as_array()?
+            .iter()
+            .find(|cookie| cookie["name"] == "bili_jct")
+            .and_then(|cookie| cookie["value"].as_str())
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    }
 
-            let video_file = VideoFile::new(path)?;
-
-            // 探测线路
-            let line = Probe::probe(&client.client)
-                .await
-                .map_err(|e| anyhow!("Failed to probe upload line: {}", e))?;
-
-            // 预上传
-            let parcel = line
-                .pre_upload(&bili, video_file)
-                .await
-                .map_err(|e| anyhow!("Failed to pre_upload: {}", e))?;
-
-            // 上传
-            // upload(client, limit, progress_callback, retry)
-            // progress callback needs to return a Stream<Item = Result<(B, usize), Kind>>
-            let video = parcel
-                .upload(
-                    client.clone(),
-                    3,
-                    |stream| {
-                        stream.map_err(biliup::error::Kind::from).map_ok(|b| {
-                            let len = b.len();
-                            (b, len)
-                        })
-                    },
-                    3,
-                )
-                .await
-                .map_err(|e| anyhow!("Failed to upload file {}: {}", filename, e))?;
+    fn episodes_request(
+        section_id: u64,
+        aid: u64,
+        cid: u64,
+        title: &str,
+        csrf: &str,
+    ) -> serde_json::Value {
+        serde_json::json!({
+            "sectionId": section_id,
+            "episodes": [{ "title": title, "aid": aid, "cid": cid, "charging_pay": 0 }],
+            "csrf": csrf,
+        })
+    }
 
-            videos.push(video);
-            tracing::info!("Uploaded: {}", filename);
+    fn check_code(ret: &serde_json::Value) -> Result<()> {
+        match ret["code"].as_i64() {
+            Some(0) => Ok(()),
+            code => {
+                Err(anyhow!("code {:?}: {}", code, ret["message"].as_str().unwrap_or_default()))
+            }
         }
+    }
 
-        if videos.is_empty() {
-            return Err(anyhow!("No videos uploaded"));
+    /// Unix time to publish at, kept within the window Bilibili accepts for `dtime`.
+    fn publish_dtime(
+        schedule: &PublishSchedule,
+        stream_ended_at: i64,
+        now: DateTime<Local>,
+    ) -> Option<i64> {
+        let earliest = now.timestamp()
+            + i64::from(MIN_PUBLISH_DELAY_HOURS) * 60 * 60
+            + Self::PUBLISH_MARGIN_SECS;
+        let latest = now.timestamp() + i64::from(MAX_PUBLISH_DELAY_HOURS) * 60 * 60;
+        let dtime = match schedule {
+            PublishSchedule::Immediate => return None,
+            PublishSchedule::AtTime { time } => {
+                let time = NaiveTime::parse_from_str(time, "%H:%M").ok()?;
+                // First occurrence of the clock time that is far enough ahead.
+                (0..=2u64)
+                    .filter_map(|offset| now.date_naive().checked_add_days(Days::new(offset)))
+                    .filter_map(|date| Local.from_local_datetime(&date.and_time(time)).earliest())
+                    .map(|at| at.timestamp())
+                    .find(|at| *at >= earliest)?
+            }
+            PublishSchedule::AfterStreamEnd { hours } => {
+                (stream_ended_at + i64::from(*hours) * 60 * 60).max(earliest)
+            }
+        };
+        Some(dtime.min(latest))
+    }
+
+    /// Uploads the cover image and returns its URL; a failed cover leaves the choice to Bilibili.
+    async fn upload_cover(bili: &BiliBili, path: &Path) -> String {
+        let bytes = match tokio::fs::read(path).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                tracing::warn!("Failed to read cover {}: {}", path.display(), e);
+                return String::new();
+            }
+        };
+        match bili.cover_up(&bytes).await {
+            Ok(url) => {
+                tracing::info!("Cover uploaded: {}", url);
+                url
+            }
+            Err(e) => {
+                tracing::warn!("Failed to upload cover {}: {}", path.display(), e);
+                String::new()
+            }
         }
+    }
 
-        // 3. 提交投稿
-        let title = Self::resolve_title(config, live_title, task_name, videos[0].title.as_deref());
+    /// Submits `videos` as a new archive.
+    async fn submit_new_archive(
+        bili: &BiliBili,
+        config: &UploadConfig,
+        vars: &TemplateVars,
+        videos: Vec<Video>,
+        context: &UploadContext,
+    ) -> Result<Submission, UploadError> {
+        let title = Self::resolve_title(config, vars, videos[0].title.as_deref())?;
+        let description = Self::render("description", &config.description, vars)?;
+        let dynamic = Self::render("dynamic", &config.dynamic, vars)?;
 
         tracing::info!(
-            "Resolved upload title: template={:?}, live_title={:?}, task_name={:?}, final_title={:?}",
+            "Resolved upload title: template={:?}, live_title={:?}, streamer={:?}, final_title={:?}",
             config.title,
-            live_title,
-            task_name,
+            vars.title,
+            vars.streamer,
             title
         );
 
@@ -161,15 +351,37 @@ impl Uploader for BilibiliUploader {
         let tag =
             if config.tags.is_empty() { "omnistream".to_string() } else { config.tags.join(",") };
 
+        let now = Local::now();
+        let dtime = Self::publish_dtime(
+            &config.publish_schedule,
+            vars.end_time.unwrap_or_else(|| now.timestamp()),
+            now,
+        );
+        if let Some(dtime) = dtime {
+            tracing::info!(
+                "Scheduled publishing: schedule={:?}, dtime={}",
+                config.publish_schedule,
+                dtime
+            );
+        }
+
+        let cover = match &context.cover {
+            Some(path) => Self::upload_cover(bili, path).await,
+            None => String::new(),
+        };
+
         let studio = Studio::builder()
-            .title(title)
+            .title(title.clone())
             .videos(videos)
             .tid(config.tid)
             .copyright(config.copyright)
-            .desc(config.description.clone())
-            .dynamic(config.dynamic.clone())
+            .desc(description)
+            .dynamic(dynamic)
             .tag(tag)
+            .cover(cover)
             .desc_v2(None) // required field
+            .dtime(dtime.and_then(|dtime| u32::try_from(dtime).ok()))
+            .is_only_self(u8::from(config.only_self))
             .build();
 
         tracing::info!("Submitting archive: {:?}", studio.title);
@@ -177,37 +389,163 @@ impl Uploader for BilibiliUploader {
         let ret = bili
             .submit_by_app(&studio, None)
             .await
-            .map_err(|e| anyhow!("Failed to submit archive: {}", e))?;
+            .map_err(|e| Self::api_error(e).context("Failed to submit archive"))?;
 
         tracing::info!("Submission result: {:?}", ret);
+        Ok(Self::submission_from_response(&serde_json::to_value(&ret).unwrap_or_default(), title))
+    }
 
-        Ok(())
+    fn api_error(error: biliup::error::Kind) -> UploadError {
+        match error {
+            biliup::error::Kind::Reqwest(e) => super::error::from_reqwest(&e, e.to_string()),
+            biliup::error::Kind::Custom(message) => UploadError::from_api_error(message),
+            other => UploadError::from_api_error(other.to_string()),
+        }
+    }
+
+    /// Reads `data.aid` / `data.bvid` of the submit response.
+    fn submission_from_response(ret: &serde_json::Value, title: String) -> Submission {
+        Submission {
+            aid: ret["data"]["aid"].as_u64(),
+            bvid: ret["data"]["bvid"].as_str().map(str::to_string),
+            title,
+            ..Default::default()
+        }
+    }
+}
+
+#[async_trait]
+impl Uploader for BilibiliUploader {
+    async fn upload(
+        &self,
+        parts: Vec<UploadPart>,
+        config: &UploadConfig,
+        live_title: Option<&str>,
+        task_name: &str,
+        context: &UploadContext,
+    ) -> Result<Option<Submission>, UploadError> {
+        if parts.is_empty() {
+            return Ok(None);
+        }
+        Self::validate_config(config)?;
+        let vars = Self::template_vars(context, live_title, task_name);
+
+        // 1. 登录
+        // login_by_cookies returns Result<BiliBili>
+        let bili = login_by_cookies(&config.account_file, None).await.map_err(|e| {
+            UploadError::AuthExpired(format!("Failed to login by {}: {}", config.account_file, e))
+        })?;
+
+        let mut videos = Vec::new();
+        let filenames: Vec<String> = parts.iter().map(|part| part.filename.clone()).collect();
+
+        // 2. 上传每个文件，已提前上传的文件直接复用
+        for (index, UploadPart { filename, title: part_title }) in parts.into_iter().enumerate() {
+            let path = Path::new(&filename);
+            if Self::is_audio_only_file(path) {
+                return Err(UploadError::FileInvalid(format!(
+                    "Bilibili does not accept audio-only files: {}",
+                    filename
+                )));
+            }
+
+            tracing::info!("Starting upload for: {}", filename);
+
+            // 分块上传，进度记录在文件旁的会话文件中，中断后从已完成的分块继续
+            let mut video =
+                upos::upload_file(&bili, &config.account_file, path, context).await.map_err(
+                    |e| UploadError::from(e).context(format!("Failed to upload file {}", filename)),
+                )?;
+
+            if let Some(part_title) = Self::resolve_part_title(config, &vars, index, part_title)? {
+                video.title = Some(part_title);
+            }
+            videos.push(video);
+            tracing::info!("Uploaded: {}", filename);
+        }
+
+        if videos.is_empty() {
+            return Err(UploadError::Other("No videos uploaded".to_string()));
+        }
+        if context.upload_only {
+            // 已上传的文件记录在分P旁，之后投稿时复用
+            return Ok(None);
+        }
+
+        // 3. 提交投稿，或追加到已有稿件
+        let mut submission = match context.append_bvid.as_deref() {
+            Some(bvid) => Self::append_to_archive(&bili, bvid, videos).await?,
+            None => Self::submit_new_archive(&bili, config, &vars, videos, context).await?,
+        };
+        if let Some(collection) = config.collection.as_ref().filter(|_| !submission.appended) {
+            submission.collection_error =
+                Self::add_to_collection(&bili, collection, &submission).await;
+        }
+        for filename in &filenames {
+            upos::forget_uploaded(Path::new(filename), &config.account_file).await;
+        }
+        Ok(Some(submission))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::BilibiliUploader;
+    use super::{BilibiliUploader, UploadContext};
+    use chrono::{Local, TimeZone};
+    use shared::PublishSchedule;
+    use std::path::Path;
+
+    fn render_title(template: &str, live_title: Option<&str>, task_name: &str) -> String {
+        let config =
+            shared::UploadConfig { title: Some(template.to_string()), ..Default::default() };
+        let vars =
+            BilibiliUploader::template_vars(&UploadContext::default(), live_title, task_name);
+        BilibiliUploader::resolve_title(&config, &vars, None).expect("valid title template")
+    }
 
     #[test]
     fn render_title_uses_live_title_placeholder() {
-        let rendered = BilibiliUploader::render_title("{title} 录播", Some("开整"), "任务名");
+        let rendered = render_title("{title} 录播", Some("开整"), "任务名");
         assert!(rendered.starts_with("开整 录播"));
     }
 
     #[test]
     fn render_title_falls_back_to_task_name_when_live_title_is_blank() {
-        let rendered = BilibiliUploader::render_title("{title} 录播", Some("   "), "任务名");
+        let rendered = render_title("{title} 录播", Some("   "), "任务名");
         assert!(rendered.starts_with("任务名 录播"));
     }
 
     #[test]
     fn render_title_applies_time_placeholders() {
-        let rendered = BilibiliUploader::render_title("{title}-%Y", Some("开整"), "任务名");
+        let rendered = render_title("{title}-%Y", Some("开整"), "任务名");
         assert!(rendered.starts_with("开整-"));
         assert_eq!(rendered.len(), "开整-2026".len());
     }
 
+    #[test]
+    fn part_titles_use_template_or_segment_title() {
+        let vars = BilibiliUploader::template_vars(&UploadContext::default(), None, "主播");
+        let part = |config: &shared::UploadConfig, segment_title: Option<&str>| {
+            BilibiliUploader::resolve_part_title(
+                config,
+                &vars,
+                1,
+                segment_title.map(str::to_string),
+            )
+            .expect("valid part template")
+        };
+        let config = shared::UploadConfig::default();
+        assert_eq!(part(&config, Some("团战")).as_deref(), Some("团战"));
+        assert_eq!(part(&config, Some(" ")), None);
+
+        let config = shared::UploadConfig {
+            part_title: Some("P{part_index}{if segment_title} {segment_title}{end}".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(part(&config, Some("团战")).as_deref(), Some("P2 团战"));
+        assert_eq!(part(&config, None).as_deref(), Some("P2"));
+    }
+
     #[test]
     fn normalize_title_truncates_to_bilibili_limit() {
         let long_title = "a".repeat(100);
@@ -223,8 +561,74 @@ mod tests {
             ..Default::default()
         };
         let live_title = "x".repeat(120);
-        let rendered = BilibiliUploader::resolve_title(&config, Some(&live_title), "Arteezy", None);
+        let vars = BilibiliUploader::template_vars(
+            &UploadContext::default(),
+            Some(&live_title),
+            "Arteezy",
+        );
+        let rendered =
+            BilibiliUploader::resolve_title(&config, &vars, None).expect("valid template");
 
         assert_eq!(rendered.chars().count(), BilibiliUploader::MAX_TITLE_CHARS);
     }
+
+    #[test]
+    fn submission_is_read_from_submit_response() {
+        let ret = serde_json::json!({
+            "code": 0,
+            "data": { "aid": 170001, "bvid": "BV17x411w7KC" },
+            "message": "0"
+        });
+        let submission = BilibiliUploader::submission_from_response(&ret, "录播".to_string());
+
+        assert_eq!(submission.aid, Some(170001));
+        assert_eq!(submission.bvid.as_deref(), Some("BV17x411w7KC"));
+        assert_eq!(submission.title, "录播");
+        assert!(!submission.appended);
+    }
+
+    #[test]
+    fn collection_episode_uses_bili_jct_as_csrf() {
+        let cookie_info = serde_json::json!({
+            "cookies": [
+                { "name": "SESSDATA", "value": "s" },
+                { "name": "bili_jct", "value": "token" }
+            ]
+        });
+        let csrf = BilibiliUploader::csrf_token(&cookie_info).expect("bili_jct cookie");
+        assert_eq!(csrf, "token");
+        assert_eq!(BilibiliUploader::csrf_token(&serde_json::json!({ "cookies": [] })), None);
+
+        let body = BilibiliUploader::episodes_request(42, 170001, 9, "录播", &csrf);
+        assert_eq!(body["sectionId"], 42);
+        assert_eq!(body["episodes"][0]["aid"], 170001);
+        assert_eq!(body["episodes"][0]["cid"], 9);
+        assert_eq!(body["csrf"], "token");
+    }
+
+    #[test]
+    fn audio_only_recordings_are_detected_by_extension() {
+        assert!(BilibiliUploader::is_audio_only_file(Path::new("a/电台-20260101.m4a")));
+        assert!(BilibiliUploader::is_audio_only_file(Path::new("b.AAC")));
+        assert!(!BilibiliUploader::is_audio_only_file(Path::new("c.mp4")));
+    }
+
+    #[test]
+    fn publish_dtime_respects_minimum_delay() {
+        let now = Local.with_ymd_and_hms(2026, 10, 19, 12, 0, 0).unwrap();
+        let at = |h, m| Local.with_ymd_and_hms(2026, 10, 19, h, m, 0).unwrap().timestamp();
+        let dtime = |schedule: PublishSchedule, ended_at| {
+            BilibiliUploader::publish_dtime(&schedule, ended_at, now)
+        };
+        let at_time = |time: &str| PublishSchedule::AtTime { time: time.to_string() };
+        let ended_at = at(11, 0);
+
+        assert_eq!(dtime(PublishSchedule::Immediate, ended_at), None);
+        assert_eq!(dtime(at_time("20:00"), ended_at), Some(at(20, 0)));
+        let tomorrow = Local.with_ymd_and_hms(2026, 10, 20, 13, 0, 0).unwrap().timestamp();
+        assert_eq!(dtime(at_time("13:00"), ended_at), Some(tomorrow));
+        assert_eq!(dtime(at_time("25:00"), ended_at), None);
+        assert_eq!(dtime(PublishSchedule::AfterStreamEnd { hours: 4 }, ended_at), Some(at(15, 0)));
+        assert_eq!(dtime(PublishSchedule::AfterStreamEnd { hours: 2 }, ended_at), Some(at(14, 5)));
+    }
 }
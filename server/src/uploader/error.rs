@@ -0,0 +1,229 @@
+//! 上传失败的分类：上传队列按类型决定是否重试、等待多久
+
+use std::fmt;
+use std::time::Duration;
+
+/// Bilibili API codes for a missing or expired login (`-101`) and a stale csrf token (`-111`).
+const AUTH_EXPIRED_CODES: &[i64] = &[-101, -111];
+/// Request blocked (`-352`, `-412`), too frequent (`-509`), submitting too fast (`21070`,
+/// `21540`).
+const RATE_LIMITED_CODES: &[i64] = &[-352, -412, -509, 21070, 21540];
+/// Daily submission limit reached (`21021`); checked before the archive-check range it is in.
+const QUOTA_EXCEEDED_CODES: &[i64] = &[21021];
+/// Codes of the creative center's archive checks (title, tags, category, copyright…).
+const CONTENT_REJECTED_CODES: std::ops::Range<i64> = 21000..22000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UploadError {
+    /// 登录失效，需要重新登录账号
+    AuthExpired(String),
+    /// 请求过于频繁；`retry_after` 为服务端要求的等待时长
+    RateLimited { message: String, retry_after: Option<Duration> },
+    /// 稿件信息不合规被拒绝
+    ContentRejected(String),
+    /// 投稿数量或空间已达上限
+    QuotaExceeded(String),
+    /// 超时、连接中断、服务端 5xx 等临时问题
+    NetworkTransient(String),
+    /// 文件不存在、不可读或不被接受
+    FileInvalid(String),
+    /// 配置错误等其他重试也无法解决的问题
+    Other(String),
+}
+
+impl UploadError {
+    /// Prefixes the message with what was being done, keeping the classification.
+    pub fn context(self, context: impl fmt::Display) -> Self {
+        let wrap = |message: String| format!("{context}: {message}");
+        match self {
+            Self::AuthExpired(message) => Self::AuthExpired(wrap(message)),
+            Self::RateLimited { message, retry_after } => {
+                Self::RateLimited { message: wrap(message), retry_after }
+            }
+            Self::ContentRejected(message) => Self::ContentRejected(wrap(message)),
+            Self::QuotaExceeded(message) => Self::QuotaExceeded(wrap(message)),
+            Self::NetworkTransient(message) => Self::NetworkTransient(wrap(message)),
+            Self::FileInvalid(message) => Self::FileInvalid(wrap(message)),
+            Self::Other(message) => Self::Other(wrap(message)),
+        }
+    }
+
+    /// Classifies a failed Bilibili API call by the `code` of its response, which the error
+    /// message carries either as JSON (`"code":-101`) or as Debug output (`code: -101`).
+    pub fn from_api_error(message: String) -> Self {
+        match api_code(&message) {
+            Some(code) if QUOTA_EXCEEDED_CODES.contains(&code) => Self::QuotaExceeded(message),
+            Some(code) if AUTH_EXPIRED_CODES.contains(&code) => Self::AuthExpired(message),
+            Some(code) if RATE_LIMITED_CODES.contains(&code) => {
+                Self::RateLimited { message, retry_after: None }
+            }
+            Some(code) if CONTENT_REJECTED_CODES.contains(&code) => Self::ContentRejected(message),
+            _ => Self::Other(message),
+        }
+    }
+}
+
+impl fmt::Display for UploadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AuthExpired(message) => write!(f, "login expired: {message}"),
+            Self::RateLimited { message, retry_after: Some(after) } => {
+                write!(f, "rate limited, retry after {}s: {message}", after.as_secs())
+            }
+            Self::RateLimited { message, retry_after: None } => {
+                write!(f, "rate limited: {message}")
+            }
+            Self::ContentRejected(message) => write!(f, "content rejected: {message}"),
+            Self::QuotaExceeded(message) => write!(f, "quota exceeded: {message}"),
+            Self::NetworkTransient(message) => write!(f, "network error: {message}"),
+            Self::FileInvalid(message) => write!(f, "invalid file: {message}"),
+            Self::Other(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for UploadError {}
+
+/// Errors raised without a classification are sorted by their source: typed upload errors
+/// pass through, HTTP and IO failures are classified by status and kind.
+impl From<anyhow::Error> for UploadError {
+    fn from(error: anyhow::Error) -> Self {
+        let error = match error.downcast::<UploadError>() {
+            Ok(error) => return error,
+            Err(error) => error,
+        };
+        let message = format!("{error:#}");
+        for cause in error.chain() {
+            if let Some(error) = cause.downcast_ref::<UploadError>() {
+                return error.clone();
+            }
+            if let Some(error) = cause.downcast_ref::<reqwest::Error>() {
+                return from_reqwest(error, message);
+            }
+            if let Some(error) = cause.downcast_ref::<std::io::Error>() {
+                return from_io(error, message);
+            }
+        }
+        Self::Other(message)
+    }
+}
+
+impl From<std::io::Error> for UploadError {
+    fn from(error: std::io::Error) -> Self {
+        let message = error.to_string();
+        from_io(&error, message)
+    }
+}
+
+pub(super) fn from_reqwest(error: &reqwest::Error, message: String) -> UploadError {
+    match error.status() {
+        Some(reqwest::StatusCode::UNAUTHORIZED) => UploadError::AuthExpired(message),
+        Some(reqwest::StatusCode::TOO_MANY_REQUESTS) => {
+            UploadError::RateLimited { message, retry_after: None }
+        }
+        Some(reqwest::StatusCode::PAYLOAD_TOO_LARGE) => UploadError::FileInvalid(message),
+        Some(status) if status.is_server_error() => UploadError::NetworkTransient(message),
+        Some(_) => UploadError::Other(message),
+        None if error.is_timeout()
+            || error.is_connect()
+            || error.is_request()
+            || error.is_body() =>
+        {
+            UploadError::NetworkTransient(message)
+        }
+        None => UploadError::Other(message),
+    }
+}
+
+fn from_io(error: &std::io::Error, message: String) -> UploadError {
+    use std::io::ErrorKind;
+    match error.kind() {
+        ErrorKind::TimedOut
+        | ErrorKind::ConnectionReset
+        | ErrorKind::ConnectionAborted
+        | ErrorKind::ConnectionRefused
+        | ErrorKind::BrokenPipe
+        | ErrorKind::Interrupted => UploadError::NetworkTransient(message),
+        ErrorKind::NotFound
+        | ErrorKind::PermissionDenied
+        | ErrorKind::UnexpectedEof
+        | ErrorKind::InvalidData => UploadError::FileInvalid(message),
+        _ => UploadError::Other(message),
+    }
+}
+
+/// The number following the first `code` key, e.g. `-101` in `{"code":-101,...}`.
+fn api_code(message: &str) -> Option<i64> {
+    let (_, rest) = message.split_once("code")?;
+    let rest = rest.trim_start_matches(|c: char| c == '"' || c == ':' || c.is_whitespace());
+    let end = rest
+        .char_indices()
+        .find(|(index, c)| !(c.is_ascii_digit() || (*index == 0 && *c == '-')))
+        .map_or(rest.len(), |(index, _)| index);
+    rest[..end].parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UploadError;
+    use std::time::Duration;
+
+    #[test]
+    fn api_errors_are_classified_by_code() {
+        let classify = |message: &str| UploadError::from_api_error(message.to_string());
+        assert!(matches!(
+            classify(r#"{"code":-101,"message":"账号未登录"}"#),
+            UploadError::AuthExpired(_)
+        ));
+        assert!(matches!(
+            classify(r#"ResponseData { code: 21070, message: "投稿过于频繁" }"#),
+            UploadError::RateLimited { retry_after: None, .. }
+        ));
+        assert!(matches!(
+            classify(r#"{"code":21012,"message":"标签不合法"}"#),
+            UploadError::ContentRejected(_)
+        ));
+        assert!(matches!(
+            classify(r#"{"code":21021,"message":"今日投稿数量已达上限"}"#),
+            UploadError::QuotaExceeded(_)
+        ));
+        // Only the code decides: a content check mentioning a limit is still a rejection.
+        assert!(matches!(
+            classify(r#"{"code":21012,"message":"标签数量超过上限"}"#),
+            UploadError::ContentRejected(_)
+        ));
+        assert!(matches!(classify("简介长度超过上限"), UploadError::Other(_)));
+        // "500" in the message no longer makes an error look transient.
+        assert!(matches!(classify("archive 500 failed"), UploadError::Other(_)));
+    }
+
+    #[test]
+    fn untyped_errors_are_classified_by_source() {
+        let typed = anyhow::Error::from(UploadError::RateLimited {
+            message: "slow down".to_string(),
+            retry_after: Some(Duration::from_secs(30)),
+        });
+        assert_eq!(
+            UploadError::from(typed).context("chunk 2"),
+            UploadError::RateLimited {
+                message: "chunk 2: slow down".to_string(),
+                retry_after: Some(Duration::from_secs(30)),
+            }
+        );
+
+        let reset = std::io::Error::from(std::io::ErrorKind::ConnectionReset);
+        assert!(matches!(
+            UploadError::from(anyhow::Error::from(reset)),
+            UploadError::NetworkTransient(_)
+        ));
+        let missing = std::io::Error::from(std::io::ErrorKind::NotFound);
+        assert!(matches!(
+            UploadError::from(anyhow::Error::from(missing)),
+            UploadError::FileInvalid(_)
+        ));
+        assert!(matches!(
+            UploadError::from(anyhow::anyhow!("account_file is required")),
+            UploadError::Other(_)
+        ));
+    }
+}
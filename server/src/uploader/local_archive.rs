@@ -0,0 +1,373 @@
+//! 本地归档：把录制文件复制或移动到按模板生成的目录（可以是挂载的 NAS），并写入元数据文件
+
+use super::template::{self, TemplateVars};
+use super::{Submission, UploadContext, UploadError, UploadPart, Uploader};
+use async_trait::async_trait;
+use chrono::Local;
+use md5::{Digest, Md5};
+use serde::Serialize;
+use shared::{UploadConfig, UploadTargetConfig};
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+const COPY_BUFFER_BYTES: usize = 1024 * 1024;
+
+pub struct LocalArchiveUploader;
+
+/// 写在归档文件旁的 JSON 元数据
+#[derive(Debug, Serialize)]
+struct ArchiveMetadata {
+    task_name: String,
+    streamer: String,
+    platform: String,
+    url: String,
+    live_title: Option<String>,
+    start_time: Option<i64>,
+    end_time: Option<i64>,
+    archived_at: i64,
+    files: Vec<ArchivedFile>,
+}
+
+#[derive(Debug, Serialize)]
+struct ArchivedFile {
+    file_name: String,
+    title: Option<String>,
+    size: u64,
+    md5: String,
+}
+
+impl LocalArchiveUploader {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// `root_dir` joined with the rendered template; every segment is made a valid file name
+    /// and `.`/`..` are dropped so the template cannot leave the root.
+    fn archive_dir(
+        root_dir: &str,
+        path_template: &str,
+        vars: &TemplateVars,
+    ) -> Result<PathBuf, UploadError> {
+        if root_dir.trim().is_empty() {
+            return Err(UploadError::Other("archive root_dir is required".to_string()));
+        }
+        let rendered = template::render(path_template, vars)
+            .map_err(|e| UploadError::Other(format!("invalid archive path template: {}", e)))?;
+        let mut dir = PathBuf::from(root_dir.trim());
+        for segment in rendered.split(['/', '\\']).map(sanitize_segment) {
+            if !segment.is_empty() && segment != "." && segment != ".." {
+                dir.push(segment);
+            }
+        }
+        Ok(dir)
+    }
+
+    /// Copies (or moves) `source` to `dest` and checks the copy against the source checksum.
+    /// A copy left by an earlier attempt with the same content is kept instead of duplicated,
+    /// and a source an earlier attempt already moved is taken from `dest`.
+    async fn archive_file(
+        source: &Path,
+        dest: &Path,
+        move_file: bool,
+        context: &UploadContext,
+    ) -> Result<(PathBuf, u64, String), UploadError> {
+        let size = match tokio::fs::metadata(source).await {
+            Ok(meta) => meta.len(),
+            Err(e) if move_file && e.kind() == std::io::ErrorKind::NotFound => {
+                let Ok(meta) = tokio::fs::metadata(dest).await else {
+                    return Err(e.into());
+                };
+                tracing::info!("{} was already moved to {}", source.display(), dest.display());
+                return Ok((dest.to_path_buf(), meta.len(), file_md5(dest).await?));
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if tokio::fs::metadata(dest).await.is_ok_and(|meta| meta.len() == size) {
+            let checksum = file_md5(source).await?;
+            if file_md5(dest).await? == checksum {
+                if move_file {
+                    tokio::fs::remove_file(source).await?;
+                }
+                return Ok((dest.to_path_buf(), size, checksum));
+            }
+        }
+
+        let dest = available_path(dest).await;
+        // Within one filesystem a move is a rename and the data is never rewritten.
+        if move_file && tokio::fs::rename(source, &dest).await.is_ok() {
+            let checksum = file_md5(&dest).await?;
+            report_progress(context, source, size, size);
+            return Ok((dest, size, checksum));
+        }
+
+        let partial = PathBuf::from(format!("{}.part", dest.display()));
+        let checksum = copy_with_md5(source, &partial, size, context).await?;
+        let copied = file_md5(&partial).await?;
+        if copied != checksum {
+            let _ = tokio::fs::remove_file(&partial).await;
+            return Err(UploadError::NetworkTransient(format!(
+                "checksum mismatch after copying to {}: expected {}, got {}",
+                dest.display(),
+                checksum,
+                copied
+            )));
+        }
+        tokio::fs::rename(&partial, &dest).await?;
+        if move_file {
+            tokio::fs::remove_file(source).await?;
+        }
+        Ok((dest, size, checksum))
+    }
+
+    async fn write_metadata(path: &Path, metadata: &ArchiveMetadata) -> Result<(), UploadError> {
+        let json = serde_json::to_vec_pretty(metadata)
+            .map_err(|e| UploadError::Other(format!("failed to encode archive metadata: {}", e)))?;
+        let partial = PathBuf::from(format!("{}.part", path.display()));
+        tokio::fs::write(&partial, json).await?;
+        tokio::fs::rename(&partial, path).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Uploader for LocalArchiveUploader {
+    async fn upload(
+        &self,
+        parts: Vec<UploadPart>,
+        config: &UploadConfig,
+        live_title: Option<&str>,
+        task_name: &str,
+        context: &UploadContext,
+    ) -> Result<Option<Submission>, UploadError> {
+        let UploadTargetConfig::LocalArchive { root_dir, path_template, move_files } =
+            &config.target
+        else {
+            return Err(UploadError::Other("upload config is not a local archive".to_string()));
+        };
+        // Segments recorded so far are archived together once the session ends.
+        if parts.is_empty() || context.upload_only {
+            return Ok(None);
+        }
+        let mut vars = context.template_vars.clone();
+        vars.title = live_title.map(str::to_string);
+        if vars.streamer.is_empty() {
+            vars.streamer = task_name.to_string();
+        }
+        let dir = Self::archive_dir(root_dir, path_template, &vars)?;
+        tokio::fs::create_dir_all(&dir).await.map_err(|e| {
+            UploadError::from(e).context(format!("Failed to create {}", dir.display()))
+        })?;
+
+        let mut files = Vec::with_capacity(parts.len());
+        for UploadPart { filename, title } in &parts {
+            let source = Path::new(filename);
+            let Some(file_name) = source.file_name() else {
+                return Err(UploadError::FileInvalid(format!("not a file path: {}", filename)));
+            };
+            tracing::info!("Archiving {} to {}", filename, dir.display());
+            let (dest, size, md5) =
+                Self::archive_file(source, &dir.join(file_name), *move_files, context)
+                    .await
+                    .map_err(|e| e.context(format!("Failed to archive {}", filename)))?;
+            files.push(ArchivedFile {
+                file_name: dest
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                title: title.clone(),
+                size,
+                md5,
+            });
+        }
+
+        let first = Path::new(&files[0].file_name);
+        let metadata_path = dir.join(first.with_extension("json"));
+        let metadata = ArchiveMetadata {
+            task_name: task_name.to_string(),
+            streamer: vars.streamer.clone(),
+            platform: vars.platform.clone(),
+            url: vars.url.clone(),
+            live_title: live_title.map(str::to_string),
+            start_time: vars.start_time,
+            end_time: vars.end_time,
+            archived_at: Local::now().timestamp(),
+            files,
+        };
+        Self::write_metadata(&metadata_path, &metadata)
+            .await
+            .map_err(|e| e.context(format!("Failed to write {}", metadata_path.display())))?;
+        tracing::info!("Archived {} file(s) to {}", metadata.files.len(), dir.display());
+        Ok(Some(Submission {
+            aid: None,
+            bvid: None,
+            title: dir.display().to_string(),
+            appended: false,
+            collection_error: None,
+        }))
+    }
+}
+
+/// Replaces characters Windows and common NAS shares reject in file names.
+fn sanitize_segment(segment: &str) -> String {
+    let cleaned: String = segment
+        .chars()
+        .map(|c| if c.is_control() || "<>:\"|?*".contains(c) { '_' } else { c })
+        .collect();
+    cleaned.trim().trim_end_matches('.').to_string()
+}
+
+/// `dest`, or `name (n).ext` when a different file already has that name.
+async fn available_path(dest: &Path) -> PathBuf {
+    if tokio::fs::metadata(dest).await.is_err() {
+        return dest.to_path_buf();
+    }
+    let stem = dest.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let extension = dest.extension().map(|e| format!(".{}", e.to_string_lossy()));
+    (1..)
+        .map(|n| dest.with_file_name(format!("{stem} ({n}){}", extension.as_deref().unwrap_or(""))))
+        .find(|candidate| !candidate.exists())
+        .unwrap_or_else(|| dest.to_path_buf())
+}
+
+async fn copy_with_md5(
+    source: &Path,
+    dest: &Path,
+    size: u64,
+    context: &UploadContext,
+) -> Result<String, UploadError> {
+    let mut reader = tokio::fs::File::open(source).await?;
+    let mut writer = tokio::fs::File::create(dest).await?;
+    let mut hasher = Md5::new();
+    let mut buffer = vec![0; COPY_BUFFER_BYTES];
+    let mut copied = 0;
+    loop {
+        let read = reader.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        writer.write_all(&buffer[..read]).await?;
+        copied += read as u64;
+        report_progress(context, source, copied, size);
+    }
+    // Flushed to disk so the verification reads what was actually stored.
+    writer.sync_all().await?;
+    Ok(hex(&hasher.finalize()))
+}
+
+async fn file_md5(path: &Path) -> Result<String, UploadError> {
+    let mut reader = tokio::fs::File::open(path).await?;
+    let mut hasher = Md5::new();
+    let mut buffer = vec![0; COPY_BUFFER_BYTES];
+    loop {
+        let read = reader.read(&mut buffer).await?;
+        if read == 0 {
+            return Ok(hex(&hasher.finalize()));
+        }
+        hasher.update(&buffer[..read]);
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn report_progress(context: &UploadContext, path: &Path, done: u64, total: u64) {
+    if let Some(progress) = &context.progress {
+        progress(path, done, total);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LocalArchiveUploader, TemplateVars, UploadContext, UploadPart, Uploader};
+    use shared::{UploadConfig, UploadTargetConfig};
+    use std::path::PathBuf;
+    use uuid::Uuid;
+
+    #[test]
+    fn archive_dir_stays_under_root() {
+        let vars = TemplateVars {
+            streamer: "a/../b:c".to_string(),
+            platform: "Bilibili".to_string(),
+            ..Default::default()
+        };
+        let dir = LocalArchiveUploader::archive_dir("/nas", "{platform}/{streamer}/", &vars);
+        assert_eq!(dir, Ok(PathBuf::from("/nas/Bilibili/a/b_c")));
+        assert!(LocalArchiveUploader::archive_dir(" ", "{platform}", &vars).is_err());
+        assert!(LocalArchiveUploader::archive_dir("/nas", "{platfrom}", &vars).is_err());
+    }
+
+    #[tokio::test]
+    async fn copies_files_and_writes_metadata() {
+        let dir = std::env::temp_dir().join(format!("omnistream-archive-{}", Uuid::new_v4()));
+        let source = dir.join("rec").join("live.flv");
+        tokio::fs::create_dir_all(source.parent().unwrap()).await.unwrap();
+        tokio::fs::write(&source, b"flv data").await.unwrap();
+        let root = dir.join("archive");
+        let config = UploadConfig {
+            target: UploadTargetConfig::LocalArchive {
+                root_dir: root.display().to_string(),
+                path_template: "{streamer}".to_string(),
+                move_files: false,
+            },
+            ..Default::default()
+        };
+        let parts = vec![UploadPart { filename: source.display().to_string(), title: None }];
+        let uploader = LocalArchiveUploader::new();
+        let context = UploadContext::default();
+
+        for _ in 0..2 {
+            let submission = uploader
+                .upload(parts.clone(), &config, Some("开播"), "主播", &context)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(submission.title, root.join("主播").display().to_string());
+        }
+        // The second run found the verified copy and did not add `live (1).flv`.
+        assert!(tokio::fs::metadata(&source).await.is_ok());
+        assert!(tokio::fs::metadata(root.join("主播/live (1).flv")).await.is_err());
+        assert_eq!(tokio::fs::read(root.join("主播/live.flv")).await.unwrap(), b"flv data");
+        let metadata: serde_json::Value =
+            serde_json::from_slice(&tokio::fs::read(root.join("主播/live.json")).await.unwrap())
+                .unwrap();
+        assert_eq!(metadata["live_title"], "开播");
+        assert_eq!(metadata["files"][0]["size"], 8);
+        assert_eq!(metadata["files"][0]["md5"], "b290c235710e346ce431c713efebcd69");
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn retried_move_uses_the_moved_file() {
+        let dir = std::env::temp_dir().join(format!("omnistream-archive-{}", Uuid::new_v4()));
+        let source = dir.join("rec").join("live.flv");
+        tokio::fs::create_dir_all(source.parent().unwrap()).await.unwrap();
+        tokio::fs::write(&source, b"flv data").await.unwrap();
+        let root = dir.join("archive");
+        let config = UploadConfig {
+            target: UploadTargetConfig::LocalArchive {
+                root_dir: root.display().to_string(),
+                path_template: "{streamer}".to_string(),
+                move_files: true,
+            },
+            ..Default::default()
+        };
+        let parts = vec![UploadPart { filename: source.display().to_string(), title: None }];
+        let uploader = LocalArchiveUploader::new();
+        let context = UploadContext::default();
+
+        // The second run is a retry after the first moved the file but failed later on.
+        for _ in 0..2 {
+            uploader.upload(parts.clone(), &config, None, "主播", &context).await.unwrap();
+        }
+        assert!(tokio::fs::metadata(&source).await.is_err());
+        assert_eq!(tokio::fs::read(root.join("主播/live.flv")).await.unwrap(), b"flv data");
+        let metadata: serde_json::Value =
+            serde_json::from_slice(&tokio::fs::read(root.join("主播/live.json")).await.unwrap())
+                .unwrap();
+        assert_eq!(metadata["files"][0]["size"], 8);
+        assert_eq!(metadata["files"][0]["md5"], "b290c235710e346ce431c713efebcd69");
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+}
@@ -1,30 +1,114 @@
-use anyhow::Result;
 use async_trait::async_trait;
-use shared::UploadConfig;
+use shared::{UploadConfig, UploadTargetConfig};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 
 pub mod bilibili;
+mod error;
+mod local_archive;
+pub mod s3;
+pub mod template;
+mod upos;
+pub mod webdav;
+pub mod youtube;
+
+pub use error::UploadError;
+
+/// 单个分P：文件路径与录制该文件时的直播标题
+#[derive(Debug, Clone, PartialEq)]
+pub struct UploadPart {
+    pub filename: String,
+    pub title: Option<String>,
+}
+
+impl From<String> for UploadPart {
+    fn from(filename: String) -> Self {
+        Self { filename, title: None }
+    }
+}
+
+/// 上传进度回调：(文件路径, 该文件已被服务端接收的字节数, 文件总字节数)
+pub type ProgressCallback = Arc<dyn Fn(&Path, u64, u64) + Send + Sync>;
+
+/// 上传限速：发送 `bytes` 字节前调用，返回发送前需要等待的时长
+pub trait UploadThrottle: Send + Sync {
+    fn reserve(&self, bytes: u64) -> Duration;
+}
+
+/// 单次上传的运行时附加项，命令行工具使用默认值即可
+#[derive(Clone, Default)]
+pub struct UploadContext {
+    pub progress: Option<ProgressCallback>,
+    pub throttles: Vec<Arc<dyn UploadThrottle>>,
+    /// 固定使用的 UPOS 线路（如 `bda2`），`None` 时自动探测
+    pub line: Option<String>,
+    /// 单个文件同时上传的分块数，0 使用默认值
+    pub threads: usize,
+    /// 只上传文件不投稿（边录边传的分段），之后投稿时直接复用已上传的文件
+    pub upload_only: bool,
+    /// 追加分P的目标稿件 BV 号，`None` 时新建稿件
+    pub append_bvid: Option<String>,
+    /// 文案模板的直播信息（主播、平台、开始/结束时间等），直播标题与分P信息由上传器填写；
+    /// 结束时间也用于「直播结束后 N 小时发布」，缺失时按当前时间计算
+    pub template_vars: template::TemplateVars,
+    /// 新建稿件时上传的封面图片
+    pub cover: Option<PathBuf>,
+}
+
+/// 投稿结果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Submission {
+    pub aid: Option<u64>,
+    pub bvid: Option<String>,
+    pub title: String,
+    /// 分P追加到了已有稿件
+    pub appended: bool,
+    /// 稿件已投稿，但加入合集失败的原因
+    pub collection_error: Option<String>,
+}
 
 #[async_trait]
 pub trait Uploader: Send + Sync {
-    /// 上传文件列表
+    /// 上传分P列表，`live_title` 用于稿件标题中的 `{title}`；只上传不投稿时返回 `None`
     async fn upload(
         &self,
-        filenames: Vec<String>,
+        parts: Vec<UploadPart>,
         config: &UploadConfig,
         live_title: Option<&str>,
         task_name: &str,
-    ) -> Result<()>;
+        context: &UploadContext,
+    ) -> Result<Option<Submission>, UploadError>;
 }
 
 // 简单的工厂方法或枚举来管理多种上传方式
 pub enum UploadTarget {
     Bilibili,
+    LocalArchive,
+    S3,
+    WebDav,
+    YouTube,
 }
 
 impl UploadTarget {
+    /// 按上传配置的目标类型选择上传方式
+    pub fn for_config(config: &UploadConfig) -> Self {
+        match config.target {
+            UploadTargetConfig::Bilibili => UploadTarget::Bilibili,
+            UploadTargetConfig::LocalArchive { .. } => UploadTarget::LocalArchive,
+            UploadTargetConfig::S3 { .. } => UploadTarget::S3,
+            UploadTargetConfig::WebDav { .. } => UploadTarget::WebDav,
+            UploadTargetConfig::YouTube { .. } => UploadTarget::YouTube,
+        }
+    }
+
     pub fn create_uploader(&self) -> Box<dyn Uploader> {
         match self {
             UploadTarget::Bilibili => Box::new(bilibili::BilibiliUploader::new()),
+            UploadTarget::LocalArchive => Box::new(local_archive::LocalArchiveUploader::new()),
+            UploadTarget::S3 => Box::new(s3::S3Uploader::new()),
+            UploadTarget::WebDav => Box::new(webdav::WebDavUploader::new()),
+            UploadTarget::YouTube => Box::new(youtube::YouTubeUploader::new()),
         }
     }
 }
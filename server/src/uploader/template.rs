@@ -0,0 +1,308 @@
+//! 投稿文案模板：标题、简介、动态与分P标题共用
+//!
+//! - `{name}` / `{name:格式}`：占位符，时间类占位符可带 strftime 格式
+//! - `{if name}…{else}…{end}`：占位符非空时渲染第一段，否则渲染 `{else}` 之后的部分
+//! - `{{` / `}}`：字面量花括号
+//! - 文本中的 strftime 写法（如 `%Y-%m-%d`）按直播开始时间展开
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local, TimeZone};
+
+const DEFAULT_TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+const TIME_VARIABLES: &[&str] = &["start_time", "end_time", "date"];
+const VARIABLES: &[&str] = &[
+    "title",
+    "streamer",
+    "platform",
+    "url",
+    "start_time",
+    "end_time",
+    "date",
+    "duration",
+    "part_index",
+    "segment_title",
+];
+
+/// 模板取值；缺失的值渲染为空字符串，`{if}` 视为假
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TemplateVars {
+    /// 直播间标题，为空时使用主播名
+    pub title: Option<String>,
+    pub streamer: String,
+    pub platform: String,
+    pub url: String,
+    /// 直播开始/结束时间（Unix 秒）
+    pub start_time: Option<i64>,
+    pub end_time: Option<i64>,
+    /// 分P序号，从 1 开始
+    pub part_index: Option<usize>,
+    pub segment_title: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Var { name: String, format: Option<String> },
+    If { name: String, then: Vec<Node>, otherwise: Vec<Node> },
+}
+
+/// Renders `template`; errors describe the first syntax problem found.
+pub fn render(template: &str, vars: &TemplateVars) -> Result<String, String> {
+    let nodes = parse(template)?;
+    let mut output = String::new();
+    render_nodes(&nodes, vars, &mut output);
+    Ok(output)
+}
+
+fn parse(template: &str) -> Result<Vec<Node>, String> {
+    // Stack of open blocks: (condition, nodes before `{else}`, whether `{else}` was seen).
+    let mut stack: Vec<(String, Vec<Node>, bool)> = Vec::new();
+    let mut nodes: Vec<Node> = Vec::new();
+    let mut text = String::new();
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let mut tag = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some('{') | None => return Err(format!("unclosed placeholder: {{{tag}")),
+                        Some(c) => tag.push(c),
+                    }
+                }
+                if !text.is_empty() {
+                    nodes.push(Node::Text(std::mem::take(&mut text)));
+                }
+                let tag = tag.trim();
+                if let Some(name) = tag.strip_prefix("if ") {
+                    let name = name.trim();
+                    check_variable(name)?;
+                    stack.push((name.to_string(), std::mem::take(&mut nodes), false));
+                } else if tag == "else" {
+                    let Some((name, before, has_else)) = stack.pop() else {
+                        return Err("{else} without {if}".to_string());
+                    };
+                    if has_else {
+                        return Err(format!("duplicate {{else}} in {{if {name}}}"));
+                    }
+                    // Keep the `then` branch aside; `nodes` collects the else branch.
+                    let then = std::mem::take(&mut nodes);
+                    let mut before = before;
+                    before.push(Node::If { name: name.clone(), then, otherwise: vec![] });
+                    stack.push((name, before, true));
+                } else if tag == "end" {
+                    let Some((name, mut before, has_else)) = stack.pop() else {
+                        return Err("{end} without {if}".to_string());
+                    };
+                    let branch = std::mem::take(&mut nodes);
+                    if has_else {
+                        if let Some(Node::If { otherwise, .. }) = before.last_mut() {
+                            *otherwise = branch;
+                        }
+                    } else {
+                        before.push(Node::If { name, then: branch, otherwise: vec![] });
+                    }
+                    nodes = before;
+                } else {
+                    let (name, format) = match tag.split_once(':') {
+                        Some((name, format)) => (name.trim(), Some(format.to_string())),
+                        None => (tag, None),
+                    };
+                    check_variable(name)?;
+                    if let Some(format) = &format {
+                        if !TIME_VARIABLES.contains(&name) {
+                            return Err(format!("{{{name}}} does not take a format"));
+                        }
+                        if !is_valid_time_format(format) {
+                            return Err(format!("invalid time format in {{{tag}}}"));
+                        }
+                    }
+                    nodes.push(Node::Var { name: name.to_string(), format });
+                }
+            }
+            c => text.push(c),
+        }
+    }
+    if let Some((name, _, _)) = stack.last() {
+        return Err(format!("{{if {name}}} is missing {{end}}"));
+    }
+    if !text.is_empty() {
+        nodes.push(Node::Text(text));
+    }
+    Ok(nodes)
+}
+
+fn check_variable(name: &str) -> Result<(), String> {
+    if VARIABLES.contains(&name) { Ok(()) } else { Err(format!("unknown placeholder: {{{name}}}")) }
+}
+
+fn is_valid_time_format(format: &str) -> bool {
+    !StrftimeItems::new(format).any(|item| matches!(item, Item::Error))
+}
+
+fn render_nodes(nodes: &[Node], vars: &TemplateVars, output: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(&expand_strftime(text, vars)),
+            Node::Var { name, format } => output.push_str(&value(name, format.as_deref(), vars)),
+            Node::If { name, then, otherwise } => {
+                let branch = if value(name, None, vars).is_empty() { otherwise } else { then };
+                render_nodes(branch, vars, output);
+            }
+        }
+    }
+}
+
+/// Plain text keeps supporting strftime (`%Y-%m-%d`) against the stream start; a `%` that
+/// starts no valid specifier (e.g. `100%好评`) is kept literally.
+fn expand_strftime(text: &str, vars: &TemplateVars) -> String {
+    if !text.contains('%') {
+        return text.to_string();
+    }
+    let chars: Vec<char> = text.chars().collect();
+    let mut escaped = String::with_capacity(text.len());
+    let mut index = 0;
+    while index < chars.len() {
+        if chars[index] != '%' {
+            escaped.push(chars[index]);
+            index += 1;
+            continue;
+        }
+        // Specifiers are two to four chars long, e.g. `%Y`, `%-d`, `%.3f`.
+        let specifier = (2..=4).rev().find(|len| {
+            chars
+                .get(index..index + len)
+                .is_some_and(|spec| is_valid_time_format(&spec.iter().collect::<String>()))
+        });
+        match specifier {
+            Some(len) => {
+                escaped.extend(&chars[index..index + len]);
+                index += len;
+            }
+            None => {
+                escaped.push_str("%%");
+                index += 1;
+            }
+        }
+    }
+    reference_time(vars.start_time).format(&escaped).to_string()
+}
+
+fn value(name: &str, format: Option<&str>, vars: &TemplateVars) -> String {
+    match name {
+        "title" => vars
+            .title
+            .as_deref()
+            .map(str::trim)
+            .filter(|title| !title.is_empty())
+            .unwrap_or(&vars.streamer)
+            .to_string(),
+        "streamer" => vars.streamer.clone(),
+        "platform" => vars.platform.clone(),
+        "url" => vars.url.clone(),
+        "start_time" => format_time(vars.start_time, format),
+        "end_time" => format_time(vars.end_time, format),
+        "date" => format_time(vars.start_time, format.or(Some(DEFAULT_DATE_FORMAT))),
+        "duration" => match (vars.start_time, vars.end_time) {
+            (Some(start), Some(end)) if end >= start => format_duration(end - start),
+            _ => String::new(),
+        },
+        "part_index" => vars.part_index.map(|index| index.to_string()).unwrap_or_default(),
+        "segment_title" => vars.segment_title.clone().unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+fn format_time(timestamp: Option<i64>, format: Option<&str>) -> String {
+    let Some(timestamp) = timestamp else {
+        return String::new();
+    };
+    reference_time(Some(timestamp)).format(format.unwrap_or(DEFAULT_TIME_FORMAT)).to_string()
+}
+
+fn reference_time(timestamp: Option<i64>) -> DateTime<Local> {
+    timestamp.and_then(|ts| Local.timestamp_opt(ts, 0).single()).unwrap_or_else(Local::now)
+}
+
+fn format_duration(secs: i64) -> String {
+    format!("{}:{:02}:{:02}", secs / 3600, secs % 3600 / 60, secs % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TemplateVars, render};
+    use chrono::{Local, TimeZone};
+
+    fn vars() -> TemplateVars {
+        let start = Local.with_ymd_and_hms(2026, 10, 19, 20, 0, 0).unwrap().timestamp();
+        TemplateVars {
+            title: Some("开整".to_string()),
+            streamer: "主播".to_string(),
+            platform: "Bilibili".to_string(),
+            url: "https://live.bilibili.com/6".to_string(),
+            start_time: Some(start),
+            end_time: Some(start + 2 * 3600 + 5 * 60 + 7),
+            part_index: Some(2),
+            segment_title: None,
+        }
+    }
+
+    #[test]
+    fn placeholders_use_stream_values_and_formats() {
+        let rendered = render(
+            "【{streamer}】{title} {start_time:%m.%d} {{{platform}}} {duration} P{part_index}",
+            &vars(),
+        );
+        assert_eq!(rendered.as_deref(), Ok("【主播】开整 10.19 {Bilibili} 2:05:07 P2"));
+        assert_eq!(render("{end_time}", &vars()).as_deref(), Ok("2026-10-19 22:05"));
+        assert_eq!(render("{date}/{date:%Y%m}", &vars()).as_deref(), Ok("2026-10-19/202610"));
+        assert_eq!(
+            render("录像 %Y-%m-%d 100%好评", &vars()).as_deref(),
+            Ok("录像 2026-10-19 100%好评")
+        );
+    }
+
+    #[test]
+    fn title_falls_back_to_streamer() {
+        let vars = TemplateVars { title: Some("  ".to_string()), ..vars() };
+        assert_eq!(render("{title} 录播", &vars).as_deref(), Ok("主播 录播"));
+    }
+
+    #[test]
+    fn conditionals_pick_branch_by_value() {
+        let template = "{if segment_title}{segment_title}{else}第{part_index}部分{end}";
+        assert_eq!(render(template, &vars()).as_deref(), Ok("第2部分"));
+        let vars = TemplateVars { segment_title: Some("团战".to_string()), ..vars() };
+        assert_eq!(render(template, &vars).as_deref(), Ok("团战"));
+        assert_eq!(
+            render("{if url}[{if segment_title}{segment_title}{end}]{end}", &vars).as_deref(),
+            Ok("[团战]")
+        );
+    }
+
+    #[test]
+    fn invalid_templates_are_rejected() {
+        for template in [
+            "{streamr}",
+            "{title",
+            "{if title}x",
+            "{end}",
+            "{if title}a{else}b{else}c{end}",
+            "{title:%Y}",
+            "{start_time:%Q}",
+        ] {
+            assert!(render(template, &vars()).is_err(), "{template} should be rejected");
+        }
+    }
+}
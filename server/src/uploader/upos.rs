@@ -0,0 +1,731 @@
+//! Resumable chunked uploads to Bilibili's UPOS storage.
+//!
+//! `biliup`'s `Parcel::upload` keeps the upload id in memory only, so an interrupted upload
+//! restarts from the first byte. This module drives the same preupload → init → chunk PUT →
+//! complete protocol itself and records every accepted chunk in a `<file>.upos.json` sidecar,
+//! which lets a later attempt (even after a process restart) upload only the missing chunks.
+//! Finished files are listed per account in a `<file>.uploaded.json` sidecar until they are
+//! submitted, so a file uploaded ahead of the submission (or before a failed one) is reused.
+
+use super::{UploadContext, UploadError, UploadThrottle};
+use anyhow::{Result, anyhow};
+use biliup::uploader::bilibili::{BiliBili, Video};
+use bytes::Bytes;
+use futures::{StreamExt, stream};
+use http_body::{Frame, SizeHint};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+const PREUPLOAD_URL: &str = "https://member.bilibili.com/preupload";
+const DEFAULT_LINE: &str = "bda2";
+const SESSION_SUFFIX: &str = ".upos.json";
+const UPLOADED_SUFFIX: &str = ".uploaded.json";
+/// UPOS auth tokens expire after a while; older sessions are restarted instead of resumed.
+const SESSION_MAX_AGE_SECS: i64 = 12 * 60 * 60;
+/// Files that never make it into an archive are purged by UPOS eventually; older records are
+/// not trusted and the file is uploaded again.
+const UPLOADED_MAX_AGE_SECS: i64 = 48 * 60 * 60;
+const DEFAULT_CHUNK_CONCURRENCY: usize = 3;
+const CHUNK_ATTEMPTS: u32 = 3;
+const CHUNK_TIMEOUT: Duration = Duration::from_secs(300);
+/// Throttled request bodies are released in slices this large.
+const THROTTLE_SLICE_BYTES: usize = 16 * 1024;
+
+/// Persisted state of one file's upload, enough to continue it with a fresh process.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(super) struct UposSession {
+    pub account_file: String,
+    pub file_size: u64,
+    pub modified_at: i64,
+    pub endpoint: String,
+    pub upos_uri: String,
+    pub auth: String,
+    pub biz_id: u64,
+    pub chunk_size: u64,
+    pub upload_id: String,
+    #[serde(default)]
+    pub completed_chunks: BTreeSet<u64>,
+    pub created_at: i64,
+}
+
+impl UposSession {
+    fn url(&self) -> String {
+        format!("https:{}/{}", self.endpoint, self.upos_uri.trim_start_matches("upos://"))
+    }
+
+    fn chunk_count(&self) -> u64 {
+        if self.chunk_size == 0 { 0 } else { self.file_size.div_ceil(self.chunk_size) }
+    }
+
+    fn chunk_len(&self, index: u64) -> u64 {
+        self.chunk_size.min(self.file_size.saturating_sub(index * self.chunk_size))
+    }
+
+    fn completed_bytes(&self) -> u64 {
+        self.completed_chunks.iter().map(|&i| self.chunk_len(i)).sum()
+    }
+
+    fn pending_chunks(&self) -> Vec<u64> {
+        (0..self.chunk_count()).filter(|i| !self.completed_chunks.contains(i)).collect()
+    }
+
+    /// Whether the session still belongs to this account and the unchanged file.
+    fn is_resumable(&self, account_file: &str, file_size: u64, modified_at: i64, now: i64) -> bool {
+        self.account_file == account_file
+            && self.file_size == file_size
+            && self.modified_at == modified_at
+            && self.chunk_size > 0
+            && now - self.created_at < SESSION_MAX_AGE_SECS
+    }
+}
+
+/// A finished upload that has not been submitted in an archive yet.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(super) struct UploadedFile {
+    pub account_file: String,
+    pub file_size: u64,
+    /// Name of the file on UPOS, referenced by the archive submission.
+    pub filename: String,
+    pub uploaded_at: i64,
+}
+
+impl UploadedFile {
+    /// The local file may already be gone (cleaned up after uploading); otherwise it must
+    /// still be the file that was uploaded.
+    fn is_reusable(&self, account_file: &str, file_size: Option<u64>, now: i64) -> bool {
+        self.account_file == account_file
+            && file_size.is_none_or(|size| size == self.file_size)
+            && now - self.uploaded_at < UPLOADED_MAX_AGE_SECS
+    }
+}
+
+/// UPOS answered a chunk with a client error, which means the stored session is no longer valid.
+#[derive(Debug)]
+struct SessionRejected(reqwest::StatusCode);
+
+impl fmt::Display for SessionRejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "upload session rejected by UPOS: {}", self.0)
+    }
+}
+
+impl std::error::Error for SessionRejected {}
+
+#[derive(Deserialize)]
+struct PreuploadResponse {
+    endpoint: String,
+    upos_uri: String,
+    auth: String,
+    biz_id: u64,
+    chunk_size: u64,
+}
+
+#[derive(Deserialize)]
+struct ProbeResponse {
+    #[serde(default)]
+    lines: Vec<ProbeLine>,
+}
+
+#[derive(Deserialize)]
+struct ProbeLine {
+    #[serde(default)]
+    os: String,
+    query: String,
+    probe_url: String,
+}
+
+/// Everything a chunk request needs, detached from the session so it can be mutated meanwhile.
+struct ChunkTarget {
+    http: reqwest::Client,
+    path: PathBuf,
+    url: String,
+    auth: String,
+    upload_id: String,
+    chunk_size: u64,
+    chunks: u64,
+    total: u64,
+    throttles: Vec<Arc<dyn UploadThrottle>>,
+}
+
+/// Chunk body that asks every throttle for permission before releasing each slice, so rate
+/// limits apply to the bytes as they go out rather than to whole chunks.
+struct ThrottledBody {
+    data: Bytes,
+    throttles: Vec<Arc<dyn UploadThrottle>>,
+    pending: Option<Bytes>,
+    delay: Option<Pin<Box<tokio::time::Sleep>>>,
+}
+
+impl http_body::Body for ThrottledBody {
+    type Data = Bytes;
+    type Error = std::io::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, std::io::Error>>> {
+        let this = &mut *self;
+        if this.pending.is_none() {
+            if this.data.is_empty() {
+                return Poll::Ready(None);
+            }
+            let slice = this.data.split_to(THROTTLE_SLICE_BYTES.min(this.data.len()));
+            let len = slice.len() as u64;
+            let wait = this.throttles.iter().map(|t| t.reserve(len)).max().unwrap_or_default();
+            if !wait.is_zero() {
+                this.delay = Some(Box::pin(tokio::time::sleep(wait)));
+            }
+            this.pending = Some(slice);
+        }
+        if let Some(delay) = this.delay.as_mut() {
+            if delay.as_mut().poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+            this.delay = None;
+        }
+        Poll::Ready(this.pending.take().map(|slice| Ok(Frame::data(slice))))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.pending.is_none() && self.data.is_empty()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        let pending = self.pending.as_ref().map_or(0, Bytes::len);
+        SizeHint::with_exact((self.data.len() + pending) as u64)
+    }
+}
+
+pub(super) fn chunk_body(data: Vec<u8>, throttles: &[Arc<dyn UploadThrottle>]) -> reqwest::Body {
+    if throttles.is_empty() {
+        return data.into();
+    }
+    reqwest::Body::wrap(ThrottledBody {
+        data: data.into(),
+        throttles: throttles.to_vec(),
+        pending: None,
+        delay: None,
+    })
+}
+
+pub(super) fn session_path(path: &Path) -> PathBuf {
+    let mut raw = path.as_os_str().to_os_string();
+    raw.push(SESSION_SUFFIX);
+    PathBuf::from(raw)
+}
+
+async fn load_session(path: &Path) -> Option<UposSession> {
+    let raw = tokio::fs::read(session_path(path)).await.ok()?;
+    serde_json::from_slice(&raw)
+        .inspect_err(|e| {
+            tracing::warn!("Ignoring unreadable upload session for {}: {}", path.display(), e)
+        })
+        .ok()
+}
+
+async fn save_session(path: &Path, session: &UposSession) -> Result<()> {
+    let target = session_path(path);
+    let mut tmp = target.clone().into_os_string();
+    tmp.push(".tmp");
+    tokio::fs::write(&tmp, serde_json::to_vec(session)?).await?;
+    tokio::fs::rename(&tmp, &target).await?;
+    Ok(())
+}
+
+fn uploaded_path(path: &Path) -> PathBuf {
+    let mut raw = path.as_os_str().to_os_string();
+    raw.push(UPLOADED_SUFFIX);
+    PathBuf::from(raw)
+}
+
+async fn load_uploaded(path: &Path) -> Vec<UploadedFile> {
+    let Ok(raw) = tokio::fs::read(uploaded_path(path)).await else {
+        return vec![];
+    };
+    serde_json::from_slice(&raw).unwrap_or_else(|e| {
+        tracing::warn!("Ignoring unreadable upload record for {}: {}", path.display(), e);
+        vec![]
+    })
+}
+
+async fn save_uploaded(path: &Path, records: &[UploadedFile]) -> Result<()> {
+    let target = uploaded_path(path);
+    if records.is_empty() {
+        if let Err(e) = tokio::fs::remove_file(&target).await
+            && e.kind() != std::io::ErrorKind::NotFound
+        {
+            return Err(e.into());
+        }
+        return Ok(());
+    }
+    let mut tmp = target.clone().into_os_string();
+    tmp.push(".tmp");
+    tokio::fs::write(&tmp, serde_json::to_vec(records)?).await?;
+    tokio::fs::rename(&tmp, &target).await?;
+    Ok(())
+}
+
+async fn record_uploaded(path: &Path, record: UploadedFile) {
+    let mut records = load_uploaded(path).await;
+    records.retain(|r| r.account_file != record.account_file);
+    records.push(record);
+    if let Err(e) = save_uploaded(path, &records).await {
+        tracing::warn!("Failed to record finished upload of {}: {}", path.display(), e);
+    }
+}
+
+/// Drops the account's upload record once the file has been submitted in an archive.
+pub(super) async fn forget_uploaded(path: &Path, account_file: &str) {
+    let mut records = load_uploaded(path).await;
+    let before = records.len();
+    records.retain(|r| r.account_file != account_file);
+    if records.len() == before {
+        return;
+    }
+    if let Err(e) = save_uploaded(path, &records).await {
+        tracing::warn!("Failed to update upload record of {}: {}", path.display(), e);
+    }
+}
+
+async fn remove_session(path: &Path) {
+    if let Err(e) = tokio::fs::remove_file(session_path(path)).await
+        && e.kind() != std::io::ErrorKind::NotFound
+    {
+        tracing::warn!("Failed to remove upload session for {}: {}", path.display(), e);
+    }
+}
+
+/// Uploads `path` to UPOS, continuing a previous session of the same account when possible.
+/// A file this account already uploaded and has not submitted yet is not sent again.
+pub(super) async fn upload_file(
+    bili: &BiliBili,
+    account_file: &str,
+    path: &Path,
+    context: &UploadContext,
+) -> Result<Video> {
+    let meta = tokio::fs::metadata(path).await.ok();
+    let now = chrono::Utc::now().timestamp();
+    if let Some(uploaded) = load_uploaded(path)
+        .await
+        .into_iter()
+        .find(|r| r.is_reusable(account_file, meta.as_ref().map(|m| m.len()), now))
+    {
+        tracing::info!("Reusing earlier upload of {}: {}", path.display(), uploaded.filename);
+        if let Some(progress) = &context.progress {
+            progress(path, uploaded.file_size, uploaded.file_size);
+        }
+        return Ok(video_for(path, uploaded.filename));
+    }
+    let Some(meta) = meta else {
+        return Err(UploadError::FileInvalid(format!("File not found: {}", path.display())).into());
+    };
+    let file_size = meta.len();
+    let modified_at = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map_or(0, |d| i64::try_from(d.as_secs()).unwrap_or_default());
+
+    let http = reqwest::Client::builder().timeout(CHUNK_TIMEOUT).build()?;
+
+    if let Some(session) = load_session(path).await
+        && session.is_resumable(account_file, file_size, modified_at, now)
+    {
+        tracing::info!(
+            "Resuming upload of {}: {}/{} chunks already accepted",
+            path.display(),
+            session.completed_chunks.len(),
+            session.chunk_count()
+        );
+        match upload_session(&http, path, session, context).await {
+            Ok(video) => {
+                record_uploaded(path, uploaded_file(account_file, file_size, &video)).await;
+                return Ok(video);
+            }
+            Err(e) if e.downcast_ref::<SessionRejected>().is_some() => {
+                tracing::warn!(
+                    "Stored upload session expired, restarting {}: {}",
+                    path.display(),
+                    e
+                );
+                remove_session(path).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    let session =
+        start_session(bili, &http, account_file, path, file_size, modified_at, context).await?;
+    save_session(path, &session).await?;
+    let video = upload_session(&http, path, session, context).await?;
+    record_uploaded(path, uploaded_file(account_file, file_size, &video)).await;
+    Ok(video)
+}
+
+fn uploaded_file(account_file: &str, file_size: u64, video: &Video) -> UploadedFile {
+    UploadedFile {
+        account_file: account_file.to_string(),
+        file_size,
+        filename: video.filename.clone(),
+        uploaded_at: chrono::Utc::now().timestamp(),
+    }
+}
+
+fn video_for(path: &Path, filename: String) -> Video {
+    Video {
+        title: path.file_stem().and_then(|s| s.to_str()).map(str::to_string),
+        filename,
+        desc: String::new(),
+    }
+}
+
+async fn start_session(
+    bili: &BiliBili,
+    http: &reqwest::Client,
+    account_file: &str,
+    path: &Path,
+    file_size: u64,
+    modified_at: i64,
+    context: &UploadContext,
+) -> Result<UposSession> {
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| anyhow!("Invalid file name: {}", path.display()))?;
+    let query = match context.line.as_deref() {
+        Some(line) => line_query(line),
+        None => probe_line(&bili.client).await,
+    };
+
+    let pre: PreuploadResponse = bili
+        .client
+        .get(with_query(
+            &format!("{PREUPLOAD_URL}?{query}"),
+            &[
+                ("r", "upos"),
+                ("profile", "ugcupos/bup"),
+                ("ssl", "0"),
+                ("version", "2.8.12"),
+                ("build", "2081200"),
+                ("name", file_name),
+                ("size", &file_size.to_string()),
+            ],
+        )?)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await
+        .map_err(|e| anyhow!("Failed to pre_upload: {}", e))?;
+
+    let mut session = UposSession {
+        account_file: account_file.to_string(),
+        file_size,
+        modified_at,
+        endpoint: pre.endpoint,
+        upos_uri: pre.upos_uri,
+        auth: pre.auth,
+        biz_id: pre.biz_id,
+        chunk_size: pre.chunk_size,
+        upload_id: String::new(),
+        completed_chunks: BTreeSet::new(),
+        created_at: chrono::Utc::now().timestamp(),
+    };
+
+    let init: serde_json::Value = http
+        .post(format!("{}?uploads&output=json", session.url()))
+        .header("X-Upos-Auth", &session.auth)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    session.upload_id = init["upload_id"]
+        .as_str()
+        .ok_or_else(|| anyhow!("UPOS init returned no upload_id: {}", init))?
+        .to_string();
+    Ok(session)
+}
+
+async fn upload_session(
+    http: &reqwest::Client,
+    path: &Path,
+    mut session: UposSession,
+    context: &UploadContext,
+) -> Result<Video> {
+    let report = |session: &UposSession| {
+        if let Some(progress) = &context.progress {
+            progress(path, session.completed_bytes(), session.file_size);
+        }
+    };
+    report(&session);
+
+    let target = ChunkTarget {
+        http: http.clone(),
+        path: path.to_path_buf(),
+        url: session.url(),
+        auth: session.auth.clone(),
+        upload_id: session.upload_id.clone(),
+        chunk_size: session.chunk_size,
+        chunks: session.chunk_count(),
+        total: session.file_size,
+        throttles: context.throttles.clone(),
+    };
+
+    let mut uploads = stream::iter(session.pending_chunks())
+        .map(|index| upload_chunk_with_retry(&target, index))
+        .buffer_unordered(if context.threads == 0 {
+            DEFAULT_CHUNK_CONCURRENCY
+        } else {
+            context.threads
+        });
+    while let Some(result) = uploads.next().await {
+        let index = result?;
+        session.completed_chunks.insert(index);
+        report(&session);
+        // Losing one progress write only costs re-sending that chunk later.
+        if let Err(e) = save_session(path, &session).await {
+            tracing::warn!("Failed to persist upload progress for {}: {}", path.display(), e);
+        }
+    }
+    drop(uploads);
+
+    let video = complete_upload(&target, &session).await?;
+    remove_session(path).await;
+    Ok(video)
+}
+
+async fn upload_chunk_with_retry(target: &ChunkTarget, index: u64) -> Result<u64> {
+    let mut attempt = 1;
+    loop {
+        match upload_chunk(target, index).await {
+            Ok(()) => return Ok(index),
+            Err(e) if e.downcast_ref::<SessionRejected>().is_some() => return Err(e),
+            Err(e) if attempt < CHUNK_ATTEMPTS => {
+                tracing::warn!(
+                    "Chunk {}/{} of {} failed (attempt {}): {}",
+                    index + 1,
+                    target.chunks,
+                    target.path.display(),
+                    attempt,
+                    e
+                );
+                let delay = match e.downcast_ref::<UploadError>() {
+                    Some(UploadError::RateLimited { retry_after: Some(after), .. }) => *after,
+                    _ => Duration::from_secs(u64::from(attempt) * 2),
+                };
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+async fn upload_chunk(target: &ChunkTarget, index: u64) -> Result<()> {
+    let start = index * target.chunk_size;
+    let len = target.chunk_size.min(target.total.saturating_sub(start));
+    let mut file = tokio::fs::File::open(&target.path).await?;
+    file.seek(std::io::SeekFrom::Start(start)).await?;
+    let mut buf = vec![0u8; usize::try_from(len)?];
+    file.read_exact(&mut buf).await?;
+
+    let response = target
+        .http
+        .put(with_query(
+            &target.url,
+            &[
+                ("partNumber", &(index + 1).to_string()),
+                ("uploadId", &target.upload_id),
+                ("chunk", &index.to_string()),
+                ("chunks", &target.chunks.to_string()),
+                ("size", &len.to_string()),
+                ("start", &start.to_string()),
+                ("end", &(start + len).to_string()),
+                ("total", &target.total.to_string()),
+            ],
+        )?)
+        .header("X-Upos-Auth", &target.auth)
+        .body(chunk_body(buf, &target.throttles))
+        .send()
+        .await?;
+
+    let status = response.status();
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return Err(rate_limited(&response, format!("UPOS throttled chunk {}", index + 1)).into());
+    }
+    if status.is_client_error() {
+        return Err(SessionRejected(status).into());
+    }
+    response.error_for_status()?;
+    Ok(())
+}
+
+async fn complete_upload(target: &ChunkTarget, session: &UposSession) -> Result<Video> {
+    let parts: Vec<_> =
+        (1..=target.chunks).map(|n| json!({ "partNumber": n, "eTag": "etag" })).collect();
+    let file_name = target.path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+
+    let response = target
+        .http
+        .post(with_query(
+            &target.url,
+            &[
+                ("output", "json"),
+                ("name", file_name),
+                ("profile", "ugcupos/bup"),
+                ("uploadId", &target.upload_id),
+                ("biz_id", &session.biz_id.to_string()),
+            ],
+        )?)
+        .header("X-Upos-Auth", &target.auth)
+        .json(&json!({ "parts": parts }))
+        .send()
+        .await?;
+    let status = response.status();
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        let message = format!("UPOS throttled completing {}", target.path.display());
+        return Err(rate_limited(&response, message).into());
+    }
+    if status.is_client_error() {
+        return Err(SessionRejected(status).into());
+    }
+    let ret: serde_json::Value = response.error_for_status()?.json().await?;
+    if ret["OK"] != 1 {
+        return Err(anyhow!("UPOS rejected completing {}: {}", target.path.display(), ret));
+    }
+
+    let filename = Path::new(&session.upos_uri)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default()
+        .to_string();
+    Ok(video_for(&target.path, filename))
+}
+
+/// A 429 answer; `Retry-After` is honoured when given in seconds.
+fn rate_limited(response: &reqwest::Response, message: String) -> UploadError {
+    UploadError::RateLimited { message, retry_after: retry_after(response) }
+}
+
+pub(super) fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+        .map(Duration::from_secs)
+}
+
+fn with_query(base: &str, params: &[(&str, &str)]) -> Result<url::Url> {
+    Ok(url::Url::parse_with_params(base, params)?)
+}
+
+fn line_query(line: &str) -> String {
+    format!("probe_version=20221109&upcdn={line}")
+}
+
+/// Picks the fastest UPOS line from Bilibili's probe list, falling back to bda2.
+async fn probe_line(client: &reqwest::Client) -> String {
+    let probe: ProbeResponse = match client.get(format!("{PREUPLOAD_URL}?r=probe")).send().await {
+        Ok(response) => response.json().await.unwrap_or(ProbeResponse { lines: vec![] }),
+        Err(e) => {
+            tracing::warn!("Failed to fetch upload line list, using default line: {}", e);
+            return line_query(DEFAULT_LINE);
+        }
+    };
+
+    let mut best: Option<(Duration, String)> = None;
+    for line in probe.lines.into_iter().filter(|l| l.os.is_empty() || l.os == "upos") {
+        let started = Instant::now();
+        let ok = client
+            .get(format!("https:{}", line.probe_url))
+            .timeout(Duration::from_secs(10))
+            .send()
+            .await
+            .is_ok_and(|r| r.status().is_success());
+        let cost = started.elapsed();
+        if ok && best.as_ref().is_none_or(|(best_cost, _)| cost < *best_cost) {
+            best = Some((cost, line.query));
+        }
+    }
+    best.map_or_else(|| line_query(DEFAULT_LINE), |(_, query)| query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        SESSION_MAX_AGE_SECS, UPLOADED_MAX_AGE_SECS, UploadedFile, UposSession, line_query,
+        session_path,
+    };
+    use std::path::Path;
+
+    fn session() -> UposSession {
+        UposSession {
+            account_file: "cookies.json".to_string(),
+            file_size: 25,
+            modified_at: 100,
+            endpoint: "//upos-cs-upcdnbda2.bilivideo.com".to_string(),
+            upos_uri: "upos://ugcfx2lf/n2401.mp4".to_string(),
+            auth: "auth".to_string(),
+            biz_id: 1,
+            chunk_size: 10,
+            upload_id: "u1".to_string(),
+            completed_chunks: [0, 2].into_iter().collect(),
+            created_at: 1_000,
+        }
+    }
+
+    #[test]
+    fn pending_chunks_skip_accepted_ones() {
+        let session = session();
+        assert_eq!(session.chunk_count(), 3);
+        assert_eq!(session.pending_chunks(), vec![1]);
+        assert_eq!(session.completed_bytes(), 15);
+        assert_eq!(session.url(), "https://upos-cs-upcdnbda2.bilivideo.com/ugcfx2lf/n2401.mp4");
+    }
+
+    #[test]
+    fn session_is_only_resumed_for_same_account_and_unchanged_file() {
+        let session = session();
+        assert!(session.is_resumable("cookies.json", 25, 100, 2_000));
+        assert!(!session.is_resumable("other.json", 25, 100, 2_000));
+        assert!(!session.is_resumable("cookies.json", 30, 100, 2_000));
+        assert!(!session.is_resumable("cookies.json", 25, 101, 2_000));
+        assert!(!session.is_resumable("cookies.json", 25, 100, 1_000 + SESSION_MAX_AGE_SECS));
+    }
+
+    #[test]
+    fn pinned_line_maps_to_preupload_query() {
+        assert_eq!(line_query("ws"), "probe_version=20221109&upcdn=ws");
+    }
+
+    #[test]
+    fn session_sidecar_sits_next_to_recording() {
+        assert_eq!(session_path(Path::new("rec/a.mp4")), Path::new("rec/a.mp4.upos.json"));
+    }
+
+    #[test]
+    fn uploaded_file_is_reused_for_same_account_even_after_local_cleanup() {
+        let uploaded = UploadedFile {
+            account_file: "cookies.json".to_string(),
+            file_size: 25,
+            filename: "n2401".to_string(),
+            uploaded_at: 1_000,
+        };
+        assert!(uploaded.is_reusable("cookies.json", Some(25), 2_000));
+        assert!(uploaded.is_reusable("cookies.json", None, 2_000));
+        assert!(!uploaded.is_reusable("other.json", Some(25), 2_000));
+        assert!(!uploaded.is_reusable("cookies.json", Some(30), 2_000));
+        assert!(!uploaded.is_reusable("cookies.json", None, 1_000 + UPLOADED_MAX_AGE_SECS));
+    }
+}
@@ -0,0 +1,444 @@
+//! Uploads to a WebDAV server such as Alist, Nextcloud or `rclone serve webdav`.
+//!
+//! The server address and credentials come from a WebDAV account in the account store. Each
+//! file is streamed from disk with a single PUT, so large recordings never sit in memory, and
+//! the size the server reports afterwards must match the local file. A file the server already
+//! holds with the right size is not sent again when a failed job is retried.
+//!
+//! Chunked uploads are not used: Nextcloud's chunking v2 is specific to that server and Alist
+//! and `rclone` do not support it, so an interrupted PUT restarts its file from the beginning.
+
+use super::template::{self, TemplateVars};
+use super::upos::retry_after;
+use super::{Submission, UploadContext, UploadError, UploadPart, UploadThrottle, Uploader};
+use anyhow::Result;
+use async_trait::async_trait;
+use bytes::Bytes;
+use http_body::{Frame, SizeHint};
+use serde::{Deserialize, Serialize};
+use shared::{UploadConfig, UploadTargetConfig};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+use tokio::sync::mpsc;
+
+/// Bytes read from disk per body chunk, and how many chunks the reader may run ahead.
+const CHUNK_BYTES: usize = 1024 * 1024;
+const READ_AHEAD_CHUNKS: usize = 4;
+/// Timeout of the metadata requests; the PUT itself runs as long as the data keeps flowing.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// Longest silence of the server on any request, e.g. while it stores a PUT before answering.
+const READ_TIMEOUT: Duration = Duration::from_secs(120);
+const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?><d:propfind xmlns:d="DAV:"><d:prop><d:getcontentlength/></d:prop></d:propfind>"#;
+
+pub struct WebDavUploader;
+
+/// A WebDAV account file of the account store.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebDavAccount {
+    pub base_url: String,
+    pub username: String,
+    #[serde(default)]
+    pub password: String,
+}
+
+/// An account with a parsed base URL and the HTTP client to reach it.
+struct Server {
+    http: reqwest::Client,
+    base_url: url::Url,
+    username: String,
+    password: String,
+}
+
+impl WebDavAccount {
+    pub async fn load(account_file: &str) -> Result<Self, UploadError> {
+        let raw = tokio::fs::read(account_file).await.map_err(|e| {
+            UploadError::Other(format!("failed to read account {}: {}", account_file, e))
+        })?;
+        serde_json::from_slice(&raw).map_err(|e| {
+            UploadError::Other(format!("{} is not a WebDAV account: {}", account_file, e))
+        })
+    }
+
+    /// Checks that the server answers and accepts the credentials.
+    pub async fn check(&self) -> Result<(), UploadError> {
+        let server = Server::connect(self)?;
+        let root = server.url(&[], true);
+        match server.size_of(&root).await? {
+            Some(_) => Ok(()),
+            None => Err(UploadError::Other(format!("WebDAV path does not exist: {}", root))),
+        }
+    }
+}
+
+impl Server {
+    fn connect(account: &WebDavAccount) -> Result<Self, UploadError> {
+        let base_url = url::Url::parse(account.base_url.trim())
+            .ok()
+            .filter(|url| matches!(url.scheme(), "http" | "https") && url.host_str().is_some())
+            .ok_or_else(|| {
+                UploadError::Other(format!("invalid WebDAV address: {}", account.base_url))
+            })?;
+        let http = reqwest::Client::builder()
+            .connect_timeout(REQUEST_TIMEOUT)
+            .read_timeout(READ_TIMEOUT)
+            .build()
+            .map_err(|e| UploadError::Other(format!("failed to build HTTP client: {}", e)))?;
+        Ok(Self {
+            http,
+            base_url,
+            username: account.username.clone(),
+            password: account.password.clone(),
+        })
+    }
+
+    /// `segments` below the base URL, each percent-encoded; collections end with a slash.
+    fn url(&self, segments: &[&str], collection: bool) -> url::Url {
+        let mut url = self.base_url.clone();
+        if let Ok(mut path) = url.path_segments_mut() {
+            path.pop_if_empty().extend(segments);
+            if collection {
+                path.push("");
+            }
+        }
+        url
+    }
+
+    fn request(&self, method: &str, url: url::Url) -> reqwest::RequestBuilder {
+        let method =
+            reqwest::Method::from_bytes(method.as_bytes()).expect("WebDAV methods are valid");
+        self.http.request(method, url).basic_auth(&self.username, Some(&self.password))
+    }
+
+    /// Size of the resource at `url` (0 for collections), `None` when it does not exist.
+    async fn size_of(&self, url: &url::Url) -> Result<Option<u64>> {
+        let response = self
+            .request("PROPFIND", url.clone())
+            .header("Depth", "0")
+            .header(reqwest::header::CONTENT_TYPE, "application/xml; charset=utf-8")
+            .timeout(REQUEST_TIMEOUT)
+            .body(PROPFIND_BODY)
+            .send()
+            .await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(webdav_error(response, &format!("PROPFIND {}", url)).await);
+        }
+        Ok(Some(content_length(&response.text().await?).unwrap_or(0)))
+    }
+
+    /// Creates every missing collection of `dir`, parents first.
+    async fn create_dirs(&self, dir: &[&str]) -> Result<()> {
+        for depth in 1..=dir.len() {
+            let url = self.url(&dir[..depth], true);
+            let response =
+                self.request("MKCOL", url.clone()).timeout(REQUEST_TIMEOUT).send().await?;
+            // 405 Method Not Allowed: the collection already exists.
+            if !response.status().is_success()
+                && response.status() != reqwest::StatusCode::METHOD_NOT_ALLOWED
+            {
+                return Err(webdav_error(response, &format!("MKCOL {}", url)).await);
+            }
+        }
+        Ok(())
+    }
+
+    /// Uploads `path` into `dir` unless the server already holds a file of the same size.
+    async fn upload_file(&self, path: &Path, dir: &[&str], context: &UploadContext) -> Result<()> {
+        let Ok(meta) = tokio::fs::metadata(path).await else {
+            return Err(
+                UploadError::FileInvalid(format!("File not found: {}", path.display())).into()
+            );
+        };
+        let size = meta.len();
+        let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+            return Err(
+                UploadError::FileInvalid(format!("not a file path: {}", path.display())).into()
+            );
+        };
+        let segments: Vec<&str> = dir.iter().copied().chain([file_name]).collect();
+        let url = self.url(&segments, false);
+
+        if self.size_of(&url).await? == Some(size) {
+            tracing::info!("{} is already on the WebDAV server, skipping", path.display());
+        } else {
+            let file = tokio::fs::File::open(path).await?;
+            let response = self
+                .request("PUT", url.clone())
+                .header(reqwest::header::CONTENT_LENGTH, size)
+                .body(file_body(file, path, size, context))
+                .send()
+                .await?;
+            if !response.status().is_success() {
+                return Err(webdav_error(response, &format!("PUT {}", url)).await);
+            }
+            let stored = self.size_of(&url).await?;
+            if stored != Some(size) {
+                return Err(UploadError::NetworkTransient(format!(
+                    "uploaded file {} has {:?} bytes, expected {}",
+                    url, stored, size
+                ))
+                .into());
+            }
+        }
+        if let Some(progress) = &context.progress {
+            progress(path, size, size);
+        }
+        Ok(())
+    }
+}
+
+impl WebDavUploader {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Rendered directory template with empty, `.` and `..` segments dropped.
+    fn remote_dir(dir_template: &str, vars: &TemplateVars) -> Result<Vec<String>, UploadError> {
+        let rendered = template::render(dir_template, vars)
+            .map_err(|e| UploadError::Other(format!("invalid WebDAV directory template: {}", e)))?;
+        Ok(rendered
+            .split(['/', '\\'])
+            .map(str::trim)
+            .filter(|segment| !segment.is_empty() && *segment != "." && *segment != "..")
+            .map(str::to_string)
+            .collect())
+    }
+}
+
+#[async_trait]
+impl Uploader for WebDavUploader {
+    async fn upload(
+        &self,
+        parts: Vec<UploadPart>,
+        config: &UploadConfig,
+        live_title: Option<&str>,
+        task_name: &str,
+        context: &UploadContext,
+    ) -> Result<Option<Submission>, UploadError> {
+        let UploadTargetConfig::WebDav { remote_dir_template } = &config.target else {
+            return Err(UploadError::Other("upload config is not a WebDAV target".to_string()));
+        };
+        // Segments recorded so far are uploaded together once the session ends.
+        if parts.is_empty() || context.upload_only {
+            return Ok(None);
+        }
+        let mut vars = context.template_vars.clone();
+        vars.title = live_title.map(str::to_string);
+        if vars.streamer.is_empty() {
+            vars.streamer = task_name.to_string();
+        }
+        let dir = Self::remote_dir(remote_dir_template, &vars)?;
+        let dir: Vec<&str> = dir.iter().map(String::as_str).collect();
+
+        let account = WebDavAccount::load(&config.account_file).await?;
+        account.check().await.map_err(|e| e.context("WebDAV login failed"))?;
+        let server = Server::connect(&account)?;
+        server.create_dirs(&dir).await.map_err(|e| {
+            UploadError::from(e).context(format!("Failed to create {}", dir.join("/")))
+        })?;
+        for UploadPart { filename, .. } in &parts {
+            tracing::info!("Uploading {} to {}", filename, server.url(&dir, true));
+            server.upload_file(Path::new(filename), &dir, context).await.map_err(|e| {
+                UploadError::from(e).context(format!("Failed to upload {}", filename))
+            })?;
+        }
+        Ok(Some(Submission {
+            aid: None,
+            bvid: None,
+            title: server.url(&dir, true).to_string(),
+            appended: false,
+            collection_error: None,
+        }))
+    }
+}
+
+/// A request body read from disk chunk by chunk by a background task.
+struct FileBody {
+    chunks: mpsc::Receiver<std::io::Result<Bytes>>,
+    remaining: u64,
+}
+
+impl http_body::Body for FileBody {
+    type Data = Bytes;
+    type Error = std::io::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, std::io::Error>>> {
+        match self.chunks.poll_recv(cx) {
+            Poll::Ready(Some(Ok(chunk))) => {
+                self.remaining = self.remaining.saturating_sub(chunk.len() as u64);
+                Poll::Ready(Some(Ok(Frame::data(chunk))))
+            }
+            Poll::Ready(Some(Err(e))) => Poll::Ready(Some(Err(e))),
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::with_exact(self.remaining)
+    }
+}
+
+/// Streams the first `size` bytes of `file`, applying the upload throttles and reporting
+/// every chunk handed to the connection as progress.
+fn file_body(
+    file: tokio::fs::File,
+    path: &Path,
+    size: u64,
+    context: &UploadContext,
+) -> reqwest::Body {
+    let (sender, chunks) = mpsc::channel(READ_AHEAD_CHUNKS);
+    let path = PathBuf::from(path);
+    let throttles: Vec<Arc<dyn UploadThrottle>> = context.throttles.clone();
+    let progress = context.progress.clone();
+    tokio::spawn(async move {
+        let mut reader = file.take(size);
+        let mut sent = 0;
+        loop {
+            let mut buffer = vec![0; CHUNK_BYTES];
+            let read = match reader.read(&mut buffer).await {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) => {
+                    let _ = sender.send(Err(e)).await;
+                    break;
+                }
+            };
+            buffer.truncate(read);
+            let wait = throttles.iter().map(|t| t.reserve(read as u64)).max().unwrap_or_default();
+            if !wait.is_zero() {
+                tokio::time::sleep(wait).await;
+            }
+            // The request was dropped, e.g. after the server rejected it.
+            if sender.send(Ok(Bytes::from(buffer))).await.is_err() {
+                break;
+            }
+            sent += read as u64;
+            if let Some(progress) = &progress {
+                progress(&path, sent, size);
+            }
+        }
+    });
+    reqwest::Body::wrap(FileBody { chunks, remaining: size })
+}
+
+/// Classifies a WebDAV error answer by its status.
+async fn webdav_error(response: reqwest::Response, action: &str) -> anyhow::Error {
+    let status = response.status();
+    let retry_after = retry_after(&response);
+    let body = response.text().await.unwrap_or_default();
+    let message = format!("WebDAV {} failed: {} {}", action, status, body.trim());
+    match status {
+        reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
+            UploadError::AuthExpired(message)
+        }
+        reqwest::StatusCode::INSUFFICIENT_STORAGE => UploadError::QuotaExceeded(message),
+        reqwest::StatusCode::PAYLOAD_TOO_LARGE => UploadError::FileInvalid(message),
+        reqwest::StatusCode::TOO_MANY_REQUESTS | reqwest::StatusCode::SERVICE_UNAVAILABLE => {
+            UploadError::RateLimited { message, retry_after }
+        }
+        status if status.is_server_error() => UploadError::NetworkTransient(message),
+        _ => UploadError::Other(message),
+    }
+    .into()
+}
+
+/// First `getcontentlength` value of a PROPFIND answer, whatever namespace prefix the server
+/// uses; empty elements (properties the server does not know) are skipped.
+fn content_length(xml: &str) -> Option<u64> {
+    xml.match_indices("getcontentlength").find_map(|(index, _)| {
+        let rest = &xml[index..];
+        let start = rest.find('>')? + 1;
+        let end = rest[start..].find('<')? + start;
+        rest[start..end].trim().parse().ok()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Server, TemplateVars, UploadContext, UploadPart, Uploader, WebDavAccount, WebDavUploader,
+        content_length,
+    };
+    use shared::{UploadConfig, UploadTargetConfig};
+
+    fn account(base_url: &str) -> WebDavAccount {
+        WebDavAccount {
+            base_url: base_url.to_string(),
+            username: "admin".to_string(),
+            password: "secret".to_string(),
+        }
+    }
+
+    #[test]
+    fn urls_encode_segments_below_the_base_path() {
+        let server = Server::connect(&account("http://127.0.0.1:5244/dav/")).unwrap();
+        assert_eq!(
+            server.url(&["B站", "主播 A", "live#1.flv"], false).as_str(),
+            "http://127.0.0.1:5244/dav/B%E7%AB%99/%E4%B8%BB%E6%92%AD%20A/live%231.flv"
+        );
+        assert_eq!(server.url(&["a"], true).as_str(), "http://127.0.0.1:5244/dav/a/");
+        assert_eq!(server.url(&[], true).as_str(), "http://127.0.0.1:5244/dav/");
+        assert!(Server::connect(&account("ftp://example.com")).is_err());
+
+        let vars = TemplateVars { streamer: "主播".to_string(), ..Default::default() };
+        assert_eq!(
+            WebDavUploader::remote_dir("/rec/../{streamer}//", &vars).unwrap(),
+            vec!["rec", "主播"]
+        );
+    }
+
+    #[test]
+    fn reads_content_length_from_propfind_answers() {
+        let alist = r#"<?xml version="1.0" encoding="UTF-8"?><D:multistatus xmlns:D="DAV:"><D:response><D:href>/dav/a.flv</D:href><D:propstat><D:prop><D:getcontentlength>1048576</D:getcontentlength></D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response></D:multistatus>"#;
+        assert_eq!(content_length(alist), Some(1_048_576));
+        let collection = r#"<d:multistatus xmlns:d="DAV:"><d:response><d:propstat><d:prop><d:getcontentlength/></d:prop><d:status>HTTP/1.1 404 Not Found</d:status></d:propstat></d:response></d:multistatus>"#;
+        assert_eq!(content_length(collection), None);
+        let unprefixed = r#"<multistatus xmlns="DAV:"><response><propstat><prop><getcontentlength xmlns="DAV:"> 42 </getcontentlength></prop></propstat></response></multistatus>"#;
+        assert_eq!(content_length(unprefixed), Some(42));
+    }
+
+    /// Needs a local WebDAV server, e.g. `rclone serve webdav /tmp/dav --user admin --pass
+    /// secret --addr 127.0.0.1:8081`, reachable at `OMNISTREAM_WEBDAV_URL`.
+    #[tokio::test]
+    #[ignore]
+    async fn uploads_to_local_webdav_server() {
+        let base_url =
+            std::env::var("OMNISTREAM_WEBDAV_URL").unwrap_or("http://127.0.0.1:8081/".to_string());
+        let dir = std::env::temp_dir().join(format!("omnistream-webdav-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        let account_file = dir.join("webdav.json");
+        tokio::fs::write(&account_file, serde_json::to_vec(&account(&base_url)).unwrap())
+            .await
+            .unwrap();
+        let file = dir.join("live.flv");
+        tokio::fs::write(&file, vec![7u8; 3 * 1024 * 1024 + 17]).await.unwrap();
+        let config = UploadConfig {
+            account_file: account_file.display().to_string(),
+            target: UploadTargetConfig::WebDav {
+                remote_dir_template: "omnistream/{streamer}".to_string(),
+            },
+            ..Default::default()
+        };
+        let parts = vec![UploadPart { filename: file.display().to_string(), title: None }];
+
+        for _ in 0..2 {
+            let submission = WebDavUploader::new()
+                .upload(parts.clone(), &config, None, "主播", &UploadContext::default())
+                .await
+                .unwrap()
+                .unwrap();
+            assert!(submission.title.ends_with("/omnistream/%E4%B8%BB%E6%92%AD/"));
+        }
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+}
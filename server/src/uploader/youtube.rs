@@ -0,0 +1,841 @@
+//! Publishes recordings to YouTube with the resumable upload protocol of the Data API v3.
+//!
+//! A YouTube account of the account store holds an OAuth client and a refresh token with the
+//! `youtube.upload` scope; an access token is fetched for every upload and refreshed when the
+//! API answers 401. Every file is sent in chunks to an upload session whose URI is kept in a
+//! `<file>.youtube.json` sidecar, so an interrupted upload continues where the server stopped.
+//! Once a file is published the sidecar keeps its video id until every video of the upload is
+//! done, so a retry after a later file failed does not publish it twice.
+//! The API base URL is part of the account, which lets tests point it at a local mock server.
+
+use super::template::{self, TemplateVars};
+use super::upos::{chunk_body, retry_after};
+use super::{Submission, UploadContext, UploadError, UploadPart, Uploader};
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use shared::{UploadConfig, UploadTargetConfig, YouTubePrivacy};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio::process::Command;
+
+const SESSION_SUFFIX: &str = ".youtube.json";
+/// Chunks must be a multiple of 256 KiB, except the last one.
+const CHUNK_SIZE: u64 = 32 * 256 * 1024;
+/// Upload session URIs expire after a week; older sessions are not worth asking about.
+const SESSION_MAX_AGE_SECS: i64 = 6 * 24 * 60 * 60;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
+const MAX_TITLE_CHARS: usize = 100;
+const MAX_DESCRIPTION_BYTES: usize = 5000;
+const MAX_TAGS_CHARS: usize = 500;
+const FFMPEG_PATH: &str = "ffmpeg";
+
+pub struct YouTubeUploader;
+
+/// A YouTube account file of the account store.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct YouTubeAccount {
+    pub client_id: String,
+    pub client_secret: String,
+    pub refresh_token: String,
+    #[serde(default = "default_api_base")]
+    pub api_base: String,
+}
+
+fn default_api_base() -> String {
+    YouTubeAccount::DEFAULT_API_BASE.to_string()
+}
+
+/// Persisted upload session of one file, enough to continue it with a fresh process.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct YouTubeSession {
+    api_base: String,
+    session_uri: String,
+    file_size: u64,
+    modified_at: i64,
+    created_at: i64,
+    /// Id of the published video once the upload finished.
+    #[serde(default)]
+    video_id: Option<String>,
+}
+
+impl YouTubeSession {
+    /// The video this file was already published as, unless the file changed since.
+    fn published_video(&self, file_size: u64, modified_at: i64) -> Option<&str> {
+        self.video_id
+            .as_deref()
+            .filter(|_| self.file_size == file_size && self.modified_at == modified_at)
+    }
+
+    fn is_resumable(&self, api_base: &str, file_size: u64, modified_at: i64, now: i64) -> bool {
+        self.api_base == api_base
+            && self.file_size == file_size
+            && self.modified_at == modified_at
+            && now - self.created_at < SESSION_MAX_AGE_SECS
+    }
+}
+
+/// The upload session answered 404/410: it expired and the upload has to start over.
+#[derive(Debug)]
+struct SessionGone(String);
+
+impl fmt::Display for SessionGone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "upload session no longer exists: {}", self.0)
+    }
+}
+
+impl std::error::Error for SessionGone {}
+
+/// Where the server stands after a chunk: bytes it holds so far, or the finished video id.
+#[derive(Debug, PartialEq)]
+enum UploadStatus {
+    Incomplete(u64),
+    Complete(String),
+}
+
+/// The Data API with a current access token.
+struct Api {
+    http: reqwest::Client,
+    account: YouTubeAccount,
+    token: String,
+}
+
+impl YouTubeAccount {
+    pub const DEFAULT_API_BASE: &str = "https://www.googleapis.com";
+
+    pub async fn load(account_file: &str) -> Result<Self, UploadError> {
+        let raw = tokio::fs::read(account_file).await.map_err(|e| {
+            UploadError::Other(format!("failed to read account {}: {}", account_file, e))
+        })?;
+        serde_json::from_slice(&raw).map_err(|e| {
+            UploadError::Other(format!("{} is not a YouTube account: {}", account_file, e))
+        })
+    }
+
+    fn api_base(&self) -> &str {
+        self.api_base.trim().trim_end_matches('/')
+    }
+
+    /// Exchanges the refresh token for an access token; a revoked or expired refresh token
+    /// is reported as [`UploadError::AuthExpired`].
+    pub async fn access_token(&self) -> Result<String, UploadError> {
+        let body = url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("client_id", &self.client_id)
+            .append_pair("client_secret", &self.client_secret)
+            .append_pair("refresh_token", &self.refresh_token)
+            .append_pair("grant_type", "refresh_token")
+            .finish();
+        let response = reqwest::Client::new()
+            .post(format!("{}/oauth2/v4/token", self.api_base()))
+            .header(reqwest::header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .timeout(Duration::from_secs(30))
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| UploadError::from(anyhow::Error::from(e)))?;
+        let status = response.status();
+        let answer: Value = response.json().await.unwrap_or_default();
+        if let Some(token) = answer["access_token"].as_str().filter(|_| status.is_success()) {
+            return Ok(token.to_string());
+        }
+        let message = format!(
+            "YouTube token refresh failed: {} {} {}",
+            status,
+            answer["error"].as_str().unwrap_or_default(),
+            answer["error_description"].as_str().unwrap_or_default()
+        );
+        Err(match status {
+            reqwest::StatusCode::BAD_REQUEST | reqwest::StatusCode::UNAUTHORIZED => {
+                UploadError::AuthExpired(message)
+            }
+            status if status.is_server_error() => UploadError::NetworkTransient(message),
+            _ => UploadError::Other(message),
+        })
+    }
+}
+
+impl Api {
+    async fn connect(account: YouTubeAccount) -> Result<Self, UploadError> {
+        // 308 means "resume incomplete" here, not a redirect to follow.
+        let http = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|e| UploadError::Other(format!("failed to build HTTP client: {}", e)))?;
+        let token = account.access_token().await?;
+        Ok(Self { http, account, token })
+    }
+
+    /// Sends the request built by `build`, once more with a fresh token after a 401.
+    async fn send(
+        &mut self,
+        build: impl Fn(&reqwest::Client, &str) -> reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response> {
+        let response = build(&self.http, &self.token).send().await?;
+        if response.status() != reqwest::StatusCode::UNAUTHORIZED {
+            return Ok(response);
+        }
+        tracing::info!("YouTube access token expired, refreshing");
+        self.token = self.account.access_token().await?;
+        Ok(build(&self.http, &self.token).send().await?)
+    }
+
+    /// Opens an upload session for a `size`-byte video described by `resource`.
+    async fn start_session(&mut self, resource: &Value, size: u64) -> Result<String> {
+        let url = format!(
+            "{}/upload/youtube/v3/videos?uploadType=resumable&part=snippet,status",
+            self.account.api_base()
+        );
+        let response = self
+            .send(|http, token| {
+                http.post(&url)
+                    .bearer_auth(token)
+                    .header("X-Upload-Content-Length", size)
+                    .header("X-Upload-Content-Type", "video/*")
+                    .json(resource)
+            })
+            .await?;
+        if !response.status().is_success() {
+            return Err(youtube_error(response, "start upload session").await);
+        }
+        response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("YouTube returned no upload session URI"))
+    }
+
+    /// Asks the session how many bytes of the file it already holds.
+    async fn session_status(&mut self, session_uri: &str, size: u64) -> Result<UploadStatus> {
+        let range = format!("bytes */{size}");
+        let response = self
+            .send(|http, token| {
+                http.put(session_uri)
+                    .bearer_auth(token)
+                    .header(reqwest::header::CONTENT_RANGE, &range)
+                    .header(reqwest::header::CONTENT_LENGTH, 0)
+            })
+            .await?;
+        upload_status(response).await
+    }
+
+    /// Sends `path` from `offset` on until the server reports the finished video.
+    async fn send_file(
+        &mut self,
+        path: &Path,
+        session_uri: &str,
+        mut offset: u64,
+        size: u64,
+        context: &UploadContext,
+    ) -> Result<String> {
+        let mut file = tokio::fs::File::open(path).await?;
+        loop {
+            if let Some(progress) = &context.progress {
+                progress(path, offset, size);
+            }
+            let len = CHUNK_SIZE.min(size - offset);
+            file.seek(std::io::SeekFrom::Start(offset)).await?;
+            let mut buf = vec![0u8; usize::try_from(len)?];
+            file.read_exact(&mut buf).await?;
+            let range = format!("bytes {}-{}/{}", offset, offset + len - 1, size);
+            let response = self
+                .send(|http, token| {
+                    http.put(session_uri)
+                        .bearer_auth(token)
+                        .header(reqwest::header::CONTENT_RANGE, &range)
+                        .body(chunk_body(buf.clone(), &context.throttles))
+                })
+                .await?;
+            match upload_status(response).await? {
+                UploadStatus::Complete(id) => return Ok(id),
+                UploadStatus::Incomplete(next) if next > offset && next <= size => offset = next,
+                UploadStatus::Incomplete(next) => {
+                    return Err(UploadError::NetworkTransient(format!(
+                        "YouTube kept {} of {} bytes after a chunk starting at {}",
+                        next, size, offset
+                    ))
+                    .into());
+                }
+            }
+        }
+    }
+
+    /// Uploads `path` as the video `resource`, continuing a stored session when possible.
+    async fn upload_video(
+        &mut self,
+        path: &Path,
+        resource: &Value,
+        context: &UploadContext,
+    ) -> Result<String> {
+        let Ok(meta) = tokio::fs::metadata(path).await else {
+            return Err(
+                UploadError::FileInvalid(format!("File not found: {}", path.display())).into()
+            );
+        };
+        let size = meta.len();
+        if size == 0 {
+            return Err(
+                UploadError::FileInvalid(format!("File is empty: {}", path.display())).into()
+            );
+        }
+        let modified_at = meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map_or(0, |d| i64::try_from(d.as_secs()).unwrap_or_default());
+        let api_base = self.account.api_base().to_string();
+
+        let stored = load_session(path).await;
+        if let Some(id) = stored.as_ref().and_then(|s| s.published_video(size, modified_at)) {
+            tracing::info!("{} is already published as {}, skipping it", path.display(), id);
+            return Ok(id.to_string());
+        }
+        if let Some(session) = stored
+            && session.video_id.is_none()
+            && session.is_resumable(&api_base, size, modified_at, Utc::now().timestamp())
+        {
+            let resumed = match self.session_status(&session.session_uri, size).await {
+                Ok(UploadStatus::Complete(id)) => Ok(id),
+                Ok(UploadStatus::Incomplete(offset)) => {
+                    tracing::info!(
+                        "Resuming YouTube upload of {} at {}/{} bytes",
+                        path.display(),
+                        offset,
+                        size
+                    );
+                    self.send_file(path, &session.session_uri, offset, size, context).await
+                }
+                Err(e) => Err(e),
+            };
+            match resumed {
+                Ok(id) => {
+                    mark_published(path, session, &id).await;
+                    return Ok(id);
+                }
+                Err(e) if e.downcast_ref::<SessionGone>().is_some() => {
+                    tracing::warn!(
+                        "YouTube upload session expired, restarting {}: {}",
+                        path.display(),
+                        e
+                    );
+                    remove_session(path).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        let session_uri = self.start_session(resource, size).await?;
+        let session = YouTubeSession {
+            api_base,
+            session_uri,
+            file_size: size,
+            modified_at,
+            created_at: Utc::now().timestamp(),
+            video_id: None,
+        };
+        save_session(path, &session).await?;
+        let id = self.send_file(path, &session.session_uri, 0, size, context).await?;
+        mark_published(path, session, &id).await;
+        Ok(id)
+    }
+}
+
+impl YouTubeUploader {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Title, description and tags rendered for one video; separate videos of a multi-part
+    /// session get the part title template, else the video title with ` P<n>` appended.
+    fn video_text(
+        config: &UploadConfig,
+        vars: &TemplateVars,
+        part: Option<usize>,
+    ) -> Result<(String, String), UploadError> {
+        let render = |field: &str, text: &str, vars: &TemplateVars| {
+            template::render(text, vars)
+                .map_err(|e| UploadError::Other(format!("invalid {} template: {}", field, e)))
+        };
+        let rendered = match &config.title {
+            Some(title) => render("title", title, vars)?,
+            None => String::new(),
+        };
+        let mut title = Some(rendered)
+            .filter(|title| !title.trim().is_empty())
+            .or_else(|| vars.title.clone())
+            .unwrap_or_else(|| vars.streamer.clone());
+        if let Some(index) = part {
+            title = match &config.part_title {
+                Some(part_title) => render("part title", part_title, vars)?,
+                None => format!("{} P{}", title.trim(), index + 1),
+            };
+        }
+        let description = render("description", &config.description, vars)?;
+        Ok((clean_title(&title), clean_description(&description)))
+    }
+}
+
+#[async_trait]
+impl Uploader for YouTubeUploader {
+    async fn upload(
+        &self,
+        parts: Vec<UploadPart>,
+        config: &UploadConfig,
+        live_title: Option<&str>,
+        task_name: &str,
+        context: &UploadContext,
+    ) -> Result<Option<Submission>, UploadError> {
+        let UploadTargetConfig::YouTube { privacy, category_id, merge_parts } = &config.target
+        else {
+            return Err(UploadError::Other("upload config is not a YouTube target".to_string()));
+        };
+        // Segments recorded so far are published together once the session ends.
+        if parts.is_empty() || context.upload_only {
+            return Ok(None);
+        }
+        let mut vars = context.template_vars.clone();
+        vars.title = live_title.map(str::to_string);
+        if vars.streamer.is_empty() {
+            vars.streamer = task_name.to_string();
+        }
+
+        // One video per file, or a single one for the merged session.
+        let mut videos: Vec<(PathBuf, String, String)> = Vec::new();
+        let merged = if *merge_parts && parts.len() > 1 {
+            let files: Vec<&Path> = parts.iter().map(|part| Path::new(&part.filename)).collect();
+            let merged = merge_files(&files).await?;
+            let (title, description) = Self::video_text(config, &vars, None)?;
+            videos.push((merged.clone(), title, description));
+            Some(merged)
+        } else {
+            for (index, part) in parts.iter().enumerate() {
+                let part_vars = TemplateVars {
+                    part_index: Some(index + 1),
+                    segment_title: part.title.clone().filter(|t| !t.trim().is_empty()),
+                    ..vars.clone()
+                };
+                let part_index = (parts.len() > 1).then_some(index);
+                let (title, description) = Self::video_text(config, &part_vars, part_index)?;
+                videos.push((PathBuf::from(&part.filename), title, description));
+            }
+            None
+        };
+
+        let account = YouTubeAccount::load(&config.account_file).await?;
+        let mut api = Api::connect(account).await.map_err(|e| e.context("YouTube login failed"))?;
+        let mut published = Vec::with_capacity(videos.len());
+        for (path, title, description) in &videos {
+            let resource = video_resource(title, description, &config.tags, *privacy, category_id);
+            tracing::info!("Uploading {} to YouTube as {:?}", path.display(), title);
+            let id = api.upload_video(path, &resource, context).await.map_err(|e| {
+                UploadError::from(e).context(format!("Failed to upload {}", path.display()))
+            })?;
+            tracing::info!("YouTube video published: https://youtu.be/{}", id);
+            published.push(format!("{} https://youtu.be/{}", title, id));
+        }
+        for (path, ..) in &videos {
+            remove_session(path).await;
+        }
+        if let Some(merged) = merged
+            && let Err(e) = tokio::fs::remove_file(&merged).await
+        {
+            tracing::warn!("Failed to remove merged file {}: {}", merged.display(), e);
+        }
+        Ok(Some(Submission {
+            aid: None,
+            bvid: None,
+            title: published.join("; "),
+            appended: false,
+            collection_error: None,
+        }))
+    }
+}
+
+/// The `videos` resource sent when opening an upload session.
+fn video_resource(
+    title: &str,
+    description: &str,
+    tags: &[String],
+    privacy: YouTubePrivacy,
+    category_id: &str,
+) -> Value {
+    let privacy = match privacy {
+        YouTubePrivacy::Private => "private",
+        YouTubePrivacy::Unlisted => "unlisted",
+        YouTubePrivacy::Public => "public",
+    };
+    json!({
+        "snippet": {
+            "title": title,
+            "description": description,
+            "tags": clean_tags(tags),
+            "categoryId": category_id,
+        },
+        "status": {
+            "privacyStatus": privacy,
+            "selfDeclaredMadeForKids": false,
+        },
+    })
+}
+
+/// YouTube rejects `<` and `>` in titles, descriptions and tags.
+fn strip_angle_brackets(text: &str) -> String {
+    text.chars().filter(|c| !matches!(c, '<' | '>')).collect()
+}
+
+fn clean_title(raw: &str) -> String {
+    let title = strip_angle_brackets(raw);
+    let title = title.trim();
+    if title.is_empty() {
+        return "Uploaded by OmniStream".to_string();
+    }
+    title.chars().take(MAX_TITLE_CHARS).collect::<String>().trim_end().to_string()
+}
+
+fn clean_description(raw: &str) -> String {
+    let mut description = strip_angle_brackets(raw);
+    if description.len() > MAX_DESCRIPTION_BYTES {
+        let end = (0..=MAX_DESCRIPTION_BYTES)
+            .rev()
+            .find(|&end| description.is_char_boundary(end))
+            .unwrap_or_default();
+        description.truncate(end);
+    }
+    description
+}
+
+/// Tags in order while they fit the 500 character budget, where a tag with a space counts
+/// its quotes and every tag after the first a separating comma.
+fn clean_tags(tags: &[String]) -> Vec<String> {
+    let mut used = 0;
+    tags.iter()
+        .map(|tag| strip_angle_brackets(tag).trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .take_while(|tag| {
+            let quotes = if tag.contains(' ') { 2 } else { 0 };
+            used += tag.chars().count() + quotes + usize::from(used > 0);
+            used <= MAX_TAGS_CHARS
+        })
+        .collect()
+}
+
+/// Reads the answer to a chunk or status request.
+async fn upload_status(response: reqwest::Response) -> Result<UploadStatus> {
+    match response.status().as_u16() {
+        308 => {
+            // `Range: bytes=0-N` covers what was stored; no header means nothing yet.
+            let received = response
+                .headers()
+                .get(reqwest::header::RANGE)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.rsplit('-').next())
+                .and_then(|last| last.trim().parse::<u64>().ok())
+                .map_or(0, |last| last + 1);
+            Ok(UploadStatus::Incomplete(received))
+        }
+        200 | 201 => {
+            let video: Value = response.json().await?;
+            video["id"]
+                .as_str()
+                .map(|id| UploadStatus::Complete(id.to_string()))
+                .ok_or_else(|| anyhow!("YouTube returned no video id: {}", video))
+        }
+        404 | 410 => Err(SessionGone(response.url().to_string()).into()),
+        _ => Err(youtube_error(response, "upload chunk").await),
+    }
+}
+
+/// Classifies a Data API error by its status and the reason of its first error.
+async fn youtube_error(response: reqwest::Response, action: &str) -> anyhow::Error {
+    let status = response.status();
+    let retry_after = retry_after(&response);
+    let body = response.text().await.unwrap_or_default();
+    let answer: Value = serde_json::from_str(&body).unwrap_or_default();
+    let reason = answer["error"]["errors"][0]["reason"].as_str().unwrap_or_default();
+    let message = format!(
+        "YouTube {} failed: {} {} {}",
+        action,
+        status,
+        reason,
+        answer["error"]["message"].as_str().unwrap_or(body.trim())
+    );
+    match (status, reason) {
+        (_, "quotaExceeded" | "uploadLimitExceeded" | "dailyLimitExceeded") => {
+            UploadError::QuotaExceeded(message)
+        }
+        (reqwest::StatusCode::TOO_MANY_REQUESTS, _)
+        | (_, "rateLimitExceeded" | "userRateLimitExceeded") => {
+            UploadError::RateLimited { message, retry_after }
+        }
+        (reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN, _) => {
+            UploadError::AuthExpired(message)
+        }
+        // Invalid title, description, tags or category.
+        (reqwest::StatusCode::BAD_REQUEST, _) => UploadError::ContentRejected(message),
+        (status, _) if status.is_server_error() => UploadError::NetworkTransient(message),
+        _ => UploadError::Other(message),
+    }
+    .into()
+}
+
+/// Joins `files` with ffmpeg's concat demuxer into `<first>.merged.<ext>` next to the first
+/// file. A merged file left by an earlier attempt is reused, so its upload session resumes.
+async fn merge_files(files: &[&Path]) -> Result<PathBuf, UploadError> {
+    let first = files[0];
+    let stem = first.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let extension = first.extension().map(|e| e.to_string_lossy().into_owned()).unwrap_or_default();
+    let output = first.with_file_name(format!("{stem}.merged.{extension}"));
+    if tokio::fs::metadata(&output).await.is_ok() {
+        return Ok(output);
+    }
+
+    let mut list = String::new();
+    for file in files {
+        let path = std::path::absolute(file)?;
+        list.push_str(&format!("file '{}'\n", path.display().to_string().replace('\'', r"'\''")));
+    }
+    let list_path = first.with_file_name(format!("{stem}.merged.txt"));
+    let partial = first.with_file_name(format!("{stem}.merging.{extension}"));
+    tokio::fs::write(&list_path, list).await?;
+    tracing::info!("Merging {} files into {}", files.len(), output.display());
+    let result = Command::new(FFMPEG_PATH)
+        .args(["-hide_banner", "-loglevel", "error", "-y", "-f", "concat", "-safe", "0", "-i"])
+        .arg(&list_path)
+        .args(["-c", "copy"])
+        .arg(&partial)
+        .output()
+        .await;
+    let _ = tokio::fs::remove_file(&list_path).await;
+    let output_status =
+        result.map_err(|e| UploadError::Other(format!("failed to execute ffmpeg: {}", e)))?;
+    if !output_status.status.success() {
+        let _ = tokio::fs::remove_file(&partial).await;
+        return Err(UploadError::FileInvalid(format!(
+            "ffmpeg could not merge {} files: {}",
+            files.len(),
+            String::from_utf8_lossy(&output_status.stderr).trim()
+        )));
+    }
+    tokio::fs::rename(&partial, &output).await?;
+    Ok(output)
+}
+
+fn session_path(path: &Path) -> PathBuf {
+    let mut raw = path.as_os_str().to_os_string();
+    raw.push(SESSION_SUFFIX);
+    PathBuf::from(raw)
+}
+
+async fn load_session(path: &Path) -> Option<YouTubeSession> {
+    let raw = tokio::fs::read(session_path(path)).await.ok()?;
+    serde_json::from_slice(&raw)
+        .inspect_err(|e| {
+            tracing::warn!(
+                "Ignoring unreadable YouTube upload session for {}: {}",
+                path.display(),
+                e
+            )
+        })
+        .ok()
+}
+
+async fn save_session(path: &Path, session: &YouTubeSession) -> Result<()> {
+    let target = session_path(path);
+    let mut tmp = target.clone().into_os_string();
+    tmp.push(".tmp");
+    tokio::fs::write(&tmp, serde_json::to_vec(session)?).await?;
+    tokio::fs::rename(&tmp, &target).await?;
+    Ok(())
+}
+
+/// Keeps the video id beside the file until the whole upload is done.
+async fn mark_published(path: &Path, session: YouTubeSession, id: &str) {
+    let session = YouTubeSession { video_id: Some(id.to_string()), ..session };
+    if let Err(e) = save_session(path, &session).await {
+        tracing::warn!("Failed to record published YouTube video for {}: {}", path.display(), e);
+    }
+}
+
+async fn remove_session(path: &Path) {
+    if let Err(e) = tokio::fs::remove_file(session_path(path)).await
+        && e.kind() != std::io::ErrorKind::NotFound
+    {
+        tracing::warn!("Failed to remove YouTube upload session for {}: {}", path.display(), e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        TemplateVars, UploadContext, UploadPart, Uploader, YouTubeAccount, YouTubeUploader,
+        clean_description, clean_tags, clean_title,
+    };
+    use axum::{
+        Json, Router,
+        body::Bytes,
+        extract::{DefaultBodyLimit, State},
+        http::{HeaderMap, StatusCode, header},
+        response::IntoResponse,
+        routing::{post, put},
+    };
+    use serde_json::{Value, json};
+    use shared::{UploadConfig, UploadTargetConfig, YouTubePrivacy};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn renders_titles_for_separate_videos() {
+        let config = UploadConfig {
+            title: Some("{streamer} <直播>".to_string()),
+            description: "{title}".to_string(),
+            ..Default::default()
+        };
+        let vars = TemplateVars {
+            title: Some("开播".to_string()),
+            streamer: "主播".to_string(),
+            ..Default::default()
+        };
+        let (title, description) = YouTubeUploader::video_text(&config, &vars, None).unwrap();
+        assert_eq!(title, "主播 直播");
+        assert_eq!(description, "开播");
+        let (title, _) = YouTubeUploader::video_text(&config, &vars, Some(1)).unwrap();
+        assert_eq!(title, "主播 直播 P2");
+        let config = UploadConfig { part_title: Some("第{part_index}段".to_string()), ..config };
+        let vars = TemplateVars { part_index: Some(2), ..vars };
+        assert_eq!(YouTubeUploader::video_text(&config, &vars, Some(1)).unwrap().0, "第2段");
+
+        assert_eq!(clean_title(&"长".repeat(120)).chars().count(), 100);
+        assert_eq!(clean_title(" <> "), "Uploaded by OmniStream");
+        assert_eq!(clean_description(&"简".repeat(2000)).len(), 4998);
+        let tags: Vec<String> = (0..100).map(|i| format!("tag {i:02}")).collect();
+        let kept = clean_tags(&tags);
+        // "tag 00" costs 8 with its quotes, every further tag 9 with the comma.
+        assert_eq!(kept.len(), 55);
+        assert_eq!(kept[0], "tag 00");
+    }
+
+    /// Token, session and chunk endpoints of the Data API; the session stores at most
+    /// `accept` bytes of each chunk, as YouTube may do.
+    #[derive(Clone)]
+    struct MockYouTube {
+        base: String,
+        accept: usize,
+        received: Arc<Mutex<Vec<u8>>>,
+        resource: Arc<Mutex<Value>>,
+    }
+
+    async fn token() -> Json<Value> {
+        Json(json!({ "access_token": "token-1", "expires_in": 3600 }))
+    }
+
+    async fn start_session(
+        State(mock): State<MockYouTube>,
+        headers: HeaderMap,
+        Json(resource): Json<Value>,
+    ) -> impl IntoResponse {
+        assert_eq!(headers[header::AUTHORIZATION], "Bearer token-1");
+        *mock.resource.lock().unwrap() = resource;
+        (StatusCode::OK, [(header::LOCATION, format!("{}/upload/session/1", mock.base))])
+    }
+
+    async fn upload_chunk(
+        State(mock): State<MockYouTube>,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> axum::response::Response {
+        let range = headers[header::CONTENT_RANGE].to_str().unwrap().to_string();
+        let (span, total) = range.trim_start_matches("bytes ").split_once('/').unwrap();
+        let total: usize = total.parse().unwrap();
+        let mut received = mock.received.lock().unwrap();
+        if let Some((start, _)) = span.split_once('-') {
+            assert_eq!(start.parse::<usize>().unwrap(), received.len());
+            received.extend_from_slice(&body[..body.len().min(mock.accept)]);
+        }
+        if received.len() == total {
+            return Json(json!({ "id": "vid123" })).into_response();
+        }
+        let resume = StatusCode::from_u16(308).unwrap();
+        (resume, [(header::RANGE, format!("bytes=0-{}", received.len() - 1))]).into_response()
+    }
+
+    #[tokio::test]
+    async fn uploads_in_chunks_to_a_mock_api() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mock = MockYouTube {
+            base: format!("http://{}", listener.local_addr().unwrap()),
+            accept: 5 * 1024 * 1024,
+            received: Arc::default(),
+            resource: Arc::default(),
+        };
+        let app = Router::new()
+            .route("/oauth2/v4/token", post(token))
+            .route("/upload/youtube/v3/videos", post(start_session))
+            .route("/upload/session/1", put(upload_chunk))
+            .layer(DefaultBodyLimit::disable())
+            .with_state(mock.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let dir = std::env::temp_dir().join(format!("omnistream-youtube-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        let account = YouTubeAccount {
+            client_id: "client".to_string(),
+            client_secret: "secret".to_string(),
+            refresh_token: "refresh".to_string(),
+            api_base: format!("{}/", mock.base),
+        };
+        let account_file = dir.join("youtube.json");
+        tokio::fs::write(&account_file, serde_json::to_vec(&account).unwrap()).await.unwrap();
+        let data: Vec<u8> = (0..10 * 1024 * 1024 + 7).map(|i| (i % 251) as u8).collect();
+        let file = dir.join("live.flv");
+        tokio::fs::write(&file, &data).await.unwrap();
+        let config = UploadConfig {
+            title: Some("{streamer} 录播".to_string()),
+            tags: vec!["live".to_string()],
+            account_file: account_file.display().to_string(),
+            target: UploadTargetConfig::YouTube {
+                privacy: YouTubePrivacy::Unlisted,
+                category_id: "20".to_string(),
+                merge_parts: false,
+            },
+            ..Default::default()
+        };
+        let part = |path: &std::path::Path| UploadPart {
+            filename: path.display().to_string(),
+            title: None,
+        };
+
+        // The second file is missing: the first one is published and remembered.
+        let failed = YouTubeUploader::new()
+            .upload(
+                vec![part(&file), part(&dir.join("missing.flv"))],
+                &config,
+                None,
+                "主播",
+                &UploadContext::default(),
+            )
+            .await;
+        assert!(failed.is_err());
+        assert!(*mock.received.lock().unwrap() == data);
+        assert!(tokio::fs::metadata(dir.join("live.flv.youtube.json")).await.is_ok());
+
+        // The retry reuses the published video; sending the file again would be refused.
+        let submission = YouTubeUploader::new()
+            .upload(vec![part(&file)], &config, None, "主播", &UploadContext::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(submission.title, "主播 录播 https://youtu.be/vid123");
+        assert!(*mock.received.lock().unwrap() == data);
+        let resource = mock.resource.lock().unwrap().clone();
+        assert_eq!(resource["snippet"]["tags"], json!(["live"]));
+        assert_eq!(resource["status"]["privacyStatus"], "unlisted");
+        // The finished upload leaves no record behind.
+        assert!(tokio::fs::metadata(dir.join("live.flv.youtube.json")).await.is_err());
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+}
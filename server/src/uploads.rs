@@ -3,16 +3,25 @@ use axum::{
     extract::{Path, State},
     http::StatusCode,
 };
-use shared::UploadTemplate;
+use chrono::{Local, NaiveTime};
+use shared::{
+    CoverSource, MAX_PUBLISH_DELAY_HOURS, MIN_PUBLISH_DELAY_HOURS, PublishSchedule, UploadConfig,
+    UploadTargetConfig, UploadTemplate, UploadTemplatePreview,
+};
 use uuid::Uuid;
 
-use crate::{accounts::storage, state::SharedState};
+use crate::{
+    accounts::storage,
+    state::SharedState,
+    uploader::template::{self, TemplateVars},
+};
 
 const MAX_UPLOAD_TITLE_TEMPLATE_CHARS: usize = 80;
 const MAX_UPLOAD_DESCRIPTION_CHARS: usize = 2_000;
 const MAX_UPLOAD_DYNAMIC_CHARS: usize = 233;
 const MAX_UPLOAD_TAGS: usize = 12;
 const MAX_UPLOAD_TAG_CHARS: usize = 20;
+const MAX_MERGE_WINDOW_HOURS: u32 = 7 * 24;
 
 pub async fn list_uploads(
     State(state): State<SharedState>,
@@ -70,9 +79,72 @@ pub async fn save_upload_service(
     Ok(())
 }
 
+pub async fn preview_upload_template(
+    Json(config): Json<UploadConfig>,
+) -> Result<Json<UploadTemplatePreview>, (StatusCode, String)> {
+    preview_upload_template_service(&config).map(Json)
+}
+
+/// Renders the config's templates against a sample two-part session.
+pub fn preview_upload_template_service(
+    config: &UploadConfig,
+) -> Result<UploadTemplatePreview, (StatusCode, String)> {
+    let vars = sample_template_vars();
+    let render = |field: &str, text: &str, vars: &TemplateVars| {
+        template::render(text, vars)
+            .map_err(|e| (StatusCode::BAD_REQUEST, format!("{} template: {}", field, e)))
+    };
+    let part_titles = [Some("第一段直播标题"), None]
+        .into_iter()
+        .enumerate()
+        .map(|(index, segment_title)| {
+            let part_vars = TemplateVars {
+                part_index: Some(index + 1),
+                segment_title: segment_title.map(str::to_string),
+                ..vars.clone()
+            };
+            match &config.part_title {
+                Some(part_title) => render("part title", part_title, &part_vars),
+                None => Ok(segment_title.unwrap_or_default().to_string()),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(UploadTemplatePreview {
+        title: render("title", config.title.as_deref().unwrap_or_default(), &vars)?,
+        description: render("description", &config.description, &vars)?,
+        dynamic: render("dynamic", &config.dynamic, &vars)?,
+        part_titles,
+    })
+}
+
+fn sample_template_vars() -> TemplateVars {
+    let end_time = Local::now().timestamp();
+    TemplateVars {
+        title: Some("示例直播标题".to_string()),
+        streamer: "示例主播".to_string(),
+        platform: "B站".to_string(),
+        url: "https://live.bilibili.com/1".to_string(),
+        start_time: Some(end_time - 3 * 60 * 60),
+        end_time: Some(end_time),
+        part_index: None,
+        segment_title: None,
+    }
+}
+
 fn normalize_upload_template(template: &mut UploadTemplate) {
     template.name = template.name.trim().to_string();
     template.config.account_file = template.config.account_file.trim().to_string();
+    let mut backups: Vec<String> = Vec::new();
+    for account in &template.config.backup_accounts {
+        let account = account.trim();
+        if !account.is_empty()
+            && account != template.config.account_file
+            && !backups.iter().any(|known| known == account)
+        {
+            backups.push(account.to_string());
+        }
+    }
+    template.config.backup_accounts = backups;
     template.config.description = template.config.description.trim().to_string();
     template.config.dynamic = template.config.dynamic.trim().to_string();
     template.config.title = template
@@ -89,12 +161,110 @@ fn normalize_upload_template(template: &mut UploadTemplate) {
         .map(|tag| tag.trim().to_string())
         .filter(|tag| !tag.is_empty())
         .collect();
+    template.config.part_title = template
+        .config
+        .part_title
+        .as_deref()
+        .map(str::trim)
+        .filter(|title| !title.is_empty())
+        .map(str::to_string);
+    template.config.append_bvid = template
+        .config
+        .append_bvid
+        .as_deref()
+        .map(str::trim)
+        .filter(|bvid| !bvid.is_empty())
+        .map(str::to_string);
+    if let PublishSchedule::AtTime { time } = &mut template.config.publish_schedule {
+        *time = time.trim().to_string();
+    }
+    if let CoverSource::File { path } = &mut template.config.cover {
+        *path = path.trim().to_string();
+    }
+    match &mut template.config.target {
+        UploadTargetConfig::Bilibili => {}
+        UploadTargetConfig::LocalArchive { root_dir, path_template, .. } => {
+            *root_dir = root_dir.trim().to_string();
+            *path_template = path_template.trim().to_string();
+        }
+        UploadTargetConfig::S3 {
+            endpoint, region, bucket, key_template, storage_class, ..
+        } => {
+            for field in [endpoint, region, bucket, key_template] {
+                *field = field.trim().to_string();
+            }
+            *storage_class = storage_class.trim().to_ascii_uppercase();
+        }
+        UploadTargetConfig::WebDav { remote_dir_template } => {
+            *remote_dir_template = remote_dir_template.trim().to_string();
+        }
+        UploadTargetConfig::YouTube { category_id, .. } => {
+            *category_id = category_id.trim().to_string();
+        }
+    }
+    // Collections only exist on Bilibili.
+    if template.config.target == UploadTargetConfig::Bilibili {
+        if let Some(collection) = &mut template.config.collection {
+            collection.title = collection.title.trim().to_string();
+        }
+    } else {
+        template.config.collection = None;
+    }
+    // Filled in from the template itself whenever a task takes its configs.
+    template.config.template_id = None;
 }
 
 async fn validate_upload_template(template: &UploadTemplate) -> Result<(), String> {
     validate_upload_template_shape(template)?;
-    if !storage::account_file_exists(&template.config.account_file).await {
-        return Err(format!("account_file does not exist: {}", template.config.account_file));
+    if let UploadTargetConfig::LocalArchive { root_dir, .. } = &template.config.target {
+        if !tokio::fs::metadata(root_dir).await.is_ok_and(|m| m.is_dir()) {
+            return Err(format!("archive root_dir is not a directory: {}", root_dir));
+        }
+        return Ok(());
+    }
+    // Backups stand in for the primary account, so they have to be of the same kind.
+    for account_file in
+        std::iter::once(&template.config.account_file).chain(&template.config.backup_accounts)
+    {
+        validate_account(&template.config.target, account_file).await?;
+    }
+    if template.config.target == UploadTargetConfig::Bilibili
+        && let CoverSource::File { path } = &template.config.cover
+        && !tokio::fs::metadata(path).await.is_ok_and(|m| m.is_file())
+    {
+        return Err(format!("cover file does not exist: {}", path));
+    }
+    Ok(())
+}
+
+async fn validate_account(target: &UploadTargetConfig, account_file: &str) -> Result<(), String> {
+    match target {
+        UploadTargetConfig::WebDav { .. } => {
+            if !storage::is_webdav_account(account_file).await {
+                return Err(format!("account_file is not a WebDAV account: {}", account_file));
+            }
+        }
+        UploadTargetConfig::YouTube { .. } => {
+            if !storage::is_youtube_account(account_file).await {
+                return Err(format!("account_file is not a YouTube account: {}", account_file));
+            }
+        }
+        UploadTargetConfig::S3 { .. } => {
+            if !storage::is_s3_account(account_file).await {
+                return Err(format!("account_file is not an S3 account: {}", account_file));
+            }
+        }
+        _ => {
+            if !storage::account_file_exists(account_file).await {
+                return Err(format!("account_file does not exist: {}", account_file));
+            }
+            if storage::is_webdav_account(account_file).await
+                || storage::is_youtube_account(account_file).await
+                || storage::is_s3_account(account_file).await
+            {
+                return Err(format!("account_file is not a Bilibili login: {}", account_file));
+            }
+        }
     }
     Ok(())
 }
@@ -103,6 +273,28 @@ fn validate_upload_template_shape(template: &UploadTemplate) -> Result<(), Strin
     if template.name.is_empty() {
         return Err("upload template name is required".to_string());
     }
+    if template.config.target != UploadTargetConfig::Bilibili {
+        if matches!(
+            template.config.target,
+            UploadTargetConfig::WebDav { .. }
+                | UploadTargetConfig::YouTube { .. }
+                | UploadTargetConfig::S3 { .. }
+        ) && template.config.account_file.is_empty()
+        {
+            return Err("account_file is required".to_string());
+        }
+        // An archive has no account and a bucket's credentials do not run into upload limits.
+        if matches!(
+            template.config.target,
+            UploadTargetConfig::LocalArchive { .. } | UploadTargetConfig::S3 { .. }
+        ) && !template.config.backup_accounts.is_empty()
+        {
+            return Err("backup_accounts are not supported by this upload target".to_string());
+        }
+        // Only the storage location applies; the submission fields are ignored.
+        return validate_storage_target(&template.config.target)
+            .and_then(|()| validate_video_text(&template.config));
+    }
     if template.config.account_file.is_empty() {
         return Err("account_file is required".to_string());
     }
@@ -126,6 +318,19 @@ fn validate_upload_template_shape(template: &UploadTemplate) -> Result<(), Strin
     if template.config.dynamic.chars().count() > MAX_UPLOAD_DYNAMIC_CHARS {
         return Err(format!("dynamic exceeds {} characters", MAX_UPLOAD_DYNAMIC_CHARS));
     }
+    let vars = sample_template_vars();
+    for (field, text) in [
+        ("title", template.config.title.as_deref()),
+        ("description", Some(template.config.description.as_str())),
+        ("dynamic", Some(template.config.dynamic.as_str())),
+        ("part title", template.config.part_title.as_deref()),
+    ] {
+        if let Some(text) = text
+            && let Err(e) = template::render(text, &vars)
+        {
+            return Err(format!("{} template: {}", field, e));
+        }
+    }
     if template.config.tags.len() > MAX_UPLOAD_TAGS {
         return Err(format!("tags exceed maximum count {}", MAX_UPLOAD_TAGS));
     }
@@ -134,9 +339,130 @@ fn validate_upload_template_shape(template: &UploadTemplate) -> Result<(), Strin
     {
         return Err(format!("tag exceeds {} characters: {}", MAX_UPLOAD_TAG_CHARS, tag));
     }
+    if let Some(bvid) = &template.config.append_bvid
+        && !is_bvid(bvid)
+    {
+        return Err(format!("append_bvid is not a valid BV id: {}", bvid));
+    }
+    if template.config.merge_window_hours > MAX_MERGE_WINDOW_HOURS {
+        return Err(format!("merge_window_hours exceeds {} hours", MAX_MERGE_WINDOW_HOURS));
+    }
+    if let Some(collection) = &template.config.collection
+        && (collection.season_id == 0 || collection.section_id == 0)
+    {
+        return Err("collection requires a season and a section".to_string());
+    }
+    match &template.config.publish_schedule {
+        PublishSchedule::Immediate => {}
+        PublishSchedule::AtTime { time } => {
+            if NaiveTime::parse_from_str(time, "%H:%M").is_err() {
+                return Err(format!("publish time must be HH:MM: {}", time));
+            }
+        }
+        PublishSchedule::AfterStreamEnd { hours } => {
+            if !(MIN_PUBLISH_DELAY_HOURS..=MAX_PUBLISH_DELAY_HOURS).contains(hours) {
+                return Err(format!(
+                    "publish delay must be between {} and {} hours",
+                    MIN_PUBLISH_DELAY_HOURS, MAX_PUBLISH_DELAY_HOURS
+                ));
+            }
+        }
+    }
+    if let CoverSource::File { path } = &template.config.cover {
+        let extension = std::path::Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase);
+        if !matches!(extension.as_deref(), Some("jpg" | "jpeg" | "png")) {
+            return Err(format!("cover file must be a jpg or png image: {}", path));
+        }
+    }
+    Ok(())
+}
+
+fn validate_storage_target(target: &UploadTargetConfig) -> Result<(), String> {
+    match target {
+        UploadTargetConfig::Bilibili => Ok(()),
+        UploadTargetConfig::LocalArchive { root_dir, path_template, .. } => {
+            if root_dir.is_empty() {
+                return Err("archive root_dir is required".to_string());
+            }
+            if !std::path::Path::new(root_dir).is_absolute() {
+                return Err(format!("archive root_dir must be an absolute path: {}", root_dir));
+            }
+            template::render(path_template, &sample_template_vars())
+                .map(|_| ())
+                .map_err(|e| format!("archive path template: {}", e))
+        }
+        UploadTargetConfig::S3 {
+            endpoint, region, bucket, key_template, storage_class, ..
+        } => {
+            if !url::Url::parse(endpoint).is_ok_and(|url| {
+                matches!(url.scheme(), "http" | "https") && url.host_str().is_some()
+            }) {
+                return Err(format!("S3 endpoint must be an http(s) URL: {}", endpoint));
+            }
+            if !is_bucket_name(bucket) {
+                return Err(format!("invalid S3 bucket name: {}", bucket));
+            }
+            // An empty region produces a signing scope every request is refused with.
+            if region.is_empty() {
+                return Err("S3 region is required".to_string());
+            }
+            if !storage_class.chars().all(|c| c.is_ascii_uppercase() || c == '_') {
+                return Err(format!("invalid S3 storage class: {}", storage_class));
+            }
+            template::render(key_template, &sample_template_vars())
+                .map(|_| ())
+                .map_err(|e| format!("S3 key template: {}", e))
+        }
+        UploadTargetConfig::WebDav { remote_dir_template } => {
+            template::render(remote_dir_template, &sample_template_vars())
+                .map(|_| ())
+                .map_err(|e| format!("WebDAV directory template: {}", e))
+        }
+        UploadTargetConfig::YouTube { category_id, .. } => {
+            if category_id.is_empty() || !category_id.chars().all(|c| c.is_ascii_digit()) {
+                return Err(format!("YouTube category id must be numeric: {}", category_id));
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Title, description and part title templates of targets that publish videos elsewhere.
+fn validate_video_text(config: &UploadConfig) -> Result<(), String> {
+    if !matches!(config.target, UploadTargetConfig::YouTube { .. }) {
+        return Ok(());
+    }
+    let vars = sample_template_vars();
+    for (field, text) in [
+        ("title", config.title.as_deref()),
+        ("description", Some(config.description.as_str())),
+        ("part title", config.part_title.as_deref()),
+    ] {
+        if let Some(text) = text
+            && let Err(e) = template::render(text, &vars)
+        {
+            return Err(format!("{} template: {}", field, e));
+        }
+    }
     Ok(())
 }
 
+fn is_bucket_name(raw: &str) -> bool {
+    (3..=63).contains(&raw.len())
+        && raw.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '.')
+        && raw.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && raw.ends_with(|c: char| c.is_ascii_alphanumeric())
+}
+
+fn is_bvid(raw: &str) -> bool {
+    raw.len() == 12
+        && raw.get(..2).is_some_and(|prefix| prefix.eq_ignore_ascii_case("bv"))
+        && raw[2..].chars().all(|c| c.is_ascii_alphanumeric())
+}
+
 pub async fn delete_upload(Path(id): Path<String>, State(state): State<SharedState>) -> StatusCode {
     match delete_upload_service(&state, &id).await {
         Ok(()) => StatusCode::OK,
@@ -158,8 +484,13 @@ pub async fn delete_upload_service(
 
 #[cfg(test)]
 mod tests {
-    use super::{normalize_upload_template, validate_upload_template_shape};
-    use shared::{UploadConfig, UploadTemplate};
+    use super::{
+        normalize_upload_template, preview_upload_template_service, validate_upload_template_shape,
+    };
+    use shared::{
+        CoverSource, PublishSchedule, UploadCollection, UploadConfig, UploadTargetConfig,
+        UploadTemplate, YouTubePrivacy,
+    };
 
     fn valid_template() -> UploadTemplate {
         UploadTemplate {
@@ -176,6 +507,8 @@ mod tests {
         template.config.account_file = "  cookies.json  ".to_string();
         template.config.title = Some("  {title}  ".to_string());
         template.config.tags = vec!["  game  ".to_string(), " ".to_string()];
+        template.config.backup_accounts =
+            vec![" b.json ".to_string(), "cookies.json".to_string(), "b.json".to_string()];
 
         normalize_upload_template(&mut template);
 
@@ -183,6 +516,7 @@ mod tests {
         assert_eq!(template.config.account_file, "cookies.json");
         assert_eq!(template.config.title.as_deref(), Some("{title}"));
         assert_eq!(template.config.tags, vec!["game"]);
+        assert_eq!(template.config.backup_accounts, vec!["b.json"]);
     }
 
     #[test]
@@ -206,4 +540,185 @@ mod tests {
         template.config.tags = vec!["x".repeat(21)];
         assert!(validate_upload_template_shape(&template).is_err());
     }
+
+    #[test]
+    fn validate_upload_template_shape_checks_append_target() {
+        let mut template = valid_template();
+        template.config.append_bvid = Some("BV1xx411c7mD".to_string());
+        template.config.merge_window_hours = 12;
+        assert!(validate_upload_template_shape(&template).is_ok());
+
+        template.config.append_bvid = Some("av170001".to_string());
+        assert!(validate_upload_template_shape(&template).is_err());
+
+        let mut template = valid_template();
+        template.config.merge_window_hours = 24 * 8;
+        assert!(validate_upload_template_shape(&template).is_err());
+    }
+
+    #[test]
+    fn upload_template_collection_needs_a_section_on_bilibili() {
+        let mut template = valid_template();
+        template.config.collection =
+            Some(UploadCollection { season_id: 11, section_id: 0, title: String::new() });
+        assert!(validate_upload_template_shape(&template).is_err());
+
+        template.config.collection = Some(UploadCollection {
+            season_id: 11,
+            section_id: 101,
+            title: " 直播回放 / 正片 ".to_string(),
+        });
+        assert!(validate_upload_template_shape(&template).is_ok());
+        normalize_upload_template(&mut template);
+        assert_eq!(
+            template.config.collection.as_ref().map(|c| c.title.as_str()),
+            Some("直播回放 / 正片")
+        );
+
+        template.config.target =
+            UploadTargetConfig::WebDav { remote_dir_template: "{streamer}".to_string() };
+        normalize_upload_template(&mut template);
+        assert_eq!(template.config.collection, None);
+    }
+
+    #[test]
+    fn validate_upload_template_shape_checks_publish_schedule() {
+        let mut template = valid_template();
+        let mut check = |schedule| {
+            template.config.publish_schedule = schedule;
+            validate_upload_template_shape(&template).is_ok()
+        };
+        assert!(check(PublishSchedule::AtTime { time: "20:30".to_string() }));
+        assert!(!check(PublishSchedule::AtTime { time: "8pm".to_string() }));
+        assert!(check(PublishSchedule::AfterStreamEnd { hours: 2 }));
+        assert!(check(PublishSchedule::AfterStreamEnd { hours: 15 * 24 }));
+        assert!(!check(PublishSchedule::AfterStreamEnd { hours: 1 }));
+        assert!(!check(PublishSchedule::AfterStreamEnd { hours: 15 * 24 + 1 }));
+    }
+
+    #[test]
+    fn validate_upload_template_shape_requires_image_cover_file() {
+        let mut template = valid_template();
+        template.config.cover = CoverSource::File { path: "covers/up.PNG".to_string() };
+        assert!(validate_upload_template_shape(&template).is_ok());
+
+        template.config.cover = CoverSource::File { path: "covers/up.mp4".to_string() };
+        assert!(validate_upload_template_shape(&template).is_err());
+    }
+
+    #[test]
+    fn validate_upload_template_shape_checks_template_syntax() {
+        let mut template = valid_template();
+        template.config.title = Some("【{streamer}】{title} {start_time:%m-%d}".to_string());
+        template.config.part_title = Some("{if segment_title}{segment_title}{end}".to_string());
+        assert!(validate_upload_template_shape(&template).is_ok());
+
+        template.config.description = "{streamer".to_string();
+        assert!(validate_upload_template_shape(&template).is_err());
+
+        let mut template = valid_template();
+        template.config.part_title = Some("{if part_index}P{part_index}".to_string());
+        assert!(validate_upload_template_shape(&template).is_err());
+    }
+
+    #[test]
+    fn validate_upload_template_shape_checks_archive_location() {
+        let mut template = valid_template();
+        let mut check = |root_dir: &str, path_template: &str| {
+            template.config.target = UploadTargetConfig::LocalArchive {
+                root_dir: root_dir.to_string(),
+                path_template: path_template.to_string(),
+                move_files: false,
+            };
+            validate_upload_template_shape(&template).is_ok()
+        };
+        assert!(check("/mnt/nas", "{platform}/{streamer}/{date}"));
+        assert!(!check("", "{platform}"));
+        assert!(!check("nas", "{platform}"));
+        assert!(!check("/mnt/nas", "{platfrom}"));
+    }
+
+    #[test]
+    fn validate_upload_template_shape_checks_s3_bucket() {
+        let mut template = valid_template();
+        template.config.account_file = "data/cookies/s3_minioadmin.json".to_string();
+        let mut check = |endpoint: &str, region: &str, bucket: &str, storage_class: &str| {
+            template.config.target = UploadTargetConfig::S3 {
+                endpoint: endpoint.to_string(),
+                region: region.to_string(),
+                bucket: bucket.to_string(),
+                key_template: "{platform}/{streamer}/{date}".to_string(),
+                storage_class: storage_class.to_string(),
+                virtual_hosted_style: false,
+            };
+            validate_upload_template_shape(&template).is_ok()
+        };
+        let minio = "http://127.0.0.1:9000";
+        assert!(check(minio, "us-east-1", "live-archive", ""));
+        assert!(check(
+            "https://s3.us-east-1.amazonaws.com",
+            "us-east-1",
+            "live.archive",
+            "STANDARD_IA"
+        ));
+        assert!(!check("127.0.0.1:9000", "us-east-1", "live-archive", ""));
+        assert!(!check(minio, "", "live-archive", ""));
+        assert!(!check(minio, "us-east-1", "Live_Archive", ""));
+        assert!(!check(minio, "us-east-1", "live-archive", "cold storage"));
+        template.config.backup_accounts = vec!["data/cookies/s3_backup.json".to_string()];
+        assert!(validate_upload_template_shape(&template).is_err());
+        template.config.backup_accounts.clear();
+        template.config.account_file.clear();
+        assert!(validate_upload_template_shape(&template).is_err());
+    }
+
+    #[test]
+    fn validate_upload_template_shape_checks_webdav_target() {
+        let mut template = valid_template();
+        template.config.target = UploadTargetConfig::WebDav {
+            remote_dir_template: "录播/{streamer}/{date}".to_string(),
+        };
+        assert!(validate_upload_template_shape(&template).is_ok());
+        template.config.account_file.clear();
+        assert!(validate_upload_template_shape(&template).is_err());
+        template.config.account_file = "data/cookies/webdav_nas_admin.json".to_string();
+        template.config.target =
+            UploadTargetConfig::WebDav { remote_dir_template: "{streamr}".to_string() };
+        assert!(validate_upload_template_shape(&template).is_err());
+    }
+
+    #[test]
+    fn validate_upload_template_shape_checks_youtube_target() {
+        let mut template = valid_template();
+        template.config.target = UploadTargetConfig::YouTube {
+            privacy: YouTubePrivacy::Unlisted,
+            category_id: "20".to_string(),
+            merge_parts: true,
+        };
+        template.config.tid = 0;
+        assert!(validate_upload_template_shape(&template).is_ok());
+        template.config.part_title = Some("{part_idx}".to_string());
+        assert!(validate_upload_template_shape(&template).is_err());
+        template.config.part_title = None;
+        template.config.target = UploadTargetConfig::YouTube {
+            privacy: YouTubePrivacy::Public,
+            category_id: "gaming".to_string(),
+            merge_parts: false,
+        };
+        assert!(validate_upload_template_shape(&template).is_err());
+    }
+
+    #[test]
+    fn preview_renders_sample_session() {
+        let config = UploadConfig {
+            title: Some("{streamer} {title}".to_string()),
+            description: "来源 {platform} {url}".to_string(),
+            part_title: Some("P{part_index}{if segment_title} {segment_title}{end}".to_string()),
+            ..Default::default()
+        };
+        let preview = preview_upload_template_service(&config).expect("preview");
+        assert_eq!(preview.title, "示例主播 示例直播标题");
+        assert_eq!(preview.description, "来源 B站 https://live.bilibili.com/1");
+        assert_eq!(preview.part_titles, vec!["P1 第一段直播标题", "P2"]);
+    }
 }
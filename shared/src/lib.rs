@@ -2,12 +2,14 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum TaskStatus {
-    Idle,          // 空闲/未开始
-    Recording,     // 录制中
-    Uploading,     // 上传中
-    Stopped,       // 已手动停止
-    Completed,     // 已完成
-    Error(String), // 失败
+    Idle,                       // 空闲/未开始
+    Recording,                  // 录制中
+    Uploading,                  // 上传中
+    Stopped,                    // 已手动停止
+    Completed,                  // 已完成
+    Error(String),              // 失败
+    PendingUpload,              // 待上传（服务重启前已录制但未提交）
+    PartiallyCompleted(String), // 部分投稿配置失败，内容为失败原因
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -19,6 +21,267 @@ pub struct StreamTask {
     pub filename: String, // 保存的文件名
     #[serde(default)]
     pub upload_configs: Vec<UploadConfig>, // 任务运行时携带的多个上传配置
+    #[serde(default)]
+    pub segments: Vec<RecordingSegment>, // 已完成分段的录制质量指标
+    #[serde(default)]
+    pub upload_progress: Option<UploadProgress>, // 上传进度（实时计算，不落库）
+}
+
+// 单个文件的上传进度
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct FileUploadProgress {
+    pub filename: String,
+    pub bytes_sent: u64,
+    pub total_bytes: u64,
+    pub bytes_per_sec: u64,
+    pub eta_secs: Option<u64>,
+}
+
+// 任务整体上传进度：多个投稿配置依次上传，每个配置都会重新发送全部文件
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct UploadProgress {
+    pub config_index: usize, // 当前投稿配置序号，从 1 开始
+    pub config_count: usize,
+    pub files: Vec<FileUploadProgress>,
+    pub bytes_sent: u64,
+    pub total_bytes: u64,
+    pub bytes_per_sec: u64,
+    pub eta_secs: Option<u64>,
+}
+
+impl UploadProgress {
+    pub fn percent(&self) -> f64 {
+        if self.total_bytes == 0 {
+            return 0.0;
+        }
+        (self.bytes_sent as f64 / self.total_bytes as f64 * 100.0).clamp(0.0, 100.0)
+    }
+}
+
+// 单个录制分段的质量指标（ffprobe 探测结果，探测失败时仅保留时间与大小）
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct RecordingSegment {
+    pub filename: String,
+    #[serde(default)]
+    pub title: Option<String>, // 录制该分段时的直播标题
+    pub started_at: i64, // Unix 秒
+    pub ended_at: i64,   // Unix 秒
+    #[serde(default)]
+    pub size_bytes: u64,
+    #[serde(default)]
+    pub duration_sec: Option<f64>,
+    #[serde(default)]
+    pub bit_rate_kbps: Option<u64>,
+    #[serde(default)]
+    pub frame_rate: Option<f64>,
+    #[serde(default)]
+    pub width: Option<u32>,
+    #[serde(default)]
+    pub height: Option<u32>,
+    #[serde(default)]
+    pub video_codec: Option<String>,
+    #[serde(default)]
+    pub audio_codec: Option<String>,
+}
+
+impl RecordingSegment {
+    /// 形如 `1080p`，无视频流时返回 None
+    pub fn resolution_label(&self) -> Option<String> {
+        self.height.map(|h| format!("{h}p"))
+    }
+}
+
+// 上传队列中的任务状态
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UploadJobState {
+    #[default]
+    Queued, // 排队中
+    Uploading,          // 上传中
+    Retrying,           // 等待重试
+    Succeeded,          // 已成功
+    PartiallySucceeded, // 部分投稿配置成功，其余失败
+    Failed,             // 失败
+    Cancelled,          // 已取消
+}
+
+impl UploadJobState {
+    pub fn label(self) -> &'static str {
+        match self {
+            UploadJobState::Queued => "排队中",
+            UploadJobState::Uploading => "上传中",
+            UploadJobState::Retrying => "等待重试",
+            UploadJobState::Succeeded => "已成功",
+            UploadJobState::PartiallySucceeded => "部分成功",
+            UploadJobState::Failed => "失败",
+            UploadJobState::Cancelled => "已取消",
+        }
+    }
+
+    pub fn is_finished(self) -> bool {
+        matches!(
+            self,
+            UploadJobState::Succeeded
+                | UploadJobState::PartiallySucceeded
+                | UploadJobState::Failed
+                | UploadJobState::Cancelled
+        )
+    }
+}
+
+// 上传任务中单个投稿配置的状态，各配置独立上传、重试
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UploadConfigState {
+    #[default]
+    Pending, // 待上传
+    Retrying,  // 等待重试
+    Succeeded, // 已成功
+    Failed,    // 失败
+}
+
+impl UploadConfigState {
+    pub fn label(self) -> &'static str {
+        match self {
+            UploadConfigState::Pending => "待上传",
+            UploadConfigState::Retrying => "等待重试",
+            UploadConfigState::Succeeded => "已成功",
+            UploadConfigState::Failed => "失败",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct UploadConfigStatus {
+    pub state: UploadConfigState,
+    #[serde(default)]
+    pub attempts: u32,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    // 最近一次上传使用的账号，成功时即发布稿件的账号
+    #[serde(default)]
+    pub account_file: Option<String>,
+}
+
+// 上传任务类型：直播结束后的整场投稿，或录制中提前上传的单个分段
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UploadJobKind {
+    #[default]
+    Archive, // 投稿
+    Segment, // 分段预上传，不投稿
+}
+
+impl UploadJobKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            UploadJobKind::Archive => "投稿",
+            UploadJobKind::Segment => "分段预上传",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetUploadJobPriorityRequest {
+    pub priority: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct UploadJobPart {
+    pub filename: String,
+    #[serde(default)]
+    pub title: Option<String>, // 分P标题，来自录制该分段时的直播标题
+    #[serde(default)]
+    pub size: u64, // 入队时的文件大小（字节），预上传的分段文件之后可能已被清理
+}
+
+// 持久化的上传任务：一次录制会话按关联的上传配置依次投稿
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct UploadJob {
+    pub id: String,
+    pub task_id: String,
+    pub task_name: String,
+    #[serde(default)]
+    pub kind: UploadJobKind,
+    #[serde(default)]
+    pub live_title: Option<String>,
+    pub parts: Vec<UploadJobPart>,
+    pub configs: Vec<UploadConfig>,
+    #[serde(default)]
+    pub auto_cleanup_after_upload: bool,
+    pub state: UploadJobState,
+    #[serde(default)]
+    pub priority: i64, // 数值越大越先上传
+    #[serde(default)]
+    pub attempts: u32,
+    pub max_attempts: u32,
+    #[serde(default)]
+    pub completed_configs: usize, // 已投稿成功的配置数量
+    #[serde(default)]
+    pub config_statuses: Vec<UploadConfigStatus>, // 与 configs 一一对应，重试时跳过已成功的配置
+    #[serde(default)]
+    pub last_error: Option<String>,
+    pub next_attempt_at: i64, // Unix 秒
+    pub created_at: i64,      // Unix 秒
+    pub updated_at: i64,      // Unix 秒
+}
+
+// 上传模板预览：用示例直播数据渲染标题、简介、动态与分P标题
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct UploadTemplatePreview {
+    pub title: String,
+    pub description: String,
+    pub dynamic: String,
+    pub part_titles: Vec<String>,
+}
+
+// 投稿历史：每个上传任务的每个投稿配置一条，成功或最终失败时写入
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct UploadHistoryEntry {
+    pub id: String,
+    pub job_id: String,
+    pub task_id: String,
+    pub task_name: String,
+    #[serde(default)]
+    pub download_id: Option<String>, // 录制源对应的下载任务
+    #[serde(default)]
+    pub template_id: Option<String>,
+    pub account_file: String,
+    #[serde(default)]
+    pub aid: Option<u64>,
+    #[serde(default)]
+    pub bvid: Option<String>,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub appended: bool, // 追加到已有稿件
+    pub files: Vec<String>,
+    pub total_size: u64, // 字节
+    pub attempts: u32,
+    #[serde(default)]
+    pub error: Option<String>, // 为空表示投稿成功
+    #[serde(default)]
+    pub collection_error: Option<String>, // 已投稿但加入合集失败
+    pub queued_at: i64,   // Unix 秒
+    pub started_at: i64,  // Unix 秒
+    pub finished_at: i64, // Unix 秒
+}
+
+impl UploadHistoryEntry {
+    pub fn video_url(&self) -> Option<String> {
+        if let Some(bvid) = &self.bvid {
+            return Some(format!("https://www.bilibili.com/video/{bvid}"));
+        }
+        self.aid.map(|aid| format!("https://www.bilibili.com/video/av{aid}"))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct UploadHistoryPage {
+    pub entries: Vec<UploadHistoryEntry>,
+    pub total: u64,
+    pub page: u32, // 从 1 开始
+    pub page_size: u32,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -51,9 +314,191 @@ pub struct UploadConfig {
     pub description: String,
     #[serde(default)]
     pub dynamic: String,
+    // 分P标题模板，为空时使用录制该分段时的直播标题
+    #[serde(default)]
+    pub part_title: Option<String>,
     // 账号配置文件路径，默认为 cookies.json
     #[serde(default = "default_account_file")]
     pub account_file: String,
+    // 备用账号，与主账号同类型；登录失效或达到投稿上限时依次换用
+    #[serde(default)]
+    pub backup_accounts: Vec<String>,
+    // 主账号与备用账号的使用顺序
+    #[serde(default)]
+    pub account_strategy: AccountStrategy,
+    // 追加到已有稿件的 BV 号，填写后新分P总是追加到该稿件
+    #[serde(default)]
+    pub append_bvid: Option<String>,
+    // 合并窗口（小时）：同一录制源在窗口内已用此模板投稿时，新分P追加到该稿件，0 表示不合并
+    #[serde(default)]
+    pub merge_window_hours: u32,
+    // 来源上传模板 ID，由服务端生成任务的上传配置时填写
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_id: Option<String>,
+    // 定时发布，仅对新建稿件生效
+    #[serde(default)]
+    pub publish_schedule: PublishSchedule,
+    // 投稿后仅自己可见（B 站 is_only_self），人工确认后在创作中心公开；
+    // 稿件照常提交审核，并不是 B 站的草稿箱。旧配置中的 draft 字段兼容读取
+    #[serde(default, alias = "draft")]
+    pub only_self: bool,
+    // 封面来源，仅对新建稿件生效
+    #[serde(default)]
+    pub cover: CoverSource,
+    // 上传目标，B 站以外的目标忽略分区、版权、封面等投稿字段
+    #[serde(default)]
+    pub target: UploadTargetConfig,
+    // 投稿成功后加入主账号的合集小节，仅对新建稿件生效
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collection: Option<UploadCollection>,
+}
+
+// B 站合集（season）中的小节（section），新稿件加入该小节
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct UploadCollection {
+    pub season_id: u64,
+    pub section_id: u64,
+    #[serde(default)]
+    pub title: String, // 合集与小节名称，仅用于展示
+}
+
+// 多账号策略：主账号优先、轮流使用，或今日投稿最少的账号优先；出错时都会换用下一个账号
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountStrategy {
+    #[default]
+    PrimaryWithFallback,
+    RoundRobin,
+    LeastUsedToday,
+}
+
+impl AccountStrategy {
+    pub fn label(self) -> &'static str {
+        match self {
+            AccountStrategy::PrimaryWithFallback => "主账号优先",
+            AccountStrategy::RoundRobin => "轮流使用",
+            AccountStrategy::LeastUsedToday => "今日投稿最少优先",
+        }
+    }
+}
+
+fn default_archive_path_template() -> String {
+    "{platform}/{streamer}/{date}".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum UploadTargetConfig {
+    #[default]
+    Bilibili, // 投稿到 B 站
+    LocalArchive {
+        root_dir: String, // 归档根目录，可以是挂载的 NAS
+        #[serde(default = "default_archive_path_template")]
+        path_template: String, // 根目录下的子目录模板，与标题模板使用相同的占位符
+        #[serde(default)]
+        move_files: bool, // 移动而非复制；其他配置未全部成功时仍复制，保留原文件供其重试
+    },
+    // S3 兼容对象存储，访问密钥保存在 account_file 指向的账号中
+    S3 {
+        endpoint: String, // 如 http://127.0.0.1:9000（MinIO）或 https://s3.us-east-1.amazonaws.com
+        #[serde(default = "default_s3_region")]
+        region: String,
+        bucket: String,
+        #[serde(default = "default_archive_path_template")]
+        key_template: String, // 对象键前缀模板，文件名追加在后
+        #[serde(default)]
+        storage_class: String, // 如 STANDARD_IA、GLACIER，为空时使用存储桶默认值
+        #[serde(default)]
+        virtual_hosted_style: bool, // 存储桶名作为子域名；MinIO 等默认使用路径形式
+    },
+    // WebDAV / Alist，服务器地址与凭据保存在 account_file 指向的账号中
+    #[serde(rename = "webdav")]
+    WebDav {
+        #[serde(default = "default_archive_path_template")]
+        remote_dir_template: String, // 服务器根路径下的目录模板，文件名追加在后
+    },
+    // YouTube，使用 account_file 指向的 OAuth 账号；标题、简介、标签与分P标题沿用投稿模板
+    #[serde(rename = "youtube")]
+    YouTube {
+        #[serde(default)]
+        privacy: YouTubePrivacy,
+        #[serde(default = "default_youtube_category")]
+        category_id: String, // 视频类别 ID，如 20（游戏）、22（人物与博客）
+        #[serde(default)]
+        merge_parts: bool, // 多个分段先用 ffmpeg 合并为一个视频；否则每个分段单独发布
+    },
+}
+
+fn default_youtube_category() -> String {
+    "20".to_string()
+}
+
+// YouTube 视频的公开范围
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum YouTubePrivacy {
+    #[default]
+    Private,
+    Unlisted,
+    Public,
+}
+
+impl YouTubePrivacy {
+    pub fn label(self) -> &'static str {
+        match self {
+            YouTubePrivacy::Private => "私享",
+            YouTubePrivacy::Unlisted => "不公开列出",
+            YouTubePrivacy::Public => "公开",
+        }
+    }
+}
+
+fn default_s3_region() -> String {
+    "us-east-1".to_string()
+}
+
+impl UploadTargetConfig {
+    pub fn label(&self) -> &'static str {
+        match self {
+            UploadTargetConfig::Bilibili => "B站投稿",
+            UploadTargetConfig::LocalArchive { .. } => "本地归档",
+            UploadTargetConfig::S3 { .. } => "S3 对象存储",
+            UploadTargetConfig::WebDav { .. } => "WebDAV",
+            UploadTargetConfig::YouTube { .. } => "YouTube",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum CoverSource {
+    #[default]
+    Auto, // 不设置封面，由 B 站自动截取
+    Frame {
+        offset_secs: u32, // 第一个分P指定秒数处的画面
+    },
+    SharpestFrame, // 第一个分P中最清晰的画面
+    LiveRoom,      // 直播间封面
+    File {
+        path: String, // 服务器上的 jpg/png 文件
+    },
+}
+
+// B 站定时发布时间须在提交后 2 小时到 15 天之间
+pub const MIN_PUBLISH_DELAY_HOURS: u32 = 2;
+pub const MAX_PUBLISH_DELAY_HOURS: u32 = 15 * 24;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum PublishSchedule {
+    #[default]
+    Immediate,
+    AtTime {
+        time: String, // 本地时间 HH:MM，取满足最短延迟的最近一次
+    },
+    AfterStreamEnd {
+        hours: u32, // 直播结束后 N 小时
+    },
 }
 
 impl Default for UploadConfig {
@@ -65,7 +510,18 @@ impl Default for UploadConfig {
             copyright: default_copyright(),
             description: "".to_string(),
             dynamic: "".to_string(),
+            part_title: None,
             account_file: default_account_file(),
+            backup_accounts: vec![],
+            account_strategy: AccountStrategy::PrimaryWithFallback,
+            append_bvid: None,
+            merge_window_hours: 0,
+            template_id: None,
+            publish_schedule: PublishSchedule::Immediate,
+            only_self: false,
+            cover: CoverSource::Auto,
+            target: UploadTargetConfig::Bilibili,
+            collection: None,
         }
     }
 }
@@ -92,6 +548,53 @@ pub struct DownloadConfig {
     pub recording_settings: Option<RecordingSettings>, // 任务级录制设置
     #[serde(default)]
     pub recording_file_size_bytes: u64, // 当前任务本地录制文件占用空间（实时计算，不落库）
+    #[serde(default)]
+    pub latest_segment: Option<RecordingSegment>, // 最近完成分段的质量指标（实时计算，不落库）
+    #[serde(default)]
+    pub upload_progress: Option<UploadProgress>, // 正在上传的任务进度（实时计算，不落库）
+    #[serde(default)]
+    pub recorder_options: RecorderOptions, // 任务级录制器高级参数
+}
+
+// 录制后端：auto 按平台解析结果选择 ffmpeg 或 streamlink
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RecorderBackend {
+    #[default]
+    Auto,
+    Streamlink,
+    Ffmpeg,
+}
+
+impl RecorderBackend {
+    pub fn label(self) -> &'static str {
+        match self {
+            RecorderBackend::Auto => "自动",
+            RecorderBackend::Streamlink => "streamlink",
+            RecorderBackend::Ffmpeg => "ffmpeg",
+        }
+    }
+}
+
+// 下载任务的录制器高级设置
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct RecorderOptions {
+    #[serde(default)]
+    pub backend: RecorderBackend,
+    #[serde(default)]
+    pub extra_args: Vec<String>, // 追加给录制进程的参数，每项一个参数
+    #[serde(default)]
+    pub headers: Vec<String>, // 自定义 HTTP 请求头，格式 `Name: value`
+    #[serde(default)]
+    pub cookies: Option<String>, // `k1=v1; k2=v2`
+    #[serde(default)]
+    pub user_agent: Option<String>,
+}
+
+impl RecorderOptions {
+    pub fn is_default(&self) -> bool {
+        self == &Self::default()
+    }
 }
 
 impl Default for DownloadConfig {
@@ -106,6 +609,9 @@ impl Default for DownloadConfig {
             use_custom_recording_settings: false,
             recording_settings: None,
             recording_file_size_bytes: 0,
+            latest_segment: None,
+            upload_progress: None,
+            recorder_options: RecorderOptions::default(),
         }
     }
 }
@@ -135,6 +641,80 @@ pub struct UploadAccount {
     pub account_file: String,
     pub valid: bool,
     pub error: Option<String>,
+    #[serde(default)]
+    pub kind: AccountKind,
+}
+
+// 账号类型：B 站 Cookie 账号、WebDAV 存储账号、YouTube OAuth 账号或 S3 访问密钥
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountKind {
+    #[default]
+    Bilibili,
+    #[serde(rename = "webdav")]
+    WebDav,
+    #[serde(rename = "youtube")]
+    YouTube,
+    S3,
+}
+
+impl AccountKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            AccountKind::Bilibili => "B站",
+            AccountKind::WebDav => "WebDAV",
+            AccountKind::YouTube => "YouTube",
+            AccountKind::S3 => "S3",
+        }
+    }
+}
+
+// 添加 WebDAV / Alist 账号
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct WebDavAccountRequest {
+    pub name: String,
+    pub base_url: String, // 如 http://127.0.0.1:5244/dav
+    pub username: String,
+    pub password: String,
+}
+
+// 添加 S3 访问密钥，Endpoint 与存储桶在上传模板中填写
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct S3AccountRequest {
+    pub name: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+}
+
+// 查询账号的合集列表
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct CollectionListRequest {
+    pub account_file: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct CollectionSeason {
+    pub id: u64,
+    pub title: String,
+    #[serde(default)]
+    pub sections: Vec<CollectionSection>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct CollectionSection {
+    pub id: u64,
+    pub title: String,
+}
+
+// 添加 YouTube 账号：Google Cloud 项目的 OAuth 客户端与已授权 youtube.upload 的刷新令牌
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct YouTubeAccountRequest {
+    pub name: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub refresh_token: String,
+    #[serde(default)]
+    pub api_base: String, // 留空使用 https://www.googleapis.com，可指向本地模拟服务
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -211,6 +791,54 @@ pub fn default_min_upload_file_size_mb() -> u64 {
     DEFAULT_MIN_UPLOAD_FILE_SIZE_MB
 }
 
+pub const DEFAULT_STALL_TIMEOUT_SEC: u64 = 60;
+
+pub fn default_stall_timeout_sec() -> u64 {
+    DEFAULT_STALL_TIMEOUT_SEC
+}
+
+pub fn default_auto_resume_uploads() -> bool {
+    true
+}
+
+pub const DEFAULT_UPLOAD_CONCURRENCY: u32 = 2;
+
+pub fn default_upload_concurrency() -> u32 {
+    DEFAULT_UPLOAD_CONCURRENCY
+}
+
+// B 站 UPOS 上传线路（upcdn 名称），`auto` 表示每个文件上传前自动探测
+pub const UPLOAD_LINE_AUTO: &str = "auto";
+pub const UPLOAD_LINES: &[&str] = &["bda2", "ws", "qn", "bldsa", "tx", "txa", "bda", "alia"];
+pub const DEFAULT_UPLOAD_THREADS: u32 = 3;
+
+pub fn default_upload_line() -> String {
+    UPLOAD_LINE_AUTO.to_string()
+}
+
+pub fn default_upload_threads() -> u32 {
+    DEFAULT_UPLOAD_THREADS
+}
+
+pub const DEFAULT_UPLOAD_MAX_ATTEMPTS: u32 = 3;
+
+pub fn default_upload_max_attempts() -> u32 {
+    DEFAULT_UPLOAD_MAX_ATTEMPTS
+}
+
+pub fn default_upload_retry_backoff_secs() -> Vec<u64> {
+    vec![30, 120]
+}
+
+// 上传线路测速结果
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct UploadLineBenchmark {
+    pub line: String,
+    pub latency_ms: Option<u64>,
+    pub throughput_kbps: Option<u64>,
+    pub error: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct RecordingSettings {
     #[serde(default)]
@@ -223,6 +851,76 @@ pub struct RecordingSettings {
     pub auto_cleanup_after_upload: bool,
     #[serde(default = "default_min_upload_file_size_mb")]
     pub min_upload_file_size_mb: u64,
+    // 录制文件连续多少秒没有增长视为卡死并重启分段，0 表示不检测
+    #[serde(default = "default_stall_timeout_sec")]
+    pub stall_timeout_sec: u64,
+    // 仅录制音频：优先平台纯音频流，否则由 ffmpeg 去除视频，输出 m4a
+    #[serde(default)]
+    pub audio_only: bool,
+    // 服务重启后自动继续上传未提交的录制文件，关闭时在界面上手动继续
+    #[serde(default = "default_auto_resume_uploads")]
+    pub auto_resume_uploads: bool,
+    // 上传队列同时处理的任务数，仅使用全局设置
+    #[serde(default = "default_upload_concurrency")]
+    pub upload_concurrency: u32,
+    // 上传限速与限速时段，仅使用全局设置
+    #[serde(default)]
+    pub upload_bandwidth: UploadBandwidthSettings,
+    // 固定上传线路或 auto，仅使用全局设置
+    #[serde(default = "default_upload_line")]
+    pub upload_line: String,
+    // 单个文件同时上传的分块数，仅使用全局设置
+    #[serde(default = "default_upload_threads")]
+    pub upload_threads: u32,
+    // 边录边传：每个分段录制结束后立即上传，直播结束后按顺序投稿
+    #[serde(default)]
+    pub upload_while_recording: bool,
+    // 上传失败重试策略，仅使用全局设置
+    #[serde(default)]
+    pub upload_retry: UploadRetrySettings,
+}
+
+// 上传失败重试：只有网络临时错误与限流会重试，登录失效、稿件被拒等直接失败
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UploadRetrySettings {
+    #[serde(default = "default_upload_max_attempts")]
+    pub max_attempts: u32, // 每个投稿配置最多尝试的次数，含首次
+    #[serde(default = "default_upload_retry_backoff_secs")]
+    pub backoff_secs: Vec<u64>, // 第 N 次失败后等待第 N 项秒数，超出后沿用最后一项；限流要求更久时以限流为准
+}
+
+impl Default for UploadRetrySettings {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_upload_max_attempts(),
+            backoff_secs: default_upload_retry_backoff_secs(),
+        }
+    }
+}
+
+// 上传带宽限制，单位 KB/s，0 表示不限速
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct UploadBandwidthSettings {
+    #[serde(default)]
+    pub limit_kbps: u64, // 所有上传共享的全局限速
+    #[serde(default)]
+    pub schedule: Vec<UploadLimitRule>, // 命中的时段覆盖全局限速
+    #[serde(default)]
+    pub account_limits: Vec<AccountUploadLimit>, // 单个账号的上传限速，与全局限速同时生效
+}
+
+// 限速时段：本地时间 `HH:MM`，包含 start 不含 end；start 晚于 end 表示跨过午夜
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct UploadLimitRule {
+    pub start: String,
+    pub end: String,
+    pub limit_kbps: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct AccountUploadLimit {
+    pub account_file: String,
+    pub limit_kbps: u64,
 }
 
 impl Default for RecordingSettings {
@@ -233,6 +931,15 @@ impl Default for RecordingSettings {
             quality: PlatformQualityConfig::default(),
             auto_cleanup_after_upload: false,
             min_upload_file_size_mb: default_min_upload_file_size_mb(),
+            stall_timeout_sec: default_stall_timeout_sec(),
+            audio_only: false,
+            auto_resume_uploads: default_auto_resume_uploads(),
+            upload_concurrency: default_upload_concurrency(),
+            upload_bandwidth: UploadBandwidthSettings::default(),
+            upload_line: default_upload_line(),
+            upload_threads: default_upload_threads(),
+            upload_while_recording: false,
+            upload_retry: UploadRetrySettings::default(),
         }
     }
 }
@@ -244,7 +951,7 @@ pub trait StreamChecker: Send + Sync {
 
 #[cfg(test)]
 mod tests {
-    use super::{DownloadConfig, RecordingSettings, TaskStatus, UploadConfig};
+    use super::{DownloadConfig, RecordingSettings, TaskStatus, UploadConfig, UploadHistoryEntry};
 
     #[test]
     fn upload_config_default_values_are_stable() {
@@ -258,6 +965,18 @@ mod tests {
         assert_eq!(config.account_file, "cookies.json");
     }
 
+    #[test]
+    fn upload_history_links_bvid_before_aid() {
+        let mut entry = UploadHistoryEntry { aid: Some(170001), ..Default::default() };
+        assert_eq!(entry.video_url().as_deref(), Some("https://www.bilibili.com/video/av170001"));
+        entry.bvid = Some("BV17x411w7KC".to_string());
+        assert_eq!(
+            entry.video_url().as_deref(),
+            Some("https://www.bilibili.com/video/BV17x411w7KC")
+        );
+        assert_eq!(UploadHistoryEntry::default().video_url(), None);
+    }
+
     #[test]
     fn upload_config_deserialize_uses_defaults() {
         let json = "{}";
@@ -265,6 +984,13 @@ mod tests {
         assert_eq!(config, UploadConfig::default());
     }
 
+    #[test]
+    fn upload_config_reads_legacy_draft_as_only_self() {
+        let config: UploadConfig =
+            serde_json::from_str(r#"{"draft":true}"#).expect("valid upload config json");
+        assert!(config.only_self);
+    }
+
     #[test]
     fn platform_quality_config_deserialize_defaults_kick_quality() {
         let json = r#"{
@@ -305,6 +1031,11 @@ mod tests {
             serde_json::from_str(json).expect("valid recording settings json");
 
         assert_eq!(settings.min_upload_file_size_mb, 5);
+        assert_eq!(settings.stall_timeout_sec, 60);
+        assert!(settings.auto_resume_uploads);
+        assert_eq!(settings.upload_concurrency, 2);
+        assert_eq!(settings.upload_line, "auto");
+        assert_eq!(settings.upload_threads, 3);
     }
 
     #[test]
@@ -321,5 +1052,22 @@ mod tests {
         let json = r#"{"id":"d1","name":"demo","url":"https://example.com"}"#;
         let config: DownloadConfig = serde_json::from_str(json).expect("valid download config");
         assert!(config.enabled);
+        assert!(config.recorder_options.is_default());
+    }
+
+    #[test]
+    fn recorder_backend_serializes_as_snake_case() {
+        let json = serde_json::to_string(&super::RecorderBackend::Streamlink)
+            .expect("serialize recorder backend");
+        assert_eq!(json, "\"streamlink\"");
+    }
+
+    #[test]
+    fn upload_job_state_serializes_as_snake_case() {
+        let json = serde_json::to_string(&super::UploadJobState::Retrying)
+            .expect("serialize upload job state");
+        assert_eq!(json, "\"retrying\"");
+        assert!(super::UploadJobState::Cancelled.is_finished());
+        assert!(!super::UploadJobState::Retrying.is_finished());
     }
 }
@@ -1,6 +1,9 @@
 use dioxus::prelude::*;
 use shared::{
-    DownloadConfig, QrStartResponse, RecordingSettings, StorageStats, UploadAccount, UploadTemplate,
+    CollectionSeason, DownloadConfig, QrStartResponse, RecordingSettings, S3AccountRequest,
+    StorageStats, StreamTask, UploadAccount, UploadConfig, UploadHistoryPage, UploadJob,
+    UploadLineBenchmark, UploadTemplate, UploadTemplatePreview, WebDavAccountRequest,
+    YouTubeAccountRequest,
 };
 
 #[cfg(feature = "server")]
@@ -27,10 +30,34 @@ pub trait BackendApi: Send + Sync {
         display_name: String,
     ) -> Result<(), String>;
     async fn delete_account(&self, account_file: String) -> Result<(), String>;
+    async fn add_webdav_account(&self, payload: WebDavAccountRequest) -> Result<(), String>;
+    async fn add_youtube_account(&self, payload: YouTubeAccountRequest) -> Result<(), String>;
+    async fn add_s3_account(&self, payload: S3AccountRequest) -> Result<(), String>;
+    async fn list_account_collections(
+        &self,
+        account_file: String,
+    ) -> Result<Vec<CollectionSeason>, String>;
     async fn fetch_recording_settings(&self) -> Result<RecordingSettings, String>;
     async fn fetch_storage_stats(&self) -> Result<StorageStats, String>;
     async fn save_recording_settings(&self, settings: RecordingSettings) -> Result<(), String>;
     async fn trigger_manual_upload(&self, id: String) -> Result<String, String>;
+    async fn resume_pending_uploads(&self, id: String) -> Result<String, String>;
+    async fn fetch_upload_jobs(&self) -> Result<Vec<UploadJob>, String>;
+    async fn retry_upload_job(&self, id: String) -> Result<String, String>;
+    async fn retry_upload_config(&self, id: String, index: usize) -> Result<String, String>;
+    async fn cancel_upload_job(&self, id: String) -> Result<String, String>;
+    async fn set_upload_job_priority(&self, id: String, priority: i64) -> Result<String, String>;
+    async fn fetch_tasks(&self) -> Result<Vec<StreamTask>, String>;
+    async fn benchmark_upload_lines(&self) -> Result<Vec<UploadLineBenchmark>, String>;
+    async fn fetch_upload_history(
+        &self,
+        page: u32,
+        page_size: u32,
+    ) -> Result<UploadHistoryPage, String>;
+    async fn preview_upload_template(
+        &self,
+        config: UploadConfig,
+    ) -> Result<UploadTemplatePreview, String>;
 }
 
 #[cfg(feature = "server")]
@@ -121,6 +148,28 @@ async fn server_delete_account(account_file: String) -> ServerFnResult<()> {
     backend().cloned()?.delete_account(account_file).await.map_err(server_error)
 }
 
+#[server]
+async fn server_add_webdav_account(payload: WebDavAccountRequest) -> ServerFnResult<()> {
+    backend().cloned()?.add_webdav_account(payload).await.map_err(server_error)
+}
+
+#[server]
+async fn server_add_youtube_account(payload: YouTubeAccountRequest) -> ServerFnResult<()> {
+    backend().cloned()?.add_youtube_account(payload).await.map_err(server_error)
+}
+
+#[server]
+async fn server_add_s3_account(payload: S3AccountRequest) -> ServerFnResult<()> {
+    backend().cloned()?.add_s3_account(payload).await.map_err(server_error)
+}
+
+#[server]
+async fn server_list_account_collections(
+    account_file: String,
+) -> ServerFnResult<Vec<CollectionSeason>> {
+    backend().cloned()?.list_account_collections(account_file).await.map_err(server_error)
+}
+
 #[server]
 async fn server_fetch_recording_settings() -> ServerFnResult<RecordingSettings> {
     backend().cloned()?.fetch_recording_settings().await.map_err(server_error)
@@ -141,6 +190,61 @@ async fn server_trigger_manual_upload(id: String) -> ServerFnResult<String> {
     backend().cloned()?.trigger_manual_upload(id).await.map_err(server_error)
 }
 
+#[server]
+async fn server_resume_pending_uploads(id: String) -> ServerFnResult<String> {
+    backend().cloned()?.resume_pending_uploads(id).await.map_err(server_error)
+}
+
+#[server]
+async fn server_fetch_upload_jobs() -> ServerFnResult<Vec<UploadJob>> {
+    backend().cloned()?.fetch_upload_jobs().await.map_err(server_error)
+}
+
+#[server]
+async fn server_retry_upload_job(id: String) -> ServerFnResult<String> {
+    backend().cloned()?.retry_upload_job(id).await.map_err(server_error)
+}
+
+#[server]
+async fn server_retry_upload_config(id: String, index: usize) -> ServerFnResult<String> {
+    backend().cloned()?.retry_upload_config(id, index).await.map_err(server_error)
+}
+
+#[server]
+async fn server_cancel_upload_job(id: String) -> ServerFnResult<String> {
+    backend().cloned()?.cancel_upload_job(id).await.map_err(server_error)
+}
+
+#[server]
+async fn server_set_upload_job_priority(id: String, priority: i64) -> ServerFnResult<String> {
+    backend().cloned()?.set_upload_job_priority(id, priority).await.map_err(server_error)
+}
+
+#[server]
+async fn server_fetch_tasks() -> ServerFnResult<Vec<StreamTask>> {
+    backend().cloned()?.fetch_tasks().await.map_err(server_error)
+}
+
+#[server]
+async fn server_benchmark_upload_lines() -> ServerFnResult<Vec<UploadLineBenchmark>> {
+    backend().cloned()?.benchmark_upload_lines().await.map_err(server_error)
+}
+
+#[server]
+async fn server_fetch_upload_history(
+    page: u32,
+    page_size: u32,
+) -> ServerFnResult<UploadHistoryPage> {
+    backend().cloned()?.fetch_upload_history(page, page_size).await.map_err(server_error)
+}
+
+#[server]
+async fn server_preview_upload_template(
+    config: UploadConfig,
+) -> ServerFnResult<UploadTemplatePreview> {
+    backend().cloned()?.preview_upload_template(config).await.map_err(server_error)
+}
+
 pub async fn fetch_downloads(_api_url: &str) -> Option<Vec<DownloadConfig>> {
     server_fetch_downloads().await.ok()
 }
@@ -197,6 +301,31 @@ pub async fn delete_account(_api_url: &str, account_file: String) {
     let _ = server_delete_account(account_file).await;
 }
 
+pub async fn add_webdav_account(
+    _api_url: &str,
+    payload: &WebDavAccountRequest,
+) -> Result<(), String> {
+    server_add_webdav_account(payload.clone()).await.map_err(|e| e.to_string())
+}
+
+pub async fn add_youtube_account(
+    _api_url: &str,
+    payload: &YouTubeAccountRequest,
+) -> Result<(), String> {
+    server_add_youtube_account(payload.clone()).await.map_err(|e| e.to_string())
+}
+
+pub async fn add_s3_account(_api_url: &str, payload: &S3AccountRequest) -> Result<(), String> {
+    server_add_s3_account(payload.clone()).await.map_err(|e| e.to_string())
+}
+
+pub async fn list_account_collections(
+    _api_url: &str,
+    account_file: String,
+) -> Result<Vec<CollectionSeason>, String> {
+    server_list_account_collections(account_file).await.map_err(|e| e.to_string())
+}
+
 pub async fn fetch_recording_settings(_api_url: &str) -> Option<RecordingSettings> {
     server_fetch_recording_settings().await.ok()
 }
@@ -215,3 +344,54 @@ pub async fn save_recording_settings(
 pub async fn trigger_manual_upload(_api_url: &str, id: &str) -> Result<String, String> {
     server_trigger_manual_upload(id.to_string()).await.map_err(|e| e.to_string())
 }
+
+pub async fn resume_pending_uploads(_api_url: &str, id: &str) -> Result<String, String> {
+    server_resume_pending_uploads(id.to_string()).await.map_err(|e| e.to_string())
+}
+
+pub async fn fetch_upload_jobs(_api_url: &str) -> Option<Vec<UploadJob>> {
+    server_fetch_upload_jobs().await.ok()
+}
+
+pub async fn retry_upload_job(_api_url: &str, id: &str) -> Result<String, String> {
+    server_retry_upload_job(id.to_string()).await.map_err(|e| e.to_string())
+}
+
+pub async fn retry_upload_config(_api_url: &str, id: &str, index: usize) -> Result<String, String> {
+    server_retry_upload_config(id.to_string(), index).await.map_err(|e| e.to_string())
+}
+
+pub async fn cancel_upload_job(_api_url: &str, id: &str) -> Result<String, String> {
+    server_cancel_upload_job(id.to_string()).await.map_err(|e| e.to_string())
+}
+
+pub async fn set_upload_job_priority(
+    _api_url: &str,
+    id: &str,
+    priority: i64,
+) -> Result<String, String> {
+    server_set_upload_job_priority(id.to_string(), priority).await.map_err(|e| e.to_string())
+}
+
+pub async fn fetch_tasks(_api_url: &str) -> Option<Vec<StreamTask>> {
+    server_fetch_tasks().await.ok()
+}
+
+pub async fn benchmark_upload_lines(_api_url: &str) -> Result<Vec<UploadLineBenchmark>, String> {
+    server_benchmark_upload_lines().await.map_err(|e| e.to_string())
+}
+
+pub async fn fetch_upload_history(
+    _api_url: &str,
+    page: u32,
+    page_size: u32,
+) -> Option<UploadHistoryPage> {
+    server_fetch_upload_history(page, page_size).await.ok()
+}
+
+pub async fn preview_upload_template(
+    _api_url: &str,
+    config: &UploadConfig,
+) -> Result<UploadTemplatePreview, String> {
+    server_preview_upload_template(config.clone()).await.map_err(|e| e.to_string())
+}
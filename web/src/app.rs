@@ -1,15 +1,21 @@
 use dioxus::prelude::*;
-use shared::{DownloadConfig, UploadTemplate};
+use shared::{
+    CollectionSeason, DownloadConfig, UploadHistoryPage, UploadLineBenchmark, UploadTemplate,
+    UploadTemplatePreview,
+};
 
 use crate::api;
 use crate::components::{
-    AccountsPage, DownloadModal, DownloadsPage, SettingsPage, TabItem, UploadModal, UploadsPage,
+    AccountsPage, DownloadModal, DownloadsPage, HistoryPage, SettingsPage, TabItem, UploadModal,
+    UploadQueuePage, UploadsPage,
 };
 use crate::models::{AppData, QrStartResponse, Tab};
 #[cfg(target_arch = "wasm32")]
 use crate::sleep_ms;
 use crate::styles::theme_css;
 
+const HISTORY_PAGE_SIZE: u32 = 20;
+
 pub fn App() -> Element {
     let api_url = api_url();
 
@@ -21,8 +27,14 @@ pub fn App() -> Element {
     let mut editing_upload = use_signal::<Option<UploadTemplate>>(|| None);
     let mut download_modal_error = use_signal::<Option<String>>(|| None);
     let mut upload_modal_error = use_signal::<Option<String>>(|| None);
+    let mut upload_preview = use_signal::<Option<UploadTemplatePreview>>(|| None);
+    let mut upload_collections = use_signal::<Vec<CollectionSeason>>(Vec::new);
     let mut settings_message = use_signal::<Option<String>>(|| None);
     let mut settings_error = use_signal(|| false);
+    let mut line_benchmarks = use_signal::<Vec<UploadLineBenchmark>>(Vec::new);
+    let mut benchmarking_lines = use_signal(|| false);
+    let mut history_page = use_signal(|| 1u32);
+    let mut upload_history = use_signal(UploadHistoryPage::default);
     let mut operation_message = use_signal::<Option<String>>(|| None);
     let mut operation_error = use_signal(|| false);
 
@@ -45,6 +57,15 @@ pub fn App() -> Element {
                 if let Some(v) = api::fetch_uploads(api_url).await {
                     next.uploads = v;
                 }
+                if let Some(v) = api::fetch_upload_jobs(api_url).await {
+                    next.upload_jobs = v;
+                }
+                if active_tab() == Tab::UploadHistory
+                    && let Some(v) =
+                        api::fetch_upload_history(api_url, history_page(), HISTORY_PAGE_SIZE).await
+                {
+                    upload_history.set(v);
+                }
                 next.storage_stats = api::fetch_storage_stats(api_url).await;
                 if account_tick == 0
                     && let Some(v) = api::fetch_accounts(api_url).await
@@ -132,6 +153,27 @@ pub fn App() -> Element {
                         compact: sidebar_collapsed(),
                         onclick: move |_| active_tab.set(Tab::Uploads),
                     }
+                    TabItem {
+                        active: active_tab() == Tab::UploadQueue,
+                        label: "上传队列",
+                        icon: "≡",
+                        compact: sidebar_collapsed(),
+                        onclick: move |_| active_tab.set(Tab::UploadQueue),
+                    }
+                    TabItem {
+                        active: active_tab() == Tab::UploadHistory,
+                        label: "投稿历史",
+                        icon: "◷",
+                        compact: sidebar_collapsed(),
+                        onclick: move |_| async move {
+                            active_tab.set(Tab::UploadHistory);
+                            if let Some(v) =
+                                api::fetch_upload_history(api_url, history_page(), HISTORY_PAGE_SIZE).await
+                            {
+                                upload_history.set(v);
+                            }
+                        },
+                    }
                     TabItem {
                         active: active_tab() == Tab::Settings,
                         label: "录制设置",
@@ -222,6 +264,31 @@ pub fn App() -> Element {
                                         data.set(next);
                                     }
                                 },
+                                on_resume_upload: move |id: String| async move {
+                                    manual_upload_message.set(Some("正在继续上传...".to_string()));
+                                    manual_upload_error.set(false);
+
+                                    match api::resume_pending_uploads(api_url, &id).await {
+                                        Ok(msg) => {
+                                            manual_upload_message.set(Some(format!("待上传任务已继续：{msg}")));
+                                            manual_upload_error.set(false);
+                                        }
+                                        Err(e) => {
+                                            manual_upload_message.set(Some(format!("继续上传失败：{e}")));
+                                            manual_upload_error.set(true);
+                                            #[cfg(target_arch = "wasm32")]
+                                            web_sys::console::error_1(&format!("resume upload failed: {e}").into());
+                                        }
+                                    }
+
+                                    if let Some(v) = api::fetch_downloads(api_url).await {
+                                        let mut next = data();
+                                        next.downloads = v;
+                                        next.storage_stats =
+                                            api::fetch_storage_stats(api_url).await;
+                                        data.set(next);
+                                    }
+                                },
                                 on_stop: move |(id, name): (String, String)| async move {
                                     operation_message.set(Some(format!("正在停止下载任务「{name}」...")));
                                     operation_error.set(false);
@@ -353,6 +420,47 @@ pub fn App() -> Element {
                                         data.set(next);
                                     }
                                 },
+                                on_add_webdav: move |payload| async move {
+                                    qr_message.set(Some("正在连接 WebDAV 服务器...".to_string()));
+                                    match api::add_webdav_account(api_url, &payload).await {
+                                        Ok(()) => {
+                                            qr_message.set(Some("WebDAV 账号已保存。".to_string()));
+                                            if let Some(accounts) = api::fetch_accounts(api_url).await {
+                                                let mut next = data();
+                                                next.accounts = accounts;
+                                                data.set(next);
+                                            }
+                                        }
+                                        Err(e) => qr_message.set(Some(format!("添加 WebDAV 账号失败：{e}"))),
+                                    }
+                                },
+                                on_add_youtube: move |payload| async move {
+                                    qr_message.set(Some("正在验证 YouTube 授权...".to_string()));
+                                    match api::add_youtube_account(api_url, &payload).await {
+                                        Ok(()) => {
+                                            qr_message.set(Some("YouTube 账号已保存。".to_string()));
+                                            if let Some(accounts) = api::fetch_accounts(api_url).await {
+                                                let mut next = data();
+                                                next.accounts = accounts;
+                                                data.set(next);
+                                            }
+                                        }
+                                        Err(e) => qr_message.set(Some(format!("添加 YouTube 账号失败：{e}"))),
+                                    }
+                                },
+                                on_add_s3: move |payload| async move {
+                                    match api::add_s3_account(api_url, &payload).await {
+                                        Ok(()) => {
+                                            qr_message.set(Some("S3 账号已保存。".to_string()));
+                                            if let Some(accounts) = api::fetch_accounts(api_url).await {
+                                                let mut next = data();
+                                                next.accounts = accounts;
+                                                data.set(next);
+                                            }
+                                        }
+                                        Err(e) => qr_message.set(Some(format!("添加 S3 账号失败：{e}"))),
+                                    }
+                                },
                             }
                         },
                         Tab::Uploads => rsx! {
@@ -361,10 +469,14 @@ pub fn App() -> Element {
                                 accounts: snapshot.accounts.clone(),
                                 on_create: move |_| {
                                     upload_modal_error.set(None);
+                                    upload_preview.set(None);
+                                    upload_collections.set(Vec::new());
                                     editing_upload.set(Some(UploadTemplate::default()));
                                 },
                                 on_edit: move |u| {
                                     upload_modal_error.set(None);
+                                    upload_preview.set(None);
+                                    upload_collections.set(Vec::new());
                                     editing_upload.set(Some(u));
                                 },
                                 on_delete: move |id: String| async move {
@@ -420,11 +532,105 @@ pub fn App() -> Element {
                                 },
                             }
                         },
+                        Tab::UploadQueue => rsx! {
+                            UploadQueuePage {
+                                jobs: snapshot.upload_jobs.clone(),
+                                on_retry: move |id: String| async move {
+                                    match api::retry_upload_job(api_url, &id).await {
+                                        Ok(_) => {
+                                            operation_message.set(Some("上传任务已重新排队。".to_string()));
+                                            operation_error.set(false);
+                                        }
+                                        Err(e) => {
+                                            operation_message.set(Some(format!("重试上传失败：{e}")));
+                                            operation_error.set(true);
+                                        }
+                                    }
+                                    if let Some(v) = api::fetch_upload_jobs(api_url).await {
+                                        let mut next = data();
+                                        next.upload_jobs = v;
+                                        data.set(next);
+                                    }
+                                },
+                                on_retry_config: move |(id, index): (String, usize)| async move {
+                                    match api::retry_upload_config(api_url, &id, index).await {
+                                        Ok(_) => {
+                                            operation_message.set(Some(format!("投稿配置 {} 已重新排队。", index + 1)));
+                                            operation_error.set(false);
+                                        }
+                                        Err(e) => {
+                                            operation_message.set(Some(format!("重试投稿配置失败：{e}")));
+                                            operation_error.set(true);
+                                        }
+                                    }
+                                    if let Some(v) = api::fetch_upload_jobs(api_url).await {
+                                        let mut next = data();
+                                        next.upload_jobs = v;
+                                        data.set(next);
+                                    }
+                                },
+                                on_cancel: move |id: String| async move {
+                                    match api::cancel_upload_job(api_url, &id).await {
+                                        Ok(_) => {
+                                            operation_message.set(Some("上传任务已取消。".to_string()));
+                                            operation_error.set(false);
+                                        }
+                                        Err(e) => {
+                                            operation_message.set(Some(format!("取消上传失败：{e}")));
+                                            operation_error.set(true);
+                                        }
+                                    }
+                                    if let Some(v) = api::fetch_upload_jobs(api_url).await {
+                                        let mut next = data();
+                                        next.upload_jobs = v;
+                                        data.set(next);
+                                    }
+                                },
+                                on_priority: move |(id, priority): (String, i64)| async move {
+                                    if let Err(e) = api::set_upload_job_priority(api_url, &id, priority).await {
+                                        operation_message.set(Some(format!("调整优先级失败：{e}")));
+                                        operation_error.set(true);
+                                    }
+                                    if let Some(v) = api::fetch_upload_jobs(api_url).await {
+                                        let mut next = data();
+                                        next.upload_jobs = v;
+                                        data.set(next);
+                                    }
+                                },
+                            }
+                        },
+                        Tab::UploadHistory => rsx! {
+                            HistoryPage {
+                                history: upload_history(),
+                                on_page: move |page: u32| async move {
+                                    history_page.set(page);
+                                    if let Some(v) =
+                                        api::fetch_upload_history(api_url, page, HISTORY_PAGE_SIZE).await
+                                    {
+                                        upload_history.set(v);
+                                    }
+                                },
+                            }
+                        },
                         Tab::Settings => rsx! {
                             SettingsPage {
                                 settings: snapshot.recording_settings.clone(),
+                                accounts: snapshot.accounts.clone(),
                                 save_message: settings_message(),
                                 save_error: settings_error(),
+                                line_benchmarks: line_benchmarks(),
+                                benchmarking_lines: benchmarking_lines(),
+                                on_benchmark_lines: move |_| async move {
+                                    benchmarking_lines.set(true);
+                                    match api::benchmark_upload_lines(api_url).await {
+                                        Ok(results) => line_benchmarks.set(results),
+                                        Err(e) => {
+                                            settings_message.set(Some(format!("线路测速失败：{e}")));
+                                            settings_error.set(true);
+                                        }
+                                    }
+                                    benchmarking_lines.set(false);
+                                },
                                 on_save: move |settings| async move {
                                     match api::save_recording_settings(api_url, &settings).await {
                                         Ok(()) => {
@@ -479,7 +685,33 @@ pub fn App() -> Element {
                     template: template.clone(),
                     accounts: snapshot.accounts.clone(),
                     save_error: upload_modal_error(),
+                    preview: upload_preview(),
+                    collections: upload_collections(),
                     on_close: move |_| editing_upload.set(None),
+                    on_preview: move |config| async move {
+                        match api::preview_upload_template(api_url, &config).await {
+                            Ok(preview) => {
+                                upload_modal_error.set(None);
+                                upload_preview.set(Some(preview));
+                            }
+                            Err(e) => {
+                                upload_preview.set(None);
+                                upload_modal_error.set(Some(format!("模板预览失败：{e}")));
+                            }
+                        }
+                    },
+                    on_load_collections: move |account_file| async move {
+                        match api::list_account_collections(api_url, account_file).await {
+                            Ok(collections) => {
+                                upload_modal_error.set(None);
+                                upload_collections.set(collections);
+                            }
+                            Err(e) => {
+                                upload_collections.set(Vec::new());
+                                upload_modal_error.set(Some(format!("读取合集失败：{e}")));
+                            }
+                        }
+                    },
                     on_save: move |payload| async move {
                         match api::save_upload(api_url, &payload).await {
                             Ok(()) => {
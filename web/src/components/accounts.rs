@@ -1,5 +1,7 @@
 use dioxus::prelude::*;
-use shared::UploadAccount;
+use shared::{
+    AccountKind, S3AccountRequest, UploadAccount, WebDavAccountRequest, YouTubeAccountRequest,
+};
 
 use crate::{models::QrStartResponse, styles::qr_image_url};
 
@@ -13,9 +15,27 @@ pub fn AccountsPage(
     on_reset_qr: EventHandler<()>,
     on_rename: EventHandler<(String, String)>,
     on_delete: EventHandler<String>,
+    on_add_webdav: EventHandler<WebDavAccountRequest>,
+    on_add_youtube: EventHandler<YouTubeAccountRequest>,
+    on_add_s3: EventHandler<S3AccountRequest>,
 ) -> Element {
     let mut renaming_file = use_signal::<Option<String>>(|| None);
     let mut rename_input = use_signal(String::new);
+    let mut webdav_open = use_signal(|| false);
+    let mut webdav_name = use_signal(String::new);
+    let mut webdav_url = use_signal(String::new);
+    let mut webdav_user = use_signal(String::new);
+    let mut webdav_password = use_signal(String::new);
+    let mut youtube_open = use_signal(|| false);
+    let mut youtube_name = use_signal(String::new);
+    let mut youtube_client_id = use_signal(String::new);
+    let mut youtube_client_secret = use_signal(String::new);
+    let mut youtube_refresh_token = use_signal(String::new);
+    let mut youtube_api_base = use_signal(String::new);
+    let mut s3_open = use_signal(|| false);
+    let mut s3_name = use_signal(String::new);
+    let mut s3_access_key = use_signal(String::new);
+    let mut s3_secret_key = use_signal(String::new);
     let total_count = accounts.len();
     let logged_in_count = accounts.iter().filter(|acc| acc.mid.is_some()).count();
     let qr_status = if qr_session.is_some() { "等待确认" } else { "未创建" };
@@ -25,9 +45,26 @@ pub fn AccountsPage(
             div { class: "page-header",
                 div {
                     h1 { "账号管理" }
-                    p { "增删改查 Bilibili 上传账号。" }
+                    p { "增删改查 Bilibili 与 YouTube 上传账号、WebDAV 与 S3 存储账号。" }
+                }
+                div { class: "inline-actions",
+                    button {
+                        class: "btn btn-ghost",
+                        onclick: move |_| webdav_open.set(true),
+                        "添加 WebDAV"
+                    }
+                    button {
+                        class: "btn btn-ghost",
+                        onclick: move |_| youtube_open.set(true),
+                        "添加 YouTube"
+                    }
+                    button {
+                        class: "btn btn-ghost",
+                        onclick: move |_| s3_open.set(true),
+                        "添加 S3"
+                    }
+                    button { class: "btn btn-primary", onclick: move |_| on_start_qr.call(()), "扫码登录" }
                 }
-                button { class: "btn btn-primary", onclick: move |_| on_start_qr.call(()), "扫码登录" }
             }
 
             div { class: "stat-grid",
@@ -59,6 +96,191 @@ pub fn AccountsPage(
                 }
             }
 
+            if webdav_open() {
+                div { class: "card",
+                    p { class: "section-title", "添加 WebDAV / Alist 账号" }
+                    div { class: "grid-2",
+                        div { class: "field",
+                            label { "显示名称" }
+                            input {
+                                class: "input",
+                                value: "{webdav_name}",
+                                oninput: move |e| webdav_name.set(e.value()),
+                            }
+                        }
+                        div { class: "field",
+                            label { "服务器地址" }
+                            input {
+                                class: "input mono",
+                                value: "{webdav_url}",
+                                placeholder: "例如 http://127.0.0.1:5244/dav",
+                                oninput: move |e| webdav_url.set(e.value()),
+                            }
+                        }
+                        div { class: "field",
+                            label { "用户名" }
+                            input {
+                                class: "input",
+                                value: "{webdav_user}",
+                                oninput: move |e| webdav_user.set(e.value()),
+                            }
+                        }
+                        div { class: "field",
+                            label { "密码" }
+                            input {
+                                class: "input",
+                                r#type: "password",
+                                value: "{webdav_password}",
+                                oninput: move |e| webdav_password.set(e.value()),
+                            }
+                        }
+                    }
+                    p { class: "label", "保存前会用该账号访问服务器地址；同一服务器与用户名再次添加时更新密码。" }
+                    div { class: "inline-actions",
+                        button {
+                            class: "btn btn-primary",
+                            onclick: move |_| {
+                                on_add_webdav.call(WebDavAccountRequest {
+                                    name: webdav_name(),
+                                    base_url: webdav_url(),
+                                    username: webdav_user(),
+                                    password: webdav_password(),
+                                });
+                                webdav_password.set(String::new());
+                                webdav_open.set(false);
+                            },
+                            "保存"
+                        }
+                        button { class: "btn btn-ghost", onclick: move |_| webdav_open.set(false), "取消" }
+                    }
+                }
+            }
+
+            if youtube_open() {
+                div { class: "card",
+                    p { class: "section-title", "添加 YouTube 账号" }
+                    div { class: "grid-2",
+                        div { class: "field",
+                            label { "显示名称" }
+                            input {
+                                class: "input",
+                                value: "{youtube_name}",
+                                oninput: move |e| youtube_name.set(e.value()),
+                            }
+                        }
+                        div { class: "field",
+                            label { "OAuth 客户端 ID" }
+                            input {
+                                class: "input mono",
+                                value: "{youtube_client_id}",
+                                oninput: move |e| youtube_client_id.set(e.value()),
+                            }
+                        }
+                        div { class: "field",
+                            label { "OAuth 客户端密钥" }
+                            input {
+                                class: "input",
+                                r#type: "password",
+                                value: "{youtube_client_secret}",
+                                oninput: move |e| youtube_client_secret.set(e.value()),
+                            }
+                        }
+                        div { class: "field",
+                            label { "Refresh Token" }
+                            input {
+                                class: "input",
+                                r#type: "password",
+                                value: "{youtube_refresh_token}",
+                                oninput: move |e| youtube_refresh_token.set(e.value()),
+                            }
+                        }
+                        div { class: "field",
+                            label { "API 地址（可选）" }
+                            input {
+                                class: "input mono",
+                                value: "{youtube_api_base}",
+                                placeholder: "留空使用 https://www.googleapis.com",
+                                oninput: move |e| youtube_api_base.set(e.value()),
+                            }
+                        }
+                    }
+                    p { class: "label",
+                        "Refresh Token 需带 youtube.upload 权限，可用 Google OAuth 2.0 Playground 或自己的 OAuth 客户端获取；保存前会换取一次访问令牌验证。"
+                    }
+                    div { class: "inline-actions",
+                        button {
+                            class: "btn btn-primary",
+                            onclick: move |_| {
+                                on_add_youtube.call(YouTubeAccountRequest {
+                                    name: youtube_name(),
+                                    client_id: youtube_client_id(),
+                                    client_secret: youtube_client_secret(),
+                                    refresh_token: youtube_refresh_token(),
+                                    api_base: youtube_api_base(),
+                                });
+                                youtube_client_secret.set(String::new());
+                                youtube_refresh_token.set(String::new());
+                                youtube_open.set(false);
+                            },
+                            "保存"
+                        }
+                        button { class: "btn btn-ghost", onclick: move |_| youtube_open.set(false), "取消" }
+                    }
+                }
+            }
+
+            if s3_open() {
+                div { class: "card",
+                    p { class: "section-title", "添加 S3 访问密钥" }
+                    div { class: "grid-2",
+                        div { class: "field",
+                            label { "显示名称" }
+                            input {
+                                class: "input",
+                                value: "{s3_name}",
+                                oninput: move |e| s3_name.set(e.value()),
+                            }
+                        }
+                        div { class: "field",
+                            label { "Access Key ID" }
+                            input {
+                                class: "input mono",
+                                value: "{s3_access_key}",
+                                oninput: move |e| s3_access_key.set(e.value()),
+                            }
+                        }
+                        div { class: "field",
+                            label { "Secret Access Key" }
+                            input {
+                                class: "input",
+                                r#type: "password",
+                                value: "{s3_secret_key}",
+                                oninput: move |e| s3_secret_key.set(e.value()),
+                            }
+                        }
+                    }
+                    p { class: "label",
+                        "密钥只保存在服务端的账号目录中；Endpoint、Region 与存储桶在上传模板中填写。同一 Access Key ID 再次添加会更新密钥。"
+                    }
+                    div { class: "inline-actions",
+                        button {
+                            class: "btn btn-primary",
+                            onclick: move |_| {
+                                on_add_s3.call(S3AccountRequest {
+                                    name: s3_name(),
+                                    access_key_id: s3_access_key(),
+                                    secret_access_key: s3_secret_key(),
+                                });
+                                s3_secret_key.set(String::new());
+                                s3_open.set(false);
+                            },
+                            "保存"
+                        }
+                        button { class: "btn btn-ghost", onclick: move |_| s3_open.set(false), "取消" }
+                    }
+                }
+            }
+
             if let Some(session) = qr_session {
                 div { class: "card qr-card",
                     div { class: "qr-box",
@@ -83,14 +305,15 @@ pub fn AccountsPage(
                         thead {
                             tr {
                                 th { "显示名称" }
+                                th { "类型" }
                                 th { "MID" }
-                                th { "Cookie 文件" }
+                                th { "账号文件" }
                                 th { class: "actions", "操作" }
                             }
                         }
                         tbody {
                             if accounts.is_empty() {
-                                tr { td { colspan: "4", class: "empty", "暂无账号" } }
+                                tr { td { colspan: "5", class: "empty", "暂无账号" } }
                             }
                             {
                                 accounts.into_iter().map(|acc| {
@@ -111,9 +334,16 @@ pub fn AccountsPage(
                                                     "{acc.name}"
                                                 }
                                             }
+                                            td { "{acc.kind.label()}" }
                                             td {
                                                 if let Some(mid) = acc.mid {
                                                     span { class: "tag tag-success", "{mid}" }
+                                                } else if acc.kind != AccountKind::Bilibili {
+                                                    if acc.valid {
+                                                        span { class: "tag tag-success", "可用" }
+                                                    } else {
+                                                        span { class: "tag tag-warning", title: "{acc.error.clone().unwrap_or_default()}", "不可用" }
+                                                    }
                                                 } else {
                                                     span { class: "tag tag-warning", "未知" }
                                                 }
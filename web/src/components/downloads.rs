@@ -1,5 +1,9 @@
 use dioxus::prelude::*;
-use shared::{DownloadConfig, StorageStats, UploadTemplate};
+use shared::{
+    DownloadConfig, RecorderOptions, RecordingSegment, StorageStats, UploadProgress, UploadTemplate,
+};
+
+use super::common::format_bytes;
 
 #[component]
 pub fn DownloadsPage(
@@ -11,6 +15,7 @@ pub fn DownloadsPage(
     on_delete: EventHandler<String>,
     on_batch_delete: EventHandler<Vec<String>>,
     on_manual_upload: EventHandler<String>,
+    on_resume_upload: EventHandler<String>,
     on_stop: EventHandler<(String, String)>,
     on_resume: EventHandler<(String, String)>,
     on_clear_files: EventHandler<(String, String)>,
@@ -152,13 +157,14 @@ pub fn DownloadsPage(
                                 th { "直播地址" }
                                 th { "当前状态" }
                                 th { "本地文件" }
+                                th { "最近分段" }
                                 th { "关联上传任务" }
                                 th { class: "actions", "操作" }
                             }
                         }
                         tbody {
                             if rows_view.is_empty() {
-                                tr { td { colspan: "7", class: "empty", "暂无下载任务" } }
+                                tr { td { colspan: "8", class: "empty", "暂无下载任务" } }
                             }
                             {
                                 rows_view.into_iter().map(|d| {
@@ -167,6 +173,7 @@ pub fn DownloadsPage(
                                     let d_id_for_check = d_id.clone();
                                     let d_id_for_delete = d_id.clone();
                                     let d_id_for_manual_upload = d_id.clone();
+                                    let d_id_for_resume_upload = d_id.clone();
                                     let d_id_for_stop = d_id.clone();
                                     let d_id_for_resume = d_id.clone();
                                     let d_id_for_clear_files = d_id.clone();
@@ -179,8 +186,16 @@ pub fn DownloadsPage(
                                         d.current_status.clone().unwrap_or_else(|| "未知".to_string());
                                     let status_class = status_class(&status_label);
                                     let can_stop = matches!(status_label.as_str(), "下载中" | "上传中" | "检测中");
+                                    let has_pending_upload = status_label == "待上传";
                                     let can_clear_files = !matches!(status_label.as_str(), "下载中" | "上传中" | "检测中");
                                     let file_size = format_bytes(d.recording_file_size_bytes);
+                                    let segment_summary = d.latest_segment.as_ref().map(format_segment_metrics);
+                                    let upload_progress = d
+                                        .upload_progress
+                                        .as_ref()
+                                        .map(|p| (p.percent(), format_upload_progress(p)));
+                                    let recorder_summary = (!d.recorder_options.is_default())
+                                        .then(|| format_recorder_options(&d.recorder_options));
                                     rsx! {
                                         tr {
                                             td {
@@ -198,12 +213,23 @@ pub fn DownloadsPage(
                                                     },
                                                 }
                                             }
-                                            td { "{d.name}" }
+                                            td {
+                                                div { "{d.name}" }
+                                                if let Some(summary) = recorder_summary.clone() {
+                                                    div { class: "muted mono", title: "{summary}", "{summary}" }
+                                                }
+                                            }
                                             td { class: "url-cell",
                                                 span { class: "mono text-ellipsis", title: "{d.url}", "{d.url}" }
                                             }
                                             td {
                                                 span { class: "{status_class}", "{status_label}" }
+                                                if let Some((percent, summary)) = upload_progress {
+                                                    div { class: "upload-progress", title: "{summary}",
+                                                        div { class: "upload-progress-fill", style: "width: {percent}%;" }
+                                                    }
+                                                    div { class: "muted mono", "{summary}" }
+                                                }
                                             }
                                             td {
                                                 span {
@@ -211,6 +237,13 @@ pub fn DownloadsPage(
                                                     "{file_size}"
                                                 }
                                             }
+                                            td {
+                                                if let Some(summary) = segment_summary {
+                                                    span { class: "mono", "{summary}" }
+                                                } else {
+                                                    span { class: "muted", "暂无" }
+                                                }
+                                            }
                                             td {
                                                 if d.linked_upload_ids.is_empty() {
                                                     span { class: "muted", "未关联" }
@@ -240,6 +273,9 @@ pub fn DownloadsPage(
                                                         "停止"
                                                     }
                                                 }
+                                                if has_pending_upload {
+                                                    button { class: "btn btn-primary", onclick: move |_| on_resume_upload.call(d_id_for_resume_upload.clone()), "继续上传" }
+                                                }
                                                 button { class: "btn btn-primary", onclick: move |_| on_manual_upload.call(d_id_for_manual_upload.clone()), "手动上传" }
                                                 button {
                                                     class: "btn btn-ghost",
@@ -267,25 +303,68 @@ fn status_class(label: &str) -> &'static str {
         "检测中" => "tag tag-info",
         "失败" => "tag tag-danger",
         "已完成" => "tag tag-success",
-        "已停止" => "tag tag-warning",
+        "已停止" | "待上传" | "部分完成" => "tag tag-warning",
         _ => "tag",
     }
 }
 
-fn format_bytes(bytes: u64) -> String {
-    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
-    let mut value = bytes as f64;
-    let mut unit_index = 0;
-    while value >= 1024.0 && unit_index + 1 < UNITS.len() {
-        value /= 1024.0;
-        unit_index += 1;
+fn format_upload_progress(progress: &UploadProgress) -> String {
+    let mut parts = vec![format!(
+        "{:.1}% · {}/{}",
+        progress.percent(),
+        format_bytes(progress.bytes_sent),
+        format_bytes(progress.total_bytes)
+    )];
+    if progress.bytes_per_sec > 0 {
+        parts.push(format!("{}/s", format_bytes(progress.bytes_per_sec)));
+    }
+    if let Some(eta) = progress.eta_secs {
+        parts.push(format!("剩余 {}", format_eta(eta)));
+    }
+    if progress.config_count > 1 {
+        parts.push(format!("投稿 {}/{}", progress.config_index, progress.config_count));
+    }
+    parts.join(" · ")
+}
+
+fn format_eta(secs: u64) -> String {
+    match secs {
+        0..60 => format!("{secs}秒"),
+        60..3600 => format!("{}分{}秒", secs / 60, secs % 60),
+        _ => format!("{}小时{}分", secs / 3600, secs % 3600 / 60),
     }
+}
 
-    if unit_index == 0 {
-        format!("{bytes} B")
-    } else if value >= 10.0 {
-        format!("{value:.1} {}", UNITS[unit_index])
-    } else {
-        format!("{value:.2} {}", UNITS[unit_index])
+fn format_recorder_options(options: &RecorderOptions) -> String {
+    let mut parts = vec![format!("录制器：{}", options.backend.label())];
+    if !options.extra_args.is_empty() {
+        parts.push(format!("参数：{}", options.extra_args.join(" ")));
+    }
+    if !options.headers.is_empty() {
+        parts.push(format!("请求头 {} 项", options.headers.len()));
+    }
+    if options.cookies.is_some() {
+        parts.push("Cookie".to_string());
+    }
+    if options.user_agent.is_some() {
+        parts.push("自定义 UA".to_string());
+    }
+    parts.join(" · ")
+}
+
+fn format_segment_metrics(segment: &RecordingSegment) -> String {
+    let mut parts = Vec::new();
+    if let Some(resolution) = segment.resolution_label() {
+        parts.push(resolution);
+    }
+    if let Some(kbps) = segment.bit_rate_kbps {
+        parts.push(format!("{:.1} Mbps", kbps as f64 / 1000.0));
+    }
+    if let Some(fps) = segment.frame_rate {
+        parts.push(format!("{}fps", fps.round() as u64));
+    }
+    if let Some(codec) = segment.video_codec.as_ref().or(segment.audio_codec.as_ref()) {
+        parts.push(codec.clone());
     }
+    if parts.is_empty() { format_bytes(segment.size_bytes) } else { parts.join(" · ") }
 }
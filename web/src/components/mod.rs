@@ -3,6 +3,8 @@ mod common;
 mod downloads;
 mod modals;
 mod settings;
+mod upload_history;
+mod upload_queue;
 mod upload_taxonomy;
 mod uploads;
 
@@ -11,4 +13,6 @@ pub use common::TabItem;
 pub use downloads::DownloadsPage;
 pub use modals::{DownloadModal, UploadModal};
 pub use settings::SettingsPage;
+pub use upload_history::HistoryPage;
+pub use upload_queue::UploadQueuePage;
 pub use uploads::UploadsPage;
@@ -1,7 +1,9 @@
 use dioxus::prelude::*;
 use shared::{
-    DownloadConfig, PlatformQualityConfig, RecordingSettings, UploadAccount, UploadConfig,
-    UploadTemplate,
+    AccountKind, AccountStrategy, CollectionSeason, CoverSource, DownloadConfig,
+    MAX_PUBLISH_DELAY_HOURS, MIN_PUBLISH_DELAY_HOURS, PlatformQualityConfig, PublishSchedule,
+    RecorderBackend, RecorderOptions, RecordingSettings, UploadAccount, UploadCollection,
+    UploadConfig, UploadTargetConfig, UploadTemplate, UploadTemplatePreview, YouTubePrivacy,
 };
 
 use super::upload_taxonomy::tid_options;
@@ -26,8 +28,17 @@ pub fn DownloadModal(
     let mut segment_time_sec =
         use_signal(|| base_settings.segment_time_sec.map(|v| v.to_string()).unwrap_or_default());
     let mut auto_cleanup_after_upload = use_signal(|| base_settings.auto_cleanup_after_upload);
+    let mut upload_while_recording = use_signal(|| base_settings.upload_while_recording);
     let mut min_upload_file_size_mb =
         use_signal(|| base_settings.min_upload_file_size_mb.to_string());
+    let mut stall_timeout_sec = use_signal(|| base_settings.stall_timeout_sec.to_string());
+    let mut audio_only = use_signal(|| base_settings.audio_only);
+    let mut auto_resume_uploads = use_signal(|| base_settings.auto_resume_uploads);
+    let upload_concurrency = base_settings.upload_concurrency;
+    let upload_bandwidth = base_settings.upload_bandwidth.clone();
+    let upload_line = base_settings.upload_line.clone();
+    let upload_threads = base_settings.upload_threads;
+    let upload_retry = base_settings.upload_retry.clone();
     let mut q_bilibili = use_signal(|| base_settings.quality.bilibili.clone());
     let mut q_douyu = use_signal(|| base_settings.quality.douyu.clone());
     let mut q_huya = use_signal(|| base_settings.quality.huya.clone());
@@ -37,6 +48,12 @@ pub fn DownloadModal(
     let mut q_youtube = use_signal(|| base_settings.quality.youtube.clone());
     let mut q_kick = use_signal(|| base_settings.quality.kick.clone());
     let mut q_default = use_signal(|| base_settings.quality.default_quality.clone());
+    let mut recorder_backend = use_signal(|| config.recorder_options.backend);
+    let mut extra_args = use_signal(|| config.recorder_options.extra_args.join("\n"));
+    let mut custom_headers = use_signal(|| config.recorder_options.headers.join("\n"));
+    let mut cookies = use_signal(|| config.recorder_options.cookies.clone().unwrap_or_default());
+    let mut user_agent =
+        use_signal(|| config.recorder_options.user_agent.clone().unwrap_or_default());
     let mut form_error = use_signal::<Option<String>>(|| None);
     let mut upload_search = use_signal(String::new);
     let upload_query = upload_search().trim().to_lowercase();
@@ -163,6 +180,30 @@ pub fn DownloadModal(
                         }
                         span { "上传全部成功后自动删除本地录制文件" }
                     }
+                    label { class: "mini-check",
+                        input {
+                            r#type: "checkbox",
+                            checked: upload_while_recording(),
+                            onchange: move |_| upload_while_recording.set(!upload_while_recording()),
+                        }
+                        span { "边录边传：分段录完立即上传，直播结束后再投稿" }
+                    }
+                    label { class: "mini-check",
+                        input {
+                            r#type: "checkbox",
+                            checked: auto_resume_uploads(),
+                            onchange: move |_| auto_resume_uploads.set(!auto_resume_uploads()),
+                        }
+                        span { "服务重启后自动继续未完成的上传" }
+                    }
+                    label { class: "mini-check",
+                        input {
+                            r#type: "checkbox",
+                            checked: audio_only(),
+                            onchange: move |_| audio_only.set(!audio_only()),
+                        }
+                        span { "仅录制音频（输出 m4a）" }
+                    }
                     div { class: "field",
                         label { "上传前删除小文件阈值（MB，0 表示不删除）" }
                         input {
@@ -172,6 +213,58 @@ pub fn DownloadModal(
                             oninput: move |e| min_upload_file_size_mb.set(e.value()),
                         }
                     }
+                    div { class: "field",
+                        label { "录制文件无增长重启阈值（秒，0 表示不检测）" }
+                        input {
+                            class: "input",
+                            value: "{stall_timeout_sec}",
+                            placeholder: "默认 60",
+                            oninput: move |e| stall_timeout_sec.set(e.value()),
+                        }
+                    }
+                }
+
+                p { class: "section-title", "录制器高级设置" }
+                div { class: "grid-2",
+                    div { class: "field",
+                        label { "录制后端" }
+                        select {
+                            class: "input",
+                            value: backend_value(recorder_backend()),
+                            onchange: move |e| recorder_backend.set(parse_backend(&e.value())),
+                            option { value: "auto", "自动（按平台解析结果选择）" }
+                            option { value: "streamlink", "强制 streamlink" }
+                            option { value: "ffmpeg", "强制 ffmpeg" }
+                        }
+                    }
+                    div { class: "field",
+                        label { "User-Agent（可留空）" }
+                        input { class: "input mono", value: "{user_agent}", oninput: move |e| user_agent.set(e.value()) }
+                    }
+                }
+                div { class: "field",
+                    label { "额外录制参数（每行一个参数，需指定录制后端）" }
+                    textarea {
+                        class: "input mono",
+                        rows: "3",
+                        placeholder: "-rw_timeout\n10000000",
+                        value: "{extra_args}",
+                        oninput: move |e| extra_args.set(e.value()),
+                    }
+                }
+                div { class: "field",
+                    label { "自定义请求头（每行一个，格式 Name: value）" }
+                    textarea {
+                        class: "input mono",
+                        rows: "2",
+                        placeholder: "Referer: https://example.com/",
+                        value: "{custom_headers}",
+                        oninput: move |e| custom_headers.set(e.value()),
+                    }
+                }
+                div { class: "field",
+                    label { "Cookie（可留空，格式 k1=v1; k2=v2）" }
+                    input { class: "input mono", value: "{cookies}", oninput: move |e| cookies.set(e.value()) }
                 }
 
                 if let Some(err) = form_error() {
@@ -221,6 +314,18 @@ pub fn DownloadModal(
                                     }
                                 }
                             };
+                            let stall_timeout_text = stall_timeout_sec();
+                            let stall_timeout = if stall_timeout_text.trim().is_empty() {
+                                shared::default_stall_timeout_sec()
+                            } else {
+                                match stall_timeout_text.trim().parse::<u64>() {
+                                    Ok(v) => v,
+                                    Err(_) => {
+                                        form_error.set(Some("卡死检测阈值只能填写非负整数".to_string()));
+                                        return;
+                                    }
+                                }
+                            };
                             let task_settings = RecordingSettings {
                                 segment_size_mb: segment_size,
                                 segment_time_sec: segment_time,
@@ -237,6 +342,15 @@ pub fn DownloadModal(
                                 },
                                 auto_cleanup_after_upload: auto_cleanup_after_upload(),
                                 min_upload_file_size_mb: min_upload_file_size,
+                                stall_timeout_sec: stall_timeout,
+                                audio_only: audio_only(),
+                                auto_resume_uploads: auto_resume_uploads(),
+                                upload_concurrency,
+                                upload_bandwidth: upload_bandwidth.clone(),
+                                upload_line: upload_line.clone(),
+                                upload_threads,
+                                upload_while_recording: upload_while_recording(),
+                                upload_retry: upload_retry.clone(),
                             };
                             form_error.set(None);
                             on_save.call(DownloadConfig {
@@ -253,6 +367,15 @@ pub fn DownloadModal(
                                     None
                                 },
                                 recording_file_size_bytes: 0,
+                                latest_segment: None,
+                                upload_progress: None,
+                                recorder_options: RecorderOptions {
+                                    backend: recorder_backend(),
+                                    extra_args: non_empty_lines(&extra_args()),
+                                    headers: non_empty_lines(&custom_headers()),
+                                    cookies: Some(cookies().trim().to_string()).filter(|v| !v.is_empty()),
+                                    user_agent: Some(user_agent().trim().to_string()).filter(|v| !v.is_empty()),
+                                },
                             });
                         },
                         "保存"
@@ -263,6 +386,53 @@ pub fn DownloadModal(
     }
 }
 
+fn backend_value(backend: RecorderBackend) -> &'static str {
+    match backend {
+        RecorderBackend::Auto => "auto",
+        RecorderBackend::Streamlink => "streamlink",
+        RecorderBackend::Ffmpeg => "ffmpeg",
+    }
+}
+
+fn parse_backend(value: &str) -> RecorderBackend {
+    match value {
+        "streamlink" => RecorderBackend::Streamlink,
+        "ffmpeg" => RecorderBackend::Ffmpeg,
+        _ => RecorderBackend::Auto,
+    }
+}
+
+fn strategy_value(strategy: AccountStrategy) -> &'static str {
+    match strategy {
+        AccountStrategy::PrimaryWithFallback => "primary_with_fallback",
+        AccountStrategy::RoundRobin => "round_robin",
+        AccountStrategy::LeastUsedToday => "least_used_today",
+    }
+}
+
+fn parse_strategy(value: &str) -> AccountStrategy {
+    match value {
+        "round_robin" => AccountStrategy::RoundRobin,
+        "least_used_today" => AccountStrategy::LeastUsedToday,
+        _ => AccountStrategy::PrimaryWithFallback,
+    }
+}
+
+/// 已读取的合集中 “合集 / 小节” 的名称
+fn collection_title(
+    collections: &[CollectionSeason],
+    season_id: u64,
+    section_id: u64,
+) -> Option<String> {
+    let season = collections.iter().find(|s| s.id == season_id)?;
+    let section = season.sections.iter().find(|s| s.id == section_id)?;
+    Some(format!("{} / {}", season.title, section.title))
+}
+
+fn non_empty_lines(text: &str) -> Vec<String> {
+    text.lines().map(str::trim).filter(|line| !line.is_empty()).map(str::to_string).collect()
+}
+
 #[component]
 fn QualitySelect(label: String, value: Signal<String>, on_change: EventHandler<String>) -> Element {
     let current = value();
@@ -285,16 +455,90 @@ fn QualitySelect(label: String, value: Signal<String>, on_change: EventHandler<S
     }
 }
 
+/// 备用账号与多账号策略，候选为主账号以外的同类型可用账号
+#[component]
+fn BackupAccounts(
+    accounts: Vec<UploadAccount>,
+    kind: AccountKind,
+    primary: String,
+    mut backups: Signal<Vec<String>>,
+    mut strategy: Signal<String>,
+) -> Element {
+    let candidates: Vec<UploadAccount> = accounts
+        .into_iter()
+        .filter(|a| a.valid && a.kind == kind && a.account_file != primary)
+        .collect();
+    rsx! {
+        div { class: "grid-2",
+            div { class: "field",
+                label { "多账号策略" }
+                select {
+                    class: "input",
+                    value: "{strategy}",
+                    onchange: move |e| strategy.set(e.value()),
+                    for option_strategy in [
+                        AccountStrategy::PrimaryWithFallback,
+                        AccountStrategy::RoundRobin,
+                        AccountStrategy::LeastUsedToday,
+                    ] {
+                        option { value: strategy_value(option_strategy), "{option_strategy.label()}" }
+                    }
+                }
+            }
+            div { class: "field",
+                label { "备用账号" }
+                if candidates.is_empty() {
+                    p { class: "label", "暂无其他同类型账号" }
+                }
+                {
+                    candidates.into_iter().map(|a| {
+                        let file = a.account_file.clone();
+                        let checked = backups().contains(&file);
+                        rsx! {
+                            label { key: "{a.account_file}", class: "mini-check",
+                                input {
+                                    r#type: "checkbox",
+                                    checked,
+                                    onchange: move |_| {
+                                        let mut next = backups();
+                                        if let Some(position) = next.iter().position(|f| f == &file) {
+                                            next.remove(position);
+                                        } else {
+                                            next.push(file.clone());
+                                        }
+                                        backups.set(next);
+                                    },
+                                }
+                                span { "{a.name}" }
+                            }
+                        }
+                    })
+                }
+            }
+        }
+        p { class: "label",
+            "账号登录失效或达到投稿上限时，依次换用后面的账号；轮流使用从上次发布稿件的账号的下一个开始，今日投稿最少优先按今天的上传历史排序。"
+        }
+    }
+}
+
 #[component]
 pub fn UploadModal(
     template: UploadTemplate,
     accounts: Vec<UploadAccount>,
     save_error: Option<String>,
+    preview: Option<UploadTemplatePreview>,
+    collections: Vec<CollectionSeason>,
     on_close: EventHandler<()>,
+    on_preview: EventHandler<UploadConfig>,
+    on_load_collections: EventHandler<String>,
     on_save: EventHandler<UploadTemplate>,
 ) -> Element {
     let mut name = use_signal(|| template.name.clone());
     let mut account_file = use_signal(|| template.config.account_file.clone());
+    let backup_accounts = use_signal(|| template.config.backup_accounts.clone());
+    let account_strategy =
+        use_signal(|| strategy_value(template.config.account_strategy).to_string());
     let mut title = use_signal(|| template.config.title.clone().unwrap_or_default());
     let mut tid = use_signal(|| template.config.tid);
     let mut copyright = use_signal(|| template.config.copyright);
@@ -302,8 +546,131 @@ pub fn UploadModal(
     let mut tag_input = use_signal(String::new);
     let mut description = use_signal(|| template.config.description.clone());
     let mut dynamic = use_signal(|| template.config.dynamic.clone());
+    let mut part_title = use_signal(|| template.config.part_title.clone().unwrap_or_default());
+    let mut append_bvid = use_signal(|| template.config.append_bvid.clone().unwrap_or_default());
+    let mut merge_window_hours = use_signal(|| template.config.merge_window_hours.to_string());
+    let saved_collection = template.config.collection.clone();
+    let mut collection_season = use_signal(|| {
+        saved_collection.as_ref().map(|c| c.season_id.to_string()).unwrap_or_default()
+    });
+    let mut collection_section = use_signal(|| {
+        saved_collection.as_ref().map(|c| c.section_id.to_string()).unwrap_or_default()
+    });
+    let (initial_publish_mode, initial_publish_time, initial_publish_hours) =
+        match &template.config.publish_schedule {
+            PublishSchedule::Immediate => ("immediate", String::new(), String::new()),
+            PublishSchedule::AtTime { time } => ("at_time", time.clone(), String::new()),
+            PublishSchedule::AfterStreamEnd { hours } => {
+                ("after_stream_end", String::new(), hours.to_string())
+            }
+        };
+    let mut publish_mode = use_signal(|| initial_publish_mode.to_string());
+    let mut publish_time = use_signal(|| initial_publish_time);
+    let mut publish_hours = use_signal(|| initial_publish_hours);
+    let mut only_self = use_signal(|| template.config.only_self);
+    let (initial_cover_mode, initial_cover_offset, initial_cover_path) =
+        match &template.config.cover {
+            CoverSource::Auto => ("auto", String::new(), String::new()),
+            CoverSource::Frame { offset_secs } => ("frame", offset_secs.to_string(), String::new()),
+            CoverSource::SharpestFrame => ("sharpest_frame", String::new(), String::new()),
+            CoverSource::LiveRoom => ("live_room", String::new(), String::new()),
+            CoverSource::File { path } => ("file", String::new(), path.clone()),
+        };
+    let mut cover_mode = use_signal(|| initial_cover_mode.to_string());
+    let mut cover_offset = use_signal(|| initial_cover_offset);
+    let mut cover_path = use_signal(|| initial_cover_path);
+    let (initial_target, initial_archive_root, initial_archive_path, initial_archive_move) =
+        match &template.config.target {
+            UploadTargetConfig::Bilibili => {
+                ("bilibili", String::new(), "{platform}/{streamer}/{date}".to_string(), false)
+            }
+            UploadTargetConfig::LocalArchive { root_dir, path_template, move_files } => {
+                ("local_archive", root_dir.clone(), path_template.clone(), *move_files)
+            }
+            UploadTargetConfig::S3 { .. } => {
+                ("s3", String::new(), "{platform}/{streamer}/{date}".to_string(), false)
+            }
+            UploadTargetConfig::WebDav { remote_dir_template } => {
+                ("webdav", String::new(), remote_dir_template.clone(), false)
+            }
+            UploadTargetConfig::YouTube { .. } => {
+                ("youtube", String::new(), "{platform}/{streamer}/{date}".to_string(), false)
+            }
+        };
+    let (initial_yt_privacy, initial_yt_category, initial_yt_merge) = match &template.config.target
+    {
+        UploadTargetConfig::YouTube { privacy, category_id, merge_parts } => {
+            let privacy = match privacy {
+                YouTubePrivacy::Private => "private",
+                YouTubePrivacy::Unlisted => "unlisted",
+                YouTubePrivacy::Public => "public",
+            };
+            (privacy, category_id.clone(), *merge_parts)
+        }
+        _ => ("private", "20".to_string(), false),
+    };
+    let initial_s3 = match &template.config.target {
+        UploadTargetConfig::S3 {
+            endpoint,
+            region,
+            bucket,
+            key_template,
+            storage_class,
+            virtual_hosted_style,
+        } => (
+            endpoint.clone(),
+            region.clone(),
+            bucket.clone(),
+            key_template.clone(),
+            storage_class.clone(),
+            *virtual_hosted_style,
+        ),
+        _ => (
+            String::new(),
+            "us-east-1".to_string(),
+            String::new(),
+            "{platform}/{streamer}/{date}".to_string(),
+            String::new(),
+            false,
+        ),
+    };
+    let mut target_type = use_signal(|| initial_target.to_string());
+    let mut s3_endpoint = use_signal(|| initial_s3.0.clone());
+    let mut s3_region = use_signal(|| initial_s3.1.clone());
+    let mut s3_bucket = use_signal(|| initial_s3.2.clone());
+    let mut s3_key_template = use_signal(|| initial_s3.3.clone());
+    let mut s3_storage_class = use_signal(|| initial_s3.4.clone());
+    let mut s3_virtual_hosted = use_signal(|| initial_s3.5);
+    let mut archive_root = use_signal(|| initial_archive_root);
+    let mut archive_path = use_signal(|| initial_archive_path);
+    let mut archive_move = use_signal(|| initial_archive_move);
+    let mut yt_privacy = use_signal(|| initial_yt_privacy.to_string());
+    let mut yt_category = use_signal(|| initial_yt_category);
+    let mut yt_merge = use_signal(|| initial_yt_merge);
     let mut form_error = use_signal::<Option<String>>(|| None);
     let title_chars = title().chars().count();
+    // YouTube 与 B 站投稿共用标题、简介、标签等字段，只是账号类型与投稿参数不同
+    let youtube = target_type() == "youtube";
+    let video_account_kind = if youtube { AccountKind::YouTube } else { AccountKind::Bilibili };
+    let title_limit = if youtube { 100 } else { 80 };
+    // 未重新读取合集时，保留已保存的合集作为选项
+    let saved_season =
+        saved_collection.clone().filter(|c| !collections.iter().any(|s| s.id == c.season_id));
+    let saved_section =
+        saved_season.clone().filter(|c| c.season_id.to_string() == collection_season());
+    let sections = collections
+        .iter()
+        .find(|s| s.id.to_string() == collection_season())
+        .map(|s| s.sections.clone())
+        .unwrap_or_default();
+    // 合集至少有一个默认小节，切换合集时选中第一个
+    let first_sections: Vec<(String, String)> = collections
+        .iter()
+        .map(|s| {
+            (s.id.to_string(), s.sections.first().map(|c| c.id.to_string()).unwrap_or_default())
+        })
+        .chain(saved_season.iter().map(|c| (c.season_id.to_string(), c.section_id.to_string())))
+        .collect();
 
     rsx! {
         div { class: "modal-wrap",
@@ -317,118 +684,537 @@ pub fn UploadModal(
                     input { class: "input", value: "{name}", oninput: move |e| name.set(e.value()) }
                 }
 
-                p { class: "section-title", "账号信息" }
+                p { class: "section-title", "上传目标" }
                 div { class: "field",
-                    label { "Bilibili 账号（必填）" }
+                    label { "目标类型" }
                     select {
                         class: "input",
-                        value: "{account_file}",
-                        onchange: move |e| account_file.set(e.value()),
-                        if accounts.is_empty() {
-                            option { value: "", "暂无可用账号" }
-                        }
-                        for a in accounts.iter().filter(|a| a.valid) {
-                            option { value: "{a.account_file}", "{a.name} ({a.mid.unwrap_or_default()})" }
-                        }
+                        value: "{target_type}",
+                        onchange: move |e| target_type.set(e.value()),
+                        option { value: "bilibili", "B站投稿" }
+                        option { value: "local_archive", "本地归档（复制到本地目录或 NAS）" }
+                        option { value: "s3", "S3 对象存储（AWS S3、MinIO 等）" }
+                        option { value: "webdav", "WebDAV（Alist、Nextcloud 等）" }
+                        option { value: "youtube", "YouTube 投稿" }
                     }
                 }
 
-                p { class: "section-title", "投稿参数" }
-                div { class: "field",
-                    label { "视频标题模板（必填）" }
-                    input { class: "input", value: "{title}", oninput: move |e| title.set(e.value()) }
-                    p {
-                        class: if title_chars > 80 { "label status-error" } else { "label" },
-                        "B 站标题最多 80 字；当前模板 {title_chars} 字。支持占位符：{{title}}（直播间标题）、%Y-%m-%d、%H:%M。"
+                if target_type() == "local_archive" {
+                    p { class: "section-title", "归档位置" }
+                    div { class: "grid-2",
+                        div { class: "field",
+                            label { "归档根目录（必填，绝对路径）" }
+                            input {
+                                class: "input mono",
+                                value: "{archive_root}",
+                                placeholder: "例如 /mnt/nas/recordings",
+                                oninput: move |e| archive_root.set(e.value()),
+                            }
+                        }
+                        div { class: "field",
+                            label { "子目录模板" }
+                            input {
+                                class: "input mono",
+                                value: "{archive_path}",
+                                placeholder: "例如 {{platform}}/{{streamer}}/{{date}}",
+                                oninput: move |e| archive_path.set(e.value()),
+                            }
+                        }
                     }
-                }
-                div { class: "field",
-                    label { "分区（必填）" }
-                    select {
-                        class: "input",
-                        value: "{tid}",
-                        onchange: move |e| {
-                            if let Ok(v) = e.value().parse::<u16>() {
-                                tid.set(v);
+                    p { class: "label",
+                        "子目录模板支持与标题相同的占位符，{{date}} 为开播日期（可带格式，如 {{date:%Y%m}}）。"
+                        "文件复制后校验 MD5，并在同目录写入同名 .json 元数据（直播标题、起止时间、文件大小与校验值）。"
+                    }
+                    label { class: "mini-check",
+                        input {
+                            r#type: "checkbox",
+                            checked: archive_move(),
+                            onchange: move |_| archive_move.set(!archive_move()),
+                        }
+                        span { "移动文件而非复制：在其他上传配置全部成功后执行，否则仍复制并保留原文件" }
+                    }
+                } else if target_type() == "s3" {
+                    p { class: "section-title", "存储桶" }
+                    div { class: "grid-2",
+                        div { class: "field",
+                            label { "Endpoint（必填）" }
+                            input {
+                                class: "input mono",
+                                value: "{s3_endpoint}",
+                                placeholder: "例如 http://127.0.0.1:9000",
+                                oninput: move |e| s3_endpoint.set(e.value()),
+                            }
+                        }
+                        div { class: "field",
+                            label { "Region" }
+                            input {
+                                class: "input mono",
+                                value: "{s3_region}",
+                                placeholder: "us-east-1",
+                                oninput: move |e| s3_region.set(e.value()),
+                            }
+                        }
+                        div { class: "field",
+                            label { "存储桶（必填）" }
+                            input {
+                                class: "input mono",
+                                value: "{s3_bucket}",
+                                placeholder: "例如 live-archive",
+                                oninput: move |e| s3_bucket.set(e.value()),
+                            }
+                        }
+                        div { class: "field",
+                            label { "存储类型（留空使用存储桶默认值）" }
+                            input {
+                                class: "input mono",
+                                value: "{s3_storage_class}",
+                                placeholder: "例如 STANDARD_IA",
+                                oninput: move |e| s3_storage_class.set(e.value()),
+                            }
+                        }
+                        div { class: "field",
+                            label { "S3 访问密钥（必填）" }
+                            select {
+                                class: "input",
+                                value: "{account_file}",
+                                onchange: move |e| account_file.set(e.value()),
+                                if !accounts.iter().any(|a| a.valid && a.kind == AccountKind::S3) {
+                                    option { value: "", "暂无可用密钥，请先在账号管理页添加" }
+                                }
+                                for a in accounts.iter().filter(|a| a.valid && a.kind == AccountKind::S3) {
+                                    option { value: "{a.account_file}", "{a.name}" }
+                                }
                             }
-                        },
-                        for (id, label) in tid_options() {
-                            option { value: "{id}", "{label}" }
                         }
                     }
-                }
-                div { class: "field",
-                    label { "版权" }
-                    div { class: "segmented",
-                        button {
-                            class: if copyright() == 1 { "segment segment-active" } else { "segment" },
-                            onclick: move |_| copyright.set(1),
-                            "自制"
+                    div { class: "field",
+                        label { "对象键模板" }
+                        input {
+                            class: "input mono",
+                            value: "{s3_key_template}",
+                            placeholder: "例如 {{platform}}/{{streamer}}/{{date}}",
+                            oninput: move |e| s3_key_template.set(e.value()),
                         }
-                        button {
-                            class: if copyright() == 2 { "segment segment-active" } else { "segment" },
-                            onclick: move |_| copyright.set(2),
-                            "转载"
+                        p { class: "label",
+                            "文件名追加在模板之后；大文件分块上传，中断后从已上传的分块继续，完成后核对对象大小。"
                         }
                     }
-                }
+                    label { class: "mini-check",
+                        input {
+                            r#type: "checkbox",
+                            checked: s3_virtual_hosted(),
+                            onchange: move |_| s3_virtual_hosted.set(!s3_virtual_hosted()),
+                        }
+                        span { "使用虚拟主机形式（存储桶名作为子域名，AWS S3 推荐；MinIO 保持不勾选）" }
+                    }
+                } else if target_type() == "webdav" {
+                    p { class: "section-title", "WebDAV 位置" }
+                    div { class: "grid-2",
+                        div { class: "field",
+                            label { "WebDAV 账号（必填）" }
+                            select {
+                                class: "input",
+                                value: "{account_file}",
+                                onchange: move |e| account_file.set(e.value()),
+                                if !accounts.iter().any(|a| a.valid && a.kind == AccountKind::WebDav) {
+                                    option { value: "", "暂无可用账号，请先在账号管理页添加" }
+                                }
+                                for a in accounts.iter().filter(|a| a.valid && a.kind == AccountKind::WebDav) {
+                                    option { value: "{a.account_file}", "{a.name}" }
+                                }
+                            }
+                        }
+                        div { class: "field",
+                            label { "远程目录模板" }
+                            input {
+                                class: "input mono",
+                                value: "{archive_path}",
+                                placeholder: "例如 {{platform}}/{{streamer}}/{{date}}",
+                                oninput: move |e| archive_path.set(e.value()),
+                            }
+                        }
+                    }
+                    BackupAccounts {
+                        accounts: accounts.clone(),
+                        kind: AccountKind::WebDav,
+                        primary: account_file(),
+                        backups: backup_accounts,
+                        strategy: account_strategy,
+                    }
+                    p { class: "label",
+                        "目录按模板逐级创建，文件名追加在后；文件从磁盘分块流式上传，完成后核对服务器上的文件大小，重试时跳过已完整上传的文件。"
+                    }
+                } else {
+                    p { class: "section-title", "账号信息" }
+                    div { class: "field",
+                        label { "{video_account_kind.label()} 账号（必填）" }
+                        select {
+                            class: "input",
+                            value: "{account_file}",
+                            onchange: move |e| account_file.set(e.value()),
+                            if !accounts.iter().any(|a| a.valid && a.kind == video_account_kind) {
+                                option { value: "", "暂无可用账号" }
+                            }
+                            for a in accounts.iter().filter(|a| a.valid && a.kind == video_account_kind) {
+                                if youtube {
+                                    option { value: "{a.account_file}", "{a.name}" }
+                                } else {
+                                    option { value: "{a.account_file}", "{a.name} ({a.mid.unwrap_or_default()})" }
+                                }
+                            }
+                        }
+                    }
+                    BackupAccounts {
+                        accounts: accounts.clone(),
+                        kind: video_account_kind,
+                        primary: account_file(),
+                        backups: backup_accounts,
+                        strategy: account_strategy,
+                    }
 
-                div { class: "field",
-                    label { "标签" }
-                    div { class: "tag-editor",
-                        div { class: "chip-row",
-                            if tags().is_empty() {
-                                span { class: "muted", "输入标签后按回车添加" }
-                            } else {
-                                {
-                                    tags().into_iter().map(|tag| {
-                                        let tag_for_remove = tag.clone();
-                                        rsx! {
-                                            button {
-                                                class: "chip chip-removable",
-                                                onclick: move |_| {
-                                                    let mut next = tags();
-                                                    next.retain(|v| v != &tag_for_remove);
-                                                    tags.set(next);
-                                                },
-                                                "{tag} ×"
+                    p { class: "section-title", "投稿参数" }
+                    div { class: "field",
+                        label { "视频标题模板（必填）" }
+                        input { class: "input", value: "{title}", oninput: move |e| title.set(e.value()) }
+                        p {
+                            class: if title_chars > title_limit { "label status-error" } else { "label" },
+                            "{video_account_kind.label()} 标题最多 {title_limit} 字；当前模板 {title_chars} 字。标题、简介、动态与分P标题均支持模板，见下方说明。"
+                        }
+                    }
+                    if youtube {
+                        div { class: "grid-2",
+                            div { class: "field",
+                                label { "公开范围" }
+                                select {
+                                    class: "input",
+                                    value: "{yt_privacy}",
+                                    onchange: move |e| yt_privacy.set(e.value()),
+                                    option { value: "private", "{YouTubePrivacy::Private.label()}" }
+                                    option { value: "unlisted", "{YouTubePrivacy::Unlisted.label()}" }
+                                    option { value: "public", "{YouTubePrivacy::Public.label()}" }
+                                }
+                            }
+                            div { class: "field",
+                                label { "分类 ID" }
+                                input {
+                                    class: "input mono",
+                                    value: "{yt_category}",
+                                    placeholder: "例如 20（游戏）、22（人物和博客）",
+                                    oninput: move |e| yt_category.set(e.value()),
+                                }
+                            }
+                        }
+                        label { class: "mini-check",
+                            input {
+                                r#type: "checkbox",
+                                checked: yt_merge(),
+                                onchange: move |_| yt_merge.set(!yt_merge()),
+                            }
+                            span { "多段录制合并为一个视频上传（需要 ffmpeg）；不勾选则每段作为单独的视频，标题使用分P标题模板" }
+                        }
+                    } else {
+                        div { class: "field",
+                            label { "分区（必填）" }
+                            select {
+                                class: "input",
+                                value: "{tid}",
+                                onchange: move |e| {
+                                    if let Ok(v) = e.value().parse::<u16>() {
+                                        tid.set(v);
+                                    }
+                                },
+                                for (id, label) in tid_options() {
+                                    option { value: "{id}", "{label}" }
+                                }
+                            }
+                        }
+                        div { class: "field",
+                            label { "版权" }
+                            div { class: "segmented",
+                                button {
+                                    class: if copyright() == 1 { "segment segment-active" } else { "segment" },
+                                    onclick: move |_| copyright.set(1),
+                                    "自制"
+                                }
+                                button {
+                                    class: if copyright() == 2 { "segment segment-active" } else { "segment" },
+                                    onclick: move |_| copyright.set(2),
+                                    "转载"
+                                }
+                            }
+                        }
+                    }
+
+                    div { class: "field",
+                        label { "标签" }
+                        div { class: "tag-editor",
+                            div { class: "chip-row",
+                                if tags().is_empty() {
+                                    span { class: "muted", "输入标签后按回车添加" }
+                                } else {
+                                    {
+                                        tags().into_iter().map(|tag| {
+                                            let tag_for_remove = tag.clone();
+                                            rsx! {
+                                                button {
+                                                    class: "chip chip-removable",
+                                                    onclick: move |_| {
+                                                        let mut next = tags();
+                                                        next.retain(|v| v != &tag_for_remove);
+                                                        tags.set(next);
+                                                    },
+                                                    "{tag} ×"
+                                                }
                                             }
-                                        }
-                                    })
+                                        })
+                                    }
                                 }
                             }
+                            input {
+                                class: "input",
+                                value: "{tag_input}",
+                                placeholder: "例如 Dota2，输入后按回车",
+                                oninput: move |e| tag_input.set(e.value()),
+                                onkeydown: move |e| {
+                                    if e.key().to_string() == "Enter" {
+                                        e.prevent_default();
+                                        let value = tag_input().trim().to_string();
+                                        if !value.is_empty() {
+                                            let mut next = tags();
+                                            if !next.iter().any(|v| v == &value) {
+                                                next.push(value);
+                                            }
+                                            tags.set(next);
+                                            tag_input.set(String::new());
+                                        }
+                                    }
+                                },
+                            }
                         }
+                    }
+
+                    div { class: "field",
+                        label { "简介" }
+                        textarea { class: "input", rows: "3", value: "{description}", oninput: move |e| description.set(e.value()) }
+                    }
+
+                    div { class: "field",
+                        label { "动态" }
+                        input { class: "input", value: "{dynamic}", oninput: move |e| dynamic.set(e.value()) }
+                    }
+
+                    div { class: "field",
+                        label { "分P标题模板" }
                         input {
                             class: "input",
-                            value: "{tag_input}",
-                            placeholder: "例如 Dota2，输入后按回车",
-                            oninput: move |e| tag_input.set(e.value()),
-                            onkeydown: move |e| {
-                                if e.key().to_string() == "Enter" {
-                                    e.prevent_default();
-                                    let value = tag_input().trim().to_string();
-                                    if !value.is_empty() {
-                                        let mut next = tags();
-                                        if !next.iter().any(|v| v == &value) {
-                                            next.push(value);
-                                        }
-                                        tags.set(next);
-                                        tag_input.set(String::new());
-                                    }
-                                }
+                            placeholder: "留空则使用录制时的直播间标题，例如 P{{part_index}} {{segment_title}}",
+                            value: "{part_title}",
+                            oninput: move |e| part_title.set(e.value()),
+                        }
+                        p { class: "label",
+                            "占位符：{{streamer}} {{title}} {{platform}} {{url}} {{start_time}} {{end_time}} {{date}} {{duration}} {{part_index}} {{segment_title}}；"
+                            "时间可带格式，如 {{start_time:%Y-%m-%d}}；条件：{{if segment_title}}…{{else}}…{{end}}；字面花括号写作 {{{{ 和 }}}}。"
+                        }
+                    }
+
+                    div { class: "inline-actions",
+                        button {
+                            class: "btn btn-ghost",
+                            onclick: move |_| {
+                                on_preview.call(UploadConfig {
+                                    title: Some(title().trim().to_string()),
+                                    description: description(),
+                                    dynamic: dynamic(),
+                                    part_title: Some(part_title().trim().to_string()).filter(|v| !v.is_empty()),
+                                    ..UploadConfig::default()
+                                });
                             },
+                            "预览模板"
+                        }
+                    }
+                    if let Some(preview) = preview.clone() {
+                        div { class: "card preview",
+                            p { class: "label", "示例直播（两段分P）渲染结果" }
+                            p { strong { "标题：" } "{preview.title}" }
+                            p { strong { "简介：" } "{preview.description}" }
+                            p { strong { "动态：" } "{preview.dynamic}" }
+                            {
+                                preview.part_titles.iter().enumerate().map(|(index, part)| {
+                                    let number = index + 1;
+                                    rsx! {
+                                        p { key: "{index}", strong { "P{number}：" } "{part}" }
+                                    }
+                                })
+                            }
                         }
                     }
-                }
 
-                div { class: "field",
-                    label { "简介" }
-                    textarea { class: "input", rows: "3", value: "{description}", oninput: move |e| description.set(e.value()) }
-                }
+                    if !youtube {
+                        p { class: "section-title", "封面" }
+                        div { class: "grid-2",
+                            div { class: "field",
+                                label { "封面来源" }
+                                select {
+                                    class: "input",
+                                    value: "{cover_mode}",
+                                    onchange: move |e| cover_mode.set(e.value()),
+                                    option { value: "auto", "不设置（B 站自动截取）" }
+                                    option { value: "frame", "第一个分P指定时间的画面" }
+                                    option { value: "sharpest_frame", "第一个分P中最清晰的画面" }
+                                    option { value: "live_room", "直播间封面" }
+                                    option { value: "file", "固定图片文件" }
+                                }
+                            }
+                            if cover_mode() == "frame" {
+                                div { class: "field",
+                                    label { "截取位置（秒）" }
+                                    input {
+                                        class: "input",
+                                        value: "{cover_offset}",
+                                        placeholder: "例如 600",
+                                        oninput: move |e| cover_offset.set(e.value()),
+                                    }
+                                }
+                            } else if cover_mode() == "file" {
+                                div { class: "field",
+                                    label { "图片路径（服务器上的 jpg/png）" }
+                                    input {
+                                        class: "input mono",
+                                        value: "{cover_path}",
+                                        placeholder: "例如 /data/covers/default.jpg",
+                                        oninput: move |e| cover_path.set(e.value()),
+                                    }
+                                }
+                            }
+                        }
 
-                div { class: "field",
-                    label { "动态" }
-                    input { class: "input", value: "{dynamic}", oninput: move |e| dynamic.set(e.value()) }
+                        p { class: "section-title", "合并投稿" }
+                        div { class: "grid-2",
+                            div { class: "field",
+                                label { "追加到稿件（BV 号，留空则新建稿件）" }
+                                input {
+                                    class: "input mono",
+                                    value: "{append_bvid}",
+                                    placeholder: "例如 BV1xx411c7mD",
+                                    oninput: move |e| append_bvid.set(e.value()),
+                                }
+                            }
+                            div { class: "field",
+                                label { "合并窗口（小时，0 表示不合并）" }
+                                input {
+                                    class: "input",
+                                    value: "{merge_window_hours}",
+                                    placeholder: "例如 12",
+                                    oninput: move |e| merge_window_hours.set(e.value()),
+                                }
+                            }
+                        }
+                        p { class: "label",
+                            "同一录制源在合并窗口内已用此模板投稿时，新的分P会追加到该稿件，避免一天内多次开播产生多个稿件。"
+                        }
+
+                        p { class: "section-title", "合集" }
+                        div { class: "grid-2",
+                            div { class: "field",
+                                label { "加入合集" }
+                                select {
+                                    class: "input",
+                                    value: "{collection_season}",
+                                    onchange: move |e| {
+                                        let season = e.value();
+                                        let first_section = first_sections
+                                            .iter()
+                                            .find(|(id, _)| *id == season)
+                                            .map(|(_, section)| section.clone())
+                                            .unwrap_or_default();
+                                        collection_season.set(season);
+                                        collection_section.set(first_section);
+                                    },
+                                    option { value: "", "不加入合集" }
+                                    if let Some(saved) = saved_season.clone() {
+                                        option { value: "{saved.season_id}", "{saved.title}" }
+                                    }
+                                    for season in collections.iter() {
+                                        option { value: "{season.id}", "{season.title}" }
+                                    }
+                                }
+                            }
+                            if !collection_season().is_empty() {
+                                div { class: "field",
+                                    label { "小节" }
+                                    select {
+                                        class: "input",
+                                        value: "{collection_section}",
+                                        onchange: move |e| collection_section.set(e.value()),
+                                        if let Some(saved) = saved_section.clone() {
+                                            option { value: "{saved.section_id}", "{saved.title}" }
+                                        }
+                                        for section in sections.iter() {
+                                            option { value: "{section.id}", "{section.title}" }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                        div { class: "inline-actions",
+                            button {
+                                class: "btn btn-ghost",
+                                disabled: account_file().trim().is_empty(),
+                                onclick: move |_| on_load_collections.call(account_file().trim().to_string()),
+                                "读取账号合集"
+                            }
+                        }
+                        p { class: "label",
+                            "新建的稿件投稿成功后自动加入所选小节，追加分P时不生效；合集属于主账号，换用备用账号投稿时不加入合集。"
+                        }
+
+                        p { class: "section-title", "发布设置" }
+                        div { class: "grid-2",
+                            div { class: "field",
+                                label { "发布时间" }
+                                select {
+                                    class: "input",
+                                    value: "{publish_mode}",
+                                    onchange: move |e| publish_mode.set(e.value()),
+                                    option { value: "immediate", "审核通过后立即发布" }
+                                    option { value: "at_time", "每天固定时间发布" }
+                                    option { value: "after_stream_end", "直播结束后 N 小时发布" }
+                                }
+                            }
+                            if publish_mode() == "at_time" {
+                                div { class: "field",
+                                    label { "发布时刻（HH:MM）" }
+                                    input {
+                                        class: "input mono",
+                                        value: "{publish_time}",
+                                        placeholder: "例如 20:00",
+                                        oninput: move |e| publish_time.set(e.value()),
+                                    }
+                                }
+                            } else if publish_mode() == "after_stream_end" {
+                                div { class: "field",
+                                    label { "延迟小时数（{MIN_PUBLISH_DELAY_HOURS}-{MAX_PUBLISH_DELAY_HOURS}）" }
+                                    input {
+                                        class: "input",
+                                        value: "{publish_hours}",
+                                        placeholder: "例如 6",
+                                        oninput: move |e| publish_hours.set(e.value()),
+                                    }
+                                }
+                            }
+                        }
+                        p { class: "label",
+                            "B 站要求定时发布时间在投稿后 2 小时到 15 天之间，不足 2 小时会顺延；追加分P时不生效。"
+                        }
+                        label { class: "mini-check",
+                            input {
+                                r#type: "checkbox",
+                                checked: only_self(),
+                                onchange: move |_| only_self.set(!only_self()),
+                            }
+                            span { "仅自己可见：稿件照常提交审核，但只有自己能看到，确认后在创作中心手动公开（不是草稿箱）" }
+                        }
+                    }
                 }
 
                 if let Some(err) = form_error() {
@@ -448,30 +1234,106 @@ pub fn UploadModal(
                                 return;
                             }
 
-                            let title_template = title().trim().to_string();
-                            if title_template.is_empty() {
-                                form_error.set(Some("请填写视频标题模板".to_string()));
-                                return;
-                            }
-
-                            if tid() == 0 {
-                                form_error.set(Some("请选择有效分区".to_string()));
-                                return;
-                            }
+                            let target = if target_type() == "local_archive" {
+                                let root_dir = archive_root().trim().to_string();
+                                if root_dir.is_empty() {
+                                    form_error.set(Some("请填写归档根目录".to_string()));
+                                    return;
+                                }
+                                UploadTargetConfig::LocalArchive {
+                                    root_dir,
+                                    path_template: archive_path().trim().to_string(),
+                                    move_files: archive_move(),
+                                }
+                            } else if target_type() == "s3" {
+                                let endpoint = s3_endpoint().trim().to_string();
+                                let bucket = s3_bucket().trim().to_string();
+                                if endpoint.is_empty() || bucket.is_empty() {
+                                    form_error.set(Some("请填写 S3 Endpoint 与存储桶".to_string()));
+                                    return;
+                                }
+                                UploadTargetConfig::S3 {
+                                    endpoint,
+                                    region: Some(s3_region().trim().to_string())
+                                        .filter(|v| !v.is_empty())
+                                        .unwrap_or_else(|| "us-east-1".to_string()),
+                                    bucket,
+                                    key_template: s3_key_template().trim().to_string(),
+                                    storage_class: s3_storage_class().trim().to_string(),
+                                    virtual_hosted_style: s3_virtual_hosted(),
+                                }
+                            } else if target_type() == "webdav" {
+                                UploadTargetConfig::WebDav {
+                                    remote_dir_template: archive_path().trim().to_string(),
+                                }
+                            } else if target_type() == "youtube" {
+                                let category_id = yt_category().trim().to_string();
+                                if category_id.is_empty() || !category_id.chars().all(|c| c.is_ascii_digit()) {
+                                    form_error.set(Some("YouTube 分类 ID 只能填写数字".to_string()));
+                                    return;
+                                }
+                                UploadTargetConfig::YouTube {
+                                    privacy: match yt_privacy().as_str() {
+                                        "public" => YouTubePrivacy::Public,
+                                        "unlisted" => YouTubePrivacy::Unlisted,
+                                        _ => YouTubePrivacy::Private,
+                                    },
+                                    category_id,
+                                    merge_parts: yt_merge(),
+                                }
+                            } else {
+                                UploadTargetConfig::Bilibili
+                            };
 
-                            let selected_account = if account_file().is_empty() {
+                            let title_template = title().trim().to_string();
+                            // 切换目标类型后，已选账号可能属于另一类型，此时改用该类型的第一个可用账号
+                            let account_kind = match target_type().as_str() {
+                                "webdav" => AccountKind::WebDav,
+                                "youtube" => AccountKind::YouTube,
+                                "s3" => AccountKind::S3,
+                                _ => AccountKind::Bilibili,
+                            };
+                            let current_account = account_file();
+                            let selected_account = if !current_account.is_empty()
+                                && !accounts
+                                    .iter()
+                                    .any(|a| a.account_file == current_account && a.kind != account_kind)
+                            {
+                                current_account
+                            } else {
                                 accounts
                                     .iter()
-                                    .find(|a| a.valid)
+                                    .find(|a| a.valid && a.kind == account_kind)
                                     .map(|a| a.account_file.clone())
                                     .unwrap_or_default()
-                            } else {
-                                account_file()
                             };
-                            if selected_account.trim().is_empty() {
-                                form_error.set(Some("请先在账号管理页添加并选择可用账号".to_string()));
+                            if matches!(
+                                target,
+                                UploadTargetConfig::WebDav { .. }
+                                    | UploadTargetConfig::YouTube { .. }
+                                    | UploadTargetConfig::S3 { .. }
+                            ) && selected_account.trim().is_empty()
+                            {
+                                form_error.set(Some(format!(
+                                    "请先在账号管理页添加并选择 {} 账号",
+                                    account_kind.label()
+                                )));
                                 return;
                             }
+                            if target == UploadTargetConfig::Bilibili {
+                                if title_template.is_empty() {
+                                    form_error.set(Some("请填写视频标题模板".to_string()));
+                                    return;
+                                }
+                                if tid() == 0 {
+                                    form_error.set(Some("请选择有效分区".to_string()));
+                                    return;
+                                }
+                                if selected_account.trim().is_empty() {
+                                    form_error.set(Some("请先在账号管理页添加并选择可用账号".to_string()));
+                                    return;
+                                }
+                            }
 
                             let mut tag_list = tags();
                             let pending_tag = tag_input().trim().to_string();
@@ -491,6 +1353,80 @@ pub fn UploadModal(
                                 .filter(|s| !s.is_empty())
                                 .collect::<Vec<_>>();
 
+                            let merge_window = match merge_window_hours().trim() {
+                                "" => 0,
+                                text => match text.parse::<u32>() {
+                                    Ok(v) if v <= 168 => v,
+                                    _ => {
+                                        form_error.set(Some("合并窗口只能填写 0 到 168 的整数（小时）".to_string()));
+                                        return;
+                                    }
+                                },
+                            };
+                            let append_to = Some(append_bvid().trim().to_string()).filter(|v| !v.is_empty());
+
+                            let publish_schedule = match publish_mode().as_str() {
+                                "at_time" => {
+                                    let time = publish_time().trim().to_string();
+                                    let valid = time
+                                        .split_once(':')
+                                        .and_then(|(h, m)| Some((h.parse::<u32>().ok()?, m.parse::<u32>().ok()?)))
+                                        .is_some_and(|(h, m)| h < 24 && m < 60);
+                                    if !valid {
+                                        form_error.set(Some("发布时刻格式应为 HH:MM，例如 20:00".to_string()));
+                                        return;
+                                    }
+                                    PublishSchedule::AtTime { time }
+                                }
+                                "after_stream_end" => match publish_hours().trim().parse::<u32>() {
+                                    Ok(hours) if (MIN_PUBLISH_DELAY_HOURS..=MAX_PUBLISH_DELAY_HOURS).contains(&hours) => {
+                                        PublishSchedule::AfterStreamEnd { hours }
+                                    }
+                                    _ => {
+                                        form_error.set(Some(format!(
+                                            "延迟小时数只能填写 {MIN_PUBLISH_DELAY_HOURS} 到 {MAX_PUBLISH_DELAY_HOURS} 的整数"
+                                        )));
+                                        return;
+                                    }
+                                },
+                                _ => PublishSchedule::Immediate,
+                            };
+
+                            let cover = match cover_mode().as_str() {
+                                "frame" => match cover_offset().trim().parse::<u32>() {
+                                    Ok(offset_secs) => CoverSource::Frame { offset_secs },
+                                    Err(_) => {
+                                        form_error.set(Some("截取位置请填写非负整数秒数".to_string()));
+                                        return;
+                                    }
+                                },
+                                "sharpest_frame" => CoverSource::SharpestFrame,
+                                "live_room" => CoverSource::LiveRoom,
+                                "file" => {
+                                    let path = cover_path().trim().to_string();
+                                    if path.is_empty() {
+                                        form_error.set(Some("请填写封面图片路径".to_string()));
+                                        return;
+                                    }
+                                    CoverSource::File { path }
+                                }
+                                _ => CoverSource::Auto,
+                            };
+
+                            let collection = if target_type() != "bilibili" || collection_season().is_empty() {
+                                None
+                            } else {
+                                let season_id = collection_season().parse::<u64>().unwrap_or_default();
+                                let Ok(section_id) = collection_section().parse::<u64>() else {
+                                    form_error.set(Some("请选择合集的小节".to_string()));
+                                    return;
+                                };
+                                let title = collection_title(&collections, season_id, section_id)
+                                    .or_else(|| saved_collection.as_ref().map(|c| c.title.clone()))
+                                    .unwrap_or_default();
+                                Some(UploadCollection { season_id, section_id, title })
+                            };
+
                             form_error.set(None);
                             on_save.call(UploadTemplate {
                                 id: template.id.clone(),
@@ -502,7 +1438,26 @@ pub fn UploadModal(
                                     copyright: copyright(),
                                     description: description(),
                                     dynamic: dynamic(),
+                                    part_title: Some(part_title().trim().to_string()).filter(|v| !v.is_empty()),
+                                    // 切换目标类型后留下的其他类型账号不作为备用账号；本地归档与 S3 不换用账号
+                                    backup_accounts: backup_accounts()
+                                        .into_iter()
+                                        .filter(|file| {
+                                            !matches!(target_type().as_str(), "local_archive" | "s3")
+                                                && file != &selected_account
+                                                && accounts.iter().any(|a| &a.account_file == file && a.kind == account_kind)
+                                        })
+                                        .collect(),
+                                    account_strategy: parse_strategy(&account_strategy()),
                                     account_file: selected_account,
+                                    append_bvid: append_to,
+                                    merge_window_hours: merge_window,
+                                    template_id: None,
+                                    publish_schedule,
+                                    only_self: only_self(),
+                                    cover,
+                                    target,
+                                    collection,
                                 },
                             });
                         },
@@ -1,5 +1,9 @@
 use dioxus::prelude::*;
-use shared::RecordingSettings;
+use shared::{
+    AccountUploadLimit, RecordingSettings, UPLOAD_LINE_AUTO, UPLOAD_LINES, UploadAccount,
+    UploadLimitRule, UploadLineBenchmark,
+};
+use std::collections::BTreeMap;
 
 const QUALITY_OPTIONS: &[&str] =
     &["best", "worst", "1080p60", "1080p", "720p60", "720p", "480p", "360p"];
@@ -7,8 +11,12 @@ const QUALITY_OPTIONS: &[&str] =
 #[component]
 pub fn SettingsPage(
     settings: RecordingSettings,
+    accounts: Vec<UploadAccount>,
     save_message: Option<String>,
     save_error: bool,
+    line_benchmarks: Vec<UploadLineBenchmark>,
+    benchmarking_lines: bool,
+    on_benchmark_lines: EventHandler<()>,
     on_save: EventHandler<RecordingSettings>,
 ) -> Element {
     let mut segment_size_mb =
@@ -26,7 +34,35 @@ pub fn SettingsPage(
     let mut kick = use_signal(|| settings.quality.kick.clone());
     let mut default_quality = use_signal(|| settings.quality.default_quality.clone());
     let mut auto_cleanup_after_upload = use_signal(|| settings.auto_cleanup_after_upload);
+    let mut upload_while_recording = use_signal(|| settings.upload_while_recording);
     let mut min_upload_file_size_mb = use_signal(|| settings.min_upload_file_size_mb.to_string());
+    let mut stall_timeout_sec = use_signal(|| settings.stall_timeout_sec.to_string());
+    let mut audio_only = use_signal(|| settings.audio_only);
+    let mut auto_resume_uploads = use_signal(|| settings.auto_resume_uploads);
+    let mut upload_concurrency = use_signal(|| settings.upload_concurrency.to_string());
+    let mut upload_line = use_signal(|| settings.upload_line.clone());
+    let mut upload_threads = use_signal(|| settings.upload_threads.to_string());
+    let mut upload_max_attempts = use_signal(|| settings.upload_retry.max_attempts.to_string());
+    let mut upload_retry_backoff = use_signal(|| {
+        settings
+            .upload_retry
+            .backoff_secs
+            .iter()
+            .map(|secs| secs.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    });
+    let mut upload_limit_kbps = use_signal(|| settings.upload_bandwidth.limit_kbps.to_string());
+    let mut upload_limit_schedule =
+        use_signal(|| format_limit_schedule(&settings.upload_bandwidth.schedule));
+    let mut account_limit_inputs = use_signal(|| {
+        settings
+            .upload_bandwidth
+            .account_limits
+            .iter()
+            .map(|l| (l.account_file.clone(), l.limit_kbps.to_string()))
+            .collect::<BTreeMap<String, String>>()
+    });
     let mut form_error = use_signal::<Option<String>>(|| None);
     let segment_size_label =
         settings.segment_size_mb.map(|v| format!("{v} MB")).unwrap_or_else(|| "未限制".to_string());
@@ -101,6 +137,17 @@ pub fn SettingsPage(
                     }
                 }
 
+                p { class: "section-title", "卡死检测" }
+                div { class: "field",
+                    label { "录制文件无增长重启阈值（秒，最少 10，0 表示不检测）" }
+                    input {
+                        class: "input",
+                        value: "{stall_timeout_sec}",
+                        placeholder: "默认 60",
+                        oninput: move |e| stall_timeout_sec.set(e.value()),
+                    }
+                }
+
                 p { class: "section-title", "平台画质（streamlink quality）" }
                 div { class: "grid-2",
                     QualitySelect { label: "Bilibili 画质".to_string(), value: bilibili, on_change: move |v| bilibili.set(v) }
@@ -115,8 +162,46 @@ pub fn SettingsPage(
                 }
 
                 p { class: "label", "可选值：best、worst、1080p60、1080p、720p60、720p、480p、360p。" }
+                label { class: "mini-check",
+                    input {
+                        r#type: "checkbox",
+                        checked: audio_only(),
+                        onchange: move |_| audio_only.set(!audio_only()),
+                    }
+                    span { "仅录制音频（优先使用平台纯音频流，否则用 ffmpeg 去除视频，输出 m4a）" }
+                }
 
                 p { class: "section-title", "上传后处理" }
+                div { class: "field",
+                    label { "上传并发数（同时处理的上传队列任务，1-8）" }
+                    input {
+                        class: "input",
+                        value: "{upload_concurrency}",
+                        placeholder: "默认 2",
+                        oninput: move |e| upload_concurrency.set(e.value()),
+                    }
+                }
+                div { class: "grid-2",
+                    div { class: "field",
+                        label { "每个投稿配置最多尝试次数（1-10）" }
+                        input {
+                            class: "input",
+                            value: "{upload_max_attempts}",
+                            placeholder: "默认 3",
+                            oninput: move |e| upload_max_attempts.set(e.value()),
+                        }
+                    }
+                    div { class: "field",
+                        label { "重试间隔（秒，逗号分隔，依次使用，超出后沿用最后一项）" }
+                        input {
+                            class: "input",
+                            value: "{upload_retry_backoff}",
+                            placeholder: "默认 30, 120",
+                            oninput: move |e| upload_retry_backoff.set(e.value()),
+                        }
+                    }
+                }
+                p { class: "label", "只有网络波动与限流会自动重试（限流要求的等待更久时以其为准）；登录失效、稿件被拒、投稿上限、文件无效直接失败。" }
                 div { class: "field",
                     label { "上传前删除小文件阈值（MB，0 表示不删除）" }
                     input {
@@ -134,6 +219,137 @@ pub fn SettingsPage(
                     }
                     span { "上传全部成功后自动删除本地录制文件（释放空间）" }
                 }
+                label { class: "mini-check",
+                    input {
+                        r#type: "checkbox",
+                        checked: upload_while_recording(),
+                        onchange: move |_| upload_while_recording.set(!upload_while_recording()),
+                    }
+                    span { "边录边传：每个分段录完立即上传，直播结束后再投稿（开启自动删除时分段上传后即删除）" }
+                }
+                p { class: "section-title", "上传线路" }
+                div { class: "grid-2",
+                    div { class: "field",
+                        label { "上传线路（auto 为每个文件上传前自动探测）" }
+                        select {
+                            class: "input",
+                            value: "{upload_line}",
+                            onchange: move |e| upload_line.set(e.value()),
+                            option { value: UPLOAD_LINE_AUTO, "auto" }
+                            for line in UPLOAD_LINES {
+                                option { value: "{line}", "{line}" }
+                            }
+                        }
+                    }
+                    div { class: "field",
+                        label { "单文件上传线程数（同时上传的分块数，1-16）" }
+                        input {
+                            class: "input",
+                            value: "{upload_threads}",
+                            placeholder: "默认 3",
+                            oninput: move |e| upload_threads.set(e.value()),
+                        }
+                    }
+                }
+                div { class: "inline-actions",
+                    button {
+                        class: "btn btn-ghost",
+                        disabled: benchmarking_lines,
+                        onclick: move |_| on_benchmark_lines.call(()),
+                        if benchmarking_lines { "测速中…" } else { "线路测速" }
+                    }
+                }
+                if !line_benchmarks.is_empty() {
+                    div { class: "table-wrap",
+                        table { class: "table",
+                            thead {
+                                tr {
+                                    th { "线路" }
+                                    th { "延迟" }
+                                    th { "上传速度" }
+                                    th { "错误信息" }
+                                }
+                            }
+                            tbody {
+                                {
+                                    line_benchmarks.iter().map(|result| {
+                                        let latency = result
+                                            .latency_ms
+                                            .map(|v| format!("{v} ms"))
+                                            .unwrap_or_else(|| "--".to_string());
+                                        let speed = result
+                                            .throughput_kbps
+                                            .map(|v| format!("{v} KB/s"))
+                                            .unwrap_or_else(|| "--".to_string());
+                                        let error = result.error.clone().unwrap_or_default();
+                                        rsx! {
+                                            tr {
+                                                td { class: "mono", "{result.line}" }
+                                                td { "{latency}" }
+                                                td { "{speed}" }
+                                                td { class: "text-ellipsis", title: "{error}", "{error}" }
+                                            }
+                                        }
+                                    })
+                                }
+                            }
+                        }
+                    }
+                }
+
+                p { class: "section-title", "上传限速" }
+                div { class: "grid-2",
+                    div { class: "field",
+                        label { "全局上传限速（KB/s，0 表示不限速）" }
+                        input {
+                            class: "input",
+                            value: "{upload_limit_kbps}",
+                            placeholder: "例如 2048",
+                            oninput: move |e| upload_limit_kbps.set(e.value()),
+                        }
+                    }
+                    div { class: "field",
+                        label { "限速时段（每行 `开始-结束 KB/s`，命中时覆盖全局限速）" }
+                        textarea {
+                            class: "input",
+                            rows: "3",
+                            value: "{upload_limit_schedule}",
+                            placeholder: "09:00-18:00 2048\n23:00-07:00 0",
+                            oninput: move |e| upload_limit_schedule.set(e.value()),
+                        }
+                    }
+                }
+                if !accounts.is_empty() {
+                    div { class: "grid-2",
+                        {
+                            accounts.iter().map(|account| {
+                                let key = account.account_file.clone();
+                                let value = account_limit_inputs().get(&key).cloned().unwrap_or_default();
+                                rsx! {
+                                    div { class: "field",
+                                        label { "账号「{account.name}」上传限速（KB/s，留空不限）" }
+                                        input {
+                                            class: "input",
+                                            value: "{value}",
+                                            placeholder: "不限速",
+                                            oninput: move |e| {
+                                                account_limit_inputs.write().insert(key.clone(), e.value());
+                                            },
+                                        }
+                                    }
+                                }
+                            })
+                        }
+                    }
+                }
+                label { class: "mini-check",
+                    input {
+                        r#type: "checkbox",
+                        checked: auto_resume_uploads(),
+                        onchange: move |_| auto_resume_uploads.set(!auto_resume_uploads()),
+                    }
+                    span { "服务重启后自动继续未完成的上传" }
+                }
 
                 if let Some(err) = form_error() {
                     p { class: "status-banner status-error", "{err}" }
@@ -184,6 +400,91 @@ pub fn SettingsPage(
                                     }
                                 }
                             };
+                            let stall_timeout_text = stall_timeout_sec();
+                            let stall_timeout = if stall_timeout_text.trim().is_empty() {
+                                shared::default_stall_timeout_sec()
+                            } else {
+                                match stall_timeout_text.trim().parse::<u64>() {
+                                    Ok(v) => v,
+                                    Err(_) => {
+                                        form_error.set(Some("卡死检测阈值只能填写非负整数".to_string()));
+                                        return;
+                                    }
+                                }
+                            };
+                            let upload_concurrency_text = upload_concurrency();
+                            let upload_concurrency_value = if upload_concurrency_text.trim().is_empty() {
+                                shared::default_upload_concurrency()
+                            } else {
+                                match upload_concurrency_text.trim().parse::<u32>() {
+                                    Ok(v) if (1..=8).contains(&v) => v,
+                                    _ => {
+                                        form_error.set(Some("上传并发数只能填写 1 到 8 的整数".to_string()));
+                                        return;
+                                    }
+                                }
+                            };
+
+                            let upload_threads_value = match upload_threads().trim() {
+                                "" => shared::default_upload_threads(),
+                                text => match text.parse::<u32>() {
+                                    Ok(v) if (1..=16).contains(&v) => v,
+                                    _ => {
+                                        form_error.set(Some("上传线程数只能填写 1 到 16 的整数".to_string()));
+                                        return;
+                                    }
+                                },
+                            };
+                            let upload_max_attempts_value = match upload_max_attempts().trim() {
+                                "" => shared::default_upload_max_attempts(),
+                                text => match text.parse::<u32>() {
+                                    Ok(v) if (1..=10).contains(&v) => v,
+                                    _ => {
+                                        form_error.set(Some("最多尝试次数只能填写 1 到 10 的整数".to_string()));
+                                        return;
+                                    }
+                                },
+                            };
+                            let mut backoff_secs = Vec::new();
+                            for text in upload_retry_backoff().split([',', '，']).map(str::trim).filter(|v| !v.is_empty()) {
+                                match text.parse::<u64>() {
+                                    Ok(v) if v <= 86_400 => backoff_secs.push(v),
+                                    _ => {
+                                        form_error.set(Some("重试间隔只能填写不超过 86400 的非负整数，用逗号分隔".to_string()));
+                                        return;
+                                    }
+                                }
+                            }
+                            let limit_kbps = match upload_limit_kbps().trim() {
+                                "" => 0,
+                                text => match text.parse::<u64>() {
+                                    Ok(v) => v,
+                                    Err(_) => {
+                                        form_error.set(Some("全局上传限速只能填写非负整数".to_string()));
+                                        return;
+                                    }
+                                },
+                            };
+                            let schedule = match parse_limit_schedule(&upload_limit_schedule()) {
+                                Ok(v) => v,
+                                Err(message) => {
+                                    form_error.set(Some(message));
+                                    return;
+                                }
+                            };
+                            let mut account_limits = Vec::new();
+                            for (account_file, text) in account_limit_inputs() {
+                                if text.trim().is_empty() {
+                                    continue;
+                                }
+                                match text.trim().parse::<u64>() {
+                                    Ok(limit_kbps) => account_limits.push(AccountUploadLimit { account_file, limit_kbps }),
+                                    Err(_) => {
+                                        form_error.set(Some("账号上传限速只能填写非负整数或留空".to_string()));
+                                        return;
+                                    }
+                                }
+                            }
 
                             form_error.set(None);
                             on_save.call(RecordingSettings {
@@ -202,6 +503,22 @@ pub fn SettingsPage(
                                 },
                                 auto_cleanup_after_upload: auto_cleanup_after_upload(),
                                 min_upload_file_size_mb: min_upload_file_size,
+                                stall_timeout_sec: stall_timeout,
+                                audio_only: audio_only(),
+                                auto_resume_uploads: auto_resume_uploads(),
+                                upload_concurrency: upload_concurrency_value,
+                                upload_bandwidth: shared::UploadBandwidthSettings {
+                                    limit_kbps,
+                                    schedule,
+                                    account_limits,
+                                },
+                                upload_line: upload_line(),
+                                upload_threads: upload_threads_value,
+                                upload_while_recording: upload_while_recording(),
+                                upload_retry: shared::UploadRetrySettings {
+                                    max_attempts: upload_max_attempts_value,
+                                    backoff_secs,
+                                },
                             });
                         },
                         "保存设置"
@@ -234,3 +551,28 @@ fn QualitySelect(label: String, value: Signal<String>, on_change: EventHandler<S
         }
     }
 }
+
+fn format_limit_schedule(rules: &[UploadLimitRule]) -> String {
+    rules
+        .iter()
+        .map(|r| format!("{}-{} {}", r.start, r.end, r.limit_kbps))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// 每行 `HH:MM-HH:MM KB/s`，时间格式由服务端校验
+fn parse_limit_schedule(text: &str) -> Result<Vec<UploadLimitRule>, String> {
+    let mut rules = Vec::new();
+    for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let invalid = || format!("限速时段格式错误：{line}，应为 `09:00-18:00 2048`");
+        let (window, limit) = line.split_once(char::is_whitespace).ok_or_else(invalid)?;
+        let (start, end) = window.split_once('-').ok_or_else(invalid)?;
+        let limit_kbps = limit.trim().parse::<u64>().map_err(|_| invalid())?;
+        rules.push(UploadLimitRule {
+            start: start.trim().to_string(),
+            end: end.trim().to_string(),
+            limit_kbps,
+        });
+    }
+    Ok(rules)
+}
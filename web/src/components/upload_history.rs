@@ -0,0 +1,145 @@
+use dioxus::prelude::*;
+use shared::UploadHistoryPage;
+
+use super::common::format_bytes;
+
+#[component]
+pub fn HistoryPage(history: UploadHistoryPage, on_page: EventHandler<u32>) -> Element {
+    let page = history.page.max(1);
+    let page_count = history.total.div_ceil(u64::from(history.page_size.max(1))).max(1);
+    let has_prev = page > 1;
+    let has_next = u64::from(page) < page_count;
+
+    rsx! {
+        div { class: "page",
+            div { class: "page-header",
+                div {
+                    h1 { "投稿历史" }
+                    p { "每个投稿配置的最终结果，共 {history.total} 条，时间为 UTC。" }
+                }
+            }
+
+            div { class: "card",
+                div { class: "table-wrap",
+                    table { class: "table",
+                        thead {
+                            tr {
+                                th { "录制任务" }
+                                th { "稿件" }
+                                th { "账号" }
+                                th { "文件" }
+                                th { "尝试次数" }
+                                th { "完成时间" }
+                                th { "结果" }
+                            }
+                        }
+                        tbody {
+                            if history.entries.is_empty() {
+                                tr { td { colspan: "7", class: "empty", "暂无投稿记录" } }
+                            }
+                            {
+                                history.entries.iter().map(|entry| {
+                                    let title = entry.title.clone().unwrap_or_default();
+                                    let video_url = entry.video_url();
+                                    let video_label = entry
+                                        .bvid
+                                        .clone()
+                                        .or_else(|| entry.aid.map(|aid| format!("av{aid}")))
+                                        .unwrap_or_default();
+                                    let files = entry.files.join("\n");
+                                    let size = format_bytes(entry.total_size);
+                                    let finished = format_unix_time(entry.finished_at);
+                                    let duration = format_duration(entry.finished_at - entry.started_at);
+                                    let error = entry.error.clone().unwrap_or_default();
+                                    let collection_error = entry.collection_error.clone().unwrap_or_default();
+                                    rsx! {
+                                        tr { key: "{entry.id}",
+                                            td { "{entry.task_name}" }
+                                            td {
+                                                if let Some(url) = video_url {
+                                                    a { href: "{url}", target: "_blank", rel: "noopener noreferrer", "{video_label}" }
+                                                } else {
+                                                    span { class: "muted", "未生成" }
+                                                }
+                                                if !title.is_empty() {
+                                                    div { class: "muted text-ellipsis", title: "{title}", "{title}" }
+                                                }
+                                                if entry.appended {
+                                                    div { class: "muted", "追加分P" }
+                                                }
+                                            }
+                                            td { class: "mono", "{entry.account_file}" }
+                                            td { title: "{files}",
+                                                div { "{entry.files.len()} 个" }
+                                                div { class: "muted", "{size}" }
+                                            }
+                                            td { "{entry.attempts}" }
+                                            td {
+                                                div { "{finished}" }
+                                                div { class: "muted", "耗时 {duration}" }
+                                            }
+                                            td { class: "text-ellipsis", title: "{error}",
+                                                if error.is_empty() {
+                                                    span { class: "tag tag-success", "成功" }
+                                                    if !collection_error.is_empty() {
+                                                        div { class: "muted", title: "{collection_error}", "未加入合集：{collection_error}" }
+                                                    }
+                                                } else {
+                                                    span { class: "tag tag-danger", "失败" }
+                                                    div { class: "muted", "{error}" }
+                                                }
+                                            }
+                                        }
+                                    }
+                                })
+                            }
+                        }
+                    }
+                }
+                div { class: "pager",
+                    button {
+                        class: "btn btn-ghost",
+                        disabled: !has_prev,
+                        onclick: move |_| on_page.call(page - 1),
+                        "上一页"
+                    }
+                    span { class: "muted", "第 {page} / {page_count} 页" }
+                    button {
+                        class: "btn btn-ghost",
+                        disabled: !has_next,
+                        onclick: move |_| on_page.call(page + 1),
+                        "下一页"
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn format_duration(secs: i64) -> String {
+    let secs = secs.max(0);
+    if secs >= 3600 {
+        format!("{}h{:02}m", secs / 3600, secs % 3600 / 60)
+    } else if secs >= 60 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{secs}s")
+    }
+}
+
+/// `YYYY-MM-DD HH:MM` in UTC.
+fn format_unix_time(ts: i64) -> String {
+    let days = ts.div_euclid(86_400);
+    let secs = ts.rem_euclid(86_400);
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02} {:02}:{:02}", secs / 3600, secs % 3600 / 60)
+}
@@ -0,0 +1,211 @@
+use dioxus::prelude::*;
+use shared::{UploadConfigState, UploadJob, UploadJobKind, UploadJobState};
+
+#[component]
+pub fn UploadQueuePage(
+    jobs: Vec<UploadJob>,
+    on_retry: EventHandler<String>,
+    on_retry_config: EventHandler<(String, usize)>,
+    on_cancel: EventHandler<String>,
+    on_priority: EventHandler<(String, i64)>,
+) -> Element {
+    let count = |state: UploadJobState| jobs.iter().filter(|j| j.state == state).count();
+    let queued_count = count(UploadJobState::Queued);
+    let uploading_count = count(UploadJobState::Uploading);
+    let retrying_count = count(UploadJobState::Retrying);
+    let failed_count = count(UploadJobState::Failed) + count(UploadJobState::PartiallySucceeded);
+
+    // 未结束的任务按执行顺序排在前面，已结束的按时间倒序
+    let mut rows = jobs.clone();
+    rows.sort_by_key(|j| {
+        (
+            j.state.is_finished(),
+            -j.priority,
+            if j.state.is_finished() { -j.updated_at } else { j.created_at },
+        )
+    });
+
+    rsx! {
+        div { class: "page",
+            div { class: "page-header",
+                div {
+                    h1 { "上传队列" }
+                    p { "录制结束后的投稿任务在此排队，服务重启后会继续处理。" }
+                }
+            }
+
+            div { class: "stat-grid",
+                div { class: "stat-card",
+                    p { class: "stat-label", "排队中" }
+                    p { class: "stat-value", "{queued_count}" }
+                    p { class: "stat-hint", "按优先级依次上传" }
+                }
+                div { class: "stat-card",
+                    p { class: "stat-label", "上传中" }
+                    p { class: "stat-value", "{uploading_count}" }
+                    p { class: "stat-hint", "并发数见录制设置" }
+                }
+                div { class: "stat-card",
+                    p { class: "stat-label", "等待重试" }
+                    p { class: "stat-value", "{retrying_count}" }
+                    p { class: "stat-hint", "临时错误自动重试" }
+                }
+                div { class: "stat-card",
+                    p { class: "stat-label", "失败" }
+                    p { class: "stat-value", "{failed_count}" }
+                    p { class: "stat-hint", "含部分成功，可只重试失败的配置" }
+                }
+            }
+
+            div { class: "card",
+                div { class: "table-wrap",
+                    table { class: "table",
+                        thead {
+                            tr {
+                                th { "录制任务" }
+                                th { "分P" }
+                                th { "状态" }
+                                th { "投稿进度" }
+                                th { "尝试次数" }
+                                th { "优先级" }
+                                th { "错误信息" }
+                                th { class: "actions", "操作" }
+                            }
+                        }
+                        tbody {
+                            if rows.is_empty() {
+                                tr { td { colspan: "8", class: "empty", "暂无上传任务" } }
+                            }
+                            {
+                                rows.into_iter().map(|job| {
+                                    let id_for_retry = job.id.clone();
+                                    let id_for_cancel = job.id.clone();
+                                    let id_for_raise = job.id.clone();
+                                    let id_for_lower = job.id.clone();
+                                    let priority = job.priority;
+                                    let finished = job.state.is_finished();
+                                    let can_retry = matches!(
+                                        job.state,
+                                        UploadJobState::Failed | UploadJobState::PartiallySucceeded | UploadJobState::Cancelled
+                                    );
+                                    let job_id = job.id.clone();
+                                    let state_label = job.state.label();
+                                    let state_class = job_state_class(job.state);
+                                    let error = job.last_error.clone().unwrap_or_default();
+                                    rsx! {
+                                        tr {
+                                            td {
+                                                div { "{job.task_name}" }
+                                                if let Some(title) = job.live_title.clone() {
+                                                    div { class: "muted text-ellipsis", title: "{title}", "{title}" }
+                                                }
+                                            }
+                                            td {
+                                                div { "{job.parts.len()}" }
+                                                if job.kind == UploadJobKind::Segment {
+                                                    div { class: "muted", "{job.kind.label()}" }
+                                                }
+                                            }
+                                            td { span { class: "{state_class}", "{state_label}" } }
+                                            td {
+                                                div { "{job.completed_configs}/{job.configs.len()}" }
+                                                {
+                                                    job.config_statuses.iter().enumerate().map(|(index, status)| {
+                                                        let number = index + 1;
+                                                        let label = status.state.label();
+                                                        let class = config_state_class(status.state);
+                                                        let error = status.last_error.clone().unwrap_or_default();
+                                                        // 配置了备用账号时显示实际使用的账号文件名
+                                                        let account = status
+                                                            .account_file
+                                                            .as_deref()
+                                                            .filter(|_| job.configs.get(index).is_some_and(|c| !c.backup_accounts.is_empty()))
+                                                            .map(|file| file.rsplit(['/', '\\']).next().unwrap_or(file).to_string());
+                                                        let can_retry_config = can_retry && status.state == UploadConfigState::Failed;
+                                                        let id_for_config = job_id.clone();
+                                                        rsx! {
+                                                            div { key: "{index}", class: "config-status", title: "{error}",
+                                                                span { class: "muted", "#{number}" }
+                                                                span { class: "{class}", "{label}" }
+                                                                span { class: "muted", "{status.attempts} 次" }
+                                                                if let Some(account) = account {
+                                                                    span { class: "muted mono", "{account}" }
+                                                                }
+                                                                if can_retry_config {
+                                                                    button {
+                                                                        class: "btn btn-ghost btn-mini",
+                                                                        onclick: move |_| on_retry_config.call((id_for_config.clone(), index)),
+                                                                        "重试此项"
+                                                                    }
+                                                                }
+                                                            }
+                                                        }
+                                                    })
+                                                }
+                                            }
+                                            td { "{job.attempts}/{job.max_attempts}" }
+                                            td { class: "mono", "{priority}" }
+                                            td { class: "text-ellipsis", title: "{error}",
+                                                if error.is_empty() {
+                                                    span { class: "muted", "无" }
+                                                } else {
+                                                    "{error}"
+                                                }
+                                            }
+                                            td { class: "actions",
+                                                button {
+                                                    class: "btn btn-ghost",
+                                                    disabled: finished,
+                                                    onclick: move |_| on_priority.call((id_for_raise.clone(), priority + 1)),
+                                                    "提高优先级"
+                                                }
+                                                button {
+                                                    class: "btn btn-ghost",
+                                                    disabled: finished,
+                                                    onclick: move |_| on_priority.call((id_for_lower.clone(), priority - 1)),
+                                                    "降低优先级"
+                                                }
+                                                button {
+                                                    class: "btn btn-primary",
+                                                    disabled: !can_retry,
+                                                    onclick: move |_| on_retry.call(id_for_retry.clone()),
+                                                    "重试"
+                                                }
+                                                button {
+                                                    class: "btn btn-warning",
+                                                    disabled: finished,
+                                                    onclick: move |_| on_cancel.call(id_for_cancel.clone()),
+                                                    "取消"
+                                                }
+                                            }
+                                        }
+                                    }
+                                })
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn job_state_class(state: UploadJobState) -> &'static str {
+    match state {
+        UploadJobState::Uploading | UploadJobState::Succeeded => "tag tag-success",
+        UploadJobState::Queued => "tag tag-info",
+        UploadJobState::Retrying
+        | UploadJobState::PartiallySucceeded
+        | UploadJobState::Cancelled => "tag tag-warning",
+        UploadJobState::Failed => "tag tag-danger",
+    }
+}
+
+fn config_state_class(state: UploadConfigState) -> &'static str {
+    match state {
+        UploadConfigState::Succeeded => "tag tag-success",
+        UploadConfigState::Pending => "tag tag-info",
+        UploadConfigState::Retrying => "tag tag-warning",
+        UploadConfigState::Failed => "tag tag-danger",
+    }
+}
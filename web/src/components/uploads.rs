@@ -1,5 +1,5 @@
 use dioxus::prelude::*;
-use shared::{UploadAccount, UploadTemplate};
+use shared::{UploadAccount, UploadTargetConfig, UploadTemplate};
 
 use super::upload_taxonomy::tid_name;
 
@@ -126,11 +126,38 @@ pub fn UploadsPage(
                             }
                             {
                                 rows_view.into_iter().map(|u| {
-                                    let account_name = accounts
-                                        .iter()
-                                        .find(|a| a.account_file == u.config.account_file)
-                                        .map(|a| a.name.clone())
-                                        .unwrap_or_else(|| u.config.account_file.clone());
+                                    let account_name = match &u.config.target {
+                                        UploadTargetConfig::LocalArchive { root_dir, .. } => {
+                                            format!("{}：{}", u.config.target.label(), root_dir)
+                                        }
+                                        UploadTargetConfig::S3 { bucket, .. } => {
+                                            format!("{}：{}", u.config.target.label(), bucket)
+                                        }
+                                        UploadTargetConfig::Bilibili
+                                        | UploadTargetConfig::WebDav { .. }
+                                        | UploadTargetConfig::YouTube { .. } => {
+                                            let name = accounts
+                                                .iter()
+                                                .find(|a| a.account_file == u.config.account_file)
+                                                .map(|a| a.name.clone())
+                                                .unwrap_or_else(|| u.config.account_file.clone());
+                                            let name = if u.config.backup_accounts.is_empty() {
+                                                name
+                                            } else {
+                                                format!(
+                                                    "{} +{} 备用（{}）",
+                                                    name,
+                                                    u.config.backup_accounts.len(),
+                                                    u.config.account_strategy.label()
+                                                )
+                                            };
+                                            if u.config.target == UploadTargetConfig::Bilibili {
+                                                name
+                                            } else {
+                                                format!("{}：{}", u.config.target.label(), name)
+                                            }
+                                        }
+                                    };
                                     let u_for_edit = u.clone();
                                     let u_id = u.id.clone();
                                     let u_id_for_check = u_id.clone();
@@ -161,6 +188,9 @@ pub fn UploadsPage(
                                                 } else {
                                                     span { class: "muted", "使用默认标题" }
                                                 }
+                                                if let Some(collection) = u.config.collection.clone() {
+                                                    span { class: "muted", " · 合集：{collection.title}" }
+                                                }
                                             }
                                             td { span { class: "tag tag-info", "{tid_name(u.config.tid)}" } }
                                             td { class: "text-ellipsis", title: "{u.config.description}", "{u.config.description}" }
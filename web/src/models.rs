@@ -1,10 +1,14 @@
-use shared::{DownloadConfig, RecordingSettings, StorageStats, UploadAccount, UploadTemplate};
+use shared::{
+    DownloadConfig, RecordingSettings, StorageStats, UploadAccount, UploadJob, UploadTemplate,
+};
 
 #[derive(Clone, Copy, PartialEq)]
 pub enum Tab {
     Downloads,
     Accounts,
     Uploads,
+    UploadQueue,
+    UploadHistory,
     Settings,
 }
 
@@ -12,6 +16,7 @@ pub enum Tab {
 pub struct AppData {
     pub downloads: Vec<DownloadConfig>,
     pub uploads: Vec<UploadTemplate>,
+    pub upload_jobs: Vec<UploadJob>,
     pub accounts: Vec<UploadAccount>,
     pub recording_settings: RecordingSettings,
     pub storage_stats: Option<StorageStats>,
@@ -97,6 +97,22 @@ pub const CSS: &str = r#"
   transition: width 0.28s ease;
 }
 
+.upload-progress {
+  width: 140px;
+  height: 6px;
+  margin: 6px 0 3px;
+  overflow: hidden;
+  border-radius: 999px;
+  background: rgba(148, 163, 184, 0.18);
+}
+
+.upload-progress-fill {
+  height: 100%;
+  border-radius: inherit;
+  background: #0a84ff;
+  transition: width 0.28s ease;
+}
+
 .toolbar {
   display: flex;
   align-items: center;
@@ -134,6 +134,33 @@ pub const CSS: &str = r#"
   font-size: 12px;
 }
 
+.pager {
+  display: flex;
+  justify-content: flex-end;
+  align-items: center;
+  gap: 8px;
+  padding: 10px 4px 2px;
+}
+
+.config-status {
+  display: flex;
+  align-items: center;
+  gap: 6px;
+  margin-top: 4px;
+}
+
+.config-status .btn-mini {
+  padding: 2px 8px;
+  font-size: 12px;
+}
+
+.card.preview p {
+  margin: 4px 0;
+  font-size: 13px;
+  white-space: pre-wrap;
+  word-break: break-all;
+}
+
 .storage-size {
   color: var(--ink);
   font-size: 12px;